@@ -2,7 +2,10 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::texture_finder::{deser_texture_format, ser_texture_format, FoundTexture};
+use crate::{
+	mesh_finder::FoundMesh,
+	texture_finder::{deser_texture_format, ser_texture_format, FoundTexture},
+};
 use dbpf::internal_file::resource_collection::texture_resource::TextureFormat;
 use dbpf_utils::editor::vector::VecEditorState;
 use dbpf_utils::editor::Editor;
@@ -13,9 +16,9 @@ use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
-pub(crate) trait TextureFilterRule {
-	/// filter the texture according to this rule, returns true if the texture should be shown
-	fn filter(&self, tex: &FoundTexture) -> bool;
+pub(crate) trait FilterRule<T> {
+	/// filter the item according to this rule, returns true if the item should be shown
+	fn filter(&self, item: &T) -> bool;
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
@@ -54,6 +57,34 @@ impl ComparisonType {
 	}
 }
 
+/// show the comparison type and the value it is compared to
+fn comparison_editor(c: &mut ComparisonType, y: &mut usize, ui: &mut Ui) -> Response {
+	let res = ComboBox::from_id_salt(ui.id().with(1))
+		.width(40.0)
+		.selected_text(format!("{c}"))
+		.show_ui(ui, |ui| {
+			[
+				ComparisonType::Less,
+				ComparisonType::LessEq,
+				ComparisonType::Greater,
+				ComparisonType::GreaterEq,
+				ComparisonType::Equal,
+				ComparisonType::NotEqual,
+			]
+			.into_iter()
+			.map(|ct| ui.selectable_value(c, ct, format!("{ct}")))
+			.reduce(|r1, r2| r1 | r2)
+			.unwrap()
+		});
+	let mut res = if let Some(inner) = res.inner {
+		res.response | inner
+	} else {
+		res.response
+	};
+	res |= ui.add(DragValue::new(y));
+	res
+}
+
 #[derive(Ord, PartialOrd, Eq, PartialEq)]
 struct SerTexFormat(TextureFormat);
 
@@ -119,7 +150,7 @@ pub enum TextureFilterOperation {
 	Mip(ComparisonType, usize),
 }
 
-impl TextureFilterRule for TextureFilterOperation {
+impl FilterRule<FoundTexture> for TextureFilterOperation {
 	fn filter(&self, tex: &FoundTexture) -> bool {
 		let (comp, y) = match self {
 			TextureFilterOperation::Format(checked) => {
@@ -212,32 +243,7 @@ impl Editor for TextureFilterOperation {
 			TextureFilterOperation::Width(c, y)
 			| TextureFilterOperation::Height(c, y)
 			| TextureFilterOperation::Memory(c, y)
-			| TextureFilterOperation::Mip(c, y) => {
-				let res = ComboBox::from_id_salt(ui.id().with(1))
-					.width(40.0)
-					.selected_text(format!("{c}"))
-					.show_ui(ui, |ui| {
-						[
-							ComparisonType::Less,
-							ComparisonType::LessEq,
-							ComparisonType::Greater,
-							ComparisonType::GreaterEq,
-							ComparisonType::Equal,
-							ComparisonType::NotEqual,
-						]
-						.into_iter()
-						.map(|ct| ui.selectable_value(c, ct, format!("{ct}")))
-						.reduce(|r1, r2| r1 | r2)
-						.unwrap()
-					});
-				let mut res = if let Some(inner) = res.inner {
-					res.response | inner
-				} else {
-					res.response
-				};
-				res |= ui.add(DragValue::new(y));
-				res
-			}
+			| TextureFilterOperation::Mip(c, y) => comparison_editor(c, y, ui),
 			TextureFilterOperation::Format(checked) => {
 				let mut inner = None;
 				let res = ui.menu_button("choose", |ui| {
@@ -282,19 +288,109 @@ impl Editor for TextureFilterOperation {
 	}
 }
 
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum MeshFilterOperation {
+	Triangles(ComparisonType, usize),
+	Vertices(ComparisonType, usize),
+	Memory(ComparisonType, usize),
+	Morphs(ComparisonType, usize),
+	Bones(ComparisonType, usize),
+}
+
+impl MeshFilterOperation {
+	const NAMES: [&'static str; 5] = ["Triangles", "Vertices", "Memory", "Morphs", "Bones"];
+
+	fn index(&self) -> usize {
+		match self {
+			MeshFilterOperation::Triangles(_, _) => 0,
+			MeshFilterOperation::Vertices(_, _) => 1,
+			MeshFilterOperation::Memory(_, _) => 2,
+			MeshFilterOperation::Morphs(_, _) => 3,
+			MeshFilterOperation::Bones(_, _) => 4,
+		}
+	}
+
+	fn comparison(&mut self) -> (&mut ComparisonType, &mut usize) {
+		match self {
+			MeshFilterOperation::Triangles(c, y)
+			| MeshFilterOperation::Vertices(c, y)
+			| MeshFilterOperation::Memory(c, y)
+			| MeshFilterOperation::Morphs(c, y)
+			| MeshFilterOperation::Bones(c, y) => (c, y),
+		}
+	}
+}
+
+impl FilterRule<FoundMesh> for MeshFilterOperation {
+	fn filter(&self, mesh: &FoundMesh) -> bool {
+		let (comp, x, y) = match self {
+			MeshFilterOperation::Triangles(c, y) => (c, mesh.triangles, y),
+			MeshFilterOperation::Vertices(c, y) => (c, mesh.vertices, y),
+			MeshFilterOperation::Memory(c, y) => (c, mesh.memory_size, y),
+			MeshFilterOperation::Morphs(c, y) => (c, mesh.morphs, y),
+			MeshFilterOperation::Bones(c, y) => (c, mesh.bones, y),
+		};
+		comp.check(x, *y)
+	}
+}
+
+impl Default for MeshFilterOperation {
+	fn default() -> Self {
+		Self::Triangles(ComparisonType::Greater, 10_000)
+	}
+}
+
+impl Editor for MeshFilterOperation {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut selected = self.index();
+
+		let ires = ComboBox::from_id_salt(ui.id().with(0))
+			.selected_text(Self::NAMES[selected])
+			.show_ui(ui, |ui| {
+				Self::NAMES
+					.iter()
+					.enumerate()
+					.map(|(i, name)| ui.selectable_value(&mut selected, i, *name))
+					.reduce(|r1, r2| r1.union(r2))
+					.unwrap()
+			});
+
+		if ires.inner.as_ref().is_some_and(|r| r.changed()) {
+			*self = match selected {
+				0 => Self::Triangles(ComparisonType::Greater, 10_000),
+				1 => Self::Vertices(ComparisonType::Greater, 10_000),
+				2 => Self::Memory(ComparisonType::Greater, 1_000_000),
+				3 => Self::Morphs(ComparisonType::Greater, 0),
+				_ => Self::Bones(ComparisonType::Greater, 0),
+			};
+		}
+
+		let mut res = ires.response;
+		if let Some(inner) = ires.inner {
+			res |= inner;
+		}
+
+		let (c, y) = self.comparison();
+		res | comparison_editor(c, y, ui)
+	}
+}
+
+/// a list of rules that all have to match for an item to be shown
 #[derive(Clone, Hash, Default, Debug, Serialize, Deserialize)]
-pub struct TextureFilter {
-	pub operations: Vec<TextureFilterOperation>,
+pub struct Filter<O> {
+	pub operations: Vec<O>,
 }
 
-impl TextureFilterRule for TextureFilter {
-	fn filter(&self, tex: &FoundTexture) -> bool {
-		!self.operations.iter().any(|filter| !filter.filter(tex))
+impl<T, O: FilterRule<T>> FilterRule<T> for Filter<O> {
+	fn filter(&self, item: &T) -> bool {
+		!self.operations.iter().any(|filter| !filter.filter(item))
 	}
 }
 
-impl Editor for TextureFilter {
-	type EditorState = VecEditorState<TextureFilterOperation>;
+impl<O: Editor<EditorState = ()> + Default> Editor for Filter<O> {
+	type EditorState = VecEditorState<O>;
 
 	fn new_editor(&self, context: &Context, gl: &Option<Arc<glow::Context>>) -> Self::EditorState {
 		self.operations.new_editor(context, gl)
@@ -0,0 +1,264 @@
+// SPDX-FileCopyrightText: 2025 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::filtered_list::filter_rule::{Filter, MeshFilterOperation, TextureFilterOperation};
+use crate::filtered_list::sort_order::{
+	MeshSortType, SortOperation, SortOrder, SortType, Sorter, TextureSortType,
+};
+use crate::mesh_finder::FoundMesh;
+use crate::texture_finder::{FoundTexture, ResourceId};
+use dbpf_utils::editor::Editor;
+use eframe::{
+	egui::{Ui, Window},
+	glow, Storage,
+};
+use filter_rule::FilterRule;
+use serde::{de::DeserializeOwned, Serialize};
+
+mod filter_rule;
+pub mod sort_order;
+
+/// the keys the state of a list is saved under
+pub struct StorageKeys {
+	pub known: &'static str,
+	pub show_known: &'static str,
+	pub filter: &'static str,
+	pub sorter: &'static str,
+	/// prefix for the visibility of every column
+	pub enabled_columns: &'static str,
+	pub open_known_gui: &'static str,
+}
+
+/// a resource that can be shown in a [FilteredList]
+pub trait FoundItem: Clone + PartialEq + Debug {
+	type Filter: FilterRule<Self> + Editor + Default + Serialize + DeserializeOwned;
+	type SortType: SortType<Item = Self> + Serialize + DeserializeOwned;
+
+	const STORAGE_KEYS: StorageKeys;
+
+	fn id(&self) -> &ResourceId;
+}
+
+impl FoundItem for FoundTexture {
+	type Filter = Filter<TextureFilterOperation>;
+	type SortType = TextureSortType;
+
+	const STORAGE_KEYS: StorageKeys = StorageKeys {
+		known: "known_textures",
+		show_known: "show_known",
+		filter: "filter_list",
+		sorter: "sorter",
+		enabled_columns: "enabled_columns_",
+		open_known_gui: "open_known_texture_gui",
+	};
+
+	fn id(&self) -> &ResourceId {
+		&self.id
+	}
+}
+
+impl FoundItem for FoundMesh {
+	type Filter = Filter<MeshFilterOperation>;
+	type SortType = MeshSortType;
+
+	const STORAGE_KEYS: StorageKeys = StorageKeys {
+		known: "known_meshes",
+		show_known: "show_known_meshes",
+		filter: "mesh_filter_list",
+		sorter: "mesh_sorter",
+		enabled_columns: "mesh_enabled_columns_",
+		open_known_gui: "open_known_mesh_gui",
+	};
+
+	fn id(&self) -> &ResourceId {
+		&self.id
+	}
+}
+
+pub struct FilteredList<T: FoundItem> {
+	known: Vec<ResourceId>,
+	show_known: bool,
+	filter: T::Filter,
+	filter_ui_state: Option<<T::Filter as Editor>::EditorState>,
+	open_filter_ui: bool,
+	sorter: Sorter<T::SortType>,
+
+	found: Vec<T>,
+	filtered: Vec<T>,
+}
+
+impl<T: FoundItem> FilteredList<T> {
+	pub fn new(storage: &Option<&dyn Storage>) -> Self {
+		let mut new = Self {
+			known: Vec::new(),
+			show_known: true,
+			filter: Default::default(),
+			filter_ui_state: None,
+			open_filter_ui: false,
+			sorter: Default::default(),
+
+			found: Vec::new(),
+			filtered: Vec::new(),
+		};
+		let keys = T::STORAGE_KEYS;
+		if let Some(storage) = storage {
+			if let Some(known_str) = storage.get_string(keys.known) {
+				if let Ok(vec) = serde_json::from_str(known_str.as_str()) {
+					new.known = vec;
+				}
+			}
+
+			if let Some(show_known) = storage
+				.get_string(keys.show_known)
+				.and_then(|str| str.parse().ok())
+			{
+				new.show_known = show_known;
+			}
+
+			if let Some(filter) = storage
+				.get_string(keys.filter)
+				.and_then(|str| serde_json::from_str(&str).ok())
+			{
+				new.filter = filter;
+			}
+
+			if let Some(sorter) = storage
+				.get_string(keys.sorter)
+				.and_then(|str| serde_json::from_str(&str).ok())
+			{
+				new.sorter = sorter;
+			}
+		}
+		new
+	}
+
+	pub fn save(&mut self, storage: &mut dyn Storage) {
+		let keys = T::STORAGE_KEYS;
+		if let Ok(str) = serde_json::to_string(&self.known) {
+			storage.set_string(keys.known, str);
+		}
+
+		storage.set_string(keys.show_known, self.get_show_known().to_string());
+
+		storage.set_string(keys.filter, serde_json::to_string(&self.filter).unwrap());
+
+		storage.set_string(keys.sorter, serde_json::to_string(&self.sorter).unwrap());
+	}
+
+	pub fn show_filter_menu(&mut self, ui: &mut Ui, gl: &Option<Arc<glow::Context>>) {
+		let res = Window::new("Filter List")
+			.resizable(false)
+			.open(&mut self.open_filter_ui)
+			.show(ui.ctx(), |ui| {
+				let state = self
+					.filter_ui_state
+					.get_or_insert_with(|| self.filter.new_editor(ui.ctx(), gl));
+				self.filter.show_editor(state, ui)
+			});
+		res.map(|r| {
+			r.inner.map(|inner| {
+				inner.changed().then(|| {
+					self.re_filter();
+				})
+			})
+		});
+
+		ui.button("Filter")
+			.on_hover_text("The filters that are being applied to the found list")
+			.clicked()
+			.then(|| {
+				self.open_filter_ui = !self.open_filter_ui;
+			});
+	}
+
+	pub fn get_known(&self) -> &Vec<ResourceId> {
+		&self.known
+	}
+
+	pub fn add_known(&mut self, known: ResourceId) -> bool {
+		for self_known in &self.known {
+			if &known == self_known {
+				return false;
+			}
+		}
+		self.known.push(known);
+		self.re_filter();
+		true
+	}
+
+	pub fn remove_known(&mut self, i: usize) {
+		self.known.remove(i);
+		self.re_filter();
+	}
+
+	pub fn is_known(&self, found: &T) -> bool {
+		self.known.contains(found.id())
+	}
+
+	pub fn set_show_known(&mut self, show: bool) {
+		self.show_known = show;
+		self.re_filter();
+	}
+
+	pub fn get_show_known(&self) -> bool {
+		self.show_known
+	}
+
+	pub fn set_sort(&mut self, sort_order: SortOperation<T::SortType>) {
+		self.sorter.set_sort(sort_order);
+		self.sort();
+	}
+
+	pub fn get_sort(&self) -> &SortOperation<T::SortType> {
+		self.sorter.get_sort()
+	}
+
+	pub fn add(&mut self, found: T) {
+		self.found.push(found.clone());
+		if self.filter_item(&found) {
+			let (Ok(idx) | Err(idx)) = self
+				.filtered
+				.binary_search_by(|item| self.sorter.sort_cmp(item, &found));
+			self.filtered.insert(idx, found);
+		}
+	}
+
+	fn filter_item(&self, found: &T) -> bool {
+		self.filter.filter(found) && (self.show_known || !self.is_known(found))
+	}
+
+	pub fn get_filtered(&self) -> &Vec<T> {
+		&self.filtered
+	}
+
+	pub fn clear(&mut self) {
+		self.found = Vec::new();
+		self.re_filter();
+	}
+
+	fn sort(&mut self) {
+		self.filtered
+			.sort_unstable_by(|item1, item2| self.sorter.sort_cmp(item1, item2));
+	}
+
+	fn re_filter(&mut self) {
+		let mut filtered = std::mem::take(&mut self.filtered);
+
+		filtered.clear();
+
+		filtered.extend(
+			self.found
+				.iter()
+				.filter(|&item| self.filter_item(item))
+				.cloned(),
+		);
+
+		self.filtered = filtered;
+
+		self.sort();
+	}
+}
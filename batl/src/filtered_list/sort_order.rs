@@ -2,15 +2,25 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::texture_finder::FoundTexture;
+use crate::{mesh_finder::FoundMesh, texture_finder::FoundTexture};
 use dbpf::internal_file::resource_collection::texture_resource::TextureFormat;
 use enum_iterator::Sequence;
 use serde::{Deserialize, Serialize};
 use std::cmp::{Ordering, PartialEq};
+use std::fmt::Debug;
 use std::ops::Not;
 
-pub trait SortOrder {
-	fn sort_cmp(&self, tex1: &FoundTexture, tex2: &FoundTexture) -> Ordering;
+pub trait SortOrder<T> {
+	fn sort_cmp(&self, item1: &T, item2: &T) -> Ordering;
+}
+
+/// a column that a list of found resources can be sorted by
+pub trait SortType: Copy + Eq + Debug + Sequence {
+	type Item;
+
+	fn default_direction(&self) -> SortDirection;
+
+	fn compare(&self, item1: &Self::Item, item2: &Self::Item) -> Ordering;
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Sequence, Serialize, Deserialize)]
@@ -26,16 +36,17 @@ pub enum TextureSortType {
 	MemorySize,
 }
 
-impl TextureSortType {
-	pub fn default_direction(&self) -> SortDirection {
-		match self {
-			TextureSortType::Path
-			| TextureSortType::Type
-			| TextureSortType::Group
-			| TextureSortType::Instance => SortDirection::Ascending,
-			_ => SortDirection::Descending,
-		}
-	}
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Sequence, Serialize, Deserialize)]
+pub enum MeshSortType {
+	Path,
+	Type,
+	Group,
+	Instance,
+	Triangles,
+	Vertices,
+	MemorySize,
+	Morphs,
+	Bones,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -56,8 +67,8 @@ impl Not for SortDirection {
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
-pub struct TextureSortOperation {
-	pub sort_type: TextureSortType,
+pub struct SortOperation<S> {
+	pub sort_type: S,
 	pub direction: SortDirection,
 }
 
@@ -80,9 +91,21 @@ fn texture_format_user_order(texture_format: TextureFormat) -> usize {
 	}
 }
 
-impl SortOrder for TextureSortOperation {
-	fn sort_cmp(&self, tex1: &FoundTexture, tex2: &FoundTexture) -> Ordering {
-		let order = match self.sort_type {
+impl SortType for TextureSortType {
+	type Item = FoundTexture;
+
+	fn default_direction(&self) -> SortDirection {
+		match self {
+			TextureSortType::Path
+			| TextureSortType::Type
+			| TextureSortType::Group
+			| TextureSortType::Instance => SortDirection::Ascending,
+			_ => SortDirection::Descending,
+		}
+	}
+
+	fn compare(&self, tex1: &FoundTexture, tex2: &FoundTexture) -> Ordering {
+		match self {
 			TextureSortType::Path => tex1.id.path.cmp(&tex2.id.path),
 			TextureSortType::Type => tex1.id.tgi.type_id.code().cmp(&tex2.id.tgi.type_id.code()),
 			TextureSortType::Group => tex1.id.tgi.group_id.cmp(&tex2.id.tgi.group_id),
@@ -94,7 +117,46 @@ impl SortOrder for TextureSortOperation {
 			}
 			TextureSortType::MipLevels => tex1.mip_levels.cmp(&tex2.mip_levels),
 			TextureSortType::MemorySize => tex1.memory_size.cmp(&tex2.memory_size),
-		};
+		}
+	}
+}
+
+impl SortType for MeshSortType {
+	type Item = FoundMesh;
+
+	fn default_direction(&self) -> SortDirection {
+		match self {
+			MeshSortType::Path
+			| MeshSortType::Type
+			| MeshSortType::Group
+			| MeshSortType::Instance => SortDirection::Ascending,
+			_ => SortDirection::Descending,
+		}
+	}
+
+	fn compare(&self, mesh1: &FoundMesh, mesh2: &FoundMesh) -> Ordering {
+		match self {
+			MeshSortType::Path => mesh1.id.path.cmp(&mesh2.id.path),
+			MeshSortType::Type => mesh1
+				.id
+				.tgi
+				.type_id
+				.code()
+				.cmp(&mesh2.id.tgi.type_id.code()),
+			MeshSortType::Group => mesh1.id.tgi.group_id.cmp(&mesh2.id.tgi.group_id),
+			MeshSortType::Instance => mesh1.id.tgi.instance_id.cmp(&mesh2.id.tgi.instance_id),
+			MeshSortType::Triangles => mesh1.triangles.cmp(&mesh2.triangles),
+			MeshSortType::Vertices => mesh1.vertices.cmp(&mesh2.vertices),
+			MeshSortType::MemorySize => mesh1.memory_size.cmp(&mesh2.memory_size),
+			MeshSortType::Morphs => mesh1.morphs.cmp(&mesh2.morphs),
+			MeshSortType::Bones => mesh1.bones.cmp(&mesh2.bones),
+		}
+	}
+}
+
+impl<S: SortType> SortOrder<S::Item> for SortOperation<S> {
+	fn sort_cmp(&self, item1: &S::Item, item2: &S::Item) -> Ordering {
+		let order = self.sort_type.compare(item1, item2);
 
 		match self.direction {
 			SortDirection::Ascending => order,
@@ -104,12 +166,12 @@ impl SortOrder for TextureSortOperation {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct TextureSorter {
-	operations: Vec<TextureSortOperation>,
+pub struct Sorter<S> {
+	operations: Vec<SortOperation<S>>,
 }
 
-impl TextureSorter {
-	pub fn set_sort(&mut self, operation: TextureSortOperation) {
+impl<S: SortType> Sorter<S> {
+	pub fn set_sort(&mut self, operation: SortOperation<S>) {
 		if let Some((idx, _)) = self
 			.operations
 			.iter()
@@ -124,24 +186,24 @@ impl TextureSorter {
 		}
 	}
 
-	pub fn get_sort(&self) -> &TextureSortOperation {
+	pub fn get_sort(&self) -> &SortOperation<S> {
 		&self.operations[0]
 	}
 }
 
-impl SortOrder for TextureSorter {
-	fn sort_cmp(&self, tex1: &FoundTexture, tex2: &FoundTexture) -> Ordering {
+impl<S: SortType> SortOrder<S::Item> for Sorter<S> {
+	fn sort_cmp(&self, item1: &S::Item, item2: &S::Item) -> Ordering {
 		self.operations.iter().fold(Ordering::Equal, |b, x| {
-			b.then_with(|| x.sort_cmp(tex1, tex2))
+			b.then_with(|| x.sort_cmp(item1, item2))
 		})
 	}
 }
 
-impl Default for TextureSorter {
+impl<S: SortType> Default for Sorter<S> {
 	fn default() -> Self {
 		Self {
 			operations: enum_iterator::all()
-				.map(|sort_type: TextureSortType| TextureSortOperation {
+				.map(|sort_type: S| SortOperation {
 					direction: sort_type.default_direction(),
 					sort_type,
 				})
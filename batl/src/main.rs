@@ -13,7 +13,9 @@
 // TODO memory problems
 // TODO add total memory size
 
-mod filtered_texture_list;
+mod filtered_list;
+mod mesh_finder;
+mod scanner;
 mod texture_finder;
 mod ui_image_cache;
 
@@ -29,12 +31,14 @@ use std::{
 	},
 };
 
-use crate::filtered_texture_list::sort_order::{
-	SortDirection, TextureSortOperation, TextureSortType,
+use crate::filtered_list::sort_order::{
+	MeshSortType, SortDirection, SortOperation, SortType, TextureSortType,
 };
 use crate::{
-	filtered_texture_list::FilteredTextureList,
-	texture_finder::{find_textures, FoundTexture},
+	filtered_list::{FilteredList, FoundItem},
+	mesh_finder::FoundMesh,
+	scanner::{find_resources, FoundResource},
+	texture_finder::FoundTexture,
 	ui_image_cache::ImageCache,
 };
 use dbpf_utils::editor::common_ui::settings::Settings;
@@ -52,235 +56,281 @@ use eframe::{
 use egui_extras::Column;
 use futures::channel::oneshot;
 use rfd::FileHandle;
+use serde::{Deserialize, Serialize};
 use tracing::{error, info, instrument, warn};
 
 const IMAGE_CACHE_N: usize = 256;
 const IMAGE_MAX_SIZE: f32 = 300.0;
 
-const EXTRA_COLUMN_NAMES: [&str; 7] = [
-	"Group", "Instance", "Width", "Height", "Memory", "Format", "Mip",
-];
-const COLUMN_INFORMATION: [(&str, &str, TextureSortType); 8] = [
-	("Path",
-	 "the location of the package file\n\
-                            If you want to know the complete path of the package file, either turn on \"Show paths\" \
-                            in the top bar, or hover over the items in the list with your cursor",
-	 TextureSortType::Path,
-	),
+const EXTRA_COLUMNS: usize = 7;
+
+const PATH_COLUMN_DESCRIPTION: &str = "the location of the package file\n\
+    If you want to know the complete path of the package file, either turn on \"Show paths\" \
+    in the top bar, or hover over the items in the list with your cursor";
+
+/// how a kind of found resource is shown in the table
+trait TableItem: FoundItem {
+	const NAME: &'static str;
+	const NAME_PLURAL: &'static str;
+	/// name, description and sort order of the path column followed by the extra columns
+	const COLUMNS: [(&'static str, &'static str, Self::SortType); EXTRA_COLUMNS + 1];
+	/// show the texture itself when hovering over the item
+	const PREVIEW: bool;
+
+	fn column_values(&self) -> [String; EXTRA_COLUMNS];
+
+	fn description(&self) -> String;
+}
+
+impl TableItem for FoundTexture {
+	const NAME: &'static str = "texture";
+	const NAME_PLURAL: &'static str = "textures";
+	const COLUMNS: [(&'static str, &'static str, TextureSortType); EXTRA_COLUMNS + 1] = [
+		("Path", PATH_COLUMN_DESCRIPTION, TextureSortType::Path),
 		("Group", "group id\n\
     This is used by the game (together with the instance id) internally to refer to the texture", TextureSortType::Group),
-	("Instance", "instance id\n\
+		("Instance", "instance id\n\
     This is used by the game (together with the group id) internally to refer to the texture", TextureSortType::Instance),
-	("Width", "width of the texture in pixels", TextureSortType::Width),
-	("Height", "height of the texture in pixels", TextureSortType::Height),
-	("Memory", "amount of bytes this texture takes in memory\n\
+		("Width", "width of the texture in pixels", TextureSortType::Width),
+		("Height", "height of the texture in pixels", TextureSortType::Height),
+		("Memory", "amount of bytes this texture takes in memory\n\
     32-bit programs have an inherent limit of 4GiB (4294967296 bytes) of memory. \
     This means that if you have a texture that takes 1MiB of memory (1048576 bytes), \
     it will use up 1/4096th of your maximum memory.", TextureSortType::MemorySize),
-	("Format", "format of the texture\n\
+		("Format", "format of the texture\n\
     Different formats use different amounts of memory:\n\
     Raw/Alt: 8 bits per pixel per color channel, so a RawBGRA texture takes 32 bits (4*8) per pixel.\n\
     Grayscale/Alpha textures are raw textures with 1 channel, so 8 bits per pixel.\n\
     DXT1: 4 bits per pixel\n\
     DXT3/5: 8 bits per pixel", TextureSortType::Format),
-	("Mip", "amount of mipmap levels\n\
+		("Mip", "amount of mipmap levels\n\
     Mipmap levels are smaller embedded textures that help make textures look smoother when zoomed out. \
     If you use DXVK, you should not have any mipmap levels and force anisotropic filtering instead.",TextureSortType::MipLevels),
-];
-
-struct DBPFApp {
-	settings: Settings<()>,
+	];
+	const PREVIEW: bool = true;
+
+	fn column_values(&self) -> [String; EXTRA_COLUMNS] {
+		[
+			format!("{:X?}", self.id.tgi.group_id),
+			format!("{:016X?}", self.id.tgi.instance_id),
+			format!("{}", self.width),
+			format!("{}", self.height),
+			humansize::format_size(self.memory_size, humansize::DECIMAL.decimal_places(3)),
+			format!("{:?}", self.format),
+			format!("{}", self.mip_levels),
+		]
+	}
 
-	show_folders: bool,
-	open_known_texture_gui: bool,
-	enabled_columns: [bool; 7],
-	scan_folders: String,
+	fn description(&self) -> String {
+		format!(
+			"Group: {:X}\n\
+            Instance: {:X}\n\
+            Width: {}\n\
+            Height: {}\n\
+            Memory size (bytes): {}\n\
+            Format: {:?}\n\
+            Mipmap levels: {}",
+			self.id.tgi.group_id,
+			self.id.tgi.instance_id,
+			self.width,
+			self.height,
+			self.memory_size,
+			self.format,
+			self.mip_levels
+		)
+	}
+}
 
-	scan_ran_with_folders: Vec<PathBuf>,
-	downloads_picker: Option<oneshot::Receiver<Option<Vec<FileHandle>>>>,
+impl TableItem for FoundMesh {
+	const NAME: &'static str = "mesh";
+	const NAME_PLURAL: &'static str = "meshes";
+	const COLUMNS: [(&'static str, &'static str, MeshSortType); EXTRA_COLUMNS + 1] = [
+		("Path", PATH_COLUMN_DESCRIPTION, MeshSortType::Path),
+		(
+			"Group",
+			"group id\n\
+    This is used by the game (together with the instance id) internally to refer to the mesh",
+			MeshSortType::Group,
+		),
+		(
+			"Instance",
+			"instance id\n\
+    This is used by the game (together with the group id) internally to refer to the mesh",
+			MeshSortType::Instance,
+		),
+		(
+			"Triangles",
+			"number of triangles in all groups of the mesh\n\
+    Every triangle has to be drawn each time the object is on screen, \
+    so very detailed meshes slow down the game even when the detail is too small to see.",
+			MeshSortType::Triangles,
+		),
+		(
+			"Vertices",
+			"number of vertices in all groups of the mesh",
+			MeshSortType::Vertices,
+		),
+		(
+			"Memory",
+			"amount of bytes the vertex data of this mesh takes in memory\n\
+    This includes the positions, normals, texture coordinates, bone weights and morph deltas.",
+			MeshSortType::MemorySize,
+		),
+		(
+			"Morphs",
+			"number of morph targets\n\
+    Every morph stores the movement of the vertices it changes, \
+    such as the fat and pregnant shapes of body meshes.",
+			MeshSortType::Morphs,
+		),
+		(
+			"Bones",
+			"number of bones the mesh is bound to",
+			MeshSortType::Bones,
+		),
+	];
+	const PREVIEW: bool = false;
+
+	fn column_values(&self) -> [String; EXTRA_COLUMNS] {
+		[
+			format!("{:X?}", self.id.tgi.group_id),
+			format!("{:016X?}", self.id.tgi.instance_id),
+			format!("{}", self.triangles),
+			format!("{}", self.vertices),
+			humansize::format_size(self.memory_size, humansize::DECIMAL.decimal_places(3)),
+			format!("{}", self.morphs),
+			format!("{}", self.bones),
+		]
+	}
 
-	texture_list: FilteredTextureList,
-	find_textures_result_stream: Option<Receiver<FoundTexture>>,
-	find_textures_progress: Arc<Mutex<Option<(PathBuf, usize, usize)>>>,
-	highlighted_texture: Option<FoundTexture>,
-	last_hovered_texture: Option<FoundTexture>,
-	ui_image_cache: ImageCache,
+	fn description(&self) -> String {
+		format!(
+			"Group: {:X}\n\
+            Instance: {:X}\n\
+            Triangles: {}\n\
+            Vertices: {}\n\
+            Memory size (bytes): {}\n\
+            Morphs: {}\n\
+            Bones: {}",
+			self.id.tgi.group_id,
+			self.id.tgi.instance_id,
+			self.triangles,
+			self.vertices,
+			self.memory_size,
+			self.morphs,
+			self.bones
+		)
+	}
 }
 
-impl DBPFApp {
-	fn new(cc: &eframe::CreationContext<'_>) -> Self {
-		let mut new = Self {
-			settings: Settings::new((), version_info!()),
-
-			show_folders: true,
-			open_known_texture_gui: false,
-			enabled_columns: [true; 7],
-			scan_folders: "".to_string(),
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+enum ShownList {
+	#[default]
+	Textures,
+	Meshes,
+}
 
-			scan_ran_with_folders: Vec::new(),
-			downloads_picker: None,
+/// a found resource list together with the state of its table
+struct ListView<T: TableItem> {
+	list: FilteredList<T>,
+	enabled_columns: [bool; EXTRA_COLUMNS],
+	open_known_gui: bool,
+	highlighted: Option<T>,
+	last_hovered: Option<T>,
+}
 
-			texture_list: FilteredTextureList::new(&cc.storage),
-			find_textures_result_stream: None,
-			find_textures_progress: Mutex::new(None).into(),
-			highlighted_texture: None,
-			last_hovered_texture: None,
-			ui_image_cache: ImageCache::new(NonZeroUsize::new(IMAGE_CACHE_N).unwrap()),
+impl<T: TableItem> ListView<T> {
+	fn new(storage: Option<&dyn Storage>) -> Self {
+		let mut new = Self {
+			list: FilteredList::new(&storage),
+			enabled_columns: [true; EXTRA_COLUMNS],
+			open_known_gui: false,
+			highlighted: None,
+			last_hovered: None,
 		};
-		if let Some(storage) = cc.storage {
-			if let Some(settings) = eframe::get_value(storage, "settings") {
-				new.settings = settings;
-			}
+		if let Some(storage) = storage {
+			let keys = T::STORAGE_KEYS;
 			if let Some(open_gui) = storage
-				.get_string("open_known_texture_gui")
+				.get_string(keys.open_known_gui)
 				.and_then(|str| str.parse().ok())
 			{
-				new.open_known_texture_gui = open_gui;
+				new.open_known_gui = open_gui;
 			}
-			if let Some(show_folders) = storage
-				.get_string("show_folders")
-				.and_then(|str| str.parse().ok())
-			{
-				new.show_folders = show_folders;
-			}
-			new.enabled_columns = EXTRA_COLUMN_NAMES.map(|name| {
-				let mut key = "enabled_columns_".to_string();
-				key.push_str(name);
+			new.enabled_columns = std::array::from_fn(|i| {
+				let mut key = keys.enabled_columns.to_string();
+				key.push_str(T::COLUMNS[i + 1].0);
 				storage
 					.get_string(&key)
 					.and_then(|str| str.parse().ok())
 					.unwrap_or(true)
 			});
-			if let Some(downloads_folder) = storage.get_string("downloads_folder") {
-				new.scan_folders = downloads_folder;
-				new.start_scannning(&cc.egui_ctx);
-			}
 		}
-
-		new.settings.init(version_info!());
-
-		cc.egui_ctx
-			.options_mut(|options| options.reduce_texture_memory = true);
-
 		new
 	}
 
-	fn open_downloads_picker(&mut self) {
-		let mut dialog = rfd::AsyncFileDialog::new();
-		let cur_dir_path = PathBuf::from(&self.scan_folders.lines().next().unwrap_or(""));
-		if cur_dir_path.is_dir() {
-			dialog = dialog.set_directory(cur_dir_path);
-		} else if let Some(dir) = cur_dir_path.parent() {
-			if dir.is_dir() {
-				dialog = dialog.set_directory(dir);
-			}
-		}
-		let (tx, rx) = oneshot::channel();
-		std::thread::spawn(|| {
-			let _ = tx.send(futures::executor::block_on(dialog.pick_folders()));
-		});
-		self.downloads_picker = Some(rx);
-	}
+	fn save(&mut self, storage: &mut dyn Storage) {
+		let keys = T::STORAGE_KEYS;
+		T::COLUMNS[1..]
+			.iter()
+			.zip(self.enabled_columns)
+			.for_each(|((name, _, _), enabled)| {
+				let mut key = keys.enabled_columns.to_string();
+				key.push_str(name);
+				storage.set_string(&key, enabled.to_string());
+			});
 
-	#[instrument(skip(self))]
-	fn start_scannning(&mut self, ctx: &Context) {
-		self.texture_list.clear();
-		self.scan_ran_with_folders = self.scan_folders.lines().map(PathBuf::from).collect();
-		self.highlighted_texture = None;
+		storage.set_string(keys.open_known_gui, self.open_known_gui.to_string());
+		self.list.save(storage);
+	}
 
-		let (tx, rx) = mpsc::channel();
-		let progress_clone = Arc::clone(&self.find_textures_progress);
-		let ctx_clone = ctx.clone();
-		tokio::task::spawn(find_textures(
-			self.scan_ran_with_folders.clone(),
-			tx,
-			move |path, current, total| {
-				info!(path = ?path.display(), current, total, "scanning");
-				let mut data = progress_clone.lock().unwrap();
-				*data = if current != total {
-					Some((path, current, total))
-				} else {
-					None
-				};
-				ctx_clone.request_repaint();
-			},
-		));
-		self.find_textures_result_stream = Some(rx);
+	fn clear(&mut self) {
+		self.list.clear();
+		self.highlighted = None;
 	}
 
-	fn update_state(&mut self, ctx: &Context) {
-		// pull in the newly found textures before showing them
-		let mut drop_stream = false;
-		if let Some(ref stream) = self.find_textures_result_stream {
-			while match stream.try_recv() {
-				Ok(texture) => {
-					self.texture_list.add(texture);
-					true
-				}
-				Err(TryRecvError::Empty) => false,
-				Err(TryRecvError::Disconnected) => {
-					drop_stream = true;
-					false
-				}
-			} {}
-		}
-		if drop_stream {
-			self.find_textures_result_stream = None;
-		}
+	/// the list specific buttons in the top bar
+	fn show_controls(&mut self, scan_folders: &[PathBuf], ctx: &Context, ui: &mut Ui) {
+		self.list.show_filter_menu(ui, &None);
 
-		// check for a downloads folder picker response
-		if let Some(ref mut picker) = self.downloads_picker {
-			match picker.try_recv() {
-				Ok(None) => {}
-				Ok(Some(res)) => {
-					if let Some(folders) = res {
-						self.scan_folders = folders
-							.iter()
-							.map(|folder| folder.path().to_string_lossy().to_string())
-							.reduce(|mut full, str| {
-								full.push('\n');
-								full.push_str(str.as_str());
-								full
-							})
-							.unwrap_or("".to_string());
-						self.start_scannning(ctx);
-					}
-					self.downloads_picker = None;
-				}
-				_ => {
-					self.downloads_picker = None;
-				}
-			}
+		self.known_menu(scan_folders, ctx, ui);
+
+		let mut show_known = self.list.get_show_known();
+		if ui
+			.checkbox(&mut show_known, "Show known")
+			.on_hover_text(format!(
+				"Show {} that have been marked as a known {}",
+				T::NAME_PLURAL,
+				T::NAME
+			))
+			.changed()
+		{
+			self.list.set_show_known(show_known);
 		}
 	}
 
-	fn texture_description_string(texture: &FoundTexture) -> String {
-		format!(
-			"Group: {:X}\n\
-            Instance: {:X}\n\
-            Width: {}\n\
-            Height: {}\n\
-            Memory size (bytes): {}\n\
-            Format: {:?}\n\
-            Mipmap levels: {}",
-			texture.id.tgi.group_id,
-			texture.id.tgi.instance_id,
-			texture.width,
-			texture.height,
-			texture.memory_size,
-			texture.format,
-			texture.mip_levels
-		)
+	fn show_column_menu(&mut self, ui: &mut Ui) {
+		MenuButton::new("Enabled columns")
+			.config(MenuConfig::new().close_behavior(PopupCloseBehavior::CloseOnClickOutside))
+			.ui(ui, |ui| {
+				self.enabled_columns
+					.iter_mut()
+					.zip(&T::COLUMNS[1..])
+					.for_each(|(enabled, (name, _, _))| {
+						ui.checkbox(enabled, *name);
+					});
+			})
+			.0
+			.on_hover_text("What columns should be shown in the table?");
 	}
 
-	fn known_texture_menu(&mut self, ctx: &Context, ui: &mut Ui) {
-		Window::new("Known textures")
+	fn known_menu(&mut self, scan_folders: &[PathBuf], ctx: &Context, ui: &mut Ui) {
+		Window::new(format!("Known {}", T::NAME_PLURAL))
 			.resizable(true)
 			.hscroll(true)
-			.open(&mut self.open_known_texture_gui)
+			.open(&mut self.open_known_gui)
 			.show(ctx, |ui| {
-				let known_textures = self.texture_list.get_known();
-				if !known_textures.is_empty() {
+				let known = self.list.get_known();
+				if !known.is_empty() {
 					let mut remove = None;
 
 					let available_width = ui.available_width();
@@ -309,59 +359,66 @@ impl DBPFApp {
 							});
 						})
 						.body(|body| {
-							body.rows(14.0, known_textures.len(), |mut row| {
+							body.rows(14.0, known.len(), |mut row| {
 								let i = row.index();
 
 								let mut path_label_fn = |ui: &mut Ui, path| {
 									ui.add(
 										Label::new(
-											Self::strip_prefix(&self.scan_ran_with_folders, path)
+											strip_prefix(scan_folders, path)
 												.unwrap_or(path)
 												.to_string_lossy(),
 										)
 										.sense(Sense::click()),
 									)
 									.context_menu(|ui| {
-										ui.button("Forget known texture").clicked().then(|| {
-											remove = Some(i);
-										});
+										ui.button(format!("Forget known {}", T::NAME))
+											.clicked()
+											.then(|| {
+												remove = Some(i);
+											});
 									});
 								};
 
 								row.col(|ui| {
-									path_label_fn(ui, &known_textures[i].path);
+									path_label_fn(ui, &known[i].path);
 								});
 								row.col(|ui| {
-									ui.label(format!("{:X?}", &known_textures[i].tgi.group_id));
+									ui.label(format!("{:X?}", &known[i].tgi.group_id));
 								});
 								row.col(|ui| {
-									ui.label(format!("{:X?}", &known_textures[i].tgi.instance_id));
+									ui.label(format!("{:X?}", &known[i].tgi.instance_id));
 								});
 							});
 						});
 
 					if let Some(i) = remove {
-						self.texture_list.remove_known(i);
+						self.list.remove_known(i);
 					}
 				} else {
-					ui.label("No known textures found");
+					ui.label(format!("No known {} found", T::NAME_PLURAL));
 				}
 			});
 
 		if ui
-			.button("Known textures")
-			.on_hover_text("List of the textures that have been marked as known")
+			.button(format!("Known {}", T::NAME_PLURAL))
+			.on_hover_text(format!(
+				"List of the {} that have been marked as known",
+				T::NAME_PLURAL
+			))
 			.clicked()
 		{
-			self.open_known_texture_gui = !self.open_known_texture_gui;
+			self.open_known_gui = !self.open_known_gui;
 		}
 	}
 
 	#[instrument(skip(self, ui))]
-	fn texture_menu(&mut self, path: &Path, texture: &FoundTexture, ui: &mut Ui) {
-		ui.button("Add to known textures").clicked().then(|| {
-			self.texture_list.add_known(texture.id.clone());
-		});
+	fn item_menu(&mut self, path: &Path, item: &T, ui: &mut Ui) {
+		ui.button(format!("Add to known {}", T::NAME_PLURAL))
+			.clicked()
+			.then(|| {
+				self.list.add_known(item.id().clone());
+			});
 
 		ui.button("Copy name").clicked().then(|| {
 			if let Some(stem) = path.file_stem().and_then(|str| str.to_str()) {
@@ -387,30 +444,27 @@ impl DBPFApp {
 		}
 	}
 
-	fn strip_prefix<'a>(scan_folders: &[PathBuf], path: &'a Path) -> Option<&'a Path> {
-		scan_folders
-			.iter()
-			.find_map(|folder| path.strip_prefix(folder).ok())
-	}
-
-	fn show_path_cell(&mut self, texture: &FoundTexture, ui: &mut Ui) -> Response {
-		let stripped_path = Self::strip_prefix(&self.scan_ran_with_folders, &texture.id.path)
-			.unwrap_or(&texture.id.path);
-
-		let text_string = if self.show_folders {
+	fn show_path_cell(
+		&mut self,
+		item: &T,
+		scan_folders: &[PathBuf],
+		show_folders: bool,
+		ui: &mut Ui,
+	) -> Response {
+		let path = &item.id().path;
+		let stripped_path = strip_prefix(scan_folders, path).unwrap_or(path);
+
+		let text_string = if show_folders {
 			stripped_path.to_string_lossy().to_string()
 		} else {
-			texture
-				.id
-				.path
-				.file_name()
+			path.file_name()
 				.map(|name| name.to_string_lossy().to_string())
 				.unwrap_or(stripped_path.to_string_lossy().to_string())
 		};
 
 		let mut text = RichText::new(text_string);
 
-		if self.texture_list.is_known(texture) {
+		if self.list.is_known(item) {
 			text = text.color(if ui.style().visuals.dark_mode {
 				Color32::DARK_GRAY
 			} else {
@@ -420,9 +474,9 @@ impl DBPFApp {
 
 		let mut frame = containers::Frame::new();
 		let selected = self
-			.highlighted_texture
+			.highlighted
 			.as_ref()
-			.map(|c| texture == c)
+			.map(|c| item == c)
 			.unwrap_or(false);
 		if selected {
 			frame.fill = if ui.style().visuals.dark_mode {
@@ -438,12 +492,12 @@ impl DBPFApp {
 			let res = ui.horizontal_centered(|ui| {
 				ui.style_mut().interaction.selectable_labels = false;
 				let lbl = ui.add(Label::new(text).sense(Sense::click()));
-				lbl.context_menu(|ui| self.texture_menu(&texture.id.path, texture, ui));
+				lbl.context_menu(|ui| self.item_menu(path, item, ui));
 				lbl.clicked().then(|| {
 					highlight = true;
 				});
 				lbl.double_clicked().then(|| {
-					if let Err(err) = open::that_detached(&texture.id.path) {
+					if let Err(err) = open::that_detached(path) {
 						error!(?err);
 					}
 				});
@@ -460,8 +514,14 @@ impl DBPFApp {
 	}
 
 	#[instrument(skip_all)]
-	fn show_table(&mut self, ui: &mut Ui) {
-		ui.push_id(ui.make_persistent_id("texture table"), |ui| {
+	fn show_table(
+		&mut self,
+		scan_folders: &[PathBuf],
+		show_folders: bool,
+		image_cache: &mut ImageCache,
+		ui: &mut Ui,
+	) {
+		ui.push_id(ui.make_persistent_id(format!("{} table", T::NAME)), |ui| {
 			let col_widths = [80.0, 140.0, 70.0, 80.0, 90.0, 80.0, 60.0];
 
 			let available_width = ui.available_width();
@@ -486,9 +546,9 @@ impl DBPFApp {
 			let mut total_rect: Option<Rect> = None;
 			let table_res = table
 				.header(30.0, |mut row| {
-					let current_sort = self.texture_list.get_sort().clone();
+					let current_sort = *self.list.get_sort();
 
-					let mut column = |info: &(&str, &str, TextureSortType)| {
+					let mut column = |info: &(&str, &str, T::SortType)| {
 						row.col(|ui| {
 							let this_column_sorted = info.2 == current_sort.sort_type;
 
@@ -519,7 +579,7 @@ impl DBPFApp {
 									info.2.default_direction()
 								};
 
-								self.texture_list.set_sort(TextureSortOperation {
+								self.list.set_sort(SortOperation {
 									sort_type: info.2,
 									direction: new_direction,
 								});
@@ -527,58 +587,45 @@ impl DBPFApp {
 						});
 					};
 
-					column(&COLUMN_INFORMATION[0]);
+					column(&T::COLUMNS[0]);
 
 					for (_, info) in self
 						.enabled_columns
 						.iter()
-						.zip(COLUMN_INFORMATION[1..].iter())
+						.zip(T::COLUMNS[1..].iter())
 						.filter(|(e, _)| **e)
 					{
 						column(info);
 					}
 				})
 				.body(|body| {
-					let filtered = self.texture_list.get_filtered();
+					let filtered = self.list.get_filtered();
 					let mut highlight = None;
 					body.rows(14.0, filtered.len(), |mut row| {
 						let idx = row.index();
-						let texture = self.texture_list.get_filtered()[idx].clone();
+						let item = self.list.get_filtered()[idx].clone();
 
 						let mut hover = false;
 						let (rect, res) = row.col(|ui| {
-							let res = self.show_path_cell(&texture, ui);
+							let res = self.show_path_cell(&item, scan_folders, show_folders, ui);
 							if res.clicked() {
-								highlight = Some(texture.clone());
+								highlight = Some(item.clone());
 							}
 							if res.hovered() {
 								hover = true;
 							}
 						});
 						if res.hovered() | hover {
-							self.last_hovered_texture = Some(texture.clone());
+							self.last_hovered = Some(item.clone());
 						}
 						match &mut total_rect {
 							Some(r) => *r = r.union(rect),
 							r => *r = Some(rect),
 						}
 
-						let columns = [
-							format!("{:X?}", texture.id.tgi.group_id),
-							format!("{:016X?}", texture.id.tgi.instance_id),
-							format!("{}", texture.width),
-							format!("{}", texture.height),
-							humansize::format_size(
-								texture.memory_size,
-								humansize::DECIMAL.decimal_places(3),
-							),
-							format!("{:?}", texture.format),
-							format!("{}", texture.mip_levels),
-						];
-
 						self.enabled_columns
 							.iter()
-							.zip(columns)
+							.zip(item.column_values())
 							.filter(|(e, _)| **e)
 							.for_each(|(_, text)| {
 								row.col(|ui| {
@@ -588,26 +635,27 @@ impl DBPFApp {
 							});
 					});
 					if highlight.is_some() {
-						self.highlighted_texture = highlight;
+						self.highlighted = highlight;
 					}
 				});
 
 			if let Some(rect) = total_rect {
-				if let Some(texture) = &self.last_hovered_texture {
+				if let Some(item) = &self.last_hovered {
 					let rect = rect.intersect(table_res.inner_rect.expand(5.0));
 					let res =
 						ui.interact(rect, ui.auto_id_with("path interact rect"), Sense::hover());
 
 					res.on_hover_ui_at_pointer(|ui| {
-						let stripped_path =
-							Self::strip_prefix(&self.scan_ran_with_folders, &texture.id.path)
-								.unwrap_or(&texture.id.path);
-						let tooltip = Self::texture_description_string(texture);
+						let path = &item.id().path;
+						let stripped_path = strip_prefix(scan_folders, path).unwrap_or(path);
 
 						ui.add(Label::new(stripped_path.to_string_lossy()).wrap());
-						ui.label(tooltip);
+						ui.label(item.description());
 
-						if let Some(tex) = self.ui_image_cache.get(&texture.id, ui.ctx()) {
+						if !T::PREVIEW {
+							return;
+						}
+						if let Some(tex) = image_cache.get(item.id(), ui.ctx()) {
 							ui.add_sized(
 								[IMAGE_MAX_SIZE, IMAGE_MAX_SIZE],
 								Image::from_texture(&tex)
@@ -629,6 +677,168 @@ impl DBPFApp {
 	}
 }
 
+fn strip_prefix<'a>(scan_folders: &[PathBuf], path: &'a Path) -> Option<&'a Path> {
+	scan_folders
+		.iter()
+		.find_map(|folder| path.strip_prefix(folder).ok())
+}
+
+struct DBPFApp {
+	settings: Settings<()>,
+
+	show_folders: bool,
+	shown_list: ShownList,
+	scan_folders: String,
+
+	scan_ran_with_folders: Vec<PathBuf>,
+	downloads_picker: Option<oneshot::Receiver<Option<Vec<FileHandle>>>>,
+
+	textures: ListView<FoundTexture>,
+	meshes: ListView<FoundMesh>,
+	find_resources_result_stream: Option<Receiver<FoundResource>>,
+	find_resources_progress: Arc<Mutex<Option<(PathBuf, usize, usize)>>>,
+	ui_image_cache: ImageCache,
+}
+
+impl DBPFApp {
+	fn new(cc: &eframe::CreationContext<'_>) -> Self {
+		let mut new = Self {
+			settings: Settings::new((), version_info!()),
+
+			show_folders: true,
+			shown_list: ShownList::Textures,
+			scan_folders: "".to_string(),
+
+			scan_ran_with_folders: Vec::new(),
+			downloads_picker: None,
+
+			textures: ListView::new(cc.storage),
+			meshes: ListView::new(cc.storage),
+			find_resources_result_stream: None,
+			find_resources_progress: Mutex::new(None).into(),
+			ui_image_cache: ImageCache::new(NonZeroUsize::new(IMAGE_CACHE_N).unwrap()),
+		};
+		if let Some(storage) = cc.storage {
+			if let Some(settings) = eframe::get_value(storage, "settings") {
+				new.settings = settings;
+			}
+			if let Some(shown_list) = eframe::get_value(storage, "shown_list") {
+				new.shown_list = shown_list;
+			}
+			if let Some(show_folders) = storage
+				.get_string("show_folders")
+				.and_then(|str| str.parse().ok())
+			{
+				new.show_folders = show_folders;
+			}
+			if let Some(downloads_folder) = storage.get_string("downloads_folder") {
+				new.scan_folders = downloads_folder;
+				new.start_scannning(&cc.egui_ctx);
+			}
+		}
+
+		new.settings.init(version_info!());
+
+		cc.egui_ctx
+			.options_mut(|options| options.reduce_texture_memory = true);
+
+		new
+	}
+
+	fn open_downloads_picker(&mut self) {
+		let mut dialog = rfd::AsyncFileDialog::new();
+		let cur_dir_path = PathBuf::from(&self.scan_folders.lines().next().unwrap_or(""));
+		if cur_dir_path.is_dir() {
+			dialog = dialog.set_directory(cur_dir_path);
+		} else if let Some(dir) = cur_dir_path.parent() {
+			if dir.is_dir() {
+				dialog = dialog.set_directory(dir);
+			}
+		}
+		let (tx, rx) = oneshot::channel();
+		std::thread::spawn(|| {
+			let _ = tx.send(futures::executor::block_on(dialog.pick_folders()));
+		});
+		self.downloads_picker = Some(rx);
+	}
+
+	#[instrument(skip(self))]
+	fn start_scannning(&mut self, ctx: &Context) {
+		self.textures.clear();
+		self.meshes.clear();
+		self.scan_ran_with_folders = self.scan_folders.lines().map(PathBuf::from).collect();
+
+		let (tx, rx) = mpsc::channel();
+		let progress_clone = Arc::clone(&self.find_resources_progress);
+		let ctx_clone = ctx.clone();
+		tokio::task::spawn(find_resources(
+			self.scan_ran_with_folders.clone(),
+			tx,
+			move |path, current, total| {
+				info!(path = ?path.display(), current, total, "scanning");
+				let mut data = progress_clone.lock().unwrap();
+				*data = if current != total {
+					Some((path, current, total))
+				} else {
+					None
+				};
+				ctx_clone.request_repaint();
+			},
+		));
+		self.find_resources_result_stream = Some(rx);
+	}
+
+	fn update_state(&mut self, ctx: &Context) {
+		// pull in the newly found resources before showing them
+		let mut drop_stream = false;
+		if let Some(ref stream) = self.find_resources_result_stream {
+			while match stream.try_recv() {
+				Ok(FoundResource::Texture(texture)) => {
+					self.textures.list.add(texture);
+					true
+				}
+				Ok(FoundResource::Mesh(mesh)) => {
+					self.meshes.list.add(mesh);
+					true
+				}
+				Err(TryRecvError::Empty) => false,
+				Err(TryRecvError::Disconnected) => {
+					drop_stream = true;
+					false
+				}
+			} {}
+		}
+		if drop_stream {
+			self.find_resources_result_stream = None;
+		}
+
+		// check for a downloads folder picker response
+		if let Some(ref mut picker) = self.downloads_picker {
+			match picker.try_recv() {
+				Ok(None) => {}
+				Ok(Some(res)) => {
+					if let Some(folders) = res {
+						self.scan_folders = folders
+							.iter()
+							.map(|folder| folder.path().to_string_lossy().to_string())
+							.reduce(|mut full, str| {
+								full.push('\n');
+								full.push_str(str.as_str());
+								full
+							})
+							.unwrap_or("".to_string());
+						self.start_scannning(ctx);
+					}
+					self.downloads_picker = None;
+				}
+				_ => {
+					self.downloads_picker = None;
+				}
+			}
+		}
+	}
+}
+
 impl App for DBPFApp {
 	#[instrument(skip_all)]
 	fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
@@ -650,37 +860,30 @@ impl App for DBPFApp {
 				ui.horizontal_wrapped(|ui| {
 					self.settings.show_ui(ui, |_ui, _settings| false);
 
-					self.texture_list.show_filter_menu(ui, &None);
+					ui.selectable_value(&mut self.shown_list, ShownList::Textures, "Textures")
+						.on_hover_text("Show the textures that were found");
+					ui.selectable_value(&mut self.shown_list, ShownList::Meshes, "Meshes")
+						.on_hover_text("Show the meshes that were found");
+					ui.separator();
 
-					self.known_texture_menu(ctx, ui);
-
-					let mut show_known = self.texture_list.get_show_known();
-					if ui
-						.checkbox(&mut show_known, "Show known")
-						.on_hover_text("Show textures that have been marked as a known texture")
-						.changed()
-					{
-						self.texture_list.set_show_known(show_known);
+					match self.shown_list {
+						ShownList::Textures => {
+							self.textures
+								.show_controls(&self.scan_ran_with_folders, ctx, ui)
+						}
+						ShownList::Meshes => {
+							self.meshes
+								.show_controls(&self.scan_ran_with_folders, ctx, ui)
+						}
 					}
 
 					ui.checkbox(&mut self.show_folders, "Show paths")
 						.on_hover_text("Show what folders the packages are in?");
 
-					MenuButton::new("Enabled columns")
-						.config(
-							MenuConfig::new()
-								.close_behavior(PopupCloseBehavior::CloseOnClickOutside),
-						)
-						.ui(ui, |ui| {
-							self.enabled_columns
-								.iter_mut()
-								.zip(EXTRA_COLUMN_NAMES)
-								.for_each(|(enabled, name)| {
-									ui.checkbox(enabled, name);
-								});
-						})
-						.0
-						.on_hover_text("What columns should be shown in the table?");
+					match self.shown_list {
+						ShownList::Textures => self.textures.show_column_menu(ui),
+						ShownList::Meshes => self.meshes.show_column_menu(ui),
+					}
 				});
 
 				ui.horizontal(|ui| {
@@ -714,12 +917,12 @@ impl App for DBPFApp {
 				// (res.response | res.inner)
 
 				if let Some((ref path, progress, total)) =
-					*self.find_textures_progress.lock().unwrap()
+					*self.find_resources_progress.lock().unwrap()
 				{
 					ui.add(
 						ProgressBar::new(progress as f32 / total as f32).text(format!(
 							"{progress}/{total} {}",
-							Self::strip_prefix(&self.scan_ran_with_folders, path)
+							strip_prefix(&self.scan_ran_with_folders, path)
 								.unwrap_or(path)
 								.display()
 						)),
@@ -728,32 +931,34 @@ impl App for DBPFApp {
 
 				ui.separator();
 
-				self.show_table(ui);
+				match self.shown_list {
+					ShownList::Textures => self.textures.show_table(
+						&self.scan_ran_with_folders,
+						self.show_folders,
+						&mut self.ui_image_cache,
+						ui,
+					),
+					ShownList::Meshes => self.meshes.show_table(
+						&self.scan_ran_with_folders,
+						self.show_folders,
+						&mut self.ui_image_cache,
+						ui,
+					),
+				}
 			});
 		});
 	}
 
 	fn save(&mut self, storage: &mut dyn Storage) {
 		eframe::set_value(storage, "settings", &self.settings);
+		eframe::set_value(storage, "shown_list", &self.shown_list);
 
 		storage.set_string("show_folders", self.show_folders.to_string());
 
-		EXTRA_COLUMN_NAMES
-			.iter()
-			.zip(self.enabled_columns)
-			.for_each(|(name, enabled)| {
-				let mut key = "enabled_columns_".to_string();
-				key.push_str(name);
-				storage.set_string(&key, enabled.to_string());
-			});
-
 		storage.set_string("downloads_folder", self.scan_folders.clone());
 
-		storage.set_string(
-			"open_known_texture_gui",
-			self.open_known_texture_gui.to_string(),
-		);
-		self.texture_list.save(storage);
+		self.textures.save(storage);
+		self.meshes.save(storage);
 	}
 }
 
@@ -0,0 +1,82 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	io::{Read, Seek},
+	path::Path,
+};
+
+use dbpf::{
+	filetypes::{DBPFFileType::Known, KnownDBPFFileType},
+	internal_file::{resource_collection::ResourceData, CompressionError, DecodedFile},
+	IndexEntry,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::texture_finder::{ResourceId, Tgi};
+
+#[derive(Clone, Eq, PartialEq, Hash, Default, Debug, Serialize, Deserialize)]
+pub struct FoundMesh {
+	pub id: ResourceId,
+
+	pub triangles: usize,
+	/// the number of vertices over all attribute groups
+	pub vertices: usize,
+	/// the size of all attribute buffers in bytes
+	pub memory_size: usize,
+	pub morphs: usize,
+	pub bones: usize,
+}
+
+pub(crate) fn get_mesh<R: Read + Seek>(
+	path: &Path,
+	file: &mut IndexEntry,
+	reader: &mut R,
+) -> Option<FoundMesh> {
+	let type_id = file.type_id;
+	let group_id = file.group_id;
+	let instance_id = file.instance_id;
+
+	if type_id != Known(KnownDBPFFileType::GeometricDataContainer) {
+		return None;
+	}
+	let decoded = file
+		.data(reader)
+		.map_err(CompressionError::BinResult)
+		.and_then(|d| d.decoded())
+		.inspect_err(|err| {
+			error!(?path, ?err);
+		})
+		.ok()??;
+	let DecodedFile::ResourceCollection(res) = decoded else {
+		return None;
+	};
+	res.entries.iter().find_map(|entry| match &entry.data {
+		ResourceData::Mesh(gmdc) => Some(FoundMesh {
+			id: ResourceId {
+				path: path.to_path_buf(),
+				tgi: Tgi {
+					type_id,
+					group_id,
+					instance_id: instance_id.id,
+				},
+			},
+			triangles: gmdc.meshes.iter().map(|mesh| mesh.poly_count()).sum(),
+			vertices: gmdc
+				.attribute_groups
+				.iter()
+				.map(|group| group.number_elements as usize)
+				.sum(),
+			memory_size: gmdc
+				.attribute_buffers
+				.iter()
+				.map(|buffer| buffer.data.len())
+				.sum(),
+			morphs: gmdc.blend_group_bindings.len(),
+			bones: gmdc.bones.len(),
+		}),
+		_ => None,
+	})
+}
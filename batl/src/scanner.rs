@@ -0,0 +1,111 @@
+// SPDX-FileCopyrightText: 2025 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{ffi::OsStr, path::PathBuf, sync::mpsc::Sender};
+
+use binrw::{io::BufReader, BinRead, BinResult};
+use dbpf::DBPFFile;
+use futures::{stream, StreamExt};
+use tokio::fs::File;
+use tracing::error;
+use walkdir::WalkDir;
+
+use crate::{
+	mesh_finder::{get_mesh, FoundMesh},
+	texture_finder::{get_texture, FoundTexture},
+};
+
+#[derive(Clone, Debug)]
+pub enum FoundResource {
+	Texture(FoundTexture),
+	Mesh(FoundMesh),
+}
+
+async fn get_path_resources(path: PathBuf) -> (PathBuf, Option<Vec<FoundResource>>) {
+	match File::open(&path).await {
+		Err(err) => {
+			error!(?err);
+			(path, None)
+		}
+		Ok(async_data) => {
+			let data = async_data.into_std().await;
+			let mut data = BufReader::new(data);
+			let path_clone = path.clone();
+			let result = tokio::task::spawn_blocking(move || -> BinResult<Vec<FoundResource>> {
+				let header = DBPFFile::read(&mut data)?;
+				Ok(header
+					.index
+					.into_iter()
+					.filter_map(|mut entry| {
+						get_texture(&path_clone, &mut entry, &mut data)
+							.map(FoundResource::Texture)
+							.or_else(|| {
+								get_mesh(&path_clone, &mut entry, &mut data)
+									.map(FoundResource::Mesh)
+							})
+					})
+					.collect())
+			})
+			.await
+			.unwrap();
+			match result {
+				Ok(resources) => (path, Some(resources)),
+				Err(err) => {
+					error!(?err);
+					(path, None)
+				}
+			}
+		}
+	}
+}
+
+/// scan all packages in the given directories for textures and meshes
+pub async fn find_resources(
+	dirs: Vec<PathBuf>,
+	tx: Sender<FoundResource>,
+	mut progress: impl FnMut(PathBuf, usize, usize),
+) {
+	let files_futures_vec = dirs
+		.iter()
+		.flat_map(|dir| {
+			WalkDir::new(dir)
+				.sort_by_file_name()
+				.into_iter()
+				.filter_map(|entry| {
+					let path = entry
+						.inspect_err(|err| error!(?err))
+						.ok()?
+						.path()
+						.to_path_buf();
+					if path.extension() == Some(OsStr::new("package")) {
+						Some(get_path_resources(path.clone()))
+					} else {
+						None
+					}
+				})
+		})
+		.enumerate()
+		.map(|(i, fut)| {
+			progress(PathBuf::from(""), 0, i);
+			fut
+		})
+		.collect::<Vec<_>>();
+	let total_files = files_futures_vec.len();
+	progress(PathBuf::from(""), 0, total_files);
+
+	let mut tgis_stream = stream::iter(files_futures_vec.into_iter())
+		.buffered(num_cpus::get())
+		.enumerate();
+
+	while let Some((i, (path, data))) = tgis_stream.next().await {
+		progress(path.clone(), i + 1, total_files);
+		if let Some(resources) = data {
+			for resource in resources.into_iter() {
+				if tx.send(resource).is_err() {
+					return;
+				}
+			}
+		}
+	}
+}
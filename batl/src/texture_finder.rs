@@ -4,27 +4,22 @@
 
 use std::{
 	cmp::max,
-	ffi::OsStr,
 	fmt::{Debug, Formatter},
 	io::{Cursor, Read, Seek},
-	path::PathBuf,
-	sync::mpsc::Sender,
+	path::{Path, PathBuf},
 };
 
-use binrw::{io::BufReader, BinRead, BinResult, BinWrite};
+use binrw::{BinRead, BinWrite};
 use dbpf::{
 	filetypes::{DBPFFileType, DBPFFileType::Known, KnownDBPFFileType},
 	internal_file::{
 		resource_collection::{texture_resource::TextureFormat, ResourceData},
 		CompressionError, DecodedFile,
 	},
-	DBPFFile,
+	IndexEntry,
 };
-use futures::{stream, StreamExt};
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
-use tokio::fs::File;
 use tracing::error;
-use walkdir::WalkDir;
 
 fn ser_file_type<S: Serializer>(t: &DBPFFileType, ser: S) -> Result<S::Ok, S::Error> {
 	ser.serialize_u32(t.code())
@@ -80,14 +75,14 @@ impl Debug for Tgi {
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Default, Debug, Serialize, Deserialize)]
-pub struct TextureId {
+pub struct ResourceId {
 	pub path: PathBuf,
 	pub tgi: Tgi,
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Default, Debug, Serialize, Deserialize)]
 pub struct FoundTexture {
-	pub id: TextureId,
+	pub id: ResourceId,
 
 	pub width: u32,
 	pub height: u32,
@@ -101,137 +96,56 @@ pub struct FoundTexture {
 	pub memory_size: usize,
 }
 
-fn get_textures<R: Read + Seek>(
-	path: PathBuf,
-	header: DBPFFile,
+pub(crate) fn get_texture<R: Read + Seek>(
+	path: &Path,
+	file: &mut IndexEntry,
 	reader: &mut R,
-) -> impl Iterator<Item = FoundTexture> + use<'_, R> {
-	header.index.into_iter().filter_map(move |mut file| {
-		let type_id = file.type_id;
-		let group_id = file.group_id;
-		let instance_id = file.instance_id;
+) -> Option<FoundTexture> {
+	let type_id = file.type_id;
+	let group_id = file.group_id;
+	let instance_id = file.instance_id;
 
-		if type_id == Known(KnownDBPFFileType::TextureResource) {
-			match file
-				.data(reader)
-				.map_err(CompressionError::BinResult)
-				.and_then(|d| d.decoded())
-				.inspect_err(|err| {
-					error!(?path, ?err);
-				})
-				.ok()?
-				.expect("TextureResource should always be allowed to decode")
-			{
-				DecodedFile::ResourceCollection(res) => match &res.entries.first()?.data {
-					ResourceData::Texture(tex) => {
-						let total_memory = (0..tex.mip_levels())
-							.map(|i| {
-								tex.get_format().compressed_size(
-									max(tex.width as usize >> i, 1),
-									max(tex.height as usize >> i, 1),
-								) * tex.textures.len()
-							})
-							.sum();
-						Some(FoundTexture {
-							id: TextureId {
-								path: path.clone(),
-								tgi: Tgi {
-									type_id,
-									group_id,
-									instance_id: instance_id.id,
-								},
-							},
-							width: tex.width,
-							height: tex.height,
-							format: tex.get_format(),
-							mip_levels: tex.mip_levels() as u32,
-							memory_size: total_memory,
-						})
-					}
-					_ => None,
-				},
-				_ => None,
-			}
-		} else {
-			None
-		}
-	})
-}
-
-async fn get_path_textures(path: PathBuf) -> (PathBuf, Option<Vec<FoundTexture>>) {
-	match File::open(&path).await {
-		Err(err) => {
-			error!(?err);
-			(path, None)
-		}
-		Ok(async_data) => {
-			let data = async_data.into_std().await;
-			let mut data = BufReader::new(data);
-			let path_clone = path.clone();
-			let result = tokio::task::spawn_blocking(move || -> BinResult<Vec<FoundTexture>> {
-				Ok(
-					get_textures(path_clone.clone(), DBPFFile::read(&mut data)?, &mut data)
-						.collect(),
-				)
-			})
-			.await
-			.unwrap();
-			match result {
-				Ok(textures) => (path, Some(textures)),
-				Err(err) => {
-					error!(?err);
-					(path, None)
-				}
-			}
-		}
+	if type_id != Known(KnownDBPFFileType::TextureResource) {
+		return None;
 	}
-}
-
-pub async fn find_textures(
-	dirs: Vec<PathBuf>,
-	tx: Sender<FoundTexture>,
-	mut progress: impl FnMut(PathBuf, usize, usize),
-) {
-	let files_futures_vec = dirs
-		.iter()
-		.flat_map(|dir| {
-			WalkDir::new(dir)
-				.sort_by_file_name()
-				.into_iter()
-				.filter_map(|entry| {
-					let path = entry
-						.inspect_err(|err| error!(?err))
-						.ok()?
-						.path()
-						.to_path_buf();
-					if path.extension() == Some(OsStr::new("package")) {
-						Some(get_path_textures(path.clone()))
-					} else {
-						None
-					}
-				})
-		})
-		.enumerate()
-		.map(|(i, fut)| {
-			progress(PathBuf::from(""), 0, i);
-			fut
+	match file
+		.data(reader)
+		.map_err(CompressionError::BinResult)
+		.and_then(|d| d.decoded())
+		.inspect_err(|err| {
+			error!(?path, ?err);
 		})
-		.collect::<Vec<_>>();
-	let total_files = files_futures_vec.len();
-	progress(PathBuf::from(""), 0, total_files);
-
-	let mut tgis_stream = stream::iter(files_futures_vec.into_iter())
-		.buffered(num_cpus::get())
-		.enumerate();
-
-	while let Some((i, (path, data))) = tgis_stream.next().await {
-		progress(path.clone(), i + 1, total_files);
-		if let Some(textures) = data {
-			for tex in textures.into_iter() {
-				if tx.send(tex).is_err() {
-					return;
-				}
+		.ok()?
+		.expect("TextureResource should always be allowed to decode")
+	{
+		DecodedFile::ResourceCollection(res) => match &res.entries.first()?.data {
+			ResourceData::Texture(tex) => {
+				let total_memory = (0..tex.mip_levels())
+					.map(|i| {
+						tex.get_format().compressed_size(
+							max(tex.width as usize >> i, 1),
+							max(tex.height as usize >> i, 1),
+						) * tex.textures.len()
+					})
+					.sum();
+				Some(FoundTexture {
+					id: ResourceId {
+						path: path.to_path_buf(),
+						tgi: Tgi {
+							type_id,
+							group_id,
+							instance_id: instance_id.id,
+						},
+					},
+					width: tex.width,
+					height: tex.height,
+					format: tex.get_format(),
+					mip_levels: tex.mip_levels() as u32,
+					memory_size: total_memory,
+				})
 			}
-		}
+			_ => None,
+		},
+		_ => None,
 	}
 }
@@ -4,7 +4,10 @@
 
 use binrw::{io::BufReader, BinRead};
 use dbpf::{
-	internal_file::{resource_collection::ResourceData, DecodedFile},
+	internal_file::{
+		resource_collection::{level_information::find_level_information, ResourceData},
+		DecodedFile,
+	},
 	DBPFFile,
 };
 use eframe::{
@@ -20,7 +23,7 @@ use std::{
 use tokio::fs::File;
 use tracing::error;
 
-use crate::texture_finder::TextureId;
+use crate::texture_finder::ResourceId;
 use crate::ui_image_cache::LoadingState::Loaded;
 
 #[derive(Copy, Clone, Debug)]
@@ -39,7 +42,7 @@ impl<T> LoadingState<T> {
 }
 
 pub struct ImageCache {
-	cache: Arc<Mutex<LruCache<TextureId, LoadingState<TextureHandle>>>>,
+	cache: Arc<Mutex<LruCache<ResourceId, LoadingState<TextureHandle>>>>,
 }
 
 impl ImageCache {
@@ -50,8 +53,8 @@ impl ImageCache {
 	}
 
 	async fn fetch_texture(
-		cache: Weak<Mutex<LruCache<TextureId, LoadingState<TextureHandle>>>>,
-		id: TextureId,
+		cache: Weak<Mutex<LruCache<ResourceId, LoadingState<TextureHandle>>>>,
+		id: ResourceId,
 		ctx: egui::Context,
 	) {
 		match File::open(&id.path).await {
@@ -63,22 +66,19 @@ impl ImageCache {
 				let mut data = BufReader::new(data);
 				let mut file = DBPFFile::read(&mut data).unwrap();
 				let index = file
-					.index
-					.iter_mut()
-					.find(|entry| {
-						entry.type_id == id.tgi.type_id
-							&& entry.group_id == id.tgi.group_id
-							&& entry.instance_id.id == id.tgi.instance_id
-					})
+					.find_entry(id.tgi.type_id, id.tgi.group_id, id.tgi.instance_id)
 					.unwrap();
 				let idata = index.data(&mut data).unwrap();
 
 				let decoded = idata.decoded().unwrap().unwrap();
 				if let DecodedFile::ResourceCollection(res) = decoded {
-					let ResourceData::Texture(tex) = &mut res.entries.first_mut().unwrap().data
-					else {
+					let ResourceData::Texture(tex) = &res.entries.first().unwrap().data else {
 						return;
 					};
+					let mut tex = tex.clone();
+					if tex.any_lifo() {
+						tex.embed_lifo(&find_level_information(&mut file, &mut data));
+					}
 					let found_texture = tex.decompress_all().into_iter().find_map(|t| {
 						t.into_iter().rev().find_map(|mip| {
 							mip.ok().map(|decoded_tex| {
@@ -106,7 +106,7 @@ impl ImageCache {
 	}
 
 	/// get, and if not found, fetch the texture from disk asynchronously, returning an `egui::TextureHandle`
-	pub fn get(&mut self, id: &TextureId, ctx: &egui::Context) -> Option<TextureHandle> {
+	pub fn get(&mut self, id: &ResourceId, ctx: &egui::Context) -> Option<TextureHandle> {
 		let mut cache = self.cache.lock().unwrap();
 		let o = cache.get(id).cloned();
 		if o.is_none() {
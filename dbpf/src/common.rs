@@ -75,6 +75,15 @@ impl<C, T: Debug> DerefMut for SizedVec<C, T> {
 	}
 }
 
+impl<C, T: Debug> SizedVec<C, T> {
+	pub fn new(data: Vec<T>) -> Self {
+		Self {
+			_t: PhantomData,
+			data,
+		}
+	}
+}
+
 impl<C, T: Debug> WriteEndian for SizedVec<C, T> {
 	const ENDIAN: EndianKind = EndianKind::Endian(Endian::Little);
 }
@@ -516,6 +525,80 @@ pub struct Guid {
 	pub id: u32,
 }
 
+/// an enum that is stored as a number, with a fallback for numbers that are not known
+macro_rules! numbered_enum {
+	($(#[$meta:meta])* $name:ident: $repr:ty { $($variant:ident = $value:literal => $text:literal,)* }) => {
+		$(#[$meta])*
+		#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+		pub enum $name {
+			$($variant,)*
+			Unknown($repr),
+		}
+
+		impl Default for $name {
+			fn default() -> Self {
+				Self::from(0)
+			}
+		}
+
+		impl From<$repr> for $name {
+			fn from(value: $repr) -> Self {
+				match value {
+					$($value => Self::$variant,)*
+					n => Self::Unknown(n),
+				}
+			}
+		}
+
+		impl From<$name> for $repr {
+			fn from(value: $name) -> Self {
+				match value {
+					$($name::$variant => $value,)*
+					$name::Unknown(n) => n,
+				}
+			}
+		}
+
+		impl $name {
+			pub const KNOWN: &'static [$name] = &[$($name::$variant,)*];
+
+			pub fn name(&self) -> Option<&'static str> {
+				match self {
+					$($name::$variant => Some($text),)*
+					$name::Unknown(_) => None,
+				}
+			}
+		}
+
+		impl binrw::BinRead for $name {
+			type Args<'a> = ();
+
+			fn read_options<R: std::io::Read + std::io::Seek>(
+				reader: &mut R,
+				endian: binrw::Endian,
+				args: Self::Args<'_>,
+			) -> binrw::BinResult<Self> {
+				<$repr>::read_options(reader, endian, args).map(Self::from)
+			}
+		}
+
+		impl binrw::BinWrite for $name {
+			type Args<'a> = ();
+
+			fn write_options<W: std::io::Write + std::io::Seek>(
+				&self,
+				writer: &mut W,
+				endian: binrw::Endian,
+				args: Self::Args<'_>,
+			) -> binrw::BinResult<()> {
+				<$repr>::from(*self).write_options(writer, endian, args)
+			}
+		}
+	};
+}
+
+pub(crate) use numbered_enum;
+
 #[cfg(test)]
 mod test {
 	use std::io::{Cursor, Seek};
@@ -11,7 +11,7 @@ use crate::{
 	filetypes::DBPFFileType,
 	header_v1::{IndexV1, IndexV1BinReadArgs},
 	header_v2::{IndexV2, IndexV2BinReadArgs},
-	internal_file::{CompressionError, FileData, FileDataBinReadArgs},
+	internal_file::{CompressionError, DecodedFile, FileData, FileDataBinReadArgs},
 	lazy_file_ptr::{LazyFilePtr, Zero},
 	CompressionType, IndexMinorVersion, IndexVersion, Timestamp, UserVersion, Version, HEADER_SIZE,
 };
@@ -103,6 +103,73 @@ impl IndexEntry {
 	pub fn data<R: Read + Seek>(&mut self, reader: &mut R) -> BinResult<&mut FileData> {
 		self.data.get(reader)
 	}
+
+	/// The decoded data of this entry, if it has been decoded already
+	///
+	/// Only decoded entries are encoded again when the package is written,
+	/// all others are written exactly as they were loaded.
+	pub fn decoded_data(&self) -> Option<&DecodedFile> {
+		self.data.get_read().and_then(FileData::as_decoded)
+	}
+}
+
+#[cfg(test)]
+impl IndexEntry {
+	/// an entry that holds already decoded data, for tests that look up resources in a package
+	pub(crate) fn from_decoded(
+		type_id: DBPFFileType,
+		group_id: u32,
+		instance_id: u64,
+		decoded: DecodedFile,
+	) -> Self {
+		Self::from_internal(
+			type_id,
+			group_id,
+			instance_id,
+			crate::internal_file::FileDataInternal::Decoded(decoded),
+		)
+	}
+
+	/// an entry that holds undecoded, uncompressed data, for tests with resources that fail to decode
+	pub(crate) fn from_raw(
+		type_id: DBPFFileType,
+		group_id: u32,
+		instance_id: u64,
+		data: Vec<u8>,
+	) -> Self {
+		Self::from_internal(
+			type_id,
+			group_id,
+			instance_id,
+			crate::internal_file::FileDataInternal::Uncompressed(
+				crate::internal_file::RawFileData { data },
+			),
+		)
+	}
+
+	fn from_internal(
+		type_id: DBPFFileType,
+		group_id: u32,
+		instance_id: u64,
+		data: crate::internal_file::FileDataInternal,
+	) -> Self {
+		Self {
+			type_id,
+			group_id,
+			instance_id: InstanceId { id: instance_id },
+			compression: CompressionType::Uncompressed,
+			data: LazyFilePtr::from_data(
+				FileData { type_id, data },
+				binrw::Endian::Little,
+				FileDataBinReadArgs::builder()
+					.count(0)
+					.compression_type(CompressionType::Uncompressed)
+					.decompressed_size(0)
+					.type_id(type_id)
+					.finalize(),
+			),
+		}
+	}
 }
 
 #[binrw]
@@ -129,6 +196,20 @@ pub trait Index {
 }
 
 impl DBPFFile {
+	/// Find the index entry with the given type, group and instance id
+	pub fn find_entry(
+		&mut self,
+		type_id: DBPFFileType,
+		group_id: u32,
+		instance_id: u64,
+	) -> Option<&mut IndexEntry> {
+		self.index.iter_mut().find(|entry| {
+			entry.type_id == type_id
+				&& entry.group_id == group_id
+				&& entry.instance_id.id == instance_id
+		})
+	}
+
 	pub fn write<W: Write + Seek, R: Read + Seek>(
 		&mut self,
 		writer: &mut W,
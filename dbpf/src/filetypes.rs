@@ -418,7 +418,7 @@ impl KnownDBPFFileType {
 			StackScript => ("Stack Script", "POSI", vec![], No),
 			PackageToolkit => ("Package Toolkit", "PTBP", vec![], No),
 			SimInformation => ("Sim Information", "SIMI", vec![], No),
-			ObjectSlot => ("Object Slot", "SLOT", vec![], No),
+			ObjectSlot => ("Object Slot", "SLOT", vec![], Embedded),
 			Sprites => ("Sprites", "SPR2", vec![], Embedded),
 			TextList => ("Text List", "STR", vec![], Embedded),
 			TATT => ("TATT", "TATT", vec![], Embedded),
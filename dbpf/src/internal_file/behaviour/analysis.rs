@@ -0,0 +1,437 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	collections::VecDeque,
+	fmt::{Display, Formatter},
+};
+
+use crate::internal_file::behaviour::{
+	behaviour_function::{BehaviourFunction, Goto, Instruction, Signature},
+	primitive::{DataOwner, FIRST_CALL_OPCODE},
+};
+
+/// primitives that can wait for the game to continue, so a loop containing them does not hang the game
+const YIELDING_OPCODES: &[u16] = &[
+	0x00, // Sleep
+	0x11, // Idle For Input
+	0x1B, // Go To Relative Position
+	0x24, // Dialog
+	0x26, // Dialog (Global Strings)
+	0x27, // Dialog (Semi-Global Strings)
+	0x2C, // Animate Sim
+	0x2D, // Go To Routing Slot
+];
+
+/// A possible problem in a BHAV
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Finding {
+	/// no path from the first instruction reaches this one
+	Unreachable {
+		instruction: usize,
+	},
+	MissingTarget {
+		instruction: usize,
+		target: u16,
+	},
+	/// a branch that can be taken returns an error
+	ReachesError {
+		instruction: usize,
+		true_branch: bool,
+	},
+	/// instructions that jump to each other without a way out and without waiting
+	InfiniteLoop {
+		instructions: Vec<usize>,
+	},
+	ParameterOutOfRange {
+		instruction: usize,
+		index: u16,
+		count: u8,
+	},
+	LocalOutOfRange {
+		instruction: usize,
+		index: u16,
+		count: u8,
+	},
+	NodeVersion {
+		instruction: usize,
+		version: u8,
+		signature: Signature,
+	},
+}
+
+impl Finding {
+	/// The first instruction the finding is about
+	pub fn instruction(&self) -> usize {
+		match self {
+			Finding::Unreachable { instruction }
+			| Finding::MissingTarget { instruction, .. }
+			| Finding::ReachesError { instruction, .. }
+			| Finding::ParameterOutOfRange { instruction, .. }
+			| Finding::LocalOutOfRange { instruction, .. }
+			| Finding::NodeVersion { instruction, .. } => *instruction,
+			Finding::InfiniteLoop { instructions } => {
+				instructions.first().copied().unwrap_or_default()
+			}
+		}
+	}
+}
+
+impl Display for Finding {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Finding::Unreachable { instruction } => {
+				write!(f, "instruction {instruction} is never run")
+			}
+			Finding::MissingTarget {
+				instruction,
+				target,
+			} => write!(
+				f,
+				"instruction {instruction} jumps to instruction {target}, which does not exist"
+			),
+			Finding::ReachesError {
+				instruction,
+				true_branch,
+			} => write!(
+				f,
+				"the {} branch of instruction {instruction} returns an error",
+				if *true_branch { "true" } else { "false" }
+			),
+			Finding::InfiniteLoop { instructions } => write!(
+				f,
+				"instructions {instructions:?} loop forever without waiting"
+			),
+			Finding::ParameterOutOfRange {
+				instruction,
+				index,
+				count,
+			} => write!(
+				f,
+				"instruction {instruction} uses Param {index}, the tree has {count} parameters"
+			),
+			Finding::LocalOutOfRange {
+				instruction,
+				index,
+				count,
+			} => write!(
+				f,
+				"instruction {instruction} uses Local {index}, the tree has {count} locals"
+			),
+			Finding::NodeVersion {
+				instruction,
+				version,
+				signature,
+			} if *signature < Signature::V5 => write!(
+				f,
+				"instruction {instruction} has node version {version}, which signature {signature:?} does not store"
+			),
+			Finding::NodeVersion {
+				instruction,
+				version,
+				..
+			} => write!(
+				f,
+				"instruction {instruction} has unknown node version {version}"
+			),
+		}
+	}
+}
+
+/// the branches of an instruction that can be taken, see [crate::internal_file::behaviour::primitive::Function::can_return_false]
+fn taken_branches(instruction: &Instruction) -> Vec<(bool, Goto)> {
+	if instruction.function().can_return_false() {
+		vec![
+			(true, instruction.true_target),
+			(false, instruction.false_target),
+		]
+	} else {
+		vec![(true, instruction.true_target)]
+	}
+}
+
+fn successors(instructions: &[Instruction]) -> Vec<Vec<usize>> {
+	instructions
+		.iter()
+		.map(|instruction| {
+			taken_branches(instruction)
+				.into_iter()
+				.filter_map(|(_, target)| match target {
+					Goto::Instr(i) if (i as usize) < instructions.len() => Some(i as usize),
+					_ => None,
+				})
+				.collect()
+		})
+		.collect()
+}
+
+/// the strongly connected components of the graph, using Tarjan's algorithm without recursion
+fn strongly_connected(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+	let n = successors.len();
+	let mut index = vec![usize::MAX; n];
+	let mut low = vec![0; n];
+	let mut on_stack = vec![false; n];
+	let mut stack = vec![];
+	let mut components = vec![];
+	let mut next = 0;
+
+	for root in 0..n {
+		if index[root] != usize::MAX {
+			continue;
+		}
+		index[root] = next;
+		low[root] = next;
+		next += 1;
+		stack.push(root);
+		on_stack[root] = true;
+		let mut call_stack = vec![(root, 0)];
+
+		while let Some(&(v, i)) = call_stack.last() {
+			if let Some(&w) = successors[v].get(i) {
+				call_stack.last_mut().unwrap().1 += 1;
+				if index[w] == usize::MAX {
+					index[w] = next;
+					low[w] = next;
+					next += 1;
+					stack.push(w);
+					on_stack[w] = true;
+					call_stack.push((w, 0));
+				} else if on_stack[w] {
+					low[v] = low[v].min(index[w]);
+				}
+			} else {
+				call_stack.pop();
+				if let Some(&(parent, _)) = call_stack.last() {
+					low[parent] = low[parent].min(low[v]);
+				}
+				if low[v] == index[v] {
+					let mut component = vec![];
+					while let Some(w) = stack.pop() {
+						on_stack[w] = false;
+						component.push(w);
+						if w == v {
+							break;
+						}
+					}
+					component.sort();
+					components.push(component);
+				}
+			}
+		}
+	}
+	components
+}
+
+impl BehaviourFunction {
+	/// Look for instructions that are never run, broken gotos, loops that hang the game,
+	/// and operands that use parameters or locals the tree does not have
+	pub fn analyze(&self) -> Vec<Finding> {
+		let instructions = &self.instructions;
+		let successors = successors(instructions);
+		let mut findings = vec![];
+
+		let mut reachable = vec![false; instructions.len()];
+		let mut open = VecDeque::new();
+		if !instructions.is_empty() {
+			reachable[0] = true;
+			open.push_back(0);
+		}
+		while let Some(i) = open.pop_front() {
+			for &next in &successors[i] {
+				if !reachable[next] {
+					reachable[next] = true;
+					open.push_back(next);
+				}
+			}
+		}
+
+		for (i, instruction) in instructions.iter().enumerate() {
+			if !reachable[i] {
+				findings.push(Finding::Unreachable { instruction: i });
+			}
+			for target in [instruction.true_target, instruction.false_target] {
+				if let Goto::Instr(target) = target {
+					if target as usize >= instructions.len() {
+						findings.push(Finding::MissingTarget {
+							instruction: i,
+							target,
+						});
+					}
+				}
+			}
+			for (true_branch, target) in taken_branches(instruction) {
+				if target == Goto::Error {
+					findings.push(Finding::ReachesError {
+						instruction: i,
+						true_branch,
+					});
+				}
+			}
+			for (owner, index) in instruction.function().data_references() {
+				match owner {
+					DataOwner::Parameter if index >= self.num_parameters as u16 => {
+						findings.push(Finding::ParameterOutOfRange {
+							instruction: i,
+							index,
+							count: self.num_parameters,
+						})
+					}
+					DataOwner::Local if index >= self.num_locals as u16 => {
+						findings.push(Finding::LocalOutOfRange {
+							instruction: i,
+							index,
+							count: self.num_locals,
+						})
+					}
+					_ => {}
+				}
+			}
+			let stored = self.signature >= Signature::V5;
+			if (!stored && instruction.node_version != 0) || instruction.node_version > 1 {
+				findings.push(Finding::NodeVersion {
+					instruction: i,
+					version: instruction.node_version,
+					signature: self.signature,
+				});
+			}
+		}
+
+		for component in strongly_connected(&successors) {
+			let is_loop = component.len() > 1 || successors[component[0]].contains(&component[0]);
+			let has_exit = component.iter().any(|&i| {
+				taken_branches(&instructions[i])
+					.into_iter()
+					.any(|(_, target)| match target {
+						Goto::Instr(t) => component.binary_search(&(t as usize)).is_err(),
+						_ => true,
+					})
+			});
+			// called trees might wait, so only loops of primitives are checked
+			let waits = component.iter().any(|&i| {
+				let opcode = instructions[i].opcode;
+				opcode >= FIRST_CALL_OPCODE || YIELDING_OPCODES.contains(&opcode)
+			});
+			if is_loop && !has_exit && !waits {
+				findings.push(Finding::InfiniteLoop {
+					instructions: component,
+				});
+			}
+		}
+
+		findings.sort_by_key(Finding::instruction);
+		findings
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::prop_assert_eq;
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::internal_file::behaviour::primitive::{Expression, ExpressionOperator, Function};
+
+	fn expression(
+		operator: ExpressionOperator,
+		true_target: Goto,
+		false_target: Goto,
+	) -> Instruction {
+		let mut instruction = Instruction {
+			true_target,
+			false_target,
+			operands: vec![0; 16],
+			..Default::default()
+		};
+		instruction.set_function(&Function::Expression(Expression {
+			operator,
+			lhs_owner: DataOwner::Local,
+			rhs_owner: DataOwner::Literal,
+			..Default::default()
+		}));
+		instruction
+	}
+
+	fn assignment(true_target: Goto) -> Instruction {
+		expression(ExpressionOperator::Assign, true_target, Goto::Error)
+	}
+
+	fn chain(n: u16) -> BehaviourFunction {
+		BehaviourFunction {
+			num_locals: 1,
+			instructions: (0..n)
+				.map(|i| {
+					assignment(if i + 1 < n {
+						Goto::Instr(i + 1)
+					} else {
+						Goto::True
+					})
+				})
+				.collect(),
+			..Default::default()
+		}
+	}
+
+	#[proptest]
+	fn chain_of_assignments_is_clean(#[strategy(1u16..40)] n: u16) {
+		prop_assert_eq!(chain(n).analyze(), vec![]);
+	}
+
+	#[proptest]
+	fn detached_instruction_is_unreachable(#[strategy(1u16..40)] n: u16) {
+		let mut bhav = chain(n);
+		bhav.instructions.push(assignment(Goto::True));
+		prop_assert_eq!(
+			bhav.analyze(),
+			vec![Finding::Unreachable {
+				instruction: n as usize
+			}]
+		);
+	}
+
+	#[proptest]
+	fn closed_loop_is_infinite(#[strategy(1u16..40)] n: u16) {
+		let mut bhav = chain(n);
+		bhav.instructions.last_mut().unwrap().true_target = Goto::Instr(0);
+		prop_assert_eq!(
+			bhav.analyze(),
+			vec![Finding::InfiniteLoop {
+				instructions: (0..n as usize).collect()
+			}]
+		);
+	}
+
+	#[proptest]
+	fn counting_loop_is_clean(#[strategy(1u16..40)] n: u16) {
+		let mut bhav = chain(n);
+		*bhav.instructions.last_mut().unwrap() = expression(
+			ExpressionOperator::IncrementAndLessThan,
+			Goto::Instr(0),
+			Goto::True,
+		);
+		prop_assert_eq!(bhav.analyze(), vec![]);
+	}
+
+	#[proptest]
+	fn always_true_primitive_does_not_reach_error(
+		#[strategy(1u16..40)] n: u16,
+		#[strategy(0usize..3)] kind: usize,
+	) {
+		let mut bhav = chain(n);
+		bhav.num_parameters = 1;
+		bhav.instructions.last_mut().unwrap().true_target = Goto::Instr(n);
+		let mut instruction = Instruction {
+			true_target: Goto::True,
+			false_target: Goto::Error,
+			operands: vec![0; 16],
+			..Default::default()
+		};
+		instruction.set_function(&match kind {
+			0 => Function::Sleep(Default::default()),
+			1 => Function::RandomNumber(Default::default()),
+			_ => Function::AnimateSim(Default::default()),
+		});
+		bhav.instructions.push(instruction);
+		prop_assert_eq!(bhav.analyze(), vec![]);
+	}
+}
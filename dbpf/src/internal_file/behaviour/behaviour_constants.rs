@@ -2,9 +2,42 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::fmt::Write as _;
+
 use binrw::binrw;
+use thiserror::Error;
+
+use crate::{
+	common::FileName,
+	internal_file::behaviour::behaviour_constants_labels::{BehaviourConstantsLabels, Label},
+};
 
-use crate::common::FileName;
+/// the columns of a tuning table, only index and value are read back
+const CSV_COLUMNS: [&str; 7] = [
+	"index",
+	"value",
+	"name",
+	"default",
+	"min",
+	"max",
+	"description",
+];
+
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum TuningCsvError {
+	#[error("the first line should name the columns, the {0} column is missing")]
+	MissingColumn(&'static str),
+	#[error("line {line}: {value:?} is not a valid {column}")]
+	InvalidValue {
+		line: usize,
+		column: &'static str,
+		value: String,
+	},
+	#[error("line {line}: a quoted field is not closed")]
+	UnclosedQuote { line: usize },
+	#[error("line {line}: index {index} does not fit, a BCON holds at most 255 constants")]
+	TooManyConstants { line: usize, index: usize },
+}
 
 #[binrw]
 #[brw(little)]
@@ -19,3 +52,254 @@ pub struct BehaviourConstants {
 	#[br(count = count)]
 	pub constants: Vec<u16>,
 }
+
+/// A constant together with the label that describes it
+#[derive(Copy, Clone, Debug)]
+pub struct TuningConstant<'a> {
+	pub index: usize,
+	pub value: u16,
+	pub label: Option<&'a Label>,
+}
+
+impl TuningConstant<'_> {
+	/// False if the label gives a range and the value is outside of it
+	pub fn in_range(&self) -> bool {
+		self.label
+			.and_then(Label::range)
+			.is_none_or(|range| range.contains(&self.value))
+	}
+}
+
+/// write a csv field, quoted if needed, line breaks become spaces so every constant stays on one line
+fn csv_field(out: &mut String, field: &str) {
+	let field = field.replace(['\n', '\r'], " ");
+	if field.contains([',', '"']) {
+		let _ = write!(out, "\"{}\"", field.replace('"', "\"\""));
+	} else {
+		out.push_str(&field);
+	}
+}
+
+/// split a csv line into fields, quotes may contain commas and doubled quotes
+fn csv_fields(line: &str) -> Option<Vec<String>> {
+	let mut fields = vec![];
+	let mut field = String::new();
+	let mut chars = line.chars().peekable();
+	let mut quoted = false;
+	while let Some(c) = chars.next() {
+		match (c, quoted) {
+			('"', true) if chars.peek() == Some(&'"') => {
+				chars.next();
+				field.push('"');
+			}
+			('"', _) => quoted = !quoted,
+			(',', false) => fields.push(std::mem::take(&mut field)),
+			(c, _) => field.push(c),
+		}
+	}
+	fields.push(field);
+	(!quoted).then_some(fields)
+}
+
+/// a constant as written in a tuning table, decimal, negative or hexadecimal with 0x
+fn parse_constant(value: &str) -> Option<u16> {
+	let value = value.trim();
+	if let Some(hex) = value.strip_prefix("0x").or(value.strip_prefix("0X")) {
+		u16::from_str_radix(hex, 16).ok()
+	} else if value.starts_with('-') {
+		value.parse::<i16>().ok().map(|v| v as u16)
+	} else {
+		value.parse().ok()
+	}
+}
+
+impl BehaviourConstants {
+	/// Every constant with its label from the TRCN with the same instance, if there is one
+	pub fn tuning<'a>(
+		&'a self,
+		labels: Option<&'a BehaviourConstantsLabels>,
+	) -> impl Iterator<Item = TuningConstant<'a>> + 'a {
+		self.constants
+			.iter()
+			.enumerate()
+			.map(move |(index, &value)| TuningConstant {
+				index,
+				value,
+				label: labels.and_then(|labels| labels.label(index)),
+			})
+	}
+
+	/// Write the constants as a CSV tuning table, with the name, default, range and description of their label
+	pub fn to_csv(&self, labels: Option<&BehaviourConstantsLabels>) -> String {
+		let mut out = CSV_COLUMNS.join(",");
+		out.push('\n');
+		for constant in self.tuning(labels) {
+			let label = constant.label;
+			let range = label.and_then(Label::range);
+			let fields = [
+				constant.index.to_string(),
+				constant.value.to_string(),
+				label.map(Label::name).unwrap_or_default(),
+				label
+					.and_then(Label::default_value)
+					.map(|v| v.to_string())
+					.unwrap_or_default(),
+				range
+					.as_ref()
+					.map(|r| r.start().to_string())
+					.unwrap_or_default(),
+				range
+					.as_ref()
+					.map(|r| r.end().to_string())
+					.unwrap_or_default(),
+				label.and_then(Label::description).unwrap_or_default(),
+			];
+			for (i, field) in fields.iter().enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				csv_field(&mut out, field);
+			}
+			out.push('\n');
+		}
+		out
+	}
+
+	/// Set the constants from a CSV tuning table like the one from [BehaviourConstants::to_csv]
+	///
+	/// Only the index and value columns are used, constants that are not in the table keep their value.
+	pub fn import_csv(&mut self, csv: &str) -> Result<(), TuningCsvError> {
+		let mut lines = csv
+			.lines()
+			.enumerate()
+			.map(|(i, line)| (i + 1, line))
+			.filter(|(_, line)| !line.trim().is_empty());
+		let header = lines
+			.next()
+			.and_then(|(_, line)| csv_fields(line))
+			.unwrap_or_default();
+		let column = |name: &'static str| {
+			header
+				.iter()
+				.position(|column| column.trim().eq_ignore_ascii_case(name))
+				.ok_or(TuningCsvError::MissingColumn(name))
+		};
+		let (index_column, value_column) = (column("index")?, column("value")?);
+
+		let mut constants = self.constants.clone();
+		for (line, text) in lines {
+			let fields = csv_fields(text).ok_or(TuningCsvError::UnclosedQuote { line })?;
+			let field = |column: usize| fields.get(column).map_or("", String::as_str);
+			let index = field(index_column).trim();
+			let index: usize = index.parse().map_err(|_| TuningCsvError::InvalidValue {
+				line,
+				column: "index",
+				value: index.to_string(),
+			})?;
+			if index >= u8::MAX as usize {
+				return Err(TuningCsvError::TooManyConstants { line, index });
+			}
+			let value = parse_constant(field(value_column)).ok_or_else(|| {
+				TuningCsvError::InvalidValue {
+					line,
+					column: "value",
+					value: field(value_column).to_string(),
+				}
+			})?;
+			if index >= constants.len() {
+				constants.resize(index + 1, 0);
+			}
+			constants[index] = value;
+		}
+		self.constants = constants;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{collection::vec, prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::{
+		common::BigString,
+		internal_file::behaviour::behaviour_constants_labels::{LabelV1, LabelV2},
+	};
+
+	#[proptest]
+	fn csv_roundtrip(
+		#[strategy(vec(0u16.., 0..255))] constants: Vec<u16>,
+		#[strategy(vec(("[a-z ,\"]{0,10}", 0u16..10, 0u16..10), 0..20))] labels: Vec<(
+			String,
+			u16,
+			u16,
+		)>,
+	) {
+		let bcon = BehaviourConstants {
+			constants: constants.clone(),
+			..Default::default()
+		};
+		let labels = BehaviourConstantsLabels {
+			labels: labels
+				.into_iter()
+				.enumerate()
+				.map(|(id, (name, min, max))| {
+					if id % 2 == 0 {
+						Label::V1(LabelV1 {
+							id: id as u32,
+							name: BigString::from(&name),
+							min,
+							max,
+							..Default::default()
+						})
+					} else {
+						Label::V2(LabelV2 {
+							id: id as u32,
+							description: name.as_str().into(),
+							..Default::default()
+						})
+					}
+				})
+				.collect(),
+			..Default::default()
+		};
+		let mut imported = BehaviourConstants::default();
+		imported.import_csv(&bcon.to_csv(Some(&labels)))?;
+		prop_assert_eq!(imported.constants, constants);
+	}
+
+	#[proptest]
+	fn parse_constant_reads_hex_and_negative_values(value: i16) {
+		let raw = value as u16;
+		prop_assert_eq!(parse_constant(&value.to_string()), Some(raw));
+		prop_assert_eq!(parse_constant(&raw.to_string()), Some(raw));
+		prop_assert_eq!(parse_constant(&format!("{raw:#x}")), Some(raw));
+		prop_assert_eq!(parse_constant(&format!(" 0X{raw:04X} ")), Some(raw));
+		prop_assert_eq!(parse_constant(&format!("{raw:#x}z")), None);
+	}
+
+	#[proptest]
+	fn in_range_checks_the_label_range(
+		value: u16,
+		#[strategy(1u16..)] max: u16,
+		#[strategy(0..=#max)] min: u16,
+	) {
+		let label = Label::V1(LabelV1 {
+			min,
+			max,
+			..Default::default()
+		});
+		let constant = |label| TuningConstant {
+			index: 0,
+			value,
+			label,
+		};
+		prop_assert_eq!(
+			constant(Some(&label)).in_range(),
+			(min..=max).contains(&value)
+		);
+		prop_assert!(constant(None).in_range());
+		prop_assert!(constant(Some(&Label::V2(LabelV2::default()))).in_range());
+	}
+}
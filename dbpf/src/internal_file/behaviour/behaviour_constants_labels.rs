@@ -2,9 +2,19 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::ops::RangeInclusive;
+
 use binrw::{args, binrw};
 
-use crate::common::{BigString, FileName, NullString};
+use crate::common::{numbered_enum, BigString, FileName, NullString};
+
+numbered_enum! {
+	Version: u32 {
+		V0 = 0 => "V0",
+		V1 = 1 => "V1",
+		V2 = 2 => "V2",
+	}
+}
 
 #[binrw]
 #[brw(magic = b"NCRT")]
@@ -17,12 +27,12 @@ pub enum Header {
 		#[br(temp)]
 		#[bw(calc = HeaderMagic)]
 		HeaderMagic,
-		u32, // TODO enum
+		Version,
 		u32,
 	),
 	ExtraNulls(
 		u32,
-		u32,
+		Version,
 		#[br(temp)]
 		#[bw(calc = HeaderMagic)]
 		HeaderMagic,
@@ -31,7 +41,7 @@ pub enum Header {
 
 impl Default for Header {
 	fn default() -> Self {
-		Self::Normal(0, 0)
+		Self::Normal(Version::V0, 0)
 	}
 }
 
@@ -66,7 +76,7 @@ pub struct LabelV2 {
 	pub description: NullString,
 	#[br(try)]
 	pub unknown2: Option<LabelV2Tag>,
-	#[brw(if(matches!(header, Header::ExtraNulls(_, 1))))]
+	#[brw(if(matches!(header, Header::ExtraNulls(_, Version::V1))))]
 	pub unknown3: [u8; 5],
 }
 
@@ -86,6 +96,48 @@ impl Default for Label {
 	}
 }
 
+impl Label {
+	pub fn id(&self) -> u32 {
+		match self {
+			Label::V1(label) => label.id,
+			Label::V2(label) => label.id,
+		}
+	}
+
+	pub fn name(&self) -> String {
+		match self {
+			Label::V1(label) => label.name.to_string(),
+			Label::V2(label) => label.name.to_string(),
+		}
+	}
+
+	pub fn description(&self) -> Option<String> {
+		match self {
+			Label::V1(_) => None,
+			Label::V2(label) => Some(label.description.to_string()),
+		}
+	}
+
+	pub fn default_value(&self) -> Option<u16> {
+		match self {
+			Label::V1(label) => Some(label.default),
+			Label::V2(_) => None,
+		}
+	}
+
+	/// The values the constant is meant to have, `None` if the label does not limit it
+	///
+	/// A minimum above the maximum is treated like no limits, as no value would fit it
+	pub fn range(&self) -> Option<RangeInclusive<u16>> {
+		match self {
+			Label::V1(label) if label.min <= label.max && (label.min, label.max) != (0, 0) => {
+				Some(label.min..=label.max)
+			}
+			Label::V1(_) | Label::V2(_) => None,
+		}
+	}
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -101,3 +153,70 @@ pub struct BehaviourConstantsLabels {
 	#[bw(args { header: *header })]
 	pub labels: Vec<Label>,
 }
+
+impl BehaviourConstantsLabels {
+	/// The label of the constant at this index in the BCON
+	///
+	/// Labels are matched by their id, and by their position if no label has that id.
+	pub fn label(&self, index: usize) -> Option<&Label> {
+		self.labels
+			.iter()
+			.find(|label| label.id() as usize == index)
+			.or_else(|| self.labels.get(index))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{collection::vec, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+
+	fn label(id: u32, min: u16, max: u16) -> Label {
+		Label::V1(LabelV1 {
+			id,
+			min,
+			max,
+			..Default::default()
+		})
+	}
+
+	#[proptest]
+	fn range_is_none_without_limits(id: u32, min: u16, max: u16) {
+		let expected = (min <= max && (min != 0 || max != 0)).then_some(min..=max);
+		prop_assert_eq!(label(id, min, max).range(), expected);
+		prop_assert_eq!(label(id, 0, 0).range(), None);
+		if min != max {
+			prop_assert_eq!(label(id, min.max(max), min.min(max)).range(), None);
+		}
+		let v2 = Label::V2(LabelV2 {
+			id,
+			..Default::default()
+		});
+		prop_assert_eq!(v2.range(), None);
+	}
+
+	#[proptest]
+	fn labels_match_by_id_before_position(
+		#[strategy(vec(0u32..20, 1..20))] ids: Vec<u32>,
+		#[strategy(0usize..25)] index: usize,
+	) {
+		let labels = BehaviourConstantsLabels {
+			labels: ids
+				.iter()
+				.enumerate()
+				.map(|(position, &id)| label(id, position as u16, 0))
+				.collect(),
+			..Default::default()
+		};
+		let expected = match ids.iter().position(|&id| id as usize == index) {
+			Some(position) => Some(position),
+			None => (index < ids.len()).then_some(index),
+		};
+		prop_assert_eq!(
+			labels.label(index),
+			expected.map(|position| &labels.labels[position])
+		);
+	}
+}
@@ -8,11 +8,16 @@ use binrw::{
 	args, binrw, BinRead, BinReaderExt, BinResult, BinWrite, BinWriterExt, Endian, NamedArgs,
 };
 
+use thiserror::Error;
+
 use crate::{
 	common::FileName,
-	internal_file::behaviour::behaviour_function::{
-		Goto::{Error, False, Instr, True},
-		Signature::*,
+	internal_file::behaviour::{
+		behaviour_function::{
+			Goto::{Error, False, Instr, True},
+			Signature::*,
+		},
+		primitive::Function,
 	},
 };
 
@@ -33,23 +38,79 @@ pub enum Signature {
 	V9,
 }
 
-#[binrw]
-#[br(import {opcode: u16})]
-pub enum Function {
-	Unknown(
-		#[br(calc = opcode)]
-		#[bw(ignore)]
-		u16,
-		[u8; 16],
-	),
+impl Signature {
+	/// The number of operand bytes of every instruction
+	pub fn operand_count(&self) -> usize {
+		if *self >= V3 {
+			16
+		} else {
+			8
+		}
+	}
+
+	/// The number of instructions that gotos can address, older versions store them in a single byte
+	pub fn max_instructions(&self) -> usize {
+		if *self < V7 {
+			0xFD
+		} else {
+			0xFFFC
+		}
+	}
 }
 
-impl Function {
-	pub fn opcode(&self) -> u16 {
-		match self {
-			Function::Unknown(opcode, _) => *opcode,
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum BehaviourFunctionError {
+	#[error("signature {signature:?} allows at most {max} instructions, found {count}")]
+	TooManyInstructions {
+		signature: Signature,
+		count: usize,
+		max: usize,
+	},
+	#[error("instruction {instruction} jumps to instruction {target}, which does not exist")]
+	MissingTarget { instruction: usize, target: u16 },
+	#[error("instruction {instruction} has {actual} operand bytes, signature {signature:?} needs {expected}")]
+	OperandCount {
+		instruction: usize,
+		signature: Signature,
+		expected: usize,
+		actual: usize,
+	},
+}
+
+fn validate_instructions(
+	signature: Signature,
+	instructions: &[Instruction],
+) -> Result<(), BehaviourFunctionError> {
+	let max = signature.max_instructions();
+	if instructions.len() > max {
+		return Err(BehaviourFunctionError::TooManyInstructions {
+			signature,
+			count: instructions.len(),
+			max,
+		});
+	}
+	for (i, instruction) in instructions.iter().enumerate() {
+		for target in [instruction.true_target, instruction.false_target] {
+			if let Instr(target) = target {
+				if target as usize >= instructions.len() {
+					return Err(BehaviourFunctionError::MissingTarget {
+						instruction: i,
+						target,
+					});
+				}
+			}
+		}
+		let expected = signature.operand_count();
+		if instruction.operands.len() != expected {
+			return Err(BehaviourFunctionError::OperandCount {
+				instruction: i,
+				signature,
+				expected,
+				actual: instruction.operands.len(),
+			});
 		}
 	}
+	Ok(())
 }
 
 #[derive(NamedArgs, Clone, Debug)]
@@ -124,8 +185,6 @@ impl BinWrite for Goto {
 #[brw(import {signature: Signature})]
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Instruction {
-	// #[br(temp)]
-	// #[bw(calc = function.opcode())]
 	pub opcode: u16,
 	#[brw(args {signature})]
 	pub true_target: Goto,
@@ -133,10 +192,23 @@ pub struct Instruction {
 	pub false_target: Goto,
 	#[brw(if(signature >= V5))]
 	pub node_version: u8,
-	#[br(count = if signature >= V3 { 16 } else { 8 })]
-	// #[bw(assert(operands.len() == if signature >= V3 { 16 } else { 8 }))]
+	#[br(count = signature.operand_count())]
 	pub operands: Vec<u8>,
-	// pub function: Function,
+}
+
+impl Instruction {
+	/// The operands decoded according to the opcode
+	pub fn function(&self) -> Function {
+		Function::decode(self.opcode, &self.operands)
+	}
+
+	/// Replace the opcode and operands, the operands keep their current length
+	pub fn set_function(&mut self, function: &Function) {
+		let len = self.operands.len();
+		self.opcode = function.opcode();
+		self.operands = function.operands();
+		self.operands.resize(len, 0);
+	}
 }
 
 #[binrw]
@@ -161,3 +233,111 @@ pub struct BehaviourFunction {
 	#[bw(args { signature: * signature })]
 	pub instructions: Vec<Instruction>,
 }
+
+impl BehaviourFunction {
+	/// Check that every goto points to an instruction and that the instructions fit the signature
+	///
+	/// functions that fail this check can still be written, so packages round-trip unchanged,
+	/// but editors should refuse to save functions they changed into an invalid state
+	pub fn validate(&self) -> Result<(), BehaviourFunctionError> {
+		validate_instructions(self.signature, &self.instructions)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::prop_assert_eq;
+	use test_strategy::proptest;
+
+	use super::*;
+
+	const SIGNATURES: [Signature; 10] = [V0, V1, V2, V3, V4, V5, V6, V7, V8, V9];
+
+	fn chain(signature: Signature, n: usize) -> BehaviourFunction {
+		BehaviourFunction {
+			signature,
+			instructions: (0..n)
+				.map(|i| Instruction {
+					true_target: if i + 1 < n { Instr(i as u16 + 1) } else { True },
+					false_target: Error,
+					operands: vec![0; signature.operand_count()],
+					..Default::default()
+				})
+				.collect(),
+			..Default::default()
+		}
+	}
+
+	#[proptest]
+	fn chain_is_valid(
+		#[strategy(0usize..10)] signature: usize,
+		#[strategy(1usize..=0xFD)] n: usize,
+	) {
+		prop_assert_eq!(chain(SIGNATURES[signature], n).validate(), Ok(()));
+	}
+
+	#[proptest]
+	fn too_many_instructions_before_v7(
+		#[strategy(0usize..7)] signature: usize,
+		#[strategy(0xFEusize..0x110)] n: usize,
+	) {
+		let signature = SIGNATURES[signature];
+		prop_assert_eq!(
+			chain(signature, n).validate(),
+			Err(BehaviourFunctionError::TooManyInstructions {
+				signature,
+				count: n,
+				max: 0xFD,
+			})
+		);
+	}
+
+	#[proptest]
+	fn v7_allows_more_instructions(#[strategy(7usize..10)] signature: usize) {
+		prop_assert_eq!(chain(SIGNATURES[signature], 0x110).validate(), Ok(()));
+	}
+
+	#[proptest]
+	fn goto_past_the_end_is_missing(
+		#[strategy(1usize..40)] n: usize,
+		#[strategy(0u16..20)] past: u16,
+		false_branch: bool,
+	) {
+		let mut bhav = chain(V9, n);
+		let target = n as u16 + past;
+		let last = bhav.instructions.last_mut().unwrap();
+		if false_branch {
+			last.false_target = Instr(target);
+		} else {
+			last.true_target = Instr(target);
+		}
+		prop_assert_eq!(
+			bhav.validate(),
+			Err(BehaviourFunctionError::MissingTarget {
+				instruction: n - 1,
+				target,
+			})
+		);
+	}
+
+	#[proptest]
+	fn operand_count_changes_at_v3(
+		#[strategy(1usize..40)] n: usize,
+		#[strategy(0usize..40)] i: usize,
+	) {
+		let i = i % n;
+		for (signature, expected, actual) in [(V2, 8, 16), (V3, 16, 8)] {
+			let mut bhav = chain(signature, n);
+			bhav.instructions[i].operands = vec![0; actual];
+			prop_assert_eq!(
+				bhav.validate(),
+				Err(BehaviourFunctionError::OperandCount {
+					instruction: i,
+					signature,
+					expected,
+					actual,
+				})
+			);
+		}
+	}
+}
@@ -0,0 +1,463 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Which BHAVs call which, across a set of packages
+//!
+//! Instructions with an opcode of 0x100 and up call another tree, the opcode is the instance of that tree.
+//! The range of the opcode decides the group the tree is looked up in,
+//! see [CallScope].
+
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
+	fmt::Write as _,
+	io::{Read, Seek},
+};
+
+use crate::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		behaviour::behaviour_function::BehaviourFunction,
+		resource_collection::{group_hash, LOCAL_GROUP},
+		CompressionError, DecodedFile,
+	},
+	DBPFFile,
+};
+
+/// the group of the global BHAVs, shared by every object
+pub const GLOBAL_GROUP: u32 = 0x7FD46CD0;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CallScope {
+	/// 0x100 to 0xFFF, trees in the global group
+	Global,
+	/// 0x1000 to 0x1FFF, trees in the group of the caller
+	Local,
+	/// 0x2000 and up, trees in the semi-global group named by the GLOB of the caller
+	SemiGlobal,
+}
+
+impl CallScope {
+	/// The scope of a call, `None` for primitives
+	pub fn of(opcode: u16) -> Option<Self> {
+		match opcode {
+			0..0x100 => None,
+			0x100..0x1000 => Some(CallScope::Global),
+			0x1000..0x2000 => Some(CallScope::Local),
+			_ => Some(CallScope::SemiGlobal),
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TreeId {
+	pub group_id: u32,
+	pub instance_id: u16,
+}
+
+impl TreeId {
+	pub fn global(instance_id: u16) -> Self {
+		Self {
+			group_id: GLOBAL_GROUP,
+			instance_id,
+		}
+	}
+}
+
+/// A package that contains a BHAV
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TreeDefinition {
+	/// the index of the package in [CallGraph::packages]
+	pub package: usize,
+	pub name: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Call {
+	pub caller: TreeId,
+	/// the package the calling BHAV is in
+	pub package: usize,
+	pub instruction: usize,
+	/// the opcode of the instruction, which is the instance of the called tree
+	pub opcode: u16,
+	pub scope: CallScope,
+	/// `None` for semi-global calls from groups without a GLOB
+	pub callee: Option<TreeId>,
+}
+
+/// A GLOB or BHAV that [CallGraph::add_package] skipped because it could not be decoded
+#[derive(Debug)]
+pub struct UndecodedResource {
+	pub type_id: DBPFFileType,
+	pub group_id: u32,
+	pub instance_id: u64,
+	pub error: CompressionError,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CallGraph {
+	pub packages: Vec<String>,
+	/// every package that contains a tree, in the order they were added
+	///
+	/// the game loads the last one, the others are overridden
+	pub trees: BTreeMap<TreeId, Vec<TreeDefinition>>,
+	/// the semi-global group of every group that has a GLOB
+	pub semi_global_groups: HashMap<u32, u32>,
+	pub calls: Vec<Call>,
+}
+
+impl CallGraph {
+	/// Add the BHAVs in a package, `name` is the name of the package file without extension
+	///
+	/// Trees in the local group (0xFFFFFFFF) get the group the game gives to a package with that name.
+	/// GLOBs and BHAVs that can not be decoded are skipped and returned, the rest of the package is still added.
+	/// Only copies of the resources are decoded, so the package is left unchanged.
+	pub fn add_package<R: Read + Seek>(
+		&mut self,
+		name: String,
+		file: &mut DBPFFile,
+		reader: &mut R,
+	) -> Vec<UndecodedResource> {
+		let mut semi_globals = vec![];
+		let mut trees = vec![];
+		let mut undecoded = vec![];
+		for entry in file.index.iter_mut() {
+			let (type_id, group_id, instance_id) =
+				(entry.type_id, entry.group_id, entry.instance_id.id);
+			let DBPFFileType::Known(
+				KnownDBPFFileType::GlobalData | KnownDBPFFileType::SimanticsBehaviourFunction,
+			) = type_id
+			else {
+				continue;
+			};
+			match entry
+				.data(reader)
+				.map_err(CompressionError::from)
+				.and_then(|data| data.decoded_copy())
+			{
+				Ok(Some(DecodedFile::GlobalData(glob))) => {
+					semi_globals.push((group_id, glob.semi_global_group()));
+				}
+				Ok(Some(DecodedFile::BehaviourFunction(bhav))) => {
+					trees.push((group_id, instance_id as u16, bhav));
+				}
+				Ok(_) => {}
+				Err(error) => undecoded.push(UndecodedResource {
+					type_id,
+					group_id,
+					instance_id,
+					error,
+				}),
+			}
+		}
+		self.add_trees(name, semi_globals, trees);
+		undecoded
+	}
+
+	/// Add the decoded GLOBs and BHAVs of a package, groups are still unresolved
+	fn add_trees(
+		&mut self,
+		name: String,
+		semi_globals: Vec<(u32, u32)>,
+		trees: Vec<(u32, u16, BehaviourFunction)>,
+	) {
+		let package = self.packages.len();
+		let package_group = group_hash(name.as_bytes());
+		self.packages.push(name);
+		let resolve_group = |group_id: u32| {
+			if group_id == LOCAL_GROUP {
+				package_group
+			} else {
+				group_id
+			}
+		};
+
+		// a new GLOB can change the callees of packages that were added before
+		let first_unresolved = if semi_globals.is_empty() {
+			self.calls.len()
+		} else {
+			0
+		};
+		self.semi_global_groups.extend(
+			semi_globals
+				.into_iter()
+				.map(|(group_id, semi_global_group)| (resolve_group(group_id), semi_global_group)),
+		);
+
+		for (group_id, instance_id, bhav) in trees {
+			let id = TreeId {
+				group_id: resolve_group(group_id),
+				instance_id,
+			};
+			self.trees.entry(id).or_default().push(TreeDefinition {
+				package,
+				name: bhav.name.name.to_string(),
+			});
+			for (instruction, instr) in bhav.instructions.iter().enumerate() {
+				let Some(scope) = CallScope::of(instr.opcode) else {
+					continue;
+				};
+				let group_id = match scope {
+					CallScope::Global => Some(GLOBAL_GROUP),
+					CallScope::Local => Some(id.group_id),
+					CallScope::SemiGlobal => None,
+				};
+				self.calls.push(Call {
+					caller: id,
+					package,
+					instruction,
+					opcode: instr.opcode,
+					scope,
+					callee: group_id.map(|group_id| TreeId {
+						group_id,
+						instance_id: instr.opcode,
+					}),
+				});
+			}
+		}
+
+		for call in &mut self.calls[first_unresolved..] {
+			if call.scope == CallScope::SemiGlobal {
+				call.callee = self
+					.semi_global_groups
+					.get(&call.caller.group_id)
+					.map(|&group_id| TreeId {
+						group_id,
+						instance_id: call.opcode,
+					});
+			}
+		}
+	}
+
+	/// Every call to the tree, like "who calls global 0x0123"
+	pub fn callers_of(&self, tree: TreeId) -> impl Iterator<Item = &Call> {
+		self.calls
+			.iter()
+			.filter(move |call| call.callee == Some(tree))
+	}
+
+	/// Every call made by the tree
+	pub fn calls_from(&self, tree: TreeId) -> impl Iterator<Item = &Call> {
+		self.calls.iter().filter(move |call| call.caller == tree)
+	}
+
+	/// The packages that contain the tree, the last one is used by the game
+	pub fn definitions(&self, tree: TreeId) -> &[TreeDefinition] {
+		self.trees.get(&tree).map_or(&[], Vec::as_slice)
+	}
+
+	/// Trees that are in more than one package, like globals that are changed by mods
+	pub fn overridden(&self) -> impl Iterator<Item = (TreeId, &[TreeDefinition])> {
+		self.trees
+			.iter()
+			.filter(|(_, definitions)| definitions.len() > 1)
+			.map(|(id, definitions)| (*id, definitions.as_slice()))
+	}
+
+	/// Write the graph in the Graphviz DOT format, every tree is a node and every calling pair an edge
+	pub fn to_dot(&self) -> String {
+		let node = |id: &TreeId| format!("\"{:08X}_{:04X}\"", id.group_id, id.instance_id);
+		let edges: BTreeSet<(TreeId, TreeId)> = self
+			.calls
+			.iter()
+			.filter_map(|call| Some((call.caller, call.callee?)))
+			.collect();
+
+		let mut out = "digraph bhav {\n".to_string();
+		let mut nodes: BTreeSet<TreeId> = self.trees.keys().copied().collect();
+		nodes.extend(edges.iter().map(|(_, callee)| *callee));
+		for id in nodes {
+			let label = match self.definitions(id).last() {
+				Some(definition) => definition.name.replace('\\', "\\\\").replace('"', "\\\""),
+				None => "(missing)".to_string(),
+			};
+			let _ = writeln!(
+				out,
+				"\t{} [label=\"{:08X} {:04X}\\n{label}\"];",
+				node(&id),
+				id.group_id,
+				id.instance_id
+			);
+		}
+		for (caller, callee) in edges {
+			let _ = writeln!(out, "\t{} -> {};", node(&caller), node(&callee));
+		}
+		out.push_str("}\n");
+		out
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{prop_assert_eq, prop_assume};
+	use test_strategy::proptest;
+
+	use std::io::Cursor;
+
+	use binrw::BinWrite;
+	use proptest::prop_assert;
+
+	use super::*;
+	use crate::{internal_file::behaviour::behaviour_function::Instruction, IndexEntry};
+
+	fn calling(opcode: u16) -> BehaviourFunction {
+		BehaviourFunction {
+			instructions: vec![Instruction {
+				opcode,
+				operands: vec![0; 16],
+				..Default::default()
+			}],
+			..Default::default()
+		}
+	}
+
+	#[proptest]
+	fn private_calls_stay_in_the_package_group(
+		#[strategy("[a-z]{1,12}")] package: String,
+		#[strategy(0x1001u16..0x2000)] opcode: u16,
+	) {
+		let mut graph = CallGraph::default();
+		graph.add_trees(
+			package.clone(),
+			vec![],
+			vec![
+				(LOCAL_GROUP, 0x1000, calling(opcode)),
+				(LOCAL_GROUP, opcode, calling(0)),
+			],
+		);
+		let group_id = group_hash(package.as_bytes());
+		let callee = TreeId {
+			group_id,
+			instance_id: opcode,
+		};
+		let callers: Vec<_> = graph.callers_of(callee).map(|call| call.caller).collect();
+		prop_assert_eq!(
+			callers,
+			vec![TreeId {
+				group_id,
+				instance_id: 0x1000
+			}]
+		);
+		prop_assert_eq!(graph.definitions(callee).len(), 1);
+	}
+
+	#[proptest]
+	fn global_calls_go_to_the_global_group(
+		#[strategy(0x7F000000u32..0x7FFFFFFF)] group_id: u32,
+		#[strategy(0x100u16..0x1000)] opcode: u16,
+	) {
+		let mut graph = CallGraph::default();
+		graph.add_trees(
+			"object".to_string(),
+			vec![],
+			vec![(group_id, 0x1000, calling(opcode))],
+		);
+		let calls: Vec<_> = graph
+			.calls_from(TreeId {
+				group_id,
+				instance_id: 0x1000,
+			})
+			.map(|call| (call.scope, call.callee))
+			.collect();
+		prop_assert_eq!(
+			calls,
+			vec![(CallScope::Global, Some(TreeId::global(opcode)))]
+		);
+	}
+
+	#[proptest]
+	fn semi_global_calls_do_not_depend_on_package_order(
+		#[strategy("[a-z]{1,12}")] semi_global: String,
+		#[strategy(0x2000u16..)] opcode: u16,
+		semi_global_first: bool,
+	) {
+		prop_assume!(semi_global != "object");
+		let semi_global_group = group_hash(semi_global.as_bytes());
+		let caller = TreeId {
+			group_id: group_hash(b"object"),
+			instance_id: 0x1000,
+		};
+
+		let mut packages = vec![
+			(
+				"object".to_string(),
+				vec![(LOCAL_GROUP, semi_global_group)],
+				vec![(LOCAL_GROUP, 0x1000, calling(opcode))],
+			),
+			(
+				semi_global.clone(),
+				vec![],
+				vec![(LOCAL_GROUP, opcode, calling(0))],
+			),
+		];
+		if semi_global_first {
+			packages.reverse();
+		}
+		let mut graph = CallGraph::default();
+		for (name, semi_globals, trees) in packages {
+			graph.add_trees(name, semi_globals, trees);
+		}
+
+		let callee = TreeId {
+			group_id: semi_global_group,
+			instance_id: opcode,
+		};
+		let callers: Vec<_> = graph.callers_of(callee).map(|call| call.caller).collect();
+		prop_assert_eq!(callers, vec![caller]);
+		prop_assert_eq!(graph.definitions(callee).len(), 1);
+	}
+
+	#[proptest]
+	fn semi_global_calls_without_glob_have_no_callee(#[strategy(0x2000u16..)] opcode: u16) {
+		let mut graph = CallGraph::default();
+		graph.add_trees(
+			"object".to_string(),
+			vec![],
+			vec![(LOCAL_GROUP, 0x1000, calling(opcode))],
+		);
+		let calls: Vec<_> = graph
+			.calls
+			.iter()
+			.map(|call| (call.scope, call.callee))
+			.collect();
+		prop_assert_eq!(calls, vec![(CallScope::SemiGlobal, None)]);
+	}
+
+	#[proptest]
+	fn add_package_skips_trees_that_fail_to_decode(
+		#[strategy("[a-z]{1,12}")] package: String,
+		#[strategy(0x100u16..0x1000)] opcode: u16,
+		#[strategy(0usize..0x40)] broken_len: usize,
+	) {
+		let mut data = Cursor::new(vec![]);
+		calling(opcode).write(&mut data)?;
+		let bhav = DBPFFileType::Known(KnownDBPFFileType::SimanticsBehaviourFunction);
+		let mut file = DBPFFile {
+			index: vec![
+				IndexEntry::from_raw(bhav, LOCAL_GROUP, 0x1000, vec![0; broken_len]),
+				IndexEntry::from_raw(bhav, LOCAL_GROUP, 0x1001, data.into_inner()),
+			],
+			..DBPFFile::default()
+		};
+
+		let mut graph = CallGraph::default();
+		let undecoded = graph.add_package(package.clone(), &mut file, &mut Cursor::new(vec![]));
+		prop_assert_eq!(
+			undecoded.iter().map(|u| u.instance_id).collect::<Vec<_>>(),
+			vec![0x1000]
+		);
+		prop_assert_eq!(
+			graph.trees.keys().copied().collect::<Vec<_>>(),
+			vec![TreeId {
+				group_id: group_hash(package.as_bytes()),
+				instance_id: 0x1001,
+			}]
+		);
+		prop_assert_eq!(graph.calls.len(), 1);
+		// the package is written back as it was read
+		prop_assert!(file
+			.index
+			.iter()
+			.all(|entry| entry.decoded_data().is_none()));
+	}
+}
@@ -0,0 +1,69 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use binrw::binrw;
+
+use crate::{
+	common::{FileName, PascalString},
+	internal_file::resource_collection::group_hash,
+};
+
+/// GLOB, names the semi-global file whose trees the objects in its group can call
+#[binrw]
+#[brw(little)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GlobalData {
+	pub file_name: FileName,
+	/// the name of the semi-global file, like `ChairGlobals`
+	pub semi_global: PascalString<u8>,
+}
+
+impl GlobalData {
+	/// The group the BHAVs of the semi-global file are in
+	pub fn semi_global_group(&self) -> u32 {
+		group_hash(&self.semi_global.data)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use binrw::{BinRead, BinWrite};
+	use proptest::{collection::vec, prelude::any, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::common::{ByteString, NullString};
+
+	#[proptest]
+	fn write_read_same(
+		#[strategy(vec(1u8.., 0..=0x40))] file_name: Vec<u8>,
+		#[strategy(vec(any::<u8>(), 0..0x100))] semi_global: Vec<u8>,
+	) {
+		let glob = GlobalData {
+			file_name: FileName {
+				name: NullString::from(file_name),
+			},
+			semi_global: PascalString::from(ByteString(semi_global)),
+		};
+		let mut cur = Cursor::new(vec![]);
+		glob.write(&mut cur)?;
+		cur.set_position(0);
+		prop_assert_eq!(GlobalData::read(&mut cur)?, glob);
+	}
+
+	#[proptest]
+	fn semi_global_group_ignores_case(#[strategy("[a-zA-Z]{1,20}")] name: String) {
+		let group = |name: &str| {
+			GlobalData {
+				semi_global: PascalString::from(name),
+				..Default::default()
+			}
+			.semi_global_group()
+		};
+		prop_assert_eq!(group(&name), group(&name.to_ascii_lowercase()));
+		prop_assert_eq!(group(&name) & 0xFF000000, 0x7F000000);
+	}
+}
@@ -0,0 +1,614 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! A minimal interpreter that runs the primitives that only touch data, so BHAV logic can be tested
+//! without the game
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::internal_file::behaviour::{
+	behaviour_function::{BehaviourFunction, Goto},
+	primitive::{primitive_name, DataOwner, Expression, ExpressionOperator, Function},
+};
+
+/// the number of temps every object has
+pub const TEMP_COUNT: usize = 8;
+
+/// the number of instructions [Interpreter::run] executes before giving up, unless changed
+pub const DEFAULT_STEP_LIMIT: usize = 10_000;
+
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum InterpreterError {
+	#[error("instruction {instruction}: {} ({opcode:#06x}) is not supported", primitive_name(*opcode).unwrap_or("call"))]
+	Unsupported { instruction: usize, opcode: u16 },
+	#[error("instruction {instruction}: {owner:?} can not be read or written")]
+	UnsupportedOwner {
+		instruction: usize,
+		owner: DataOwner,
+	},
+	#[error("instruction {instruction}: a literal can not be assigned to")]
+	ReadOnly { instruction: usize },
+	#[error("instruction {instruction}: {} does not exist", owner.describe(*index))]
+	OutOfRange {
+		instruction: usize,
+		owner: DataOwner,
+		index: u16,
+	},
+	#[error("instruction {instruction}: division by zero")]
+	DivideByZero { instruction: usize },
+	#[error("instruction {instruction} went to error")]
+	Error { instruction: usize },
+	#[error("instruction {instruction} jumps to instruction {target}, which does not exist")]
+	MissingTarget { instruction: usize, target: u16 },
+	#[error("the tree has no instructions")]
+	Empty,
+	#[error("stopped after {0} steps")]
+	StepLimit(usize),
+}
+
+/// a tree that is being run, with its own parameters and locals
+struct Frame<'a> {
+	tree: &'a BehaviourFunction,
+	params: Vec<i16>,
+	locals: Vec<i16>,
+	instruction: usize,
+}
+
+impl<'a> Frame<'a> {
+	fn new(tree: &'a BehaviourFunction, mut params: Vec<i16>, mut locals: Vec<i16>) -> Self {
+		params.resize(params.len().max(tree.num_parameters as usize), 0);
+		locals.resize(locals.len().max(tree.num_locals as usize), 0);
+		Self {
+			tree,
+			params,
+			locals,
+			instruction: 0,
+		}
+	}
+}
+
+/// Runs BHAVs on a set of values that can be prepared and inspected afterward
+///
+/// Supports expressions, random numbers, object type tests and calls to the trees in
+/// [Interpreter::trees], any other primitive stops the run with [InterpreterError::Unsupported].
+#[derive(Clone, Debug)]
+pub struct Interpreter {
+	/// the parameters of the tree that is run, extended to the number of parameters it takes
+	pub params: Vec<i16>,
+	/// the locals of the tree that is run, extended to the number of locals it has
+	pub locals: Vec<i16>,
+	pub temps: [i16; TEMP_COUNT],
+	/// every other value, like attributes, globals and constants, missing values read as 0
+	pub attributes: HashMap<(DataOwner, u16), i16>,
+	/// the GUIDs of the objects by their id, for testing the object type
+	pub objects: HashMap<i16, u32>,
+	/// the trees that can be called, by their instance
+	pub trees: HashMap<u16, BehaviourFunction>,
+	pub step_limit: usize,
+	/// the state of the random number generator, the same seed gives the same numbers
+	pub seed: u64,
+	/// the number of instructions executed by the last run
+	pub steps: usize,
+}
+
+impl Default for Interpreter {
+	fn default() -> Self {
+		Self {
+			params: vec![],
+			locals: vec![],
+			temps: [0; TEMP_COUNT],
+			attributes: HashMap::new(),
+			objects: HashMap::new(),
+			trees: HashMap::new(),
+			step_limit: DEFAULT_STEP_LIMIT,
+			seed: 0x2545F4914F6CDD1D,
+			steps: 0,
+		}
+	}
+}
+
+impl Interpreter {
+	/// Run a tree from its first instruction until it returns true or false
+	///
+	/// The parameters and locals of the tree are kept in [Interpreter::params] and [Interpreter::locals],
+	/// also when the run fails.
+	pub fn run(&mut self, tree: &BehaviourFunction) -> Result<bool, InterpreterError> {
+		let trees = std::mem::take(&mut self.trees);
+		let mut stack = vec![Frame::new(
+			tree,
+			std::mem::take(&mut self.params),
+			std::mem::take(&mut self.locals),
+		)];
+		self.steps = 0;
+		let res = self.run_frames(&mut stack, &trees);
+		if let Some(root) = stack.into_iter().next() {
+			self.params = root.params;
+			self.locals = root.locals;
+		}
+		self.trees = trees;
+		res
+	}
+
+	fn run_frames<'a>(
+		&mut self,
+		stack: &mut Vec<Frame<'a>>,
+		trees: &'a HashMap<u16, BehaviourFunction>,
+	) -> Result<bool, InterpreterError> {
+		if tree_is_empty(stack) {
+			return Err(InterpreterError::Empty);
+		}
+		loop {
+			if self.steps >= self.step_limit {
+				return Err(InterpreterError::StepLimit(self.steps));
+			}
+			self.steps += 1;
+
+			// the stack is never empty here, the root frame is only popped when returning
+			let frame = stack.last_mut().unwrap();
+			let instruction = frame.instruction;
+			let mut result = match frame.tree.instructions[instruction].function() {
+				Function::Call(opcode, call) => {
+					let tree = trees.get(&opcode).ok_or(InterpreterError::Unsupported {
+						instruction,
+						opcode,
+					})?;
+					stack.push(Frame::new(tree, call.arguments.to_vec(), vec![]));
+					if tree_is_empty(stack) {
+						return Err(InterpreterError::Empty);
+					}
+					continue;
+				}
+				function => {
+					let result = self.execute(frame, instruction, &function)?;
+					// the analyzer relies on this to skip the false branch
+					debug_assert!(result || function.can_return_false());
+					result
+				}
+			};
+
+			// follow the goto, returning to the caller as long as a tree ends
+			loop {
+				let frame = stack.last_mut().unwrap();
+				let instruction = frame.instruction;
+				let current = &frame.tree.instructions[instruction];
+				let target = if result {
+					current.true_target
+				} else {
+					current.false_target
+				};
+				match target {
+					Goto::Instr(target) if (target as usize) < frame.tree.instructions.len() => {
+						frame.instruction = target as usize;
+						break;
+					}
+					Goto::Instr(target) => {
+						return Err(InterpreterError::MissingTarget {
+							instruction,
+							target,
+						})
+					}
+					Goto::Error => return Err(InterpreterError::Error { instruction }),
+					Goto::True | Goto::False => {
+						result = target == Goto::True;
+						if stack.len() == 1 {
+							return Ok(result);
+						}
+						stack.pop();
+					}
+				}
+			}
+		}
+	}
+
+	fn execute(
+		&mut self,
+		frame: &mut Frame,
+		instruction: usize,
+		function: &Function,
+	) -> Result<bool, InterpreterError> {
+		match function {
+			Function::Expression(expression) => self.expression(frame, instruction, expression),
+			Function::RandomNumber(random) => {
+				let range = self.get(frame, instruction, random.range_owner, random.range_data)?;
+				let value = if range > 0 {
+					(self.next_random() % range as u64) as i16
+				} else {
+					0
+				};
+				self.set(
+					frame,
+					instruction,
+					random.destination_owner,
+					random.destination_data,
+					value,
+				)?;
+				Ok(true)
+			}
+			Function::TestObjectType(test) => {
+				let id = self.get(frame, instruction, test.id_owner, test.id_data)?;
+				Ok(self.objects.get(&id) == Some(&test.guid))
+			}
+			function => Err(InterpreterError::Unsupported {
+				instruction,
+				opcode: function.opcode(),
+			}),
+		}
+	}
+
+	fn expression(
+		&mut self,
+		frame: &mut Frame,
+		instruction: usize,
+		e: &Expression,
+	) -> Result<bool, InterpreterError> {
+		let lhs = self.get(frame, instruction, e.lhs_owner, e.lhs_data)?;
+		let rhs = self.get(frame, instruction, e.rhs_owner, e.rhs_data)?;
+		let flag = 1i16.wrapping_shl((rhs as u32).wrapping_sub(1));
+		let value = match e.operator {
+			ExpressionOperator::GreaterThan => return Ok(lhs > rhs),
+			ExpressionOperator::LessThan => return Ok(lhs < rhs),
+			ExpressionOperator::Equal => return Ok(lhs == rhs),
+			ExpressionOperator::GreaterOrEqual => return Ok(lhs >= rhs),
+			ExpressionOperator::LessOrEqual => return Ok(lhs <= rhs),
+			ExpressionOperator::NotEqual => return Ok(lhs != rhs),
+			ExpressionOperator::IsFlagSet => return Ok(lhs & flag != 0),
+			ExpressionOperator::IncrementAndLessThan => {
+				let value = lhs.wrapping_add(1);
+				self.set(frame, instruction, e.lhs_owner, e.lhs_data, value)?;
+				return Ok(value < rhs);
+			}
+			ExpressionOperator::DecrementAndGreaterThan => {
+				let value = lhs.wrapping_sub(1);
+				self.set(frame, instruction, e.lhs_owner, e.lhs_data, value)?;
+				return Ok(value > rhs);
+			}
+			ExpressionOperator::Add => lhs.wrapping_add(rhs),
+			ExpressionOperator::Subtract => lhs.wrapping_sub(rhs),
+			ExpressionOperator::Assign => rhs,
+			ExpressionOperator::Multiply => lhs.wrapping_mul(rhs),
+			ExpressionOperator::Divide | ExpressionOperator::Modulo if rhs == 0 => {
+				return Err(InterpreterError::DivideByZero { instruction })
+			}
+			ExpressionOperator::Divide => lhs.wrapping_div(rhs),
+			ExpressionOperator::Modulo => lhs.wrapping_rem(rhs),
+			ExpressionOperator::SetFlag => lhs | flag,
+			ExpressionOperator::ClearFlag => lhs & !flag,
+			ExpressionOperator::And => lhs & rhs,
+			ExpressionOperator::Unknown(_) => {
+				return Err(InterpreterError::Unsupported {
+					instruction,
+					opcode: 0x02,
+				})
+			}
+		};
+		self.set(frame, instruction, e.lhs_owner, e.lhs_data, value)?;
+		Ok(true)
+	}
+
+	fn get(
+		&self,
+		frame: &Frame,
+		instruction: usize,
+		owner: DataOwner,
+		data: u16,
+	) -> Result<i16, InterpreterError> {
+		let out_of_range = InterpreterError::OutOfRange {
+			instruction,
+			owner,
+			index: data,
+		};
+		match owner {
+			DataOwner::Literal => Ok(data as i16),
+			DataOwner::Temp => self.temps.get(data as usize).copied().ok_or(out_of_range),
+			DataOwner::TempByTemp => {
+				let index = self.get(frame, instruction, DataOwner::Temp, data)?;
+				self.get(frame, instruction, DataOwner::Temp, index as u16)
+			}
+			DataOwner::Parameter => frame.params.get(data as usize).copied().ok_or(out_of_range),
+			DataOwner::Local => frame.locals.get(data as usize).copied().ok_or(out_of_range),
+			DataOwner::Unknown(_) => Err(InterpreterError::UnsupportedOwner { instruction, owner }),
+			owner => Ok(self
+				.attributes
+				.get(&(owner, data))
+				.copied()
+				.unwrap_or_default()),
+		}
+	}
+
+	fn set(
+		&mut self,
+		frame: &mut Frame,
+		instruction: usize,
+		owner: DataOwner,
+		data: u16,
+		value: i16,
+	) -> Result<(), InterpreterError> {
+		let out_of_range = InterpreterError::OutOfRange {
+			instruction,
+			owner,
+			index: data,
+		};
+		let slot = match owner {
+			DataOwner::Literal => return Err(InterpreterError::ReadOnly { instruction }),
+			DataOwner::Temp => self.temps.get_mut(data as usize),
+			DataOwner::TempByTemp => {
+				let index = self.get(frame, instruction, DataOwner::Temp, data)?;
+				return self.set(frame, instruction, DataOwner::Temp, index as u16, value);
+			}
+			DataOwner::Parameter => frame.params.get_mut(data as usize),
+			DataOwner::Local => frame.locals.get_mut(data as usize),
+			DataOwner::Unknown(_) => {
+				return Err(InterpreterError::UnsupportedOwner { instruction, owner })
+			}
+			owner => {
+				self.attributes.insert((owner, data), value);
+				return Ok(());
+			}
+		};
+		*slot.ok_or(out_of_range)? = value;
+		Ok(())
+	}
+
+	/// xorshift64*, which is plenty for picking branches
+	fn next_random(&mut self) -> u64 {
+		if self.seed == 0 {
+			self.seed = Self::default().seed;
+		}
+		self.seed ^= self.seed >> 12;
+		self.seed ^= self.seed << 25;
+		self.seed ^= self.seed >> 27;
+		self.seed.wrapping_mul(0x2545F4914F6CDD1D) >> 32
+	}
+}
+
+fn tree_is_empty(stack: &[Frame]) -> bool {
+	stack
+		.last()
+		.is_none_or(|frame| frame.tree.instructions.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::internal_file::behaviour::{
+		behaviour_function::Instruction,
+		primitive::{Call, RandomNumber, TestObjectType},
+	};
+
+	fn instruction(function: Function, true_target: Goto, false_target: Goto) -> Instruction {
+		let mut instruction = Instruction {
+			true_target,
+			false_target,
+			operands: vec![0; 16],
+			..Default::default()
+		};
+		instruction.set_function(&function);
+		instruction
+	}
+
+	fn expression(
+		operator: ExpressionOperator,
+		lhs: (DataOwner, u16),
+		rhs: (DataOwner, u16),
+	) -> Function {
+		Function::Expression(Expression {
+			operator,
+			lhs_owner: lhs.0,
+			lhs_data: lhs.1,
+			rhs_owner: rhs.0,
+			rhs_data: rhs.1,
+			..Default::default()
+		})
+	}
+
+	fn tree(num_parameters: u8, instructions: Vec<Instruction>) -> BehaviourFunction {
+		BehaviourFunction {
+			num_parameters,
+			num_locals: 1,
+			instructions,
+			..Default::default()
+		}
+	}
+
+	#[proptest]
+	fn arithmetic_wraps(a: i16, b: i16) {
+		let bhav = tree(
+			0,
+			vec![
+				instruction(
+					expression(
+						ExpressionOperator::Assign,
+						(DataOwner::Local, 0),
+						(DataOwner::Literal, a as u16),
+					),
+					Goto::Instr(1),
+					Goto::Error,
+				),
+				instruction(
+					expression(
+						ExpressionOperator::Add,
+						(DataOwner::Local, 0),
+						(DataOwner::Literal, b as u16),
+					),
+					Goto::True,
+					Goto::Error,
+				),
+			],
+		);
+		let mut interpreter = Interpreter::default();
+		prop_assert!(interpreter.run(&bhav)?);
+		prop_assert_eq!(interpreter.locals, vec![a.wrapping_add(b)]);
+		prop_assert_eq!(interpreter.steps, 2);
+	}
+
+	#[proptest]
+	fn counting_loop_ends(#[strategy(1i16..200)] n: i16) {
+		let bhav = tree(
+			0,
+			vec![instruction(
+				expression(
+					ExpressionOperator::IncrementAndLessThan,
+					(DataOwner::Local, 0),
+					(DataOwner::Parameter, 0),
+				),
+				Goto::Instr(0),
+				Goto::False,
+			)],
+		);
+		let mut interpreter = Interpreter {
+			params: vec![n],
+			..Default::default()
+		};
+		prop_assert!(!interpreter.run(&bhav)?);
+		prop_assert_eq!(interpreter.locals, vec![n]);
+		prop_assert_eq!(interpreter.steps, n as usize);
+	}
+
+	#[proptest]
+	fn closed_loop_hits_step_limit(#[strategy(1usize..500)] step_limit: usize) {
+		let bhav = tree(
+			0,
+			vec![instruction(
+				expression(
+					ExpressionOperator::Add,
+					(DataOwner::MyAttribute, 3),
+					(DataOwner::Literal, 1),
+				),
+				Goto::Instr(0),
+				Goto::Error,
+			)],
+		);
+		let mut interpreter = Interpreter {
+			step_limit,
+			..Default::default()
+		};
+		prop_assert_eq!(
+			interpreter.run(&bhav),
+			Err(InterpreterError::StepLimit(step_limit))
+		);
+		prop_assert_eq!(
+			interpreter.attributes[&(DataOwner::MyAttribute, 3)],
+			step_limit as i16
+		);
+	}
+
+	#[proptest]
+	fn random_number_is_in_range(#[strategy(1i16..1000)] range: i16, seed: u64) {
+		let bhav = tree(
+			0,
+			vec![instruction(
+				Function::RandomNumber(RandomNumber {
+					destination_owner: DataOwner::Temp,
+					destination_data: 2,
+					range_owner: DataOwner::Literal,
+					range_data: range as u16,
+					..Default::default()
+				}),
+				Goto::True,
+				Goto::Error,
+			)],
+		);
+		let mut interpreter = Interpreter {
+			seed,
+			..Default::default()
+		};
+		prop_assert!(interpreter.run(&bhav)?);
+		prop_assert!((0..range).contains(&interpreter.temps[2]));
+	}
+
+	#[proptest]
+	fn test_object_type_branches(guid: u32, other: u32, id: i16) {
+		let bhav = tree(
+			1,
+			vec![instruction(
+				Function::TestObjectType(TestObjectType {
+					guid,
+					id_owner: DataOwner::Parameter,
+					id_data: 0,
+					..Default::default()
+				}),
+				Goto::True,
+				Goto::False,
+			)],
+		);
+		let mut interpreter = Interpreter {
+			params: vec![id],
+			objects: HashMap::from([(id, other)]),
+			..Default::default()
+		};
+		prop_assert_eq!(interpreter.run(&bhav)?, guid == other);
+	}
+
+	#[proptest]
+	fn call_returns_to_caller(argument: i16, threshold: i16) {
+		let callee = tree(
+			1,
+			vec![instruction(
+				expression(
+					ExpressionOperator::GreaterThan,
+					(DataOwner::Parameter, 0),
+					(DataOwner::Literal, threshold as u16),
+				),
+				Goto::True,
+				Goto::False,
+			)],
+		);
+		let caller = tree(
+			0,
+			vec![
+				instruction(
+					Function::Call(
+						0x1000,
+						Call {
+							arguments: [argument, 0, 0, 0],
+							..Default::default()
+						},
+					),
+					Goto::Instr(1),
+					Goto::False,
+				),
+				instruction(
+					expression(
+						ExpressionOperator::Assign,
+						(DataOwner::Local, 0),
+						(DataOwner::Literal, 1),
+					),
+					Goto::True,
+					Goto::Error,
+				),
+			],
+		);
+		let mut interpreter = Interpreter {
+			trees: HashMap::from([(0x1000, callee)]),
+			..Default::default()
+		};
+		let greater = argument > threshold;
+		prop_assert_eq!(interpreter.run(&caller)?, greater);
+		prop_assert_eq!(interpreter.locals, vec![greater as i16]);
+	}
+
+	#[proptest]
+	fn unknown_primitive_is_reported(#[strategy(0x21u16..0x100)] opcode: u16) {
+		let bhav = tree(
+			0,
+			vec![instruction(
+				Function::Unknown(opcode, vec![]),
+				Goto::True,
+				Goto::False,
+			)],
+		);
+		prop_assert_eq!(
+			Interpreter::default().run(&bhav),
+			Err(InterpreterError::Unsupported {
+				instruction: 0,
+				opcode
+			})
+		);
+	}
+}
@@ -0,0 +1,489 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! A text format for BHAVs that can be reviewed and edited, and assembled back into the same bytes
+//!
+//! ```text
+//! name "Function - Example"
+//! signature V9
+//! tree_type 0x00
+//! num_parameters 1
+//! num_locals 1
+//! header_flags 0x00
+//! tree_version 0
+//! cache_flags 0x00
+//!
+//! L0: 0x0002 Expression [19 00 ... 00] true: L1 false: Error version: 0 ; Local 0 += Param 0
+//! L1: 0x0000 Sleep [00 00 ... 00] true: True false: False version: 0 ; Sleep for Param 0 ticks
+//! ```
+//!
+//! Everything after a `;` is a comment.
+//! The name and decoded operands of an instruction are only there for the reader.
+
+use std::{collections::HashMap, fmt::Write};
+
+use thiserror::Error;
+
+use crate::{
+	common::{FileName, NullString},
+	internal_file::behaviour::{
+		behaviour_function::{BehaviourFunction, Goto, Instruction, Signature},
+		behaviour_function_labels::BehaviourFunctionLabels,
+		primitive::{primitive_name, OperandLabels, FIRST_CALL_OPCODE},
+	},
+};
+
+const SIGNATURES: [(Signature, &str); 10] = [
+	(Signature::V0, "V0"),
+	(Signature::V1, "V1"),
+	(Signature::V2, "V2"),
+	(Signature::V3, "V3"),
+	(Signature::V4, "V4"),
+	(Signature::V5, "V5"),
+	(Signature::V6, "V6"),
+	(Signature::V7, "V7"),
+	(Signature::V8, "V8"),
+	(Signature::V9, "V9"),
+];
+
+const HEADER_FIELDS: [&str; 8] = [
+	"name",
+	"signature",
+	"tree_type",
+	"num_parameters",
+	"num_locals",
+	"header_flags",
+	"tree_version",
+	"cache_flags",
+];
+
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum ListingError {
+	#[error("the header field {field} is missing")]
+	MissingField { field: &'static str },
+	#[error("line {line}: unknown header field {field}")]
+	UnknownField { line: usize, field: String },
+	#[error("line {line}: the header field {field} is given twice")]
+	DuplicateField { line: usize, field: String },
+	#[error("line {line}: invalid value {value:?}")]
+	InvalidValue { line: usize, value: String },
+	#[error("line {line}: the name must be a quoted string of at most 64 bytes")]
+	InvalidName { line: usize },
+	#[error("line {line}: expected an instruction like `L0: 0x0002 Expression [..] true: L1 false: L2 version: 0`")]
+	InvalidInstruction { line: usize },
+	#[error("line {line}: the label {label} is defined twice")]
+	DuplicateLabel { line: usize, label: String },
+	#[error("line {line}: the label {label} is not defined")]
+	UndefinedLabel { line: usize, label: String },
+	#[error("line {line}: signature {signature:?} needs {expected} operand bytes, found {actual}")]
+	OperandCount {
+		line: usize,
+		signature: Signature,
+		expected: usize,
+		actual: usize,
+	},
+}
+
+fn signature_name(signature: Signature) -> &'static str {
+	SIGNATURES
+		.iter()
+		.find(|(s, _)| *s == signature)
+		.map(|(_, name)| *name)
+		.unwrap_or_default()
+}
+
+fn instruction_name(opcode: u16) -> &'static str {
+	match primitive_name(opcode) {
+		Some(name) => name,
+		None if opcode >= FIRST_CALL_OPCODE => "Call",
+		None => "Primitive",
+	}
+}
+
+fn quote(bytes: &[u8]) -> String {
+	let mut out = "\"".to_string();
+	for &b in bytes {
+		match b {
+			b'"' | b'\\' => {
+				out.push('\\');
+				out.push(b as char);
+			}
+			0x20..=0x7E => out.push(b as char),
+			_ => {
+				let _ = write!(out, "\\x{b:02X}");
+			}
+		}
+	}
+	out.push('"');
+	out
+}
+
+fn unquote(text: &str) -> Option<Vec<u8>> {
+	let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+	let mut out = vec![];
+	let mut bytes = inner.bytes();
+	while let Some(b) = bytes.next() {
+		match b {
+			b'\\' => match bytes.next()? {
+				b'x' => {
+					let digits = [bytes.next()?, bytes.next()?];
+					out.push(u8::from_str_radix(std::str::from_utf8(&digits).ok()?, 16).ok()?);
+				}
+				escaped @ (b'"' | b'\\') => out.push(escaped),
+				_ => return None,
+			},
+			b'"' => return None,
+			b => out.push(b),
+		}
+	}
+	Some(out)
+}
+
+/// cut off the comment of a line, ignoring `;` in quoted strings
+fn strip_comment(line: &str) -> &str {
+	let mut quoted = false;
+	let mut escaped = false;
+	for (i, c) in line.char_indices() {
+		match c {
+			_ if escaped => escaped = false,
+			'\\' if quoted => escaped = true,
+			'"' => quoted = !quoted,
+			';' if !quoted => return &line[..i],
+			_ => {}
+		}
+	}
+	line
+}
+
+fn parse_number<T: TryFrom<i64>>(text: &str, line: usize) -> Result<T, ListingError> {
+	let invalid = || ListingError::InvalidValue {
+		line,
+		value: text.to_string(),
+	};
+	let (negative, digits) = match text.strip_prefix('-') {
+		Some(digits) => (true, digits),
+		None => (false, text),
+	};
+	let value = match digits
+		.strip_prefix("0x")
+		.or_else(|| digits.strip_prefix("0X"))
+	{
+		Some(hex) => i64::from_str_radix(hex, 16),
+		None => digits.parse::<i64>(),
+	}
+	.map_err(|_| invalid())?;
+	T::try_from(if negative { -value } else { value }).map_err(|_| invalid())
+}
+
+fn goto_text(goto: Goto, num_instructions: usize) -> String {
+	match goto {
+		Goto::Error => "Error".to_string(),
+		Goto::True => "True".to_string(),
+		Goto::False => "False".to_string(),
+		Goto::Instr(i) if (i as usize) < num_instructions => format!("L{i}"),
+		Goto::Instr(i) => format!("@{i}"),
+	}
+}
+
+/// an instruction that has been parsed, but whose targets have not been resolved yet
+struct PendingInstruction<'a> {
+	line: usize,
+	opcode: u16,
+	node_version: u8,
+	operands: Vec<u8>,
+	true_target: &'a str,
+	false_target: &'a str,
+}
+
+fn parse_instruction(
+	text: &str,
+	line: usize,
+) -> Result<(&str, PendingInstruction<'_>), ListingError> {
+	let invalid = || ListingError::InvalidInstruction { line };
+	let (label, rest) = text.split_once(':').ok_or_else(invalid)?;
+	let (opcode, rest) = rest.trim_start().split_once(' ').ok_or_else(invalid)?;
+	let (_name, rest) = rest.split_once('[').ok_or_else(invalid)?;
+	let (operands, rest) = rest.split_once(']').ok_or_else(invalid)?;
+
+	let operands = operands
+		.split_whitespace()
+		.map(|byte| {
+			u8::from_str_radix(byte, 16).map_err(|_| ListingError::InvalidValue {
+				line,
+				value: byte.to_string(),
+			})
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let fields = rest.split_whitespace().collect::<Vec<_>>();
+	let ["true:", true_target, "false:", false_target, "version:", node_version] = fields[..]
+	else {
+		return Err(invalid());
+	};
+
+	Ok((
+		label.trim(),
+		PendingInstruction {
+			line,
+			opcode: parse_number(opcode, line)?,
+			node_version: parse_number(node_version, line)?,
+			operands,
+			true_target,
+			false_target,
+		},
+	))
+}
+
+impl BehaviourFunction {
+	/// Write the function as a listing, `labels` gives names to the parameters and locals
+	pub fn disassemble(&self, labels: Option<&BehaviourFunctionLabels>) -> String {
+		let labels = labels.map(OperandLabels::from).unwrap_or_default();
+		let mut out = String::new();
+
+		// writing to a string can not fail
+		let _ = (|| -> std::fmt::Result {
+			writeln!(out, "name {}", quote(&Vec::from(self.name.name.clone())))?;
+			writeln!(out, "signature {}", signature_name(self.signature))?;
+			writeln!(out, "tree_type {:#04x}", self.tree_type)?;
+			writeln!(out, "num_parameters {}", self.num_parameters)?;
+			writeln!(out, "num_locals {}", self.num_locals)?;
+			writeln!(out, "header_flags {:#04x}", self.header_flags)?;
+			writeln!(out, "tree_version {}", self.tree_version)?;
+			writeln!(out, "cache_flags {:#04x}", self.cache_flags)?;
+			for (i, param) in labels.params.iter().enumerate() {
+				writeln!(out, "; param {i}: {param}")?;
+			}
+			for (i, local) in labels.locals.iter().enumerate() {
+				writeln!(out, "; local {i}: {local}")?;
+			}
+			writeln!(out)?;
+
+			let num_instructions = self.instructions.len();
+			for (i, instruction) in self.instructions.iter().enumerate() {
+				write!(
+					out,
+					"L{i}: {:#06x} {} [",
+					instruction.opcode,
+					instruction_name(instruction.opcode)
+				)?;
+				for (j, byte) in instruction.operands.iter().enumerate() {
+					if j > 0 {
+						write!(out, " ")?;
+					}
+					write!(out, "{byte:02X}")?;
+				}
+				writeln!(
+					out,
+					"] true: {} false: {} version: {} ; {}",
+					goto_text(instruction.true_target, num_instructions),
+					goto_text(instruction.false_target, num_instructions),
+					instruction.node_version,
+					instruction.function().describe(&labels)
+				)?;
+			}
+			Ok(())
+		})();
+
+		out
+	}
+
+	/// Read a function from a listing written by [BehaviourFunction::disassemble]
+	pub fn assemble(text: &str) -> Result<Self, ListingError> {
+		let mut header: HashMap<&str, (usize, &str)> = HashMap::new();
+		let mut labels: HashMap<&str, u16> = HashMap::new();
+		let mut pending = vec![];
+
+		for (i, line) in text.lines().enumerate() {
+			let line_number = i + 1;
+			let line = strip_comment(line).trim();
+			if line.is_empty() {
+				continue;
+			}
+			let (field, value) = line.split_once(' ').unwrap_or((line, ""));
+			if HEADER_FIELDS.contains(&field) {
+				if header.insert(field, (line_number, value.trim())).is_some() {
+					return Err(ListingError::DuplicateField {
+						line: line_number,
+						field: field.to_string(),
+					});
+				}
+			} else if line.contains(':') {
+				let (label, instruction) = parse_instruction(line, line_number)?;
+				if labels.insert(label, pending.len() as u16).is_some() {
+					return Err(ListingError::DuplicateLabel {
+						line: line_number,
+						label: label.to_string(),
+					});
+				}
+				pending.push(instruction);
+			} else {
+				return Err(ListingError::UnknownField {
+					line: line_number,
+					field: field.to_string(),
+				});
+			}
+		}
+
+		let field = |field: &'static str| {
+			header
+				.get(field)
+				.copied()
+				.ok_or(ListingError::MissingField { field })
+		};
+		fn number<T: TryFrom<i64>>((line, value): (usize, &str)) -> Result<T, ListingError> {
+			parse_number(value, line)
+		}
+
+		let (name_line, name) = field("name")?;
+		let name = unquote(name)
+			.filter(|name| name.len() <= 0x40 && !name.contains(&0))
+			.ok_or(ListingError::InvalidName { line: name_line })?;
+
+		let (signature_line, signature) = field("signature")?;
+		let signature = SIGNATURES
+			.iter()
+			.find(|(_, name)| *name == signature)
+			.map(|(s, _)| *s)
+			.ok_or_else(|| ListingError::InvalidValue {
+				line: signature_line,
+				value: signature.to_string(),
+			})?;
+
+		let resolve = |target: &str, line: usize| -> Result<Goto, ListingError> {
+			Ok(match target {
+				"Error" => Goto::Error,
+				"True" => Goto::True,
+				"False" => Goto::False,
+				_ => match target.strip_prefix('@') {
+					Some(index) => Goto::Instr(parse_number(index, line)?),
+					None => Goto::Instr(*labels.get(target).ok_or_else(|| {
+						ListingError::UndefinedLabel {
+							line,
+							label: target.to_string(),
+						}
+					})?),
+				},
+			})
+		};
+
+		let instructions = pending
+			.into_iter()
+			.map(|instruction| {
+				let expected = signature.operand_count();
+				if instruction.operands.len() != expected {
+					return Err(ListingError::OperandCount {
+						line: instruction.line,
+						signature,
+						expected,
+						actual: instruction.operands.len(),
+					});
+				}
+				Ok(Instruction {
+					opcode: instruction.opcode,
+					true_target: resolve(instruction.true_target, instruction.line)?,
+					false_target: resolve(instruction.false_target, instruction.line)?,
+					node_version: instruction.node_version,
+					operands: instruction.operands,
+				})
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(BehaviourFunction {
+			name: FileName {
+				name: NullString::from(name),
+			},
+			signature,
+			tree_type: number(field("tree_type")?)?,
+			num_parameters: number(field("num_parameters")?)?,
+			num_locals: number(field("num_locals")?)?,
+			header_flags: number(field("header_flags")?)?,
+			tree_version: number(field("tree_version")?)?,
+			cache_flags: number(field("cache_flags")?)?,
+			instructions,
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use binrw::{BinRead, BinWrite};
+	use proptest::{collection::vec, prelude::*};
+	use test_strategy::proptest;
+
+	use super::*;
+
+	fn goto(num_instructions: u16) -> impl Strategy<Value = Goto> {
+		prop_oneof![
+			Just(Goto::Error),
+			Just(Goto::True),
+			Just(Goto::False),
+			(0..num_instructions + 2).prop_map(Goto::Instr),
+		]
+	}
+
+	fn behaviour_function() -> impl Strategy<Value = BehaviourFunction> {
+		(
+			0..SIGNATURES.len(),
+			vec(0x20u8..0x7F, 0..0x40),
+			any::<[u8; 4]>(),
+			any::<i32>(),
+			any::<u8>(),
+			0u16..6,
+		)
+			.prop_flat_map(
+				|(signature, name, [tree_type, params, locals, flags], version, cache, n)| {
+					let signature = SIGNATURES[signature].0;
+					let instruction = (
+						any::<u16>(),
+						goto(n),
+						goto(n),
+						any::<u8>(),
+						vec(any::<u8>(), signature.operand_count()),
+					)
+						.prop_map(move |(opcode, t, f, node_version, operands)| Instruction {
+							opcode,
+							true_target: t,
+							false_target: f,
+							node_version: if signature >= Signature::V5 {
+								node_version
+							} else {
+								0
+							},
+							operands,
+						});
+					vec(instruction, n as usize).prop_map(move |instructions| BehaviourFunction {
+						name: FileName {
+							name: NullString::from(name.clone()),
+						},
+						signature,
+						tree_type,
+						num_parameters: params,
+						num_locals: locals,
+						header_flags: flags,
+						tree_version: version,
+						cache_flags: if signature == Signature::V9 { cache } else { 0 },
+						instructions,
+					})
+				},
+			)
+	}
+
+	#[proptest]
+	fn listing_roundtrip(#[strategy(behaviour_function())] bhav: BehaviourFunction) {
+		let mut bytes = Cursor::new(vec![]);
+		bhav.write(&mut bytes).unwrap();
+		let bytes = bytes.into_inner();
+		let read = BehaviourFunction::read(&mut Cursor::new(&bytes)).unwrap();
+
+		let text = read.disassemble(None);
+		let assembled = BehaviourFunction::assemble(&text).unwrap();
+		prop_assert_eq!(&assembled, &read);
+
+		let mut assembled_bytes = Cursor::new(vec![]);
+		assembled.write(&mut assembled_bytes).unwrap();
+		prop_assert_eq!(assembled_bytes.into_inner(), bytes);
+	}
+}
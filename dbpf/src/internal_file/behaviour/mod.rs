@@ -2,8 +2,15 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod analysis;
 pub mod behaviour_constants;
 pub mod behaviour_constants_labels;
 pub mod behaviour_function;
 pub mod behaviour_function_labels;
+pub mod call_graph;
+pub mod global_data;
+pub mod interpreter;
+pub mod listing;
 pub mod object_functions;
+pub mod pie_menu_functions;
+pub mod primitive;
@@ -6,6 +6,44 @@ use binrw::binrw;
 
 use crate::common::FileName;
 
+/// what the game uses the functions in [ObjectFunctions::entries] for, by index
+pub const FUNCTION_NAMES: &[&str] = &[
+	"init",
+	"main",
+	"load",
+	"cleanup",
+	"queue skipped",
+	"allow intersection",
+	"wall adjacency changed",
+	"room changed",
+	"dynamic multi-tile update",
+	"placement",
+	"pick up",
+	"user placement",
+	"user pick up",
+	"level info request",
+	"serving surface",
+	"portal",
+	"gardening",
+	"wash hands",
+	"prepare food",
+	"cook",
+	"surface",
+	"dispose",
+	"eat",
+	"pick up from slot",
+	"wash dish",
+	"eating surface",
+	"sit",
+	"stand",
+	"clean",
+];
+
+/// The name of the function at this index, `None` for functions that are not known
+pub fn function_name(index: usize) -> Option<&'static str> {
+	FUNCTION_NAMES.get(index).copied()
+}
+
 #[binrw]
 #[brw(magic = b"fJBO")]
 struct HeaderMagic;
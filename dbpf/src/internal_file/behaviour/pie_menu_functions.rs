@@ -0,0 +1,274 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! TTAB, the interactions an object offers in its pie menu
+//!
+//! Every interaction runs an action BHAV, and can only be chosen when its guard BHAV returns true.
+//! The text in the pie menu is in the TTAs (pie menu strings) with the same instance.
+
+use binrw::{args, binrw, helpers::args_iter, VecArgs};
+
+use crate::common::{numbered_enum, FileName};
+
+/// the first format that stores a motive table for every kind of sim instead of only one
+pub const FORMAT_MOTIVE_TABLES: u32 = 0x44;
+
+/// the number of advertisements in the single motive table of formats before [FORMAT_MOTIVE_TABLES],
+/// which is not stored in the file
+pub const OLD_MOTIVE_COUNT: usize = 16;
+
+/// the names of the bits of [Interaction::flags]
+pub const INTERACTION_FLAG_NAMES: [&str; 16] = [
+	"visitors",
+	"joinable",
+	"run immediately",
+	"consecutive",
+	"carrying",
+	"debug",
+	"auto first",
+	"not adult",
+	"not child",
+	"not elder",
+	"not teen",
+	"not toddler",
+	"",
+	"",
+	"",
+	"",
+];
+
+numbered_enum! {
+	/// How quickly the advertisements of an interaction get weaker with distance
+	Attenuation: u32 {
+		Custom = 0 => "Custom",
+		Off = 1 => "None",
+		Low = 2 => "Low",
+		Medium = 3 => "Medium",
+		High = 4 => "High",
+	}
+}
+
+/// How much an interaction promises to change one motive, used by autonomy
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct MotiveAdvertisement {
+	pub min: i16,
+	pub delta: i16,
+	/// the personality trait that scales the advertisement
+	pub personality: u16,
+}
+
+#[binrw]
+#[brw(import { format: u32 })]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Interaction {
+	/// the BHAV that runs the interaction
+	pub action_id: u16,
+	/// the BHAV that decides if the interaction is shown, 0 for always
+	pub guardian_id: u16,
+
+	#[br(temp, if(format >= FORMAT_MOTIVE_TABLES, 1))]
+	#[bw(calc = motive_tables.len() as u32, if(format >= FORMAT_MOTIVE_TABLES))]
+	table_count: u32,
+	#[br(temp, count = table_count, if(format >= FORMAT_MOTIVE_TABLES, vec![OLD_MOTIVE_COUNT as u32]))]
+	#[bw(calc = motive_tables.iter().map(|table| table.len() as u32).collect(), if(format >= FORMAT_MOTIVE_TABLES))]
+	table_sizes: Vec<u32>,
+
+	pub flags: u16,
+	pub flags2: u16,
+	/// the index of the name of the interaction in the TTAs
+	pub string_index: u32,
+	pub attenuation: Attenuation,
+	/// used when the attenuation is [Attenuation::Custom]
+	pub attenuation_value: f32,
+	/// the motive level below which sims choose the interaction by themselves
+	pub autonomy: u32,
+	/// the interaction other sims join, or 0xFFFFFFFF
+	pub joining_index: u32,
+
+	#[brw(if(format >= 0x45))]
+	pub ui_display_type: u16,
+	#[brw(if(format >= 0x46))]
+	pub facial_animation: u32,
+	#[brw(if(format >= 0x4A))]
+	pub memory_multiplier: f32,
+	#[brw(if(format >= 0x4A))]
+	pub object_type: u32,
+	#[brw(if(format >= 0x4C))]
+	pub model_table_id: u32,
+
+	/// one table of advertisements per kind of sim,
+	/// formats before 0x44 have exactly one with [OLD_MOTIVE_COUNT] advertisements
+	#[br(parse_with = args_iter(table_sizes.iter().map(|&count| VecArgs { count: count as usize, inner: () })))]
+	#[bw(assert(
+		format >= FORMAT_MOTIVE_TABLES || (motive_tables.len() == 1 && motive_tables[0].len() == OLD_MOTIVE_COUNT),
+		"format {format:#X} stores exactly one motive table of {OLD_MOTIVE_COUNT} advertisements"
+	))]
+	pub motive_tables: Vec<Vec<MotiveAdvertisement>>,
+}
+
+impl Interaction {
+	/// The name of the interaction, from the strings of the TTAs
+	pub fn name<'a>(&self, strings: &'a [String]) -> Option<&'a str> {
+		strings.get(self.string_index as usize).map(String::as_str)
+	}
+}
+
+/// TTAB
+#[binrw]
+#[brw(little)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PieMenuFunctions {
+	pub file_name: FileName,
+	pub unknown: u32,
+	pub format: u32,
+
+	#[br(temp)]
+	#[bw(calc = interactions.len() as u16)]
+	count: u16,
+	#[br(count = count, args { inner: args! { format } })]
+	#[bw(args { format: *format })]
+	pub interactions: Vec<Interaction>,
+}
+
+impl Default for PieMenuFunctions {
+	fn default() -> Self {
+		Self {
+			file_name: FileName::default(),
+			unknown: 0xFFFFFFFF,
+			format: 0x54,
+			interactions: vec![],
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use binrw::{BinRead, BinWrite};
+	use proptest::{collection::vec, prelude::*, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+
+	/// the bytes of a TTAB header for a single interaction
+	fn header(format: u32) -> Vec<u8> {
+		let mut bytes = vec![0; 0x40];
+		bytes.extend(0xFFFFFFFFu32.to_le_bytes());
+		bytes.extend(format.to_le_bytes());
+		bytes.extend(1u16.to_le_bytes());
+		bytes
+	}
+
+	/// the bytes of the interaction fields between the table sizes and the motive tables
+	fn fields(flags: u16, string_index: u32) -> Vec<u8> {
+		let mut bytes = vec![];
+		bytes.extend(flags.to_le_bytes());
+		bytes.extend(0u16.to_le_bytes());
+		bytes.extend(string_index.to_le_bytes());
+		bytes.extend(1u32.to_le_bytes());
+		bytes.extend(0.5f32.to_le_bytes());
+		bytes.extend(50u32.to_le_bytes());
+		bytes.extend(0xFFFFFFFFu32.to_le_bytes());
+		bytes
+	}
+
+	fn motive_bytes(table: &[(i16, i16, u16)]) -> Vec<u8> {
+		table
+			.iter()
+			.flat_map(|(min, delta, personality)| {
+				[
+					min.to_le_bytes(),
+					delta.to_le_bytes(),
+					personality.to_le_bytes(),
+				]
+			})
+			.flatten()
+			.collect()
+	}
+
+	fn advertisements(table: &[(i16, i16, u16)]) -> Vec<MotiveAdvertisement> {
+		table
+			.iter()
+			.map(|&(min, delta, personality)| MotiveAdvertisement {
+				min,
+				delta,
+				personality,
+			})
+			.collect()
+	}
+
+	/// read a TTAB with one interaction, check its fields and write it back
+	fn read_single(
+		bytes: &[u8],
+		ids: (u16, u16),
+		flags: u16,
+		string_index: u32,
+	) -> Result<Interaction, TestCaseError> {
+		let ttab = PieMenuFunctions::read(&mut Cursor::new(bytes))?;
+		prop_assert_eq!(ttab.interactions.len(), 1);
+		let interaction = ttab.interactions[0].clone();
+		prop_assert_eq!((interaction.action_id, interaction.guardian_id), ids);
+		prop_assert_eq!(interaction.flags, flags);
+		prop_assert_eq!(interaction.string_index, string_index);
+		prop_assert_eq!(interaction.attenuation, Attenuation::Off);
+		prop_assert_eq!(interaction.autonomy, 50);
+		prop_assert_eq!(interaction.joining_index, 0xFFFFFFFF);
+
+		let mut cur = Cursor::new(vec![]);
+		ttab.write(&mut cur)?;
+		prop_assert_eq!(cur.into_inner(), bytes);
+		Ok(interaction)
+	}
+
+	#[proptest]
+	fn read_single_motive_table(
+		#[strategy(0x3Fu32..FORMAT_MOTIVE_TABLES)] format: u32,
+		ids: (u16, u16),
+		flags: u16,
+		string_index: u32,
+		#[strategy(vec(any::<(i16, i16, u16)>(), OLD_MOTIVE_COUNT))] table: Vec<(i16, i16, u16)>,
+	) {
+		let mut bytes = header(format);
+		bytes.extend(ids.0.to_le_bytes());
+		bytes.extend(ids.1.to_le_bytes());
+		bytes.extend(fields(flags, string_index));
+		bytes.extend(motive_bytes(&table));
+
+		let interaction = read_single(&bytes, ids, flags, string_index)?;
+		prop_assert_eq!(interaction.motive_tables, vec![advertisements(&table)]);
+	}
+
+	#[proptest]
+	fn read_motive_tables(
+		ids: (u16, u16),
+		flags: u16,
+		string_index: u32,
+		#[strategy(vec(vec(any::<(i16, i16, u16)>(), 0..20), 0..8))] tables: Vec<
+			Vec<(i16, i16, u16)>,
+		>,
+	) {
+		let mut bytes = header(FORMAT_MOTIVE_TABLES);
+		bytes.extend(ids.0.to_le_bytes());
+		bytes.extend(ids.1.to_le_bytes());
+		bytes.extend((tables.len() as u32).to_le_bytes());
+		for table in &tables {
+			bytes.extend((table.len() as u32).to_le_bytes());
+		}
+		bytes.extend(fields(flags, string_index));
+		for table in &tables {
+			bytes.extend(motive_bytes(table));
+		}
+
+		let interaction = read_single(&bytes, ids, flags, string_index)?;
+		prop_assert_eq!(
+			interaction.motive_tables,
+			tables
+				.iter()
+				.map(|table| advertisements(table))
+				.collect::<Vec<_>>()
+		);
+	}
+}
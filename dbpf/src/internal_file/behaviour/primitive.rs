@@ -0,0 +1,589 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Typed operands of the standard primitives, the instructions with an opcode below 0x100
+
+use std::{
+	fmt::{Display, Formatter},
+	io::Cursor,
+};
+
+use binrw::{args, binrw, helpers::until_eof, BinRead, BinWriterExt};
+
+use crate::{
+	common::numbered_enum,
+	internal_file::behaviour::behaviour_function_labels::BehaviourFunctionLabels,
+};
+
+/// the first opcode that calls another BHAV instead of a primitive
+pub const FIRST_CALL_OPCODE: u16 = 0x100;
+
+const PRIMITIVE_NAMES: &[&str] = &[
+	"Sleep",
+	"Generic Sims Call",
+	"Expression",
+	"Find Best Interaction",
+	"Grab",
+	"Drop",
+	"Change Suit/Accessory",
+	"Refresh",
+	"Random Number",
+	"Burn",
+	"Tutorial",
+	"Get Distance To",
+	"Get Direction To",
+	"Push Interaction",
+	"Find Best Object For Function",
+	"Breakpoint",
+	"Find Location For",
+	"Idle For Input",
+	"Remove Object Instance",
+	"Make New Character",
+	"Run Functional Tree",
+	"Show String",
+	"Look Towards",
+	"Play/Stop Sound Event",
+	"Old Relationship",
+	"Alter Budget",
+	"Relationship",
+	"Go To Relative Position",
+	"Run Tree By Name",
+	"Set Motive Change",
+	"Gosub Found Action",
+	"Set To Next",
+	"Test Object Type",
+	"Find 5 Worst Motives",
+	"UI Effect",
+	"Special Effect",
+	"Dialog",
+	"Test Sim Interacting With",
+	"Dialog (Global Strings)",
+	"Dialog (Semi-Global Strings)",
+	"Online Jobs Call",
+	"Set Balloon/Headline",
+	"Create New Object Instance",
+	"Drop Onto",
+	"Animate Sim",
+	"Go To Routing Slot",
+	"Snap",
+	"Reach",
+	"Stop All Sounds",
+	"Notify Stack Object Out Of Idle",
+	"Add/Change Action String",
+	"Manage Inventory",
+];
+
+/// The names of the parameters and locals of a tree, as stored in its TPRP
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OperandLabels {
+	pub params: Vec<String>,
+	pub locals: Vec<String>,
+}
+
+impl From<&BehaviourFunctionLabels> for OperandLabels {
+	fn from(labels: &BehaviourFunctionLabels) -> Self {
+		Self {
+			params: labels.params.iter().map(|s| s.to_string()).collect(),
+			locals: labels.locals.iter().map(|s| s.to_string()).collect(),
+		}
+	}
+}
+
+/// The name of a primitive, `None` for unknown primitives and calls to other BHAVs
+pub fn primitive_name(opcode: u16) -> Option<&'static str> {
+	PRIMITIVE_NAMES.get(opcode as usize).copied()
+}
+
+numbered_enum! {
+	/// Where the value of an operand comes from, the data of the operand is the index in this scope
+	DataOwner: u8 {
+		MyAttribute = 0x00 => "My Attribute",
+		StackObjectAttribute = 0x01 => "Stack Object's Attribute",
+		TargetAttribute = 0x02 => "Target's Attribute",
+		MyData = 0x03 => "My",
+		StackObjectData = 0x04 => "Stack Object's",
+		TargetData = 0x05 => "Target's",
+		Global = 0x06 => "Global",
+		Literal = 0x07 => "Literal",
+		Temp = 0x08 => "Temp",
+		Parameter = 0x09 => "Param",
+		StackObjectId = 0x0A => "Stack Object ID",
+		TempByTemp = 0x0B => "Temp[Temp]",
+		TreeAdRange = 0x0C => "Tree Ad Range",
+		StackObjectTemp = 0x0D => "Stack Object's Temp",
+		MyMotive = 0x0E => "My Motive",
+		StackObjectMotive = 0x0F => "Stack Object's Motive",
+		StackObjectSlot = 0x10 => "Stack Object's Slot",
+		StackObjectMotiveByTemp = 0x11 => "Stack Object's Motive[Temp]",
+		MyPersonData = 0x12 => "My Person Data",
+		StackObjectPersonData = 0x13 => "Stack Object's Person Data",
+		MySlot = 0x14 => "My Slot",
+		StackObjectDefinition = 0x15 => "Stack Object's Definition",
+		StackObjectAttributeByParameter = 0x16 => "Stack Object's Attribute[Param]",
+		RoomByTemp0 = 0x17 => "Room[Temp 0]",
+		NeighborInStackObject = 0x18 => "Neighbor in Stack Object",
+		Local = 0x19 => "Local",
+		Constant = 0x1A => "Const",
+	}
+}
+
+impl DataOwner {
+	/// Describe the value an owner and its data refer to, like `Local 2` or `Param 0`
+	pub fn describe(&self, data: u16) -> String {
+		self.describe_labelled(data, &OperandLabels::default())
+	}
+
+	/// Describe the value like [DataOwner::describe], using the names of parameters and locals where known
+	pub fn describe_labelled(&self, data: u16, labels: &OperandLabels) -> String {
+		let label = match self {
+			DataOwner::Parameter => labels.params.get(data as usize),
+			DataOwner::Local => labels.locals.get(data as usize),
+			_ => None,
+		};
+		if let Some(label) = label.filter(|label| !label.is_empty()) {
+			return format!("{} {label}", self.name().unwrap_or_default());
+		}
+		match self {
+			DataOwner::Literal => format!("{}", data as i16),
+			DataOwner::StackObjectId => "Stack Object ID".to_string(),
+			DataOwner::Constant => format!("Const {data:#06x}"),
+			DataOwner::Unknown(owner) => format!("Owner {owner:#04x} {data}"),
+			known => format!("{} {data}", known.name().unwrap_or_default()),
+		}
+	}
+}
+
+numbered_enum! {
+	ExpressionOperator: u8 {
+		GreaterThan = 0 => ">",
+		LessThan = 1 => "<",
+		Equal = 2 => "==",
+		Add = 3 => "+=",
+		Subtract = 4 => "-=",
+		Assign = 5 => ":=",
+		Multiply = 6 => "*=",
+		Divide = 7 => "/=",
+		IsFlagSet = 8 => "flag set?",
+		SetFlag = 9 => "set flag",
+		ClearFlag = 10 => "clear flag",
+		IncrementAndLessThan = 11 => "++ and <",
+		Modulo = 12 => "%=",
+		And = 13 => "&=",
+		GreaterOrEqual = 14 => ">=",
+		LessOrEqual = 15 => "<=",
+		NotEqual = 16 => "!=",
+		DecrementAndGreaterThan = 17 => "-- and >",
+	}
+}
+
+impl ExpressionOperator {
+	/// Whether the expression can return false
+	///
+	/// Assignments always return true, comparisons and the loop operators that assign and then
+	/// compare can return either. Unknown operators are assumed to return either.
+	pub fn can_return_false(&self) -> bool {
+		!matches!(
+			self,
+			ExpressionOperator::Add
+				| ExpressionOperator::Subtract
+				| ExpressionOperator::Assign
+				| ExpressionOperator::Multiply
+				| ExpressionOperator::Divide
+				| ExpressionOperator::SetFlag
+				| ExpressionOperator::ClearFlag
+				| ExpressionOperator::Modulo
+				| ExpressionOperator::And
+		)
+	}
+}
+
+numbered_enum! {
+	/// What kind of object [SetToNext] searches for
+	SearchType: u8 {
+		ObjectOfType = 0 => "object of type",
+		Person = 1 => "person",
+		NonPerson = 2 => "non-person",
+		PartOfMultiTile = 3 => "part of a multi-tile object",
+		ObjectWithCategory = 4 => "object with category = Temp 0",
+		NeighborInHouse = 5 => "neighbor in house",
+		ObjectOnSameTile = 6 => "object on the same tile",
+		ObjectAdjacentToSelf = 7 => "object adjacent to self",
+		Career = 8 => "career",
+		Neighbor = 9 => "neighbor",
+	}
+}
+
+/// wait for a number of ticks, or until woken up by another object
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Sleep {
+	/// the parameter that holds the number of ticks, it counts down while sleeping
+	pub ticks_parameter: u16,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GenericSimsCall {
+	pub call: u8,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+/// compare two values, or assign the result of an operation to the left hand side
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Expression {
+	pub lhs_data: u16,
+	pub rhs_data: u16,
+	pub flags: u8,
+	pub operator: ExpressionOperator,
+	pub lhs_owner: DataOwner,
+	pub rhs_owner: DataOwner,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FindBestInteraction {
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+/// store a random number from 0 up to, but not including, the range
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RandomNumber {
+	pub destination_data: u16,
+	pub destination_owner: DataOwner,
+	pub unused: u8,
+	pub range_data: u16,
+	pub range_owner: DataOwner,
+	pub unused2: u8,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+/// add an interaction of an object to the queue of the stack object
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PushInteraction {
+	pub interaction: u8,
+	/// the temp or parameter that holds the object the interaction is on
+	pub object_location: u8,
+	pub priority: u8,
+	pub flags: u8,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+/// run a tree of the stack object by the name in a string table
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RunTreeByName {
+	pub string_table: u16,
+	pub string_index: u8,
+	pub destination: u8,
+	pub flags: u8,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+/// set the target to the next object that matches the search, going to false when there are none left
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SetToNext {
+	/// the object type that is searched for with [SearchType::ObjectOfType]
+	pub guid: u32,
+	/// the search type in the lower 7 bits
+	pub flags: u8,
+	pub target_owner: DataOwner,
+	pub local: u8,
+	pub target_data: u16,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+impl SetToNext {
+	pub fn search_type(&self) -> SearchType {
+		SearchType::from(self.flags & 0x7F)
+	}
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TestObjectType {
+	pub guid: u32,
+	pub id_data: u16,
+	pub id_owner: DataOwner,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AnimateSim {
+	pub animation: u16,
+	/// the tree that is run for the events in the animation
+	pub event_tree: u8,
+	pub source: u8,
+	pub flags: u8,
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+/// run another BHAV, the opcode is the instance of that BHAV
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Call {
+	/// the values for the first parameters of the called tree
+	pub arguments: [i16; 4],
+	#[br(parse_with = until_eof)]
+	pub rest: Vec<u8>,
+}
+
+/// An instruction with its operands decoded according to its opcode
+#[binrw]
+#[brw(little)]
+#[br(import {opcode: u16})]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Function {
+	#[br(pre_assert(opcode == 0x00))]
+	Sleep(Sleep),
+	#[br(pre_assert(opcode == 0x01))]
+	GenericSimsCall(GenericSimsCall),
+	#[br(pre_assert(opcode == 0x02))]
+	Expression(Expression),
+	#[br(pre_assert(opcode == 0x03))]
+	FindBestInteraction(FindBestInteraction),
+	#[br(pre_assert(opcode == 0x08))]
+	RandomNumber(RandomNumber),
+	#[br(pre_assert(opcode == 0x0D))]
+	PushInteraction(PushInteraction),
+	#[br(pre_assert(opcode == 0x1C))]
+	RunTreeByName(RunTreeByName),
+	#[br(pre_assert(opcode == 0x1F))]
+	SetToNext(SetToNext),
+	#[br(pre_assert(opcode == 0x20))]
+	TestObjectType(TestObjectType),
+	#[br(pre_assert(opcode == 0x2C))]
+	AnimateSim(AnimateSim),
+	#[br(pre_assert(opcode >= FIRST_CALL_OPCODE))]
+	Call(
+		#[br(calc = opcode)]
+		#[bw(ignore)]
+		u16,
+		Call,
+	),
+	/// a primitive without typed operands, or operands that are too short for their primitive
+	Unknown(
+		#[br(calc = opcode)]
+		#[bw(ignore)]
+		u16,
+		#[br(parse_with = until_eof)] Vec<u8>,
+	),
+}
+
+impl Function {
+	/// Decode the operands of an instruction, this never fails as unknown operands are kept as they are
+	pub fn decode(opcode: u16, operands: &[u8]) -> Self {
+		Self::read_args(&mut Cursor::new(operands), args! {opcode})
+			.unwrap_or_else(|_| Function::Unknown(opcode, operands.to_vec()))
+	}
+
+	pub fn opcode(&self) -> u16 {
+		match self {
+			Function::Sleep(_) => 0x00,
+			Function::GenericSimsCall(_) => 0x01,
+			Function::Expression(_) => 0x02,
+			Function::FindBestInteraction(_) => 0x03,
+			Function::RandomNumber(_) => 0x08,
+			Function::PushInteraction(_) => 0x0D,
+			Function::RunTreeByName(_) => 0x1C,
+			Function::SetToNext(_) => 0x1F,
+			Function::TestObjectType(_) => 0x20,
+			Function::AnimateSim(_) => 0x2C,
+			Function::Call(opcode, _) | Function::Unknown(opcode, _) => *opcode,
+		}
+	}
+
+	/// Whether the primitive can return false
+	///
+	/// Assignments, sleeping, random numbers and animations always return true. Calls return
+	/// whatever the called tree returns, so they and every other primitive are assumed to return either.
+	pub fn can_return_false(&self) -> bool {
+		match self {
+			Function::Expression(e) => e.operator.can_return_false(),
+			Function::Sleep(_) | Function::RandomNumber(_) | Function::AnimateSim(_) => false,
+			_ => true,
+		}
+	}
+
+	/// The values the operands read or write, as owner and data pairs
+	pub fn data_references(&self) -> Vec<(DataOwner, u16)> {
+		match self {
+			Function::Sleep(sleep) => vec![(DataOwner::Parameter, sleep.ticks_parameter)],
+			Function::Expression(e) => vec![(e.lhs_owner, e.lhs_data), (e.rhs_owner, e.rhs_data)],
+			Function::RandomNumber(r) => vec![
+				(r.destination_owner, r.destination_data),
+				(r.range_owner, r.range_data),
+			],
+			Function::SetToNext(s) => vec![(s.target_owner, s.target_data)],
+			Function::TestObjectType(t) => vec![(t.id_owner, t.id_data)],
+			_ => vec![],
+		}
+	}
+
+	/// Describe what the instruction does, using the names of parameters and locals where known
+	pub fn describe(&self, labels: &OperandLabels) -> String {
+		Described {
+			function: self,
+			labels,
+		}
+		.to_string()
+	}
+
+	/// Encode the operands back to bytes
+	pub fn operands(&self) -> Vec<u8> {
+		let mut cursor = Cursor::new(vec![]);
+		// writing to a vec can not fail
+		let _ = cursor.write_le(self);
+		cursor.into_inner()
+	}
+}
+
+impl Display for Function {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		Described {
+			function: self,
+			labels: &OperandLabels::default(),
+		}
+		.fmt(f)
+	}
+}
+
+struct Described<'a> {
+	function: &'a Function,
+	labels: &'a OperandLabels,
+}
+
+impl Display for Described<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let labels = self.labels;
+		match self.function {
+			Function::Sleep(sleep) => {
+				write!(f, "Sleep for Param {} ticks", sleep.ticks_parameter)
+			}
+			Function::GenericSimsCall(call) => write!(f, "Generic Sims Call {:#04x}", call.call),
+			Function::Expression(e) => {
+				let operator = e
+					.operator
+					.name()
+					.map(str::to_string)
+					.unwrap_or_else(|| format!("operator {:#04x}", u8::from(e.operator)));
+				write!(
+					f,
+					"{} {operator} {}",
+					e.lhs_owner.describe_labelled(e.lhs_data, labels),
+					e.rhs_owner.describe_labelled(e.rhs_data, labels)
+				)
+			}
+			Function::FindBestInteraction(_) => write!(f, "Find Best Interaction"),
+			Function::RandomNumber(r) => write!(
+				f,
+				"{} := Random({})",
+				r.destination_owner
+					.describe_labelled(r.destination_data, labels),
+				r.range_owner.describe_labelled(r.range_data, labels)
+			),
+			Function::PushInteraction(p) => write!(
+				f,
+				"Push Interaction {} of the object in {}, priority {}",
+				p.interaction, p.object_location, p.priority
+			),
+			Function::RunTreeByName(r) => write!(
+				f,
+				"Run Tree By Name: string {} of table {:#06x}",
+				r.string_index, r.string_table
+			),
+			Function::SetToNext(s) => {
+				let search = s
+					.search_type()
+					.name()
+					.map(str::to_string)
+					.unwrap_or_else(|| format!("search type {}", s.flags & 0x7F));
+				write!(
+					f,
+					"Set {} to next {search}",
+					s.target_owner.describe_labelled(s.target_data, labels)
+				)?;
+				if s.search_type() == SearchType::ObjectOfType {
+					write!(f, " {:#010x}", s.guid)?;
+				}
+				Ok(())
+			}
+			Function::TestObjectType(t) => write!(
+				f,
+				"Is {} of type {:#010x}",
+				t.id_owner.describe_labelled(t.id_data, labels),
+				t.guid
+			),
+			Function::AnimateSim(a) => write!(f, "Animate Sim {}", a.animation),
+			Function::Call(opcode, call) => write!(
+				f,
+				"Call {opcode:#06x}({}, {}, {}, {})",
+				call.arguments[0], call.arguments[1], call.arguments[2], call.arguments[3]
+			),
+			Function::Unknown(opcode, operands) => {
+				match primitive_name(*opcode) {
+					Some(name) => write!(f, "{name}")?,
+					None => write!(f, "Primitive {opcode:#06x}")?,
+				}
+				write!(f, " (")?;
+				for (i, byte) in operands.iter().enumerate() {
+					if i > 0 {
+						write!(f, " ")?;
+					}
+					write!(f, "{byte:02X}")?;
+				}
+				write!(f, ")")
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::prop_assert_eq;
+	use test_strategy::proptest;
+
+	use super::*;
+
+	#[proptest]
+	fn operands_roundtrip(
+		#[strategy(0u16..0x120)] opcode: u16,
+		#[strategy(proptest::collection::vec(proptest::arbitrary::any::<u8>(), 8..=16))]
+		operands: Vec<u8>,
+	) {
+		let function = Function::decode(opcode, &operands);
+		prop_assert_eq!(function.opcode(), opcode);
+		prop_assert_eq!(function.operands(), operands);
+	}
+
+	#[proptest]
+	fn expression_display(#[strategy(0u16..100)] lhs: u16, #[strategy(0u16..100)] rhs: u16) {
+		let function = Function::Expression(Expression {
+			lhs_data: lhs,
+			rhs_data: rhs,
+			operator: ExpressionOperator::Add,
+			lhs_owner: DataOwner::Local,
+			rhs_owner: DataOwner::Parameter,
+			..Default::default()
+		});
+		prop_assert_eq!(function.to_string(), format!("Local {lhs} += Param {rhs}"));
+		prop_assert_eq!(Function::decode(0x02, &function.operands()), function);
+	}
+}
@@ -9,6 +9,7 @@ pub mod dbpf_directory;
 pub mod image;
 pub mod material_shader;
 pub mod object_data;
+pub mod object_slot;
 pub mod resource_collection;
 pub mod sim_description;
 pub mod sim_outfits;
@@ -21,7 +22,8 @@ use std::{
 
 use behaviour::{
 	behaviour_constants::BehaviourConstants, behaviour_function::BehaviourFunction,
-	object_functions::ObjectFunctions,
+	global_data::GlobalData, object_functions::ObjectFunctions,
+	pie_menu_functions::PieMenuFunctions,
 };
 use binrw::{binread, binrw, BinRead, BinResult, BinWrite, NamedArgs};
 use cpf::{binary_index::BinaryIndex, property_set::PropertySet};
@@ -46,6 +48,7 @@ use crate::{
 		},
 		cpf::CPF,
 		object_data::ObjectData,
+		object_slot::ObjectSlot,
 		resource_collection::ResourceCollection,
 		sim_outfits::SimOutfits,
 		text_list::TextList,
@@ -157,6 +160,26 @@ impl FileData {
 			_ => unreachable!(),
 		}
 	}
+
+	/// The decoded data, without decoding it if that has not happened yet
+	pub fn as_decoded(&self) -> Option<&DecodedFile> {
+		match &self.data {
+			FileDataInternal::Decoded(decoded) => Some(decoded),
+			_ => None,
+		}
+	}
+
+	/// Decodes a copy of the data, without storing the result
+	///
+	/// Use this to read other resources, so they are written back exactly as they were loaded
+	pub fn decoded_copy(&self) -> Result<Option<DecodedFile>, CompressionError> {
+		let decompressed = match &self.data {
+			FileDataInternal::Decoded(decoded) => return Ok(Some(decoded.clone())),
+			FileDataInternal::Compressed(data) => &data.clone().decompress()?,
+			FileDataInternal::Uncompressed(data) => data,
+		};
+		Ok(decompressed.decode(self.type_id).transpose()?)
+	}
 }
 
 #[binrw]
@@ -285,12 +308,15 @@ pub enum DecodedFile {
 	BehaviourConstants(BehaviourConstants),
 	BehaviourConstantsLabels(BehaviourConstantsLabels),
 	ObjectFunctions(ObjectFunctions),
+	GlobalData(GlobalData),
+	PieMenuFunctions(PieMenuFunctions),
 
 	// others
 	SimOutfits(SimOutfits),
 	AudioReference(AudioReference),
 	TextList(TextList),
 	ObjectData(ObjectData),
+	ObjectSlot(ObjectSlot),
 	SimDescription(SimDescription),
 	MaterialShader(MaterialShader),
 	Image(Image),
@@ -335,7 +361,15 @@ impl DecodedFile {
 			DBPFFileType::Known(
 				KnownDBPFFileType::TextureResource
 				| KnownDBPFFileType::MaterialDefinition
-				| KnownDBPFFileType::GeometricDataContainer,
+				| KnownDBPFFileType::GeometricDataContainer
+				| KnownDBPFFileType::CreationResource
+				| KnownDBPFFileType::Shape
+				| KnownDBPFFileType::GeometricNode
+				| KnownDBPFFileType::AmbientLight
+				| KnownDBPFFileType::DirectionalLight
+				| KnownDBPFFileType::PointLight
+				| KnownDBPFFileType::Spotlight
+				| KnownDBPFFileType::LevelInformation,
 			) => Some(ResourceCollection::read(&mut cursor).map(DecodedFile::ResourceCollection)),
 			DBPFFileType::Known(
 				KnownDBPFFileType::TextList
@@ -359,9 +393,18 @@ impl DecodedFile {
 			DBPFFileType::Known(KnownDBPFFileType::ObjectFunctions) => {
 				Some(ObjectFunctions::read(&mut cursor).map(DecodedFile::ObjectFunctions))
 			}
+			DBPFFileType::Known(KnownDBPFFileType::GlobalData) => {
+				Some(GlobalData::read(&mut cursor).map(DecodedFile::GlobalData))
+			}
+			DBPFFileType::Known(KnownDBPFFileType::PieMenuFunctions) => {
+				Some(PieMenuFunctions::read(&mut cursor).map(DecodedFile::PieMenuFunctions))
+			}
 			DBPFFileType::Known(KnownDBPFFileType::ObjectData) => {
 				Some(ObjectData::read(&mut cursor).map(DecodedFile::ObjectData))
 			}
+			DBPFFileType::Known(KnownDBPFFileType::ObjectSlot) => {
+				Some(ObjectSlot::read(&mut cursor).map(DecodedFile::ObjectSlot))
+			}
 			DBPFFileType::Known(KnownDBPFFileType::AudioReference) => {
 				Some(AudioReference::read(&mut cursor).map(DecodedFile::AudioReference))
 			}
@@ -392,7 +435,10 @@ impl DecodedFile {
 			DecodedFile::BehaviourConstants(x) => x.write(&mut data)?,
 			DecodedFile::BehaviourConstantsLabels(x) => x.write(&mut data)?,
 			DecodedFile::ObjectFunctions(x) => x.write(&mut data)?,
+			DecodedFile::GlobalData(x) => x.write(&mut data)?,
+			DecodedFile::PieMenuFunctions(x) => x.write(&mut data)?,
 			DecodedFile::ObjectData(x) => x.write(&mut data)?,
+			DecodedFile::ObjectSlot(x) => x.write(&mut data)?,
 			DecodedFile::AudioReference(x) => x.write(&mut data)?,
 			DecodedFile::SimDescription(x) => x.write(&mut data)?,
 			DecodedFile::MaterialShader(x) => x.write(&mut data)?,
@@ -3,8 +3,9 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use binrw::{binrw, parser, writer, BinRead, BinResult, BinWrite};
+use modular_bitfield::prelude::*;
 
-use crate::common::{BigString, ByteString, FileName, PascalString};
+use crate::common::{numbered_enum, BigString, ByteString, FileName, PascalString};
 
 #[parser(reader)]
 fn filename_parser(version: Version) -> BinResult<ByteString> {
@@ -34,6 +35,189 @@ pub enum Version {
 	S2P = 0x8d,
 }
 
+numbered_enum! {
+	ObjectType: u16 {
+		Person = 2 => "Person",
+		Normal = 4 => "Normal",
+		SimType = 7 => "Sim type",
+		Door = 8 => "Door",
+		Window = 9 => "Window",
+		Stairs = 10 => "Stairs",
+		ModularStairs = 11 => "Modular stairs",
+		ModularStairsPortal = 12 => "Modular stairs portal",
+		Vehicle = 13 => "Vehicle",
+		Outfit = 14 => "Outfit",
+		Memory = 15 => "Memory",
+		Template = 16 => "Template",
+	}
+}
+
+numbered_enum! {
+	ShadowType: u16 {
+		None = 0 => "None",
+		Normal = 1 => "Normal",
+	}
+}
+
+/// the ages that can use the object
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CatalogUse {
+	pub adults: bool,
+	pub children: bool,
+	pub group: bool,
+	pub teens: bool,
+	pub elders: bool,
+	pub toddlers: bool,
+	unused: B10,
+}
+
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct RoomSort {
+	pub kitchen: bool,
+	pub bedroom: bool,
+	pub bathroom: bool,
+	pub living_room: bool,
+	pub outside: bool,
+	pub dining_room: bool,
+	pub misc: bool,
+	pub study: bool,
+	pub kids: bool,
+	unused: B7,
+}
+
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct FunctionSort {
+	pub seating: bool,
+	pub surfaces: bool,
+	pub appliances: bool,
+	pub electronics: bool,
+	pub plumbing: bool,
+	pub decorative: bool,
+	pub general: bool,
+	pub lighting: bool,
+	pub hobbies: bool,
+	unknown: bool,
+	pub aspiration_rewards: bool,
+	pub career_rewards: bool,
+	unused: B4,
+}
+
+/// the expansion packs the object is valid in, the first half of them
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExpansionFlags {
+	pub base: bool,
+	pub university: bool,
+	pub nightlife: bool,
+	pub business: bool,
+	pub family: bool,
+	pub glamour: bool,
+	pub pets: bool,
+	pub seasons: bool,
+	pub celebration: bool,
+	pub hm_fashion: bool,
+	pub bon_voyage: bool,
+	pub teen_style: bool,
+	unknown: bool,
+	pub free_time: bool,
+	pub kitchen_bath: bool,
+	pub ikea: bool,
+}
+
+/// the expansion packs the object is valid in, continued from [ExpansionFlags]
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExpansionFlags2 {
+	pub apartment_life: bool,
+	pub mansion_garden: bool,
+	unused: B14,
+}
+
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuildModeType {
+	pub miscellaneous: bool,
+	unknown: bool,
+	pub garden: bool,
+	pub doors_windows: bool,
+	unused: B12,
+}
+
+/// what the bits mean depends on the [BuildModeType], the names list the miscellaneous, garden and
+/// doors & windows meaning of each bit in that order, skipping the types that do not use it
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuildModeSubsort {
+	pub trees_doors: bool,
+	pub shrubs_multi_story_windows: bool,
+	pub flowers_windows: bool,
+	pub columns_gates: bool,
+	pub gardening_archways: bool,
+	pub stairs: bool,
+	pub swimming_pool: bool,
+	unknown: bool,
+	pub multi_story_columns_doors: bool,
+	pub column_arches: bool,
+	pub garage: bool,
+	pub elevators: bool,
+	pub architecture: bool,
+	unused: B3,
+}
+
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ForSaleFlags {
+	pub for_sale: bool,
+	unused: B15,
+}
+
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CommunitySort {
+	pub food: bool,
+	pub shopping: bool,
+	pub outdoor: bool,
+	pub street: bool,
+	pub miscellaneous: bool,
+	unused: B11,
+}
+
+#[bitfield(bits = 16)]
+#[repr(u16)]
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Skills {
+	pub cooking: bool,
+	pub mechanical: bool,
+	pub logic: bool,
+	pub body: bool,
+	pub creativity: bool,
+	pub charisma: bool,
+	pub cleaning: bool,
+	unused: B9,
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -48,14 +232,14 @@ pub struct ObjectData {
 	pub default_allowed_height: u16,
 	pub interaction_table_id: u16,
 	pub interaction_group: u16,
-	pub object_type: u16,
+	pub object_type: ObjectType,
 	pub multi_tile_master_id: u16,
 	pub multi_tile_sub_index: u16,
 	pub use_default_placement: u16,
 	pub look_at_score: u16,
 	pub guid: u32,
 	pub unlockable: u16,
-	pub catalog_use: u16,
+	pub catalog_use: CatalogUse,
 	pub price: u16,
 	pub body_strings_id: u16,
 	pub slot_id: u16,
@@ -74,8 +258,8 @@ pub struct ObjectData {
 	pub daily_depreciation: u16,
 	pub self_depreciating: u16,
 	pub depreciation_limit: u16,
-	pub room_sort: u16,
-	pub function_sort: u16,
+	pub room_sort: RoomSort,
+	pub function_sort: FunctionSort,
 	pub catalog_strings_id: u16,
 	pub is_global_sim_object: u16,
 	pub tooltip_name_type: u16,
@@ -91,23 +275,23 @@ pub struct ObjectData {
 	pub catalog_popup_id: u16,
 	pub ignore_current_model_index: u16,
 	pub level_offset: u16,
-	pub shadow_type: u16,
+	pub shadow_type: ShadowType,
 	pub num_attributes: u16,
 	pub num_object_arrays: u16,
-	pub for_sale_flags: u16,
+	pub for_sale_flags: ForSaleFlags,
 	pub front_direction: u16,
 	pub unused2: u16,
 	pub multi_tile_lead: u16,
-	pub expansion_flags_1: u16, // ts1 dynamic sprites id
-	pub expansion_flags_2: u16, // ts1 num dynamic sprites
+	pub expansion_flags_1: ExpansionFlags, // ts1 dynamic sprites id
+	pub expansion_flags_2: ExpansionFlags2, // ts1 num dynamic sprites
 	pub chair_entry_flags: u16,
 	pub tile_width: u16,
 	pub inhibit_suit_copying: u16,
-	pub build_mode_type: u16,
+	pub build_mode_type: BuildModeType,
 	pub original_guid: u32,
 	pub default_graphic: u16, // object model guid?
 	pub unused3: u16,
-	pub build_mode_subsort: u16,
+	pub build_mode_subsort: BuildModeSubsort,
 	pub selector_category: u16,     // ts1 thumbnail graphic
 	pub selector_sub_category: u16, // ts1 shadow flags
 	pub footprint_mask: u16,
@@ -122,7 +306,7 @@ pub struct ObjectData {
 	pub energy_rating: u16,
 	pub fun_rating: u16,
 	pub room_rating: u16,
-	pub gives_skill: u16,
+	pub gives_skill: Skills,
 	pub num_type_attributes: u16,
 	pub misc_flags: u16,
 	pub type_attribute_guid: u32, // ts1
@@ -132,7 +316,7 @@ pub struct ObjectData {
 	pub vacation_sort: u16,
 	pub reset_lot_action: u16,
 	pub object_type_3d: u16,
-	pub community_sort: u16,
+	pub community_sort: CommunitySort,
 	pub dream_flags: u16,
 	pub thumbnail_flags: u16,
 	pub scratch_rating: u16,
@@ -147,3 +331,55 @@ pub struct ObjectData {
 	#[bw(write_with = filename_writer)]
 	pub file_name_2: ByteString,
 }
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use binrw::{BinRead, BinWrite};
+	use proptest::prop_assert_eq;
+	use test_strategy::proptest;
+
+	use super::*;
+
+	#[proptest]
+	fn typed_fields_keep_unknown_values(
+		object_type: u16,
+		catalog_use: u16,
+		room_sort: u16,
+		function_sort: u16,
+		expansion_flags: (u16, u16),
+		build_mode_type: u16,
+		build_mode_subsort: u16,
+		shadow_type: u16,
+		for_sale_flags: u16,
+		community_sort: u16,
+		gives_skill: u16,
+	) {
+		let objd = ObjectData {
+			object_type: object_type.into(),
+			catalog_use: catalog_use.into(),
+			room_sort: room_sort.into(),
+			function_sort: function_sort.into(),
+			expansion_flags_1: expansion_flags.0.into(),
+			expansion_flags_2: expansion_flags.1.into(),
+			build_mode_type: build_mode_type.into(),
+			build_mode_subsort: build_mode_subsort.into(),
+			shadow_type: shadow_type.into(),
+			for_sale_flags: for_sale_flags.into(),
+			community_sort: community_sort.into(),
+			gives_skill: gives_skill.into(),
+			..Default::default()
+		};
+		let mut cur = Cursor::new(vec![]);
+		objd.write(&mut cur)?;
+		cur.set_position(0);
+		let read = ObjectData::read(&mut cur)?;
+		prop_assert_eq!(u16::from(read.object_type), object_type);
+		prop_assert_eq!(u16::from(read.room_sort), room_sort);
+		prop_assert_eq!(u16::from(read.function_sort), function_sort);
+		prop_assert_eq!(u16::from(read.build_mode_subsort), build_mode_subsort);
+		prop_assert_eq!(u16::from(read.shadow_type), shadow_type);
+		prop_assert_eq!(read, objd);
+	}
+}
@@ -0,0 +1,175 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! SLOT, the places around an object where sims stand and other objects are put
+
+use binrw::{args, binrw};
+
+use crate::common::{numbered_enum, FileName};
+
+/// the names of the bits of [Slot::flags]
+pub const SLOT_FLAG_NAMES: [&str; 17] = [
+	"N",
+	"NE",
+	"E",
+	"SE",
+	"S",
+	"SW",
+	"W",
+	"NW",
+	"any rotation",
+	"absolute",
+	"facing away",
+	"ignore rooms",
+	"snap to direction",
+	"random scoring",
+	"allow failure trees",
+	"allow different alts",
+	"use average location",
+];
+
+numbered_enum! {
+	SlotType: u16 {
+		Container = 0 => "Container",
+		Location = 1 => "Location",
+		Routing = 3 => "Routing",
+		Target = 4 => "Target",
+	}
+}
+
+#[binrw]
+#[brw(import { version: u32 })]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Slot {
+	pub slot_type: SlotType,
+	/// the offset from the object
+	pub position: [f32; 3],
+
+	pub standing: i32,
+	pub sitting: i32,
+	pub ground: i32,
+	/// the directions a sim may come from and how the slot is scored, see [SLOT_FLAG_NAMES]
+	pub flags: u32,
+	pub snap_target_slot: i32,
+
+	#[brw(if(version >= 6))]
+	pub min_proximity: i32,
+	#[brw(if(version >= 6))]
+	pub max_proximity: i32,
+	#[brw(if(version >= 6))]
+	pub optimal_proximity: i32,
+	#[brw(if(version >= 6))]
+	pub unknown: [i32; 2],
+
+	#[brw(if(version >= 7))]
+	pub gradient: f32,
+	#[brw(if(version >= 8))]
+	pub height: i32,
+	/// -3 for any direction, -2 towards the object, -1 away from it, otherwise a direction
+	#[brw(if(version >= 9))]
+	pub facing: i32,
+	#[brw(if(version >= 10))]
+	pub resolution: i32,
+}
+
+/// SLOT
+#[binrw]
+#[brw(little)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectSlot {
+	pub file_name: FileName,
+	pub unknown: u32,
+	pub version: u32,
+
+	#[brw(magic = b"TOLS")]
+	#[br(temp)]
+	#[bw(calc = slots.len() as u32)]
+	count: u32,
+	#[br(count = count, args { inner: args! { version } })]
+	#[bw(args { version: *version })]
+	pub slots: Vec<Slot>,
+}
+
+impl Default for ObjectSlot {
+	fn default() -> Self {
+		Self {
+			file_name: FileName::default(),
+			unknown: 0,
+			version: 10,
+			slots: vec![],
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use binrw::{BinRead, BinWrite};
+	use proptest::{collection::vec, prelude::*, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+
+	fn slot(version: u32) -> impl Strategy<Value = Slot> {
+		(
+			any::<(u16, [i16; 3], i32, i32, i32, u32, i32)>(),
+			any::<(i32, i32, i32, [i32; 2], i32, i32, i32)>(),
+		)
+			.prop_map(
+				move |(
+					(slot_type, position, standing, sitting, ground, flags, snap_target_slot),
+					(min, max, optimal, unknown, height, facing, resolution),
+				)| {
+					let mut slot = Slot {
+						slot_type: slot_type.into(),
+						position: position.map(f32::from),
+						standing,
+						sitting,
+						ground,
+						flags,
+						snap_target_slot,
+						..Default::default()
+					};
+					if version >= 6 {
+						slot.min_proximity = min;
+						slot.max_proximity = max;
+						slot.optimal_proximity = optimal;
+						slot.unknown = unknown;
+					}
+					if version >= 7 {
+						slot.gradient = 0.25;
+					}
+					if version >= 8 {
+						slot.height = height;
+					}
+					if version >= 9 {
+						slot.facing = facing;
+					}
+					if version >= 10 {
+						slot.resolution = resolution;
+					}
+					slot
+				},
+			)
+	}
+
+	fn object_slot() -> impl Strategy<Value = ObjectSlot> {
+		(4u32..=10)
+			.prop_flat_map(|version| (Just(version), vec(slot(version), 0..10)))
+			.prop_map(|(version, slots)| ObjectSlot {
+				version,
+				slots,
+				..Default::default()
+			})
+	}
+
+	#[proptest]
+	fn write_read_same(#[strategy(object_slot())] slot: ObjectSlot) {
+		let mut cur = Cursor::new(vec![]);
+		slot.write(&mut cur)?;
+		cur.set_position(0);
+		prop_assert_eq!(ObjectSlot::read(&mut cur)?, slot);
+	}
+}
@@ -0,0 +1,159 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use binrw::binrw;
+
+use crate::{
+	common::BigString,
+	internal_file::resource_collection::{
+		object_graph::{
+			BoundedNode, CompositionTreeNode, EmbeddedTransformNode, ObjectGraphNode,
+			RenderableNode,
+		},
+		FileName, ResourceBlockVersion,
+	},
+};
+
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ResourceNodeItem {
+	pub unknown: i16,
+	/// index of the child block inside the resource collection
+	pub child_node: i32,
+}
+
+#[binrw]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResourceNodeData {
+	#[brw(magic = 1u8)]
+	Resource {
+		file_name: FileName,
+		composition_tree_node: CompositionTreeNode,
+		#[br(temp)]
+		#[bw(calc = items.len() as u8)]
+		count: u8,
+		#[br(count = count)]
+		items: Vec<ResourceNodeItem>,
+		unknown: i32,
+	},
+	#[brw(magic = 0u8)]
+	Graph {
+		object_graph_node: ObjectGraphNode,
+		item: ResourceNodeItem,
+	},
+}
+
+impl Default for ResourceNodeData {
+	fn default() -> Self {
+		Self::Resource {
+			file_name: FileName::default(),
+			composition_tree_node: CompositionTreeNode::default(),
+			items: vec![],
+			unknown: 0,
+		}
+	}
+}
+
+/// cResourceNode, the root block of a CRES
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResourceNode {
+	pub data: ResourceNodeData,
+	pub unknown: i32,
+}
+
+impl ResourceNode {
+	pub fn file_name(&self) -> Option<&FileName> {
+		match &self.data {
+			ResourceNodeData::Resource { file_name, .. } => Some(file_name),
+			ResourceNodeData::Graph { .. } => None,
+		}
+	}
+
+	pub fn children(&self) -> Vec<i32> {
+		match &self.data {
+			ResourceNodeData::Resource { items, .. } => {
+				items.iter().map(|item| item.child_node).collect()
+			}
+			ResourceNodeData::Graph { item, .. } => vec![item.child_node],
+		}
+	}
+
+	pub fn object_graph_node(&self) -> &ObjectGraphNode {
+		match &self.data {
+			ResourceNodeData::Resource {
+				composition_tree_node,
+				..
+			} => &composition_tree_node.object_graph_node,
+			ResourceNodeData::Graph {
+				object_graph_node, ..
+			} => object_graph_node,
+		}
+	}
+}
+
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ShapeRefNodeItem {
+	pub unknown1: u16,
+	/// index into the resource collection links, pointing to a SHPE
+	pub shape_link: i32,
+}
+
+/// cShapeRefNode, places a SHPE in the scenegraph
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShapeRefNode {
+	pub renderable_node: RenderableNode,
+	pub bounded_node: BoundedNode,
+	pub transform_node: EmbeddedTransformNode,
+
+	pub unknown1: i16,
+	pub unknown2: i32,
+	pub name: BigString,
+	pub unknown3: i32,
+	pub unknown4: u8,
+
+	#[br(temp)]
+	#[bw(calc = shapes.len() as u32)]
+	shape_count: u32,
+	#[br(count = shape_count)]
+	pub shapes: Vec<ShapeRefNodeItem>,
+
+	pub unknown5: i32,
+
+	#[br(temp)]
+	#[bw(calc = blend_indices.len() as u32)]
+	blend_count: u32,
+	#[br(count = blend_count)]
+	pub blend_indices: Vec<i32>,
+	#[br(count = blend_count)]
+	#[brw(if(version == ResourceBlockVersion::V21))]
+	#[bw(assert(version != ResourceBlockVersion::V21 || blend_names.len() == blend_indices.len()))]
+	pub blend_names: Vec<BigString>,
+
+	#[br(temp)]
+	#[bw(calc = data.len() as u32)]
+	data_len: u32,
+	#[br(count = data_len)]
+	pub data: Vec<u8>,
+
+	pub unknown6: i32,
+}
+
+/// cLightRefNode, places a LGHT in the scenegraph
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LightRefNode {
+	pub renderable_node: RenderableNode,
+	pub bounded_node: BoundedNode,
+	pub transform_node: EmbeddedTransformNode,
+
+	/// index into the resource collection links, pointing to a LGHT
+	pub light_link: u16,
+	pub unknown: [u8; 4],
+}
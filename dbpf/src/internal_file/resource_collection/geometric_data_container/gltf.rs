@@ -8,31 +8,147 @@ use std::{
 	iter,
 };
 
+use futures::executor::block_on;
 use gltf_kun::{
 	extensions::DefaultExtensions,
 	graph::{
 		gltf::{
-			accessor::{iter::AccessorIter, ComponentType, Type},
+			accessor::{
+				iter::{AccessorIter, AccessorIterCreateError},
+				ComponentType, Type,
+			},
 			node::{Quat, Vec3},
 			primitive::{Mode, Semantic},
-			Accessor, GltfDocument, Node,
+			Accessor, GltfDocument, Node, Primitive,
 		},
 		Graph, GraphNodeWeight,
 	},
-	io::format::glb::{GlbExport, GlbFormat},
+	io::{
+		format::{
+			glb::{GlbExport, GlbExportError, GlbFormat, GlbImport, GlbImportError},
+			gltf::{import::GltfImportError as GltfFormatImportError, GltfFormat, GltfImport},
+		},
+		resolver::DataUriResolver,
+	},
 };
 use itertools::Itertools;
+use log::warn;
 use serde_json::{Number, Value};
+use thiserror::Error;
+
+use crate::{
+	common::{BigString, SizedVec},
+	internal_file::resource_collection::{
+		geometric_data_container::{
+			math::{Mat4, Quaternion, Transform, Vertex},
+			skeleton::Skeleton,
+			AttributeBinding, AttributeBuffer, AttributeGroup, AttributeType, BlendGroupBinding,
+			BlockFormat, BoundingMesh, GeometricDataContainer, IndexSet, Mesh, PrimitiveType,
+			Reference,
+		},
+		FileName,
+	},
+};
 
-use crate::internal_file::resource_collection::geometric_data_container::{
-	math::Mat4, AttributeBuffer, AttributeType, BlockFormat, BoundingMesh, GeometricDataContainer,
-	PrimitiveType,
+/// rotation applied to the base node on export, converts from the z-up gmdc space to y-up gltf space
+const BASE_ROTATION: Quaternion = Quaternion {
+	x: 0.0,
+	y: FRAC_1_SQRT_2,
+	z: FRAC_1_SQRT_2,
+	w: 0.0,
 };
 
-impl GeometricDataContainer {
-	pub fn export_gltf(&self) -> Option<GlbFormat> {
-		eprintln!("self.file_name = {:#?}", self.file_name);
+/// the maximum number of bones that can influence a single vertex
+const MAX_BONE_INFLUENCES: usize = 3;
+
+#[derive(Error, Debug)]
+pub enum GltfImportError {
+	#[error(transparent)]
+	Glb(#[from] GlbImportError),
+	#[error(transparent)]
+	Gltf(#[from] GltfFormatImportError),
+	#[error(transparent)]
+	Json(#[from] serde_json::Error),
+	#[error("the file does not contain any meshes")]
+	NoMeshes,
+	#[error("mesh {0} does not have any vertex positions")]
+	MissingPositions(String),
+	#[error("mesh {0} has more vertices than can be indexed")]
+	TooManyVertices(String),
+	#[error("mesh {0} is influenced by more than 255 bones")]
+	TooManyBones(String),
+	#[error("accessor in mesh {0} has an unsupported type")]
+	UnsupportedAccessor(String),
+	#[error("more than 255 blend groups are used")]
+	TooManyBlendGroups,
+	#[error("mesh {mesh} uses joint {joint}, but there are only {num_bones} bones")]
+	JointOutOfRange {
+		mesh: String,
+		joint: u32,
+		num_bones: usize,
+	},
+}
 
+#[derive(Error, Debug)]
+pub enum GltfExportError {
+	#[error(transparent)]
+	Glb(#[from] GlbExportError),
+	#[error(transparent)]
+	Accessor(#[from] AccessorIterCreateError),
+	#[error(transparent)]
+	Json(#[from] serde_json::Error),
+	#[error("the generated glb file is malformed")]
+	MalformedGlb,
+	#[error("mesh {mesh} uses attribute group {group}, which does not exist")]
+	MissingAttributeGroup { mesh: String, group: u32 },
+	#[error("mesh {mesh} uses attribute buffer {buffer}, which does not exist")]
+	MissingAttribute { mesh: String, buffer: u32 },
+	#[error("attribute buffer {buffer} stores {binding_type:?} as {block_format:?}, which is not supported")]
+	BadAttributeFormat {
+		buffer: u32,
+		binding_type: AttributeType,
+		block_format: BlockFormat,
+	},
+	#[error("attribute buffer {buffer} does not contain a whole number of elements")]
+	BadAttributeSize { buffer: u32 },
+	#[error("mesh {mesh} uses vertex {index}, but only has {num_vertices} vertices")]
+	IndexOutOfRange {
+		mesh: String,
+		index: u32,
+		num_vertices: usize,
+	},
+	#[error("mesh {mesh} uses bone slot {slot}, but only binds {num_slots} bones")]
+	BoneSlotOutOfRange {
+		mesh: String,
+		slot: u8,
+		num_slots: usize,
+	},
+	#[error("mesh {mesh} binds bone {bone}, but there are only {num_bones} bones")]
+	BoneOutOfRange {
+		mesh: String,
+		bone: u32,
+		num_bones: usize,
+	},
+	#[error(
+		"mesh {mesh} has {num_indices} indices, which is not a whole number of {primitive_type:?}"
+	)]
+	UnsupportedPrimitive {
+		mesh: String,
+		primitive_type: PrimitiveType,
+		num_indices: usize,
+	},
+}
+
+impl GeometricDataContainer {
+	/// Export all meshes to a binary gltf file
+	///
+	/// Meshes that reference missing or malformed data are skipped with a warning,
+	/// the export only fails if none of the meshes could be exported.
+	///
+	/// `skeleton` is the joint hierarchy of the CRES that uses this mesh, as found by
+	/// [find_skeleton](super::skeleton::find_skeleton).
+	/// Without it, or if it does not drive every bone, each bone is exported on its own at its bind pose.
+	pub fn export_gltf(&self, skeleton: Option<&Skeleton>) -> Result<GlbFormat, GltfExportError> {
 		let mut graph = Graph::default();
 
 		let doc = GltfDocument::new(&mut graph);
@@ -40,11 +156,10 @@ impl GeometricDataContainer {
 		let accessors: Vec<_> = self
 			.attribute_buffers
 			.iter()
-			.map(|element| {
-				// eprintln!("element = {:#?}", element);
-
-				if element.data.is_empty() {
-					return None;
+			.enumerate()
+			.map(|(i, element)| {
+				if element.data.is_empty() || check_attribute_format(i as u32, element).is_err() {
+					return Ok(None);
 				}
 
 				let mut gltf_accessor = match element.binding.binding_type {
@@ -66,19 +181,16 @@ impl GeometricDataContainer {
 								ComponentType::F32,
 								Type::Vec4,
 								false,
-							)
-							.unwrap(),
-						) // TODO error handling
+							)?,
+						)
 					}
 					AttributeType::BoneKeys => {
 						// bone indices have to be rebuilt using the mesh's bone bindings
-						return None;
+						return Ok(None);
 					}
 					AttributeType::Tangents | AttributeType::TangentDeltas => {
 						// tangents need their handedness specified in the w component
 						// gmdc meshes only have one handedness
-						debug_assert!(element.block_format == BlockFormat::F32Vec3);
-
 						Accessor::from_iter(
 							&mut graph,
 							AccessorIter::new(
@@ -101,9 +213,8 @@ impl GeometricDataContainer {
 								ComponentType::F32,
 								Type::Vec4,
 								false,
-							)
-							.unwrap(),
-						) // TODO error handling
+							)?,
+						)
 					}
 					_ => {
 						let (component_type, element_type) = match element.block_format {
@@ -115,9 +226,8 @@ impl GeometricDataContainer {
 
 						Accessor::from_iter(
 							&mut graph,
-							AccessorIter::new(&element.data, component_type, element_type, false)
-								.unwrap(),
-						) // TODO error handling
+							AccessorIter::new(&element.data, component_type, element_type, false)?,
+						)
 					}
 				};
 
@@ -132,9 +242,9 @@ impl GeometricDataContainer {
 					element.index_set,
 				));
 
-				Some(gltf_accessor)
+				Ok(Some(gltf_accessor))
 			})
-			.collect();
+			.collect::<Result<_, GltfExportError>>()?;
 
 		let model_name = String::from_utf8_lossy(&self.file_name.name.0).to_string();
 
@@ -148,7 +258,12 @@ impl GeometricDataContainer {
 		let base_node_w = gltf_base_node.get_mut(&mut graph);
 
 		base_node_w.name = Some(model_name);
-		base_node_w.rotation = Quat::from_xyzw(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0);
+		base_node_w.rotation = Quat::from_xyzw(
+			BASE_ROTATION.x,
+			BASE_ROTATION.y,
+			BASE_ROTATION.z,
+			BASE_ROTATION.w,
+		);
 
 		gltf_scene.add_node(&mut graph, gltf_base_node);
 
@@ -157,7 +272,7 @@ impl GeometricDataContainer {
 			graph: &mut Graph,
 			b_mesh: &BoundingMesh,
 			name: String,
-		) -> Node {
+		) -> Result<Node, GltfExportError> {
 			let mut gltf_node = doc.create_node(graph);
 			let node_w = gltf_node.get_mut(graph);
 
@@ -186,9 +301,8 @@ impl GeometricDataContainer {
 						ComponentType::F32,
 						Type::Vec3,
 						false,
-					)
-					.unwrap(),
-				); // TODO error handling
+					)?,
+				);
 				doc.add_accessor(graph, vertices_accessor);
 				gltf_primitive.set_attribute(graph, Semantic::Positions, Some(vertices_accessor));
 
@@ -203,32 +317,79 @@ impl GeometricDataContainer {
 						ComponentType::U32,
 						Type::Scalar,
 						false,
-					)
-					.unwrap(),
-				); // TODO error handling
+					)?,
+				);
 				doc.add_accessor(graph, indices_accessor);
 				gltf_primitive.set_indices(graph, Some(indices_accessor));
 
 				gltf_node.set_mesh(graph, Some(gltf_mesh));
 			}
 
-			gltf_node
+			Ok(gltf_node)
 		}
 
 		let mut bone_nodes = vec![];
 
-		if !self.bounding_mesh.vertices.is_empty() || !self.dynamic_bounding_mesh.is_empty() {
+		let main_bounding_mesh = if !self.bounding_mesh.vertices.is_empty()
+			|| !self.dynamic_bounding_mesh.is_empty()
+			|| !self.bones.is_empty()
+		{
 			let main_bounding_mesh =
-				make_bounding_mesh(&doc, &mut graph, &self.bounding_mesh, "b_mesh".to_string());
+				make_bounding_mesh(&doc, &mut graph, &self.bounding_mesh, "b_mesh".to_string())?;
 			gltf_base_node.add_child(&mut graph, &main_bounding_mesh);
+			Some(main_bounding_mesh)
+		} else {
+			None
+		};
 
-			bone_nodes = self
-				.dynamic_bounding_mesh
-				.iter()
-				.enumerate()
-				.map(|(bone_idx, b_mesh)| {
+		if let Some(skeleton) = skeleton.filter(|skeleton| skeleton.covers(self)) {
+			let mut joint_nodes: Vec<Node> = vec![];
+			for joint in &skeleton.joints {
+				let mut joint_node = doc.create_node(&mut graph);
+				let node_w = joint_node.get_mut(&mut graph);
+				let r = joint.transform.rotation;
+				let t = joint.transform.translation;
+				node_w.name = Some(joint.name.clone());
+				node_w.rotation = Quat::from_xyzw(r.x, r.y, r.z, r.w);
+				node_w.translation = Vec3::new(t.x, t.y, t.z);
+
+				// parents always come before their children
+				let parent = joint
+					.parent
+					.map_or(gltf_base_node, |parent| joint_nodes[parent]);
+				parent.add_child(&mut graph, &joint_node);
+				joint_nodes.push(joint_node);
+			}
+
+			bone_nodes = (0..self.bones.len())
+				.filter_map(|bone| Some(joint_nodes[skeleton.joint_for_bone(bone)?]))
+				.collect();
+
+			// dynamic bounding meshes are relative to their bone, so they are attached to the joint
+			if let Some(main_bounding_mesh) = main_bounding_mesh {
+				for (bone_idx, b_mesh) in self.dynamic_bounding_mesh.iter().enumerate() {
+					if b_mesh.vertices.is_empty() {
+						continue;
+					}
+					let bone_bounding_mesh =
+						make_bounding_mesh(&doc, &mut graph, b_mesh, format!("b_mesh#{bone_idx}"))?;
+					bone_nodes
+						.get(bone_idx)
+						.unwrap_or(&main_bounding_mesh)
+						.add_child(&mut graph, &bone_bounding_mesh);
+				}
+			}
+		} else if let Some(main_bounding_mesh) = main_bounding_mesh {
+			// without a skeleton every bone gets a node at its bind pose, below the bounding mesh
+			let no_bounding_mesh = BoundingMesh::default();
+			bone_nodes = (0..self.bones.len().max(self.dynamic_bounding_mesh.len()))
+				.map(|bone_idx| {
+					let b_mesh = self
+						.dynamic_bounding_mesh
+						.get(bone_idx)
+						.unwrap_or(&no_bounding_mesh);
 					let mut bone_bounding_mesh =
-						make_bounding_mesh(&doc, &mut graph, b_mesh, format!("bone#{bone_idx}"));
+						make_bounding_mesh(&doc, &mut graph, b_mesh, format!("bone#{bone_idx}"))?;
 
 					main_bounding_mesh.add_child(&mut graph, &bone_bounding_mesh);
 
@@ -242,19 +403,19 @@ impl GeometricDataContainer {
 						node_w.translation = Vec3::new(t.x, t.y, t.z);
 					}
 
-					bone_bounding_mesh
+					Ok(bone_bounding_mesh)
 				})
-				.collect();
+				.collect::<Result<_, GltfExportError>>()?;
 		}
-		// TODO add nodes for remaining bone translations
 
-		let gltf_skin = if !bone_nodes.is_empty() {
+		let gltf_skin = if !self.bones.is_empty() {
 			let mut gltf_skin = doc.create_skin(&mut graph);
 			let skin_w = gltf_skin.get_mut(&mut graph);
 
 			skin_w.name = Some("Armature".to_string());
 
-			for (idx, bone) in bone_nodes.iter().enumerate() {
+			// the inverse bind matrices only cover actual bones
+			for (idx, bone) in bone_nodes.iter().take(self.bones.len()).enumerate() {
 				gltf_skin.add_joint(&mut graph, bone, idx);
 			}
 
@@ -274,9 +435,8 @@ impl GeometricDataContainer {
 					ComponentType::F32,
 					Type::Mat4,
 					false,
-				)
-				.unwrap(),
-			); // TODO error handling
+				)?,
+			);
 			doc.add_accessor(&mut graph, inverse_transforms_accessor);
 			gltf_skin.set_inverse_bind_matrices(&mut graph, Some(inverse_transforms_accessor));
 
@@ -285,10 +445,17 @@ impl GeometricDataContainer {
 			None
 		};
 
+		let mut first_error = None;
+		let mut num_exported = 0;
 		for group in self.meshes.iter() {
 			let group_name = String::from_utf8_lossy(&group.name.0).to_string();
 
-			eprintln!("group_name = {:?}", group_name);
+			if let Err(err) = self.check_mesh(group) {
+				warn!("skipping mesh {group_name} in gltf export: {err}");
+				first_error.get_or_insert(err);
+				continue;
+			}
+			num_exported += 1;
 
 			let mut gltf_mesh = doc.create_mesh(&mut graph);
 
@@ -312,9 +479,8 @@ impl GeometricDataContainer {
 					ComponentType::U32,
 					Type::Scalar,
 					false,
-				)
-				.unwrap(),
-			); // TODO error handling
+				)?,
+			);
 			doc.add_accessor(&mut graph, indices_accessor);
 			gltf_primitive.set_indices(&mut graph, Some(indices_accessor));
 
@@ -326,9 +492,11 @@ impl GeometricDataContainer {
 				.map(|idx| &self.attribute_buffers[idx.0 as usize])
 				.filter(|attr| attr.binding.binding_type == AttributeType::BlendKeys)
 				.collect::<Vec<_>>();
+			// 0xff marks an unused blend key slot
 			let active_morphs = blend_keys
 				.iter()
 				.flat_map(|keys| keys.data.iter().copied())
+				.filter(|key| *key != 0xff)
 				.collect::<BTreeSet<_>>();
 			let mut morph_to_buffer = active_morphs
 				.iter()
@@ -368,7 +536,7 @@ impl GeometricDataContainer {
 					for (buffer_key, (_, _, buffer)) in morph_to_buffer.iter_mut() {
 						if keys.clone().contains(buffer_key) {
 							// this buffer needs to get the new data, see if a buffer already exists
-							buffer.entry(b_type).or_insert_with(|| {
+							let buffer = buffer.entry(b_type).or_insert_with(|| {
 								iter::repeat(0.0f32.to_le_bytes())
 									.flatten()
 									.take(delta.data.len())
@@ -376,12 +544,7 @@ impl GeometricDataContainer {
 							});
 							keys.clone()
 								.zip(delta.data.chunks_exact(delta_data_chunk_size))
-								.zip(
-									buffer
-										.get_mut(&b_type)
-										.unwrap()
-										.chunks_exact_mut(delta_data_chunk_size),
-								)
+								.zip(buffer.chunks_exact_mut(delta_data_chunk_size))
 								.for_each(|((key, delta), buf)| {
 									if key == *buffer_key {
 										buf.copy_from_slice(delta);
@@ -394,7 +557,6 @@ impl GeometricDataContainer {
 
 			for attr_idx in link.attributes.iter() {
 				let attr = &self.attribute_buffers[attr_idx.0 as usize];
-				eprintln!("attr = {:#?}", attr);
 				let binding_idx = attr.binding.binding_slot;
 
 				let identity = match attr.binding.binding_type {
@@ -434,7 +596,6 @@ impl GeometricDataContainer {
 										if *i == 0xff {
 											0u16.to_le_bytes()
 										} else {
-											// TODO handle bone idx out of range
 											(group.bone_references[*i as usize].0 as u16)
 												.to_le_bytes()
 										}
@@ -443,9 +604,8 @@ impl GeometricDataContainer {
 								ComponentType::U16,
 								Type::Vec4,
 								false,
-							)
-							.unwrap(),
-						); // TODO error handling
+							)?,
+						);
 						doc.add_accessor(&mut graph, bone_keys_accessor);
 
 						gltf_primitive.set_attribute(
@@ -503,8 +663,8 @@ impl GeometricDataContainer {
 
 					let accessor = Accessor::from_iter(
 						&mut graph,
-						AccessorIter::new(&buf, ComponentType::F32, components, false).unwrap(),
-					); // TODO error handling
+						AccessorIter::new(&buf, ComponentType::F32, components, false)?,
+					);
 					doc.add_accessor(&mut graph, accessor);
 
 					morph.set_attribute(&mut graph, identity, Some(accessor));
@@ -544,6 +704,1170 @@ impl GeometricDataContainer {
 			gltf_base_node.add_child(&mut graph, &gltf_node);
 		}
 
-		GlbExport::<DefaultExtensions>::export(&mut graph, &doc).ok()
+		// gltf_kun does not write morph targets, so they are added to the json afterwards
+		let mesh_targets: Vec<Value> = doc
+			.meshes(&graph)
+			.iter()
+			.map(|mesh| {
+				Value::Array(
+					mesh.primitives(&graph)
+						.iter()
+						.map(|primitive| {
+							Value::Array(
+								primitive
+									.morph_targets(&graph)
+									.iter()
+									.map(|target| {
+										Value::Object(
+											target
+												.attributes(&graph)
+												.into_iter()
+												.filter_map(|(semantic, accessor)| {
+													Some((
+														semantic.to_string(),
+														Value::from(
+															doc.accessor_index(&graph, accessor)?,
+														),
+													))
+												})
+												.collect(),
+										)
+									})
+									.collect(),
+							)
+						})
+						.collect(),
+				)
+			})
+			.collect();
+
+		if let Some(err) = first_error.filter(|_| num_exported == 0) {
+			return Err(err);
+		}
+
+		let glb = GlbExport::<DefaultExtensions>::export(&mut graph, &doc)?;
+		patch_glb_json(glb, |json| {
+			let Some(meshes) = json.get_mut("meshes").and_then(Value::as_array_mut) else {
+				return;
+			};
+			for (mesh, targets) in meshes.iter_mut().zip(&mesh_targets) {
+				let Some(primitives) = mesh.get_mut("primitives").and_then(Value::as_array_mut)
+				else {
+					continue;
+				};
+				let Some(targets) = targets.as_array() else {
+					continue;
+				};
+				for (primitive, targets) in primitives.iter_mut().zip(targets) {
+					if targets.as_array().is_some_and(|t| !t.is_empty()) {
+						primitive["targets"] = targets.clone();
+					}
+				}
+			}
+		})
+	}
+
+	/// check that everything a mesh references exists and can be represented in gltf
+	fn check_mesh(&self, mesh: &Mesh) -> Result<(), GltfExportError> {
+		let name = || String::from_utf8_lossy(&mesh.name.0).to_string();
+
+		let group = self
+			.attribute_groups
+			.get(mesh.attribute_group_index as usize)
+			.ok_or_else(|| GltfExportError::MissingAttributeGroup {
+				mesh: name(),
+				group: mesh.attribute_group_index,
+			})?;
+
+		let mut num_vertices = group.number_elements as usize;
+		for buffer in group.attributes.iter() {
+			let attr = self
+				.attribute_buffers
+				.get(buffer.0 as usize)
+				.ok_or_else(|| GltfExportError::MissingAttribute {
+					mesh: name(),
+					buffer: buffer.0,
+				})?;
+			check_attribute_format(buffer.0, attr)?;
+
+			match attr.binding.binding_type {
+				AttributeType::Positions => {
+					num_vertices = attr.data.len() / attr.element_size();
+				}
+				AttributeType::BoneKeys => {
+					if let Some(slot) = attr.data.iter().find(|slot| {
+						**slot != 0xff && **slot as usize >= mesh.bone_references.len()
+					}) {
+						return Err(GltfExportError::BoneSlotOutOfRange {
+							mesh: name(),
+							slot: *slot,
+							num_slots: mesh.bone_references.len(),
+						});
+					}
+				}
+				_ => {}
+			}
+		}
+
+		if let Some(bone) = mesh
+			.bone_references
+			.iter()
+			.find(|bone| bone.0 as usize >= self.bones.len())
+		{
+			return Err(GltfExportError::BoneOutOfRange {
+				mesh: name(),
+				bone: bone.0,
+				num_bones: self.bones.len(),
+			});
+		}
+
+		if let Some(index) = mesh
+			.indices
+			.iter()
+			.find(|index| index.0 as usize >= num_vertices)
+		{
+			return Err(GltfExportError::IndexOutOfRange {
+				mesh: name(),
+				index: index.0,
+				num_vertices,
+			});
+		}
+
+		let primitive_size = match mesh.primitive_type {
+			PrimitiveType::Points => 1,
+			PrimitiveType::Lines => 2,
+			PrimitiveType::Triangles => 3,
+		};
+		if !mesh.indices.len().is_multiple_of(primitive_size) {
+			return Err(GltfExportError::UnsupportedPrimitive {
+				mesh: name(),
+				primitive_type: mesh.primitive_type.clone(),
+				num_indices: mesh.indices.len(),
+			});
+		}
+
+		Ok(())
+	}
+}
+
+/// check that the data of an attribute buffer can be converted to the accessor type gltf expects
+fn check_attribute_format(buffer: u32, attr: &AttributeBuffer) -> Result<(), GltfExportError> {
+	let supported = match attr.binding.binding_type {
+		AttributeType::BoneKeys | AttributeType::BlendKeys => {
+			attr.block_format == BlockFormat::U8Vec4
+		}
+		AttributeType::BoneWeights => attr.block_format != BlockFormat::U8Vec4,
+		AttributeType::Positions
+		| AttributeType::Normals
+		| AttributeType::Tangents
+		| AttributeType::PositionDeltas
+		| AttributeType::NormalDeltas
+		| AttributeType::TangentDeltas => attr.block_format == BlockFormat::F32Vec3,
+		_ => true,
+	};
+	if !supported {
+		return Err(GltfExportError::BadAttributeFormat {
+			buffer,
+			binding_type: attr.binding.binding_type,
+			block_format: attr.block_format.clone(),
+		});
+	}
+	if !attr.data.len().is_multiple_of(attr.element_size()) {
+		return Err(GltfExportError::BadAttributeSize { buffer });
+	}
+	Ok(())
+}
+
+/// rewrite the json chunk of a binary gltf file, leaving the binary chunk untouched
+fn patch_glb_json(
+	glb: GlbFormat,
+	patch: impl FnOnce(&mut Value),
+) -> Result<GlbFormat, GltfExportError> {
+	const HEADER_SIZE: usize = 12;
+	const CHUNK_HEADER_SIZE: usize = 8;
+
+	let bytes = glb.0;
+	let json_len = bytes
+		.get(HEADER_SIZE..HEADER_SIZE + 4)
+		.and_then(|len| len.try_into().ok())
+		.map(u32::from_le_bytes)
+		.ok_or(GltfExportError::MalformedGlb)?;
+	let json_start = HEADER_SIZE + CHUNK_HEADER_SIZE;
+	let json_end = json_start + json_len as usize;
+	let mut json: Value = serde_json::from_slice(
+		bytes
+			.get(json_start..json_end)
+			.ok_or(GltfExportError::MalformedGlb)?,
+	)?;
+	patch(&mut json);
+
+	let mut json = serde_json::to_vec(&json)?;
+	// chunks have to be aligned to 4 bytes, json is padded with spaces
+	json.resize(json.len().next_multiple_of(4), b' ');
+	let rest = &bytes[json_end..];
+
+	let total_len = HEADER_SIZE + CHUNK_HEADER_SIZE + json.len() + rest.len();
+	let mut out = Vec::with_capacity(total_len);
+	out.extend_from_slice(&bytes[0..8]);
+	out.extend_from_slice(&(total_len as u32).to_le_bytes());
+	out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+	out.extend_from_slice(&bytes[HEADER_SIZE + 4..json_start]);
+	out.extend_from_slice(&json);
+	out.extend_from_slice(rest);
+	Ok(GlbFormat(out))
+}
+
+fn to_vertex(v: Vec3) -> Vertex {
+	Vertex {
+		x: v.x,
+		y: v.y,
+		z: v.z,
+	}
+}
+
+fn vertex_bytes(v: Vertex) -> impl Iterator<Item = u8> {
+	[v.x, v.y, v.z].into_iter().flat_map(f32::to_le_bytes)
+}
+
+/// read every element of an accessor as a float vector of at least `N` components,
+/// normalized integers are mapped to the [0, 1] or [-1, 1] range
+fn read_vectors<const N: usize>(
+	graph: &Graph,
+	accessor: Accessor,
+	mesh_name: &str,
+) -> Result<Vec<[f32; N]>, GltfImportError> {
+	let weight = accessor.get(graph);
+	let components = weight.element_type.multiplicity();
+	if components < N {
+		return Err(GltfImportError::UnsupportedAccessor(mesh_name.to_string()));
+	}
+	let normalized = weight.normalized;
+	let values: Vec<f32> = weight
+		.data
+		.chunks_exact(weight.component_type.size())
+		.map(|c| match weight.component_type {
+			ComponentType::I8 if normalized => (c[0] as i8 as f32 / 127.0).max(-1.0),
+			ComponentType::I8 => c[0] as i8 as f32,
+			ComponentType::U8 if normalized => c[0] as f32 / 255.0,
+			ComponentType::U8 => c[0] as f32,
+			ComponentType::I16 if normalized => {
+				(i16::from_le_bytes([c[0], c[1]]) as f32 / 32767.0).max(-1.0)
+			}
+			ComponentType::I16 => i16::from_le_bytes([c[0], c[1]]) as f32,
+			ComponentType::U16 if normalized => u16::from_le_bytes([c[0], c[1]]) as f32 / 65535.0,
+			ComponentType::U16 => u16::from_le_bytes([c[0], c[1]]) as f32,
+			ComponentType::U32 => u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f32,
+			ComponentType::F32 => f32::from_le_bytes([c[0], c[1], c[2], c[3]]),
+		})
+		.collect();
+	Ok(values
+		.chunks_exact(components)
+		.map(|e| std::array::from_fn(|i| e[i]))
+		.collect())
+}
+
+/// read an index accessor, integer values are read exactly instead of going through a float
+fn read_indices(graph: &Graph, accessor: Accessor) -> Vec<u32> {
+	let weight = accessor.get(graph);
+	weight
+		.data
+		.chunks_exact(weight.component_type.size())
+		.map(|c| match weight.component_type {
+			ComponentType::I8 | ComponentType::U8 => c[0] as u32,
+			ComponentType::I16 | ComponentType::U16 => u16::from_le_bytes([c[0], c[1]]) as u32,
+			ComponentType::U32 => u32::from_le_bytes([c[0], c[1], c[2], c[3]]),
+			ComponentType::F32 => f32::from_le_bytes([c[0], c[1], c[2], c[3]]) as u32,
+		})
+		.collect()
+}
+
+/// convert strips, loops and fans into the plain lists gmdc meshes support
+fn unroll_indices(mode: Mode, indices: Vec<u32>) -> (PrimitiveType, Vec<u32>) {
+	match mode {
+		Mode::Points => (PrimitiveType::Points, indices),
+		Mode::Lines => (PrimitiveType::Lines, indices),
+		Mode::LineStrip | Mode::LineLoop => {
+			let mut lines: Vec<u32> = indices
+				.iter()
+				.tuple_windows()
+				.flat_map(|(a, b)| [*a, *b])
+				.collect();
+			if mode == Mode::LineLoop && indices.len() > 2 {
+				lines.extend([indices[indices.len() - 1], indices[0]]);
+			}
+			(PrimitiveType::Lines, lines)
+		}
+		Mode::Triangles => (PrimitiveType::Triangles, indices),
+		Mode::TriangleStrip => (
+			PrimitiveType::Triangles,
+			indices
+				.iter()
+				.tuple_windows()
+				.enumerate()
+				// every other triangle has to be flipped to keep the winding order consistent
+				.flat_map(|(i, (a, b, c))| {
+					if i % 2 == 0 {
+						[*a, *b, *c]
+					} else {
+						[*b, *a, *c]
+					}
+				})
+				.collect(),
+		),
+		Mode::TriangleFan => (
+			PrimitiveType::Triangles,
+			indices
+				.iter()
+				.skip(1)
+				.tuple_windows()
+				.flat_map(|(b, c)| [indices[0], *b, *c])
+				.collect(),
+		),
+	}
+}
+
+/// find all nodes with a mesh below `node`, together with their transform into gmdc space
+fn collect_mesh_nodes(graph: &Graph, node: Node, parent: &Mat4, out: &mut Vec<(Node, Mat4)>) {
+	let weight = node.get(graph);
+	// bounding meshes are generated from the imported geometry instead
+	if weight
+		.name
+		.as_deref()
+		.is_some_and(|name| name.starts_with("b_mesh"))
+	{
+		return;
+	}
+	let r = weight.rotation;
+	let world = *parent
+		* Mat4::from_trs(
+			to_vertex(weight.translation),
+			Quaternion {
+				x: r.x,
+				y: r.y,
+				z: r.z,
+				w: r.w,
+			},
+			to_vertex(weight.scale),
+		);
+	if node.mesh(graph).is_some() {
+		out.push((node, world));
+	}
+	for child in node.children(graph) {
+		collect_mesh_nodes(graph, child, &world, out);
+	}
+}
+
+impl GeometricDataContainer {
+	/// build a new container from a glTF (.gltf with embedded buffers) or binary glTF (.glb) file
+	///
+	/// every primitive in the scene becomes a separate mesh, node transforms are applied to the vertices.
+	/// When `original` is given its bones, blend group bindings and file name are kept,
+	/// otherwise the bones are reconstructed from the inverse bind matrices of the first skin.
+	/// Skin joints are expected to be in the same order as the bones of the container.
+	pub fn import_gltf(
+		bytes: &[u8],
+		original: Option<&GeometricDataContainer>,
+	) -> Result<Self, GltfImportError> {
+		let mut graph = Graph::default();
+		let doc = if bytes.starts_with(b"glTF") {
+			block_on(GlbImport::<DefaultExtensions>::import_slice(
+				&mut graph, bytes,
+			))?
+		} else {
+			let format = GltfFormat {
+				json: serde_json::from_slice(bytes)?,
+				..Default::default()
+			};
+			block_on(GltfImport::<DefaultExtensions>::import(
+				&mut graph,
+				format,
+				Some(DataUriResolver),
+			))?
+		};
+
+		let scene = doc
+			.default_scene(&graph)
+			.or_else(|| doc.scenes(&graph).first().copied());
+		let roots = match scene {
+			Some(scene) => scene.nodes(&graph),
+			None => doc
+				.nodes(&graph)
+				.into_iter()
+				.filter(|node| node.parents(&graph).is_empty())
+				.collect(),
+		};
+
+		// undo the axis conversion that is done on export
+		let base = Mat4::rotation(BASE_ROTATION.inverse());
+		let mut mesh_nodes = vec![];
+		for root in &roots {
+			collect_mesh_nodes(&graph, *root, &base, &mut mesh_nodes);
+		}
+		if mesh_nodes.is_empty() {
+			return Err(GltfImportError::NoMeshes);
+		}
+
+		let mut gmdc = GeometricDataContainer::default();
+		if let Some(original) = original {
+			gmdc.file_name = original.file_name.clone();
+			gmdc.bones = original.bones.clone();
+			gmdc.blend_group_bindings = original.blend_group_bindings.clone();
+		} else {
+			let name = scene
+				.and_then(|scene| scene.get(&graph).name.clone())
+				.or_else(|| roots.first().and_then(|node| node.get(&graph).name.clone()))
+				.unwrap_or_default();
+			gmdc.file_name = FileName {
+				block_id: 0,
+				version: 2,
+				name: BigString::from(name),
+			};
+
+			if let Some((skin, world)) = mesh_nodes
+				.iter()
+				.find_map(|(node, world)| Some((node.skin(&graph)?, world)))
+			{
+				let joints = skin.joints(&graph).len();
+				let bones = match skin.inverse_bind_matrices(&graph) {
+					Some(accessor) => {
+						// the inverse bind matrices apply to the vertices before the node transform
+						let to_mesh = world.inverse().unwrap_or(Mat4::identity());
+						read_vectors::<16>(&graph, accessor, "skin")?
+							.into_iter()
+							.map(|m| {
+								// gltf matrices are column-major
+								Transform::from_matrix(&(Mat4(m).transpose() * to_mesh))
+							})
+							.collect()
+					}
+					None => vec![Transform::identity(); joints],
+				};
+				gmdc.bones = SizedVec::new(bones);
+			}
+		}
+
+		let mut mesh_count = 0;
+		for (node, world) in &mesh_nodes {
+			let Some(gltf_mesh) = node.mesh(&graph) else {
+				continue;
+			};
+			let mesh_w = gltf_mesh.get(&graph);
+			let base_name = node
+				.get(&graph)
+				.name
+				.clone()
+				.or_else(|| mesh_w.name.clone())
+				.unwrap_or_else(|| format!("mesh{mesh_count}"));
+			let extras = mesh_w
+				.extras
+				.as_ref()
+				.and_then(|extras| serde_json::from_str::<Value>(extras.get()).ok());
+			let extras_opacity = extras
+				.as_ref()
+				.and_then(|extras| extras.get("opacity")?.as_i64())
+				.map(|opacity| opacity as i32);
+			// morph target names as written by blender and most other exporters
+			let target_names: Vec<String> = extras
+				.as_ref()
+				.and_then(|extras| extras.get("targetNames")?.as_array().cloned())
+				.unwrap_or_default()
+				.into_iter()
+				.map(|name| name.as_str().unwrap_or_default().to_string())
+				.collect();
+
+			for (i, primitive) in gltf_mesh.primitives(&graph).into_iter().enumerate() {
+				let name = if i == 0 {
+					base_name.clone()
+				} else {
+					format!("{base_name}_{i}")
+				};
+				let opacity = extras_opacity
+					.or_else(|| {
+						original?
+							.meshes
+							.iter()
+							.find(|mesh| mesh.name.0.data == name.as_bytes())
+							.map(|mesh| mesh.opacity)
+					})
+					.unwrap_or(0);
+				gmdc.import_primitive(&graph, primitive, name, opacity, &target_names, world)?;
+				mesh_count += 1;
+			}
+		}
+
+		gmdc.recompute_bounding_mesh();
+
+		Ok(gmdc)
+	}
+
+	pub(super) fn push_attribute_buffer(
+		&mut self,
+		binding_type: AttributeType,
+		binding_slot: u32,
+		block_format: BlockFormat,
+		index_set: IndexSet,
+		data: Vec<u8>,
+	) -> Reference {
+		self.attribute_buffers.push(AttributeBuffer {
+			number_elements: (data.len() / block_format.element_size()) as u32,
+			binding: AttributeBinding {
+				binding_type,
+				binding_slot,
+			},
+			block_format,
+			index_set,
+			data: SizedVec::new(data),
+			references: Default::default(),
+		});
+		Reference(self.attribute_buffers.len() as u32 - 1)
+	}
+
+	/// Add a mesh with its own attribute group, for tests
+	///
+	/// The group holds the positions, followed by the `attributes` given as their type, format and data.
+	#[cfg(test)]
+	pub(super) fn push_test_mesh(
+		&mut self,
+		name: &str,
+		positions: &[Vertex],
+		attributes: Vec<(AttributeType, BlockFormat, Vec<u8>)>,
+		indices: impl IntoIterator<Item = u32>,
+	) -> &mut Mesh {
+		let mut references = vec![self.push_attribute_buffer(
+			AttributeType::Positions,
+			0,
+			BlockFormat::F32Vec3,
+			IndexSet::Main,
+			positions.iter().copied().flat_map(vertex_bytes).collect(),
+		)];
+		for (binding_type, block_format, data) in attributes {
+			references.push(self.push_attribute_buffer(
+				binding_type,
+				0,
+				block_format,
+				IndexSet::Main,
+				data,
+			));
+		}
+		self.attribute_groups.push(AttributeGroup {
+			attributes: SizedVec::new(references),
+			number_elements: positions.len() as u32,
+			..Default::default()
+		});
+		self.meshes.push(Mesh {
+			name: BigString::from(name),
+			attribute_group_index: self.attribute_groups.len() as u32 - 1,
+			indices: SizedVec::new(indices.into_iter().map(Reference).collect()),
+			..Default::default()
+		});
+		self.meshes.last_mut().unwrap()
+	}
+
+	fn import_primitive(
+		&mut self,
+		graph: &Graph,
+		primitive: Primitive,
+		name: String,
+		opacity: i32,
+		target_names: &[String],
+		world: &Mat4,
+	) -> Result<(), GltfImportError> {
+		let positions = primitive
+			.attribute(graph, Semantic::Positions)
+			.ok_or_else(|| GltfImportError::MissingPositions(name.clone()))?;
+		let positions: Vec<Vertex> = read_vectors::<3>(graph, positions, &name)?
+			.into_iter()
+			.map(|[x, y, z]| *world * Vertex { x, y, z })
+			.collect();
+		let num_vertices = positions.len();
+		// indices are stored as 16-bit values in current resource versions
+		if num_vertices > u16::MAX as usize {
+			return Err(GltfImportError::TooManyVertices(name));
+		}
+
+		let mut attributes = vec![self.push_attribute_buffer(
+			AttributeType::Positions,
+			0,
+			BlockFormat::F32Vec3,
+			IndexSet::Main,
+			positions.into_iter().flat_map(vertex_bytes).collect(),
+		)];
+
+		let normal_matrix = world
+			.inverse()
+			.map(Mat4::transpose)
+			.unwrap_or(Mat4::identity());
+
+		if let Some(normals) = primitive.attribute(graph, Semantic::Normals) {
+			let data = read_vectors::<3>(graph, normals, &name)?
+				.into_iter()
+				.flat_map(|[x, y, z]| {
+					vertex_bytes(normal_matrix.mul_direction(Vertex { x, y, z }).normalize())
+				})
+				.collect();
+			attributes.push(self.push_attribute_buffer(
+				AttributeType::Normals,
+				0,
+				BlockFormat::F32Vec3,
+				IndexSet::Norms,
+				data,
+			));
+		}
+
+		if let Some(tangents) = primitive.attribute(graph, Semantic::Tangents) {
+			// the handedness in the w component is dropped, gmdc meshes only have one handedness
+			let data = read_vectors::<4>(graph, tangents, &name)?
+				.into_iter()
+				.flat_map(|[x, y, z, _]| {
+					vertex_bytes(world.mul_direction(Vertex { x, y, z }).normalize())
+				})
+				.collect();
+			attributes.push(self.push_attribute_buffer(
+				AttributeType::Tangents,
+				0,
+				BlockFormat::F32Vec3,
+				IndexSet::Main,
+				data,
+			));
+		}
+
+		for (semantic, accessor) in primitive.attributes(graph) {
+			let Semantic::TexCoords(slot) = semantic else {
+				continue;
+			};
+			let data = read_vectors::<2>(graph, accessor, &name)?
+				.into_iter()
+				.flatten()
+				.flat_map(f32::to_le_bytes)
+				.collect();
+			attributes.push(self.push_attribute_buffer(
+				AttributeType::TexCoords,
+				slot,
+				BlockFormat::F32Vec2,
+				IndexSet::UV,
+				data,
+			));
+		}
+
+		// gather the bone influences of all joint sets, keeping only the strongest ones
+		let mut influences = vec![Vec::<(u32, f32)>::new(); num_vertices];
+		let mut set = 0;
+		while let (Some(joints), Some(weights)) = (
+			primitive.attribute(graph, Semantic::Joints(set)),
+			primitive.attribute(graph, Semantic::Weights(set)),
+		) {
+			let joints = read_vectors::<4>(graph, joints, &name)?;
+			let weights = read_vectors::<4>(graph, weights, &name)?;
+			for (vertex, (joints, weights)) in
+				influences.iter_mut().zip(joints.iter().zip(&weights))
+			{
+				vertex.extend(
+					joints
+						.iter()
+						.zip(weights)
+						.filter(|(_, w)| **w > 0.0)
+						.map(|(j, w)| (*j as u32, *w)),
+				);
+			}
+			set += 1;
+		}
+		for vertex in influences.iter_mut() {
+			vertex.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+			vertex.truncate(MAX_BONE_INFLUENCES);
+			let total: f32 = vertex.iter().map(|(_, w)| w).sum();
+			vertex.iter_mut().for_each(|(_, w)| *w /= total);
+		}
+
+		let bone_references: Vec<u32> = influences
+			.iter()
+			.flatten()
+			.map(|(bone, _)| *bone)
+			.unique()
+			.collect();
+		// 0xff is used to mark unused bone slots
+		if bone_references.len() > 0xff {
+			return Err(GltfImportError::TooManyBones(name));
+		}
+		if let Some(joint) = bone_references
+			.iter()
+			.find(|joint| **joint as usize >= self.bones.len())
+		{
+			return Err(GltfImportError::JointOutOfRange {
+				mesh: name,
+				joint: *joint,
+				num_bones: self.bones.len(),
+			});
+		}
+		let max_influences = influences.iter().map(Vec::len).max().unwrap_or(0);
+		if max_influences > 0 {
+			let keys = influences
+				.iter()
+				.flat_map(|vertex| {
+					let mut keys = [0xff; 4];
+					for (key, (bone, _)) in keys.iter_mut().zip(vertex) {
+						*key = bone_references.iter().position(|b| b == bone).unwrap() as u8;
+					}
+					keys
+				})
+				.collect();
+			attributes.push(self.push_attribute_buffer(
+				AttributeType::BoneKeys,
+				0,
+				BlockFormat::U8Vec4,
+				IndexSet::Main,
+				keys,
+			));
+
+			let weights = influences
+				.iter()
+				.flat_map(|vertex| {
+					(0..max_influences)
+						.map(|i| vertex.get(i).map_or(0.0, |(_, w)| *w))
+						.flat_map(f32::to_le_bytes)
+						.collect::<Vec<_>>()
+				})
+				.collect();
+			let block_format = match max_influences {
+				1 => BlockFormat::F32Scalar,
+				2 => BlockFormat::F32Vec2,
+				_ => BlockFormat::F32Vec3,
+			};
+			attributes.push(self.push_attribute_buffer(
+				AttributeType::BoneWeights,
+				0,
+				block_format,
+				IndexSet::Main,
+				weights,
+			));
+		}
+
+		attributes.extend(self.import_morph_targets(
+			graph,
+			primitive,
+			&name,
+			num_vertices,
+			target_names,
+			[world, &normal_matrix, world],
+		)?);
+
+		let indices = primitive
+			.indices(graph)
+			.map(|accessor| read_indices(graph, accessor))
+			.unwrap_or_else(|| (0..num_vertices as u32).collect());
+		if indices.iter().any(|i| *i as usize >= num_vertices) {
+			return Err(GltfImportError::UnsupportedAccessor(name));
+		}
+		let (primitive_type, indices) = unroll_indices(primitive.get(graph).mode, indices);
+
+		self.attribute_groups.push(AttributeGroup {
+			referenced_active: attributes.len() as u32,
+			attributes: SizedVec::new(attributes),
+			number_elements: num_vertices as u32,
+			..Default::default()
+		});
+		self.meshes.push(Mesh {
+			primitive_type,
+			attribute_group_index: self.attribute_groups.len() as u32 - 1,
+			name: BigString::from(name),
+			indices: SizedVec::new(indices.into_iter().map(Reference).collect()),
+			opacity,
+			bone_references: SizedVec::new(bone_references.into_iter().map(Reference).collect()),
+		});
+
+		Ok(())
+	}
+
+	/// find the blend group binding for a morph target name, adding it if it does not exist yet
+	///
+	/// names are expected in the `blend_group::element` form used on export
+	fn blend_group_binding_index(&mut self, name: &str) -> Result<u8, GltfImportError> {
+		let (blend_group, element) = name.split_once("::").unwrap_or((name, name));
+		let index = self
+			.blend_group_bindings
+			.iter()
+			.position(|binding| {
+				binding.blend_group.0.data == blend_group.as_bytes()
+					&& binding.element.0.data == element.as_bytes()
+			})
+			.unwrap_or_else(|| {
+				self.blend_group_bindings.push(BlendGroupBinding {
+					blend_group: BigString::from(blend_group),
+					element: BigString::from(element),
+				});
+				self.blend_group_bindings.len() - 1
+			});
+		// 0xff is used to mark unused blend key slots
+		u8::try_from(index)
+			.ok()
+			.filter(|index| *index != 0xff)
+			.ok_or(GltfImportError::TooManyBlendGroups)
+	}
+
+	/// convert the morph targets of a primitive into blend keys and delta buffers
+	///
+	/// every vertex gets one blend key slot per morph target that moves it,
+	/// the delta buffers are bound to the same slots as the keys they belong to
+	fn import_morph_targets(
+		&mut self,
+		graph: &Graph,
+		primitive: Primitive,
+		name: &str,
+		num_vertices: usize,
+		target_names: &[String],
+		matrices: [&Mat4; 3],
+	) -> Result<Vec<Reference>, GltfImportError> {
+		let delta_types = [
+			(Semantic::Positions, AttributeType::PositionDeltas),
+			(Semantic::Normals, AttributeType::NormalDeltas),
+			(Semantic::Tangents, AttributeType::TangentDeltas),
+		];
+
+		let mut morphs = vec![];
+		for (i, target) in primitive.morph_targets(graph).into_iter().enumerate() {
+			let mut deltas: [Option<Vec<Vertex>>; 3] = Default::default();
+			for ((semantic, _), (deltas, matrix)) in
+				delta_types.iter().zip(deltas.iter_mut().zip(matrices))
+			{
+				if let Some(accessor) = target.attribute(graph, semantic.clone()) {
+					let values = read_vectors::<3>(graph, accessor, name)?;
+					if values.len() != num_vertices {
+						return Err(GltfImportError::UnsupportedAccessor(name.to_string()));
+					}
+					*deltas = Some(
+						values
+							.into_iter()
+							.map(|[x, y, z]| matrix.mul_direction(Vertex { x, y, z }))
+							.collect(),
+					);
+				}
+			}
+			let target_name = target_names
+				.get(i)
+				.filter(|target_name| !target_name.is_empty())
+				.cloned()
+				.unwrap_or_else(|| format!("{name}::morph{i}"));
+			morphs.push((self.blend_group_binding_index(&target_name)?, deltas));
+		}
+
+		let moves = |deltas: &[Option<Vec<Vertex>>; 3], v: usize| {
+			deltas.iter().flatten().any(|d| d[v] != Vertex::identity())
+		};
+		let slots: Vec<Vec<usize>> = (0..num_vertices)
+			.map(|v| {
+				morphs
+					.iter()
+					.enumerate()
+					.filter(|(_, (_, deltas))| moves(deltas, v))
+					.map(|(m, _)| m)
+					.collect()
+			})
+			.collect();
+		let num_slots = slots.iter().map(Vec::len).max().unwrap_or(0);
+
+		let mut attributes = vec![];
+		for keys_slot in 0..num_slots.div_ceil(4) {
+			let keys = slots
+				.iter()
+				.flat_map(|vertex_slots| {
+					std::array::from_fn::<u8, 4, _>(|c| {
+						vertex_slots
+							.get(keys_slot * 4 + c)
+							.map_or(0xff, |m| morphs[*m].0)
+					})
+				})
+				.collect();
+			attributes.push(self.push_attribute_buffer(
+				AttributeType::BlendKeys,
+				keys_slot as u32,
+				BlockFormat::U8Vec4,
+				IndexSet::Main,
+				keys,
+			));
+		}
+
+		for (d, (_, attribute_type)) in delta_types.iter().enumerate() {
+			if morphs.iter().all(|(_, deltas)| deltas[d].is_none()) {
+				continue;
+			}
+			for slot in 0..num_slots {
+				let data = slots
+					.iter()
+					.enumerate()
+					.flat_map(|(v, vertex_slots)| {
+						let delta = vertex_slots
+							.get(slot)
+							.and_then(|m| morphs[*m].1[d].as_ref())
+							.map_or(Vertex::identity(), |deltas| deltas[v]);
+						vertex_bytes(delta)
+					})
+					.collect();
+				attributes.push(self.push_attribute_buffer(
+					*attribute_type,
+					slot as u32,
+					BlockFormat::F32Vec3,
+					IndexSet::Main,
+					data,
+				));
+			}
+		}
+
+		Ok(attributes)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::internal_file::resource_collection::geometric_data_container::skeleton::Joint;
+
+	fn vertex([x, y, z]: [f32; 3]) -> Vertex {
+		Vertex { x, y, z }
+	}
+
+	#[proptest]
+	fn gltf_roundtrip_keeps_geometry(
+		#[strategy(proptest::collection::vec([-10.0f32..10.0, -10.0f32..10.0, -10.0f32..10.0], 3..30))]
+		positions: Vec<[f32; 3]>,
+	) {
+		let num_triangles = positions.len() / 3;
+		let mut gmdc = GeometricDataContainer::default();
+		let vertices: Vec<_> = positions.iter().copied().map(vertex).collect();
+		gmdc.push_test_mesh("mesh", &vertices, vec![], 0..num_triangles as u32 * 3)
+			.opacity = 3;
+
+		let glb = gmdc.export_gltf(None).unwrap();
+		let imported = GeometricDataContainer::import_gltf(&glb.0, None).unwrap();
+
+		prop_assert_eq!(imported.meshes.len(), 1);
+		let mesh = &imported.meshes[0];
+		prop_assert_eq!(&mesh.name.0.data, b"mesh");
+		prop_assert_eq!(mesh.opacity, 3);
+		prop_assert_eq!(&mesh.indices.data, &gmdc.meshes[0].indices.data);
+
+		let group = &imported.attribute_groups[mesh.attribute_group_index as usize];
+		let buffer = &imported.attribute_buffers[group.attributes[0].0 as usize];
+		prop_assert_eq!(buffer.binding.binding_type, AttributeType::Positions);
+		let imported_positions = buffer
+			.data
+			.chunks_exact(4)
+			.map(|c| f32::from_le_bytes(c.try_into().unwrap()));
+		for (a, b) in imported_positions.zip(positions.iter().flatten()) {
+			prop_assert!((a - b).abs() < 1e-4, "{a} != {b}");
+		}
+
+		prop_assert_eq!(imported.bounding_mesh.faces.len(), num_triangles * 3);
+	}
+
+	#[proptest]
+	fn gltf_export_keeps_joint_hierarchy(
+		#[strategy(proptest::array::uniform3(-10.0f32..10.0))] spine: [f32; 3],
+		#[strategy(proptest::array::uniform3(-10.0f32..10.0))] arm: [f32; 3],
+	) {
+		let joint = |name: &str, parent, translation, bone| Joint {
+			name: name.to_string(),
+			parent,
+			transform: Transform {
+				rotation: Quaternion::identity(),
+				translation,
+			},
+			bone,
+		};
+		// the root only positions the other joints, and bone 1 comes before bone 0
+		let skeleton = Skeleton {
+			joints: vec![
+				joint("root", None, Vertex::identity(), None),
+				joint("spine", Some(0), vertex(spine), Some(1)),
+				joint("arm", Some(1), vertex(arm), Some(0)),
+			],
+		};
+		let mut gmdc = GeometricDataContainer {
+			bones: SizedVec::new(vec![Transform::identity(); 2]),
+			..Default::default()
+		};
+		gmdc.push_test_mesh(
+			"body",
+			&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]].map(vertex),
+			vec![(
+				AttributeType::BoneKeys,
+				BlockFormat::U8Vec4,
+				[
+					0, 0xff, 0xff, 0xff, 1, 0xff, 0xff, 0xff, 0, 0xff, 0xff, 0xff,
+				]
+				.to_vec(),
+			)],
+			0..3,
+		)
+		.bone_references = SizedVec::new(vec![Reference(0), Reference(1)]);
+
+		let glb = gmdc.export_gltf(Some(&skeleton)).unwrap();
+		let mut graph = Graph::default();
+		let doc = block_on(GlbImport::<DefaultExtensions>::import_slice(
+			&mut graph, &glb.0,
+		))
+		.unwrap();
+
+		let name = |node: Node| node.get(&graph).name.clone().unwrap_or_default();
+		let node = |joint: &str| {
+			doc.nodes(&graph)
+				.into_iter()
+				.find(|node| name(*node) == joint)
+				.unwrap()
+		};
+		let parent = |joint: &str| node(joint).parents(&graph).first().map(|p| name(*p));
+		prop_assert_eq!(parent("spine"), Some("root".to_string()));
+		prop_assert_eq!(parent("arm"), Some("spine".to_string()));
+		let translation = node("arm").get(&graph).translation;
+		prop_assert_eq!([translation.x, translation.y, translation.z], arm);
+
+		// the joints of the skin are in bone order
+		let skins = doc.skins(&graph);
+		prop_assert_eq!(skins.len(), 1);
+		let joints: Vec<_> = skins[0].joints(&graph).into_iter().map(name).collect();
+		prop_assert_eq!(joints, vec!["arm".to_string(), "spine".to_string()]);
+	}
+
+	#[proptest]
+	fn gltf_import_rejects_joints_without_bones(
+		#[strategy(2usize..10)] num_bones: usize,
+		#[strategy(1usize..#num_bones)] kept_bones: usize,
+	) {
+		let mut gmdc = GeometricDataContainer {
+			bones: SizedVec::new(vec![Transform::identity(); num_bones]),
+			..Default::default()
+		};
+		let last = num_bones as u8 - 1;
+		gmdc.push_test_mesh(
+			"body",
+			&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]].map(vertex),
+			vec![
+				(
+					AttributeType::BoneKeys,
+					BlockFormat::U8Vec4,
+					[
+						0, 0xff, 0xff, 0xff, 0, 0xff, 0xff, 0xff, 1, 0xff, 0xff, 0xff,
+					]
+					.to_vec(),
+				),
+				(
+					AttributeType::BoneWeights,
+					BlockFormat::F32Scalar,
+					1.0f32.to_le_bytes().repeat(3),
+				),
+			],
+			0..3,
+		)
+		.bone_references = SizedVec::new(vec![Reference(0), Reference(last as u32)]);
+		let glb = gmdc.export_gltf(None).unwrap();
+
+		prop_assert!(GeometricDataContainer::import_gltf(&glb.0, Some(&gmdc)).is_ok());
+
+		let mut original = gmdc.clone();
+		original.bones = SizedVec::new(vec![Transform::identity(); kept_bones]);
+		let rejected = matches!(
+			GeometricDataContainer::import_gltf(&glb.0, Some(&original)),
+			Err(GltfImportError::JointOutOfRange { joint, num_bones, .. })
+				if joint == last as u32 && num_bones == kept_bones
+		);
+		prop_assert!(rejected);
+	}
+
+	#[proptest]
+	fn gltf_export_skips_broken_meshes(#[strategy(3u32..1000)] bad_index: u32) {
+		let triangle = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]].map(vertex);
+		let mut gmdc = GeometricDataContainer::default();
+		gmdc.push_test_mesh("broken", &triangle, vec![], [0, 1, bad_index]);
+
+		let skipped_all = matches!(
+			gmdc.export_gltf(None),
+			Err(GltfExportError::IndexOutOfRange { index, .. }) if index == bad_index
+		);
+		prop_assert!(skipped_all);
+
+		gmdc.push_test_mesh("valid", &triangle, vec![], 0..3);
+
+		let glb = gmdc.export_gltf(None).unwrap();
+		let imported = GeometricDataContainer::import_gltf(&glb.0, None).unwrap();
+		prop_assert_eq!(imported.meshes.len(), 1);
+		prop_assert_eq!(&imported.meshes[0].name.0.data, b"valid");
+	}
+
+	#[proptest]
+	fn gltf_roundtrip_keeps_morphs(
+		#[strategy(proptest::collection::vec([-1.0f32..1.0, -1.0f32..1.0, -1.0f32..1.0], 3))]
+		deltas: Vec<[f32; 3]>,
+	) {
+		let mut gmdc = GeometricDataContainer::default();
+		gmdc.blend_group_bindings.push(BlendGroupBinding {
+			blend_group: BigString::from("fat"),
+			element: BigString::from("body"),
+		});
+		gmdc.push_test_mesh(
+			"body",
+			&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]].map(vertex),
+			vec![
+				(
+					AttributeType::BlendKeys,
+					BlockFormat::U8Vec4,
+					[0, 0xff, 0xff, 0xff].repeat(3),
+				),
+				(
+					AttributeType::PositionDeltas,
+					BlockFormat::F32Vec3,
+					deltas
+						.iter()
+						.flatten()
+						.flat_map(|f| f.to_le_bytes())
+						.collect(),
+				),
+			],
+			0..3,
+		);
+
+		let glb = gmdc.export_gltf(None).unwrap();
+		let imported = GeometricDataContainer::import_gltf(&glb.0, None).unwrap();
+
+		let group = &imported.attribute_groups[imported.meshes[0].attribute_group_index as usize];
+		let find = |binding_type| {
+			group
+				.attributes
+				.iter()
+				.map(|r| &imported.attribute_buffers[r.0 as usize])
+				.find(|attr| attr.binding.binding_type == binding_type)
+		};
+		let moved = deltas.iter().any(|d| *d != [0.0; 3]);
+		if moved {
+			prop_assert_eq!(
+				&imported.blend_group_bindings.data,
+				&gmdc.blend_group_bindings.data
+			);
+		}
+		prop_assert_eq!(find(AttributeType::BlendKeys).is_some(), moved);
+		if let (Some(keys), Some(imported_deltas)) = (
+			find(AttributeType::BlendKeys),
+			find(AttributeType::PositionDeltas),
+		) {
+			for ((keys, delta), expected) in keys
+				.data
+				.chunks_exact(4)
+				.zip(imported_deltas.data.chunks_exact(12))
+				.zip(&deltas)
+			{
+				let delta: Vec<f32> = delta
+					.chunks_exact(4)
+					.map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+					.collect();
+				if *expected == [0.0; 3] {
+					prop_assert_eq!(keys[0], 0xff);
+				} else {
+					prop_assert_eq!(keys[0], 0);
+					for (a, b) in delta.iter().zip(expected) {
+						prop_assert!((a - b).abs() < 1e-4, "{a} != {b}");
+					}
+				}
+			}
+		}
 	}
 }
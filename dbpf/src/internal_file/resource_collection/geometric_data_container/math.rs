@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::ops::{Add, AddAssign, Mul};
+use std::ops::{Add, AddAssign, Mul, Sub};
 
 use crate::binrw;
 
@@ -34,6 +34,70 @@ impl Quaternion {
 			w: self.w / sq_mag,
 		}
 	}
+
+	/// extract the rotation from the upper 3x3 part of a matrix, the scale is removed first
+	pub fn from_rotation_matrix(m: &Mat4) -> Self {
+		let m = m.0;
+		let columns = [0, 1, 2].map(|c| {
+			let len = (m[c] * m[c] + m[c + 4] * m[c + 4] + m[c + 8] * m[c + 8]).sqrt();
+			if len > f32::EPSILON {
+				len
+			} else {
+				1.0
+			}
+		});
+		let r = |row: usize, col: usize| m[col + row * 4] / columns[col];
+
+		let trace = r(0, 0) + r(1, 1) + r(2, 2);
+		let q = if trace > 0.0 {
+			let s = (trace + 1.0).sqrt() * 2.0;
+			Self {
+				x: (r(2, 1) - r(1, 2)) / s,
+				y: (r(0, 2) - r(2, 0)) / s,
+				z: (r(1, 0) - r(0, 1)) / s,
+				w: s / 4.0,
+			}
+		} else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+			let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+			Self {
+				x: s / 4.0,
+				y: (r(0, 1) + r(1, 0)) / s,
+				z: (r(0, 2) + r(2, 0)) / s,
+				w: (r(2, 1) - r(1, 2)) / s,
+			}
+		} else if r(1, 1) > r(2, 2) {
+			let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+			Self {
+				x: (r(0, 1) + r(1, 0)) / s,
+				y: s / 4.0,
+				z: (r(1, 2) + r(2, 1)) / s,
+				w: (r(0, 2) - r(2, 0)) / s,
+			}
+		} else {
+			let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+			Self {
+				x: (r(0, 2) + r(2, 0)) / s,
+				y: (r(1, 2) + r(2, 1)) / s,
+				z: s / 4.0,
+				w: (r(1, 0) - r(0, 1)) / s,
+			}
+		};
+		q.normalize()
+	}
+
+	pub fn normalize(self) -> Self {
+		let mag = (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt();
+		if mag > f32::EPSILON {
+			Self {
+				x: self.x / mag,
+				y: self.y / mag,
+				z: self.z / mag,
+				w: self.w / mag,
+			}
+		} else {
+			Self::identity()
+		}
+	}
 }
 
 #[binrw]
@@ -60,6 +124,36 @@ impl Vertex {
 			z: -self.z,
 		}
 	}
+
+	pub fn dot(self, rhs: Self) -> f32 {
+		self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+	}
+
+	pub fn cross(self, rhs: Self) -> Self {
+		Self {
+			x: self.y * rhs.z - self.z * rhs.y,
+			y: self.z * rhs.x - self.x * rhs.z,
+			z: self.x * rhs.y - self.y * rhs.x,
+		}
+	}
+
+	pub fn length(self) -> f32 {
+		self.dot(self).sqrt()
+	}
+
+	/// scale to unit length, zero length vectors are returned unchanged
+	pub fn normalize(self) -> Self {
+		let len = self.length();
+		if len > f32::EPSILON {
+			Self {
+				x: self.x / len,
+				y: self.y / len,
+				z: self.z / len,
+			}
+		} else {
+			self
+		}
+	}
 }
 
 impl Add for Vertex {
@@ -80,6 +174,30 @@ impl AddAssign for Vertex {
 	}
 }
 
+impl Mul<f32> for Vertex {
+	type Output = Vertex;
+
+	fn mul(self, rhs: f32) -> Self::Output {
+		Self {
+			x: self.x * rhs,
+			y: self.y * rhs,
+			z: self.z * rhs,
+		}
+	}
+}
+
+impl Sub for Vertex {
+	type Output = Vertex;
+
+	fn sub(self, rhs: Self) -> Self::Output {
+		Self {
+			x: self.x - rhs.x,
+			y: self.y - rhs.y,
+			z: self.z - rhs.z,
+		}
+	}
+}
+
 #[binrw]
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct Transform {
@@ -102,6 +220,18 @@ impl Transform {
 			rotation: quat_inverse,
 		}
 	}
+
+	/// decompose a matrix into a rotation and translation, any scale or shear is lost
+	pub fn from_matrix(m: &Mat4) -> Self {
+		Self {
+			rotation: Quaternion::from_rotation_matrix(m),
+			translation: Vertex {
+				x: m.0[3],
+				y: m.0[7],
+				z: m.0[11],
+			},
+		}
+	}
 }
 
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
@@ -144,6 +274,107 @@ impl Mat4 {
 		Self::translation(t.translation) * Self::rotation(t.rotation)
 	}
 
+    #[rustfmt::skip]
+	pub fn scale(v: Vertex) -> Self {
+        Self([
+            v.x, 0.0, 0.0, 0.0,
+            0.0, v.y, 0.0, 0.0,
+            0.0, 0.0, v.z, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+	pub fn from_trs(translation: Vertex, rotation: Quaternion, scale: Vertex) -> Self {
+		Self::translation(translation) * Self::rotation(rotation) * Self::scale(scale)
+	}
+
+	/// transform a direction, ignoring the translation part of the matrix
+	pub fn mul_direction(&self, v: Vertex) -> Vertex {
+		let m = self.0;
+		Vertex {
+			x: m[0] * v.x + m[1] * v.y + m[2] * v.z,
+			y: m[4] * v.x + m[5] * v.y + m[6] * v.z,
+			z: m[8] * v.x + m[9] * v.y + m[10] * v.z,
+		}
+	}
+
+	/// general inverse using cofactor expansion, returns `None` for singular matrices
+	pub fn inverse(&self) -> Option<Self> {
+		let m = self.0;
+		let mut inv = [0.0f32; 16];
+
+		inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15]
+			+ m[9] * m[7] * m[14]
+			+ m[13] * m[6] * m[11]
+			- m[13] * m[7] * m[10];
+		inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15]
+			- m[8] * m[7] * m[14]
+			- m[12] * m[6] * m[11]
+			+ m[12] * m[7] * m[10];
+		inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15]
+			+ m[8] * m[7] * m[13]
+			+ m[12] * m[5] * m[11]
+			- m[12] * m[7] * m[9];
+		inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14]
+			- m[8] * m[6] * m[13]
+			- m[12] * m[5] * m[10]
+			+ m[12] * m[6] * m[9];
+		inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15]
+			- m[9] * m[3] * m[14]
+			- m[13] * m[2] * m[11]
+			+ m[13] * m[3] * m[10];
+		inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15]
+			+ m[8] * m[3] * m[14]
+			+ m[12] * m[2] * m[11]
+			- m[12] * m[3] * m[10];
+		inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15]
+			- m[8] * m[3] * m[13]
+			- m[12] * m[1] * m[11]
+			+ m[12] * m[3] * m[9];
+		inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14]
+			+ m[8] * m[2] * m[13]
+			+ m[12] * m[1] * m[10]
+			- m[12] * m[2] * m[9];
+		inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15]
+			+ m[5] * m[3] * m[14]
+			+ m[13] * m[2] * m[7]
+			- m[13] * m[3] * m[6];
+		inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15]
+			- m[4] * m[3] * m[14]
+			- m[12] * m[2] * m[7]
+			+ m[12] * m[3] * m[6];
+		inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15]
+			+ m[4] * m[3] * m[13]
+			+ m[12] * m[1] * m[7]
+			- m[12] * m[3] * m[5];
+		inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14]
+			- m[4] * m[2] * m[13]
+			- m[12] * m[1] * m[6]
+			+ m[12] * m[2] * m[5];
+		inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11]
+			- m[5] * m[3] * m[10]
+			- m[9] * m[2] * m[7]
+			+ m[9] * m[3] * m[6];
+		inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11]
+			+ m[4] * m[3] * m[10]
+			+ m[8] * m[2] * m[7]
+			- m[8] * m[3] * m[6];
+		inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11]
+			- m[4] * m[3] * m[9]
+			- m[8] * m[1] * m[7]
+			+ m[8] * m[3] * m[5];
+		inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10]
+			+ m[4] * m[2] * m[9]
+			+ m[8] * m[1] * m[6]
+			- m[8] * m[2] * m[5];
+
+		let det = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8] + m[3] * inv[12];
+		if det.abs() < f32::EPSILON * f32::EPSILON {
+			return None;
+		}
+		Some(Self(inv.map(|v| v / det)))
+	}
+
     #[rustfmt::skip]
 	pub fn rotation_x(a: f32) -> Self {
         Self([
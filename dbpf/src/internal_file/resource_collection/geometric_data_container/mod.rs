@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::{
+	collections::HashMap,
 	fmt::{Debug, Formatter},
 	io::{Read, Seek, Write},
 };
@@ -13,13 +14,19 @@ use itertools::Either::{Left, Right};
 use crate::{
 	common::{BigString, SizedVec},
 	internal_file::resource_collection::{
-		geometric_data_container::math::{Transform, Vertex},
+		geometric_data_container::math::{Mat4, Transform, Vertex},
 		FileName, ResourceBlockVersion,
 	},
 };
 
 pub mod gltf;
 pub mod math;
+pub mod obj;
+pub mod processing;
+pub mod render;
+pub mod simplify;
+pub mod skeleton;
+pub mod stl;
 
 #[derive(Copy, Clone, Default, PartialEq)]
 pub struct Reference(
@@ -472,3 +479,291 @@ impl Mesh {
 		}
 	}
 }
+
+/// The vertex data of a mesh group that most tools work with, with the index sets already applied
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshGeometry {
+	pub positions: Vec<Vertex>,
+	/// empty if the mesh has no normals
+	pub normals: Vec<Vertex>,
+	/// the first uv set, empty if the mesh has no texture coordinates
+	pub tex_coords: Vec<[f32; 2]>,
+}
+
+fn read_floats<'a>(
+	group: &'a AttributeGroup,
+	attr: &'a AttributeBuffer,
+) -> impl Iterator<Item = Vec<f32>> + 'a {
+	group.get_buffer_data(attr).map(|e| {
+		e.chunks_exact(4)
+			.map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+			.collect()
+	})
+}
+
+/// collects bounding mesh triangles, merging vertices that are exactly the same
+#[derive(Default)]
+struct BoundingMeshBuilder {
+	mesh: BoundingMesh,
+	lookup: HashMap<[u32; 3], u32>,
+}
+
+impl BoundingMeshBuilder {
+	fn push(&mut self, v: Vertex) {
+		let key = [v.x.to_bits(), v.y.to_bits(), v.z.to_bits()];
+		let index = *self.lookup.entry(key).or_insert_with(|| {
+			self.mesh.vertices.push(v);
+			self.mesh.vertices.len() as u32 - 1
+		});
+		self.mesh.faces.push(Reference(index));
+	}
+}
+
+impl GeometricDataContainer {
+	/// read the positions, normals and first uv set of a mesh group
+	///
+	/// returns `None` if the attribute group or its positions are missing
+	pub fn mesh_geometry(&self, mesh: &Mesh) -> Option<MeshGeometry> {
+		self.group_geometry(
+			self.attribute_groups
+				.get(mesh.attribute_group_index as usize)?,
+		)
+	}
+
+	fn group_geometry(&self, group: &AttributeGroup) -> Option<MeshGeometry> {
+		let find = |binding_type: AttributeType, block_format: BlockFormat| {
+			group
+				.attributes
+				.iter()
+				.filter_map(|r| self.attribute_buffers.get(r.0 as usize))
+				.find(|attr| {
+					attr.binding.binding_type == binding_type
+						&& attr.binding.binding_slot == 0
+						&& attr.block_format == block_format
+				})
+		};
+		let vertices = |attr| {
+			read_floats(group, attr)
+				.map(|v| Vertex {
+					x: v[0],
+					y: v[1],
+					z: v[2],
+				})
+				.collect()
+		};
+
+		Some(MeshGeometry {
+			positions: vertices(find(AttributeType::Positions, BlockFormat::F32Vec3)?),
+			normals: find(AttributeType::Normals, BlockFormat::F32Vec3)
+				.map(vertices)
+				.unwrap_or_default(),
+			tex_coords: find(AttributeType::TexCoords, BlockFormat::F32Vec2)
+				.map(|attr| read_floats(group, attr).map(|uv| [uv[0], uv[1]]).collect())
+				.unwrap_or_default(),
+		})
+	}
+
+	/// read the morph deltas of every vertex in a group as (morph id, delta) pairs
+	///
+	/// the blend keys of the group tell which morph each slot of `delta_type` belongs to
+	fn group_morph_deltas(
+		&self,
+		group: &AttributeGroup,
+		num_vertices: usize,
+		delta_type: AttributeType,
+	) -> Vec<Vec<(u8, Vertex)>> {
+		let mut morphs = vec![vec![]; num_vertices];
+		let buffers: Vec<_> = group
+			.attributes
+			.iter()
+			.filter_map(|r| self.attribute_buffers.get(r.0 as usize))
+			.collect();
+		let find = |binding_type: AttributeType, slot: u32| {
+			buffers
+				.iter()
+				.find(|attr| {
+					attr.binding.binding_type == binding_type && attr.binding.binding_slot == slot
+				})
+				.map(|attr| read_floats(group, attr).collect::<Vec<_>>())
+				.filter(|elements| elements.len() == num_vertices)
+		};
+
+		for keys_attr in buffers
+			.iter()
+			.filter(|attr| attr.binding.binding_type == AttributeType::BlendKeys)
+		{
+			let keys: Vec<&[u8]> = group.get_buffer_data(keys_attr).collect();
+			if keys.len() != num_vertices {
+				continue;
+			}
+			for c in 0..4 {
+				let Some(deltas) = find(delta_type, keys_attr.binding.binding_slot * 4 + c as u32)
+				else {
+					continue;
+				};
+				for (v, (keys, delta)) in keys.iter().zip(deltas).enumerate() {
+					let (Some(morph), &[x, y, z, ..]) = (keys.get(c), &delta[..]) else {
+						continue;
+					};
+					if *morph != 0xff {
+						morphs[v].push((*morph, Vertex { x, y, z }));
+					}
+				}
+			}
+		}
+
+		morphs
+	}
+
+	/// remove attribute groups that are not used by any mesh,
+	/// and attribute buffers that are not used by any of the remaining groups
+	pub fn remove_unused_attributes(&mut self) {
+		let mut group_map = vec![None; self.attribute_groups.len()];
+		for mesh in self.meshes.iter() {
+			if let Some(index) = group_map.get_mut(mesh.attribute_group_index as usize) {
+				*index = Some(0);
+			}
+		}
+		for (kept, index) in group_map.iter_mut().flatten().enumerate() {
+			*index = kept as u32;
+		}
+		let mut groups = group_map.iter();
+		self.attribute_groups
+			.retain(|_| groups.next().is_some_and(Option::is_some));
+		for mesh in self.meshes.iter_mut() {
+			if let Some(Some(index)) = group_map.get(mesh.attribute_group_index as usize) {
+				mesh.attribute_group_index = *index;
+			}
+		}
+
+		let mut buffer_map = vec![None; self.attribute_buffers.len()];
+		for attr in self
+			.attribute_groups
+			.iter()
+			.flat_map(|group| group.attributes.iter())
+		{
+			if let Some(index) = buffer_map.get_mut(attr.0 as usize) {
+				*index = Some(0);
+			}
+		}
+		for (kept, index) in buffer_map.iter_mut().flatten().enumerate() {
+			*index = kept as u32;
+		}
+		let mut buffers = buffer_map.iter();
+		self.attribute_buffers
+			.retain(|_| buffers.next().is_some_and(Option::is_some));
+		for attr in self
+			.attribute_groups
+			.iter_mut()
+			.flat_map(|group| group.attributes.iter_mut())
+		{
+			if let Some(Some(index)) = buffer_map.get(attr.0 as usize) {
+				attr.0 = *index;
+			}
+		}
+	}
+
+	/// rebuild the static and dynamic bounding meshes from the triangles of all meshes
+	///
+	/// skinned triangles are assigned to the bone with the most influence on their first corner
+	/// and stored relative to that bone, all other triangles end up in the static bounding mesh
+	pub fn recompute_bounding_mesh(&mut self) {
+		let mut static_mesh = BoundingMeshBuilder::default();
+		let mut dynamic_meshes: Vec<BoundingMeshBuilder> =
+			self.bones.iter().map(|_| Default::default()).collect();
+		let bone_matrices: Vec<Mat4> = self
+			.bones
+			.iter()
+			.map(|bone| Mat4::transform(*bone))
+			.collect();
+		let mut any_dynamic = false;
+
+		for mesh in self
+			.meshes
+			.iter()
+			.filter(|mesh| mesh.primitive_type == PrimitiveType::Triangles)
+		{
+			let Some(group) = self
+				.attribute_groups
+				.get(mesh.attribute_group_index as usize)
+			else {
+				continue;
+			};
+			let find = |binding_type: AttributeType| {
+				group
+					.attributes
+					.iter()
+					.filter_map(|r| self.attribute_buffers.get(r.0 as usize))
+					.find(|attr| {
+						attr.binding.binding_type == binding_type && attr.binding.binding_slot == 0
+					})
+			};
+
+			let Some(MeshGeometry { positions, .. }) = self.mesh_geometry(mesh) else {
+				continue;
+			};
+
+			// the local bone slot with the largest weight for every vertex
+			let dominant_slots: Option<Vec<u8>> = find(AttributeType::BoneKeys)
+				.filter(|keys| keys.block_format == BlockFormat::U8Vec4)
+				.map(|keys| {
+					let weights: Vec<Vec<f32>> = find(AttributeType::BoneWeights)
+						.filter(|weights| weights.block_format != BlockFormat::U8Vec4)
+						.map(|weights| read_floats(group, weights).collect())
+						.unwrap_or_default();
+					group
+						.get_buffer_data(keys)
+						.enumerate()
+						.map(|(i, keys)| {
+							let strongest = weights
+								.get(i)
+								.and_then(|w| {
+									w.iter()
+										.enumerate()
+										.max_by(|(_, a), (_, b)| a.total_cmp(b))
+										.map(|(slot, _)| slot)
+								})
+								.unwrap_or(0);
+							keys[strongest]
+						})
+						.collect()
+				});
+
+			for triangle in mesh.indices.chunks_exact(3) {
+				let bone = dominant_slots
+					.as_ref()
+					.and_then(|slots| slots.get(triangle[0].0 as usize))
+					.filter(|slot| **slot != 0xff)
+					.and_then(|slot| mesh.bone_references.get(*slot as usize))
+					.map(|bone| bone.0 as usize)
+					.filter(|bone| *bone < bone_matrices.len());
+
+				let corners = triangle
+					.iter()
+					.filter_map(|i| positions.get(i.0 as usize).copied());
+				if corners.clone().count() != 3 {
+					continue;
+				}
+				match bone {
+					Some(bone) => {
+						any_dynamic = true;
+						corners.for_each(|v| dynamic_meshes[bone].push(bone_matrices[bone] * v));
+					}
+					None => corners.for_each(|v| static_mesh.push(v)),
+				}
+			}
+		}
+
+		self.bounding_mesh = static_mesh.mesh;
+		self.dynamic_bounding_mesh = if any_dynamic {
+			SizedVec::new(
+				dynamic_meshes
+					.into_iter()
+					.map(|builder| builder.mesh)
+					.collect(),
+			)
+		} else {
+			Default::default()
+		};
+	}
+}
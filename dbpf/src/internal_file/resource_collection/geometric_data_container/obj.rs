@@ -0,0 +1,630 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{collections::HashMap, fmt::Write, ops::Range, str::SplitWhitespace};
+
+use log::warn;
+use thiserror::Error;
+
+use crate::{
+	common::SizedVec,
+	internal_file::resource_collection::{
+		geometric_data_container::{
+			math::Vertex, AttributeGroup, AttributeType, BlockFormat, GeometricDataContainer,
+			IndexSet, Mesh, PrimitiveType, Reference,
+		},
+		material_definition::MaterialDefinition,
+	},
+};
+
+/// A material written to the MTL file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjMaterial {
+	pub name: String,
+	/// file name of the diffuse texture, relative to the MTL file
+	pub texture: Option<String>,
+}
+
+impl ObjMaterial {
+	/// Use the name and base texture of a TXMT
+	///
+	/// The texture is expected to be exported as a png next to the MTL file.
+	pub fn from_material_definition(txmt: &MaterialDefinition) -> Self {
+		Self {
+			name: String::from_utf8_lossy(&txmt.file_name.name.0).to_string(),
+			texture: txmt
+				.base_texture()
+				.map(|texture| format!("{}.png", String::from_utf8_lossy(texture))),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjExport {
+	pub obj: String,
+	pub mtl: String,
+}
+
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum ObjImportError {
+	#[error("mesh group {0} does not exist")]
+	MissingMesh(usize),
+	#[error("line {line}: {message}")]
+	Parse { line: usize, message: String },
+	#[error("line {line}: index {index} is out of range")]
+	IndexOutOfRange { line: usize, index: i64 },
+	#[error("the file does not contain any faces")]
+	NoFaces,
+	#[error("the file has no object named {0}, and more than one object to choose from")]
+	NoMatchingObject(String),
+}
+
+/// position, texture coordinate and normal index of a face corner, starting at 0
+type Corner = (usize, Option<usize>, Option<usize>);
+
+#[derive(Clone, Debug)]
+struct ObjObject {
+	name: String,
+	triangles: Vec<[Corner; 3]>,
+	/// the positions, texture coordinates and normals that were declared inside this object
+	positions: Range<usize>,
+	tex_coords: Range<usize>,
+	normals: Range<usize>,
+}
+
+impl ObjObject {
+	fn start(name: String, file: &ObjFile) -> Self {
+		Self {
+			name,
+			triangles: vec![],
+			positions: file.positions.len()..file.positions.len(),
+			tex_coords: file.tex_coords.len()..file.tex_coords.len(),
+			normals: file.normals.len()..file.normals.len(),
+		}
+	}
+
+	fn finish(mut self, file: &mut ObjFile) {
+		self.positions.end = file.positions.len();
+		self.tex_coords.end = file.tex_coords.len();
+		self.normals.end = file.normals.len();
+		file.objects.push(self);
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+struct ObjFile {
+	positions: Vec<Vertex>,
+	normals: Vec<Vertex>,
+	tex_coords: Vec<[f32; 2]>,
+	objects: Vec<ObjObject>,
+}
+
+fn parse_floats<const N: usize>(
+	mut parts: SplitWhitespace,
+	line: usize,
+) -> Result<[f32; N], ObjImportError> {
+	let mut out = [0.0; N];
+	for value in out.iter_mut() {
+		*value = parts
+			.next()
+			.and_then(|part| part.parse().ok())
+			.ok_or_else(|| ObjImportError::Parse {
+				line,
+				message: format!("expected {N} numbers"),
+			})?;
+	}
+	Ok(out)
+}
+
+/// obj indices start at 1, negative indices count back from the last element
+fn parse_index(index: &str, len: usize, line: usize) -> Result<usize, ObjImportError> {
+	let index: i64 = index.parse().map_err(|_| ObjImportError::Parse {
+		line,
+		message: format!("invalid index {index}"),
+	})?;
+	let resolved = if index < 0 {
+		len as i64 + index
+	} else {
+		index - 1
+	};
+	if (0..len as i64).contains(&resolved) {
+		Ok(resolved as usize)
+	} else {
+		Err(ObjImportError::IndexOutOfRange { line, index })
+	}
+}
+
+fn parse_obj(text: &str) -> Result<ObjFile, ObjImportError> {
+	let mut file = ObjFile::default();
+	let mut current = ObjObject::start(String::new(), &file);
+
+	for (i, line) in text.lines().enumerate() {
+		let line_number = i + 1;
+		let line = line.split('#').next().unwrap_or_default();
+		let mut parts = line.split_whitespace();
+		let Some(keyword) = parts.next() else {
+			continue;
+		};
+		match keyword {
+			"v" => {
+				let [x, y, z] = parse_floats(parts, line_number)?;
+				file.positions.push(Vertex { x, y, z });
+			}
+			"vn" => {
+				let [x, y, z] = parse_floats(parts, line_number)?;
+				file.normals.push(Vertex { x, y, z });
+			}
+			"vt" => {
+				let [u, v] = parse_floats(parts, line_number)?;
+				// obj has the origin of the texture in the bottom left corner
+				file.tex_coords.push([u, 1.0 - v]);
+			}
+			"o" | "g" => {
+				let name = parts.collect::<Vec<_>>().join(" ");
+				if current.triangles.is_empty() && current.positions.start == file.positions.len() {
+					current.name = name;
+				} else {
+					let next = ObjObject::start(name, &file);
+					std::mem::replace(&mut current, next).finish(&mut file);
+				}
+			}
+			"f" => {
+				let corners = parts
+					.map(|corner| {
+						let mut indices = corner.split('/');
+						let position = parse_index(
+							indices.next().unwrap_or_default(),
+							file.positions.len(),
+							line_number,
+						)?;
+						let mut optional = |len| {
+							indices
+								.next()
+								.filter(|index| !index.is_empty())
+								.map(|index| parse_index(index, len, line_number))
+								.transpose()
+						};
+						let tex_coord = optional(file.tex_coords.len())?;
+						let normal = optional(file.normals.len())?;
+						Ok((position, tex_coord, normal))
+					})
+					.collect::<Result<Vec<Corner>, _>>()?;
+				if corners.len() < 3 {
+					return Err(ObjImportError::Parse {
+						line: line_number,
+						message: "faces need at least 3 corners".to_string(),
+					});
+				}
+				// polygons are split into a triangle fan
+				for i in 1..corners.len() - 1 {
+					current
+						.triangles
+						.push([corners[0], corners[i], corners[i + 1]]);
+				}
+			}
+			// materials, smoothing groups, lines and points are not used
+			_ => {}
+		}
+	}
+	current.finish(&mut file);
+
+	Ok(file)
+}
+
+/// Split the corners of an object into vertices and the triangle indices into those vertices
+///
+/// Objects whose corners use the same position, texture coordinate and normal relative to the
+/// ones declared inside the object, like the ones written by [export_obj](GeometricDataContainer::export_obj),
+/// keep their vertices and vertex order.
+/// Other objects get one vertex per unique combination of indices.
+///
+/// Returns the vertices, the indices into them, and whether the vertex order was kept.
+fn object_vertices(object: &ObjObject) -> (Vec<Corner>, Vec<u32>, bool) {
+	let corners = || object.triangles.iter().flatten();
+	let relative =
+		|index: usize, range: &Range<usize>| range.contains(&index).then(|| index - range.start);
+
+	let aligned = corners().all(|c| {
+		let position = relative(c.0, &object.positions);
+		position.is_some()
+			&& c.1
+				.is_none_or(|t| relative(t, &object.tex_coords) == position)
+			&& c.2.is_none_or(|n| relative(n, &object.normals) == position)
+	});
+
+	if aligned {
+		let num_vertices = object.positions.len();
+		let has_tex_coords =
+			object.tex_coords.len() == num_vertices && corners().all(|c| c.1.is_some());
+		let has_normals = object.normals.len() == num_vertices && corners().all(|c| c.2.is_some());
+		let vertices = (0..num_vertices)
+			.map(|i| {
+				(
+					object.positions.start + i,
+					has_tex_coords.then_some(object.tex_coords.start + i),
+					has_normals.then_some(object.normals.start + i),
+				)
+			})
+			.collect();
+		let indices = corners()
+			.map(|c| (c.0 - object.positions.start) as u32)
+			.collect();
+		(vertices, indices, true)
+	} else {
+		let mut vertices = vec![];
+		let mut lookup = HashMap::new();
+		let indices = corners()
+			.map(|corner| {
+				*lookup.entry(*corner).or_insert_with(|| {
+					vertices.push(*corner);
+					vertices.len() as u32 - 1
+				})
+			})
+			.collect();
+		(vertices, indices, false)
+	}
+}
+
+fn vertex_bytes(vertices: &[Vertex]) -> Vec<u8> {
+	vertices
+		.iter()
+		.flat_map(|v| [v.x, v.y, v.z])
+		.flat_map(f32::to_le_bytes)
+		.collect()
+}
+
+impl GeometricDataContainer {
+	/// Export all mesh groups to a Wavefront OBJ file and its material library
+	///
+	/// Every group becomes an object with the same name, using the material returned by `material_for`,
+	/// or a material named after the group if there is none.
+	/// Positions are written in gmdc space, texture coordinates are flipped to the obj convention.
+	pub fn export_obj(
+		&self,
+		mtl_file_name: &str,
+		material_for: impl Fn(&Mesh) -> Option<ObjMaterial>,
+	) -> ObjExport {
+		let mut obj = String::new();
+		let mut materials: Vec<ObjMaterial> = vec![];
+		// writing to a string can not fail
+		let _ = writeln!(obj, "mtllib {mtl_file_name}");
+
+		// obj indices are global to the file and start at 1
+		let mut position_offset = 1;
+		let mut tex_coord_offset = 1;
+		let mut normal_offset = 1;
+
+		for mesh in self.meshes.iter() {
+			let name = String::from_utf8_lossy(&mesh.name.0).to_string();
+			let Some(geometry) = self.mesh_geometry(mesh) else {
+				warn!("skipping mesh {name} in obj export, it does not have vertex positions");
+				continue;
+			};
+			let num_vertices = geometry.positions.len();
+			let has_tex_coords = geometry.tex_coords.len() == num_vertices;
+			let has_normals = geometry.normals.len() == num_vertices;
+
+			let material = material_for(mesh).unwrap_or_else(|| ObjMaterial {
+				name: name.clone(),
+				texture: None,
+			});
+			let _ = writeln!(obj, "o {name}");
+			let _ = writeln!(obj, "usemtl {}", material.name);
+			if !materials.iter().any(|m| m.name == material.name) {
+				materials.push(material);
+			}
+
+			for v in &geometry.positions {
+				let _ = writeln!(obj, "v {} {} {}", v.x, v.y, v.z);
+			}
+			if has_tex_coords {
+				for [u, v] in &geometry.tex_coords {
+					let _ = writeln!(obj, "vt {u} {}", 1.0 - v);
+				}
+			}
+			if has_normals {
+				for n in &geometry.normals {
+					let _ = writeln!(obj, "vn {} {} {}", n.x, n.y, n.z);
+				}
+			}
+
+			let corner = |i: usize| {
+				let position = i + position_offset;
+				match (has_tex_coords, has_normals) {
+					(true, true) => {
+						format!("{position}/{}/{}", i + tex_coord_offset, i + normal_offset)
+					}
+					(true, false) => format!("{position}/{}", i + tex_coord_offset),
+					(false, true) => format!("{position}//{}", i + normal_offset),
+					(false, false) => position.to_string(),
+				}
+			};
+			let (keyword, size) = match mesh.primitive_type {
+				PrimitiveType::Points => ("p", 1),
+				PrimitiveType::Lines => ("l", 2),
+				PrimitiveType::Triangles => ("f", 3),
+			};
+			for primitive in mesh.indices.chunks_exact(size) {
+				if primitive.iter().any(|i| i.0 as usize >= num_vertices) {
+					continue;
+				}
+				let _ = writeln!(
+					obj,
+					"{keyword} {}",
+					primitive
+						.iter()
+						.map(|i| if size == 3 {
+							corner(i.0 as usize)
+						} else {
+							(i.0 as usize + position_offset).to_string()
+						})
+						.collect::<Vec<_>>()
+						.join(" ")
+				);
+			}
+
+			position_offset += num_vertices;
+			if has_tex_coords {
+				tex_coord_offset += num_vertices;
+			}
+			if has_normals {
+				normal_offset += num_vertices;
+			}
+		}
+
+		let mut mtl = String::new();
+		for material in materials {
+			let _ = writeln!(mtl, "newmtl {}", material.name);
+			let _ = writeln!(mtl, "Kd 1 1 1");
+			if let Some(texture) = material.texture {
+				let _ = writeln!(mtl, "map_Kd {texture}");
+			}
+			let _ = writeln!(mtl);
+		}
+
+		ObjExport { obj, mtl }
+	}
+
+	/// Replace the geometry of a mesh group with an object from a Wavefront OBJ file
+	///
+	/// The object with the same name as the group is used, or the only object in the file.
+	/// When the object keeps the vertex order of the group, meaning it has the same vertices
+	/// and faces and only the positions, normals or texture coordinates were changed,
+	/// all other vertex data like bone weights and morphs is kept.
+	/// Otherwise that data can not be matched to the new vertices, and the group becomes a static mesh
+	/// with only positions, normals and texture coordinates.
+	pub fn import_obj(&mut self, mesh_index: usize, text: &str) -> Result<(), ObjImportError> {
+		let mesh = self
+			.meshes
+			.get(mesh_index)
+			.ok_or(ObjImportError::MissingMesh(mesh_index))?;
+		let name = String::from_utf8_lossy(&mesh.name.0).to_string();
+
+		let file = parse_obj(text)?;
+		let objects: Vec<_> = file
+			.objects
+			.iter()
+			.filter(|object| !object.triangles.is_empty())
+			.collect();
+		let object = match objects.as_slice() {
+			[] => return Err(ObjImportError::NoFaces),
+			[object] => *object,
+			_ => *objects
+				.iter()
+				.find(|object| object.name == name)
+				.ok_or_else(|| ObjImportError::NoMatchingObject(name.clone()))?,
+		};
+
+		let (vertices, indices, kept_order) = object_vertices(object);
+		let positions: Vec<Vertex> = vertices.iter().map(|c| file.positions[c.0]).collect();
+		let normals: Option<Vec<Vertex>> = vertices
+			.iter()
+			.map(|c| c.2.map(|n| file.normals[n]))
+			.collect();
+		let tex_coords: Option<Vec<u8>> = vertices
+			.iter()
+			.map(|c| c.1.map(|t| file.tex_coords[t]))
+			.collect::<Option<Vec<_>>>()
+			.map(|uvs| {
+				uvs.into_iter()
+					.flatten()
+					.flat_map(f32::to_le_bytes)
+					.collect()
+			});
+
+		let old_group = self
+			.attribute_groups
+			.get(mesh.attribute_group_index as usize)
+			.filter(|_| {
+				kept_order
+					&& mesh.indices.iter().map(|i| i.0).eq(indices.iter().copied())
+					&& self
+						.mesh_geometry(mesh)
+						.is_some_and(|geometry| geometry.positions.len() == positions.len())
+			})
+			.cloned();
+		let keep_vertex_data = old_group.is_some();
+		let has_skin_or_morphs = !mesh.bone_references.is_empty()
+			|| self
+				.attribute_groups
+				.get(mesh.attribute_group_index as usize)
+				.is_some_and(|group| {
+					group.attributes.iter().any(|attr| {
+						self.attribute_buffers
+							.get(attr.0 as usize)
+							.is_some_and(|buffer| {
+								!matches!(
+									buffer.binding.binding_type,
+									AttributeType::Positions
+										| AttributeType::Normals | AttributeType::TexCoords
+								)
+							})
+					})
+				});
+		if !keep_vertex_data && has_skin_or_morphs {
+			warn!(
+				"the vertex order of mesh group {name} changed, its bone weights and morphs are removed"
+			);
+		}
+
+		let mut group = old_group.unwrap_or_else(|| AttributeGroup {
+			number_elements: positions.len() as u32,
+			..Default::default()
+		});
+		// the replaced buffers are not indexed, the index sets of the group still apply to the kept data
+		let index_set = |index_set| {
+			if keep_vertex_data {
+				IndexSet::None
+			} else {
+				index_set
+			}
+		};
+		let replaced = [
+			Some((
+				AttributeType::Positions,
+				BlockFormat::F32Vec3,
+				index_set(IndexSet::Main),
+				vertex_bytes(&positions),
+			)),
+			normals.map(|normals| {
+				(
+					AttributeType::Normals,
+					BlockFormat::F32Vec3,
+					index_set(IndexSet::Norms),
+					vertex_bytes(&normals),
+				)
+			}),
+			tex_coords.map(|tex_coords| {
+				(
+					AttributeType::TexCoords,
+					BlockFormat::F32Vec2,
+					index_set(IndexSet::UV),
+					tex_coords,
+				)
+			}),
+		];
+
+		for (binding_type, block_format, index_set, data) in replaced.into_iter().flatten() {
+			let buffer = self.push_attribute_buffer(binding_type, 0, block_format, index_set, data);
+			let existing = group.attributes.iter_mut().find(|attr| {
+				self.attribute_buffers
+					.get(attr.0 as usize)
+					.is_some_and(|attr| {
+						attr.binding.binding_type == binding_type && attr.binding.binding_slot == 0
+					})
+			});
+			match existing {
+				Some(existing) => *existing = buffer,
+				None => group.attributes.push(buffer),
+			}
+		}
+		if !keep_vertex_data {
+			group.referenced_active = group.attributes.len() as u32;
+		}
+
+		self.attribute_groups.push(group);
+		let mesh = &mut self.meshes[mesh_index];
+		mesh.attribute_group_index = self.attribute_groups.len() as u32 - 1;
+		mesh.primitive_type = PrimitiveType::Triangles;
+		mesh.indices = SizedVec::new(indices.into_iter().map(Reference).collect());
+		if !keep_vertex_data {
+			mesh.bone_references = Default::default();
+		}
+
+		self.remove_unused_attributes();
+		self.recompute_bounding_mesh();
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+
+	/// a single mesh group "body" with every vertex bound to bone 0, and its triangles in reverse order
+	fn skinned_mesh(positions: &[[f32; 3]]) -> GeometricDataContainer {
+		let num_vertices = positions.len();
+		let mut gmdc = GeometricDataContainer::default();
+		let vertices: Vec<_> = positions
+			.iter()
+			.map(|&[x, y, z]| Vertex { x, y, z })
+			.collect();
+		gmdc.push_test_mesh(
+			"body",
+			&vertices,
+			vec![(
+				AttributeType::BoneKeys,
+				BlockFormat::U8Vec4,
+				[0, 0xff, 0xff, 0xff].repeat(num_vertices),
+			)],
+			(0..num_vertices as u32 / 3 * 3).rev(),
+		)
+		.bone_references = SizedVec::new(vec![Reference(0)]);
+		gmdc.attribute_groups[0].referenced_active = 1;
+		gmdc
+	}
+
+	#[proptest]
+	fn obj_roundtrip_keeps_bone_data(
+		#[strategy(proptest::collection::vec([-10.0f32..10.0, -10.0f32..10.0, -10.0f32..10.0], 3..30))]
+		positions: Vec<[f32; 3]>,
+	) {
+		let gmdc = skinned_mesh(&positions);
+
+		let export = gmdc.export_obj("body.mtl", |_| None);
+		prop_assert!(export.mtl.contains("newmtl body"));
+
+		let mut imported = gmdc.clone();
+		imported.import_obj(0, &export.obj).unwrap();
+
+		prop_assert_eq!(
+			&imported.meshes[0].indices.data,
+			&gmdc.meshes[0].indices.data
+		);
+		prop_assert_eq!(&imported.meshes[0].bone_references.data, &[Reference(0)]);
+		prop_assert_eq!(imported.attribute_buffers.len(), 2);
+		let group = imported.meshes[0].attribute_group_index as usize;
+		prop_assert_eq!(imported.attribute_groups[group].referenced_active, 1);
+		let geometry = imported.mesh_geometry(&imported.meshes[0]).unwrap();
+		for (a, b) in geometry.positions.iter().zip(&positions) {
+			prop_assert!((a.x - b[0]).abs() < 1e-4 && (a.y - b[1]).abs() < 1e-4);
+		}
+	}
+
+	#[proptest]
+	fn obj_reordered_vertices_drop_bone_data(
+		#[strategy(proptest::collection::vec([-10.0f32..10.0, -10.0f32..10.0, -10.0f32..10.0], 3..30))]
+		positions: Vec<[f32; 3]>,
+	) {
+		let mut gmdc = skinned_mesh(&positions);
+
+		// the same triangles, with the vertices declared in reverse order
+		let num_vertices = positions.len();
+		let mut obj = "o body\n".to_string();
+		for [x, y, z] in positions.iter().rev() {
+			obj.push_str(&format!("v {x} {y} {z}\n"));
+		}
+		for triangle in gmdc.meshes[0].indices.chunks_exact(3) {
+			let corners: Vec<_> = triangle
+				.iter()
+				.map(|i| (num_vertices - i.0 as usize).to_string())
+				.collect();
+			obj.push_str(&format!("f {}\n", corners.join(" ")));
+		}
+
+		gmdc.import_obj(0, &obj).unwrap();
+
+		prop_assert!(gmdc.meshes[0].bone_references.is_empty());
+		prop_assert!(gmdc
+			.attribute_buffers
+			.iter()
+			.all(|buffer| buffer.binding.binding_type != AttributeType::BoneKeys));
+		let group = &gmdc.attribute_groups[gmdc.meshes[0].attribute_group_index as usize];
+		prop_assert_eq!(group.referenced_active, group.attributes.len() as u32);
+	}
+}
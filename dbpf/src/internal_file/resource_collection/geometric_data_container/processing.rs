@@ -0,0 +1,628 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+
+use log::warn;
+use thiserror::Error;
+
+use crate::{
+	common::SizedVec,
+	internal_file::resource_collection::geometric_data_container::{
+		math::Vertex, AttributeGroup, AttributeType, BlockFormat, GeometricDataContainer, IndexSet,
+		PrimitiveType, Reference,
+	},
+};
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum NormalMode {
+	/// average the normals of all triangles that share a position, hiding the edges between them
+	#[default]
+	Smooth,
+	/// give every triangle its own vertices facing the same way, showing every edge
+	Flat,
+}
+
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum AttributeGroupError {
+	#[error("attribute group {group} uses attribute buffer {buffer}, which does not exist")]
+	MissingBuffer { group: usize, buffer: u32 },
+	#[error("attribute buffer {buffer} says it has {declared} elements, but contains {actual}")]
+	BufferSize {
+		buffer: usize,
+		declared: u32,
+		actual: usize,
+	},
+	#[error("attribute buffer {buffer} in group {group} has {elements} elements, the group has {expected}")]
+	ElementCount {
+		group: usize,
+		buffer: u32,
+		elements: usize,
+		expected: u32,
+	},
+	#[error("attribute group {group} indexes element {index} of buffer {buffer}, which only has {elements}")]
+	IndexOutOfRange {
+		group: usize,
+		buffer: u32,
+		index: u32,
+		elements: usize,
+	},
+}
+
+pub(super) fn position_key(v: Vertex) -> [u32; 3] {
+	[v.x.to_bits(), v.y.to_bits(), v.z.to_bits()]
+}
+
+fn vertex_bytes(vertices: &[Vertex]) -> Vec<u8> {
+	vertices
+		.iter()
+		.flat_map(|v| [v.x, v.y, v.z])
+		.flat_map(f32::to_le_bytes)
+		.collect()
+}
+
+/// any unit vector perpendicular to `v`
+fn perpendicular(v: Vertex) -> Vertex {
+	let axis = if v.x.abs() < 0.9 {
+		Vertex {
+			x: 1.0,
+			y: 0.0,
+			z: 0.0,
+		}
+	} else {
+		Vertex {
+			x: 0.0,
+			y: 1.0,
+			z: 0.0,
+		}
+	};
+	v.cross(axis).normalize()
+}
+
+impl AttributeGroup {
+	fn index_list(&self, index_set: &IndexSet) -> &[Reference] {
+		match index_set {
+			IndexSet::Main => &self.vertex_indices,
+			IndexSet::Norms => &self.normal_indices,
+			IndexSet::UV => &self.uv_indices,
+			IndexSet::None => &[],
+		}
+	}
+
+	fn is_indexed(&self) -> bool {
+		!self.vertex_indices.is_empty()
+			|| !self.normal_indices.is_empty()
+			|| !self.uv_indices.is_empty()
+	}
+}
+
+impl GeometricDataContainer {
+	/// attribute groups that are only used by triangle meshes, with the indices of those meshes
+	pub(super) fn triangle_groups(&self) -> Vec<(usize, Vec<usize>)> {
+		(0..self.attribute_groups.len())
+			.filter_map(|group| {
+				let meshes: Vec<usize> = self
+					.meshes
+					.iter()
+					.enumerate()
+					.filter(|(_, mesh)| mesh.attribute_group_index as usize == group)
+					.map(|(i, _)| i)
+					.collect();
+				(!meshes.is_empty()
+					&& meshes
+						.iter()
+						.all(|mesh| self.meshes[*mesh].primitive_type == PrimitiveType::Triangles))
+				.then_some((group, meshes))
+			})
+			.collect()
+	}
+
+	/// rebuild all buffers of an attribute group so that vertex `i` holds the data of old vertex `vertices[i]`
+	///
+	/// the index sets of the group are applied and cleared, the indices of the meshes are not changed
+	pub(super) fn remap_group_vertices(&mut self, group_index: usize, vertices: &[u32]) {
+		let group = &self.attribute_groups[group_index];
+		let num_vertices = self
+			.group_geometry(group)
+			.map(|geometry| geometry.positions.len())
+			.unwrap_or(group.number_elements as usize);
+
+		let buffers: Vec<_> = group
+			.attributes
+			.iter()
+			.filter_map(|r| self.attribute_buffers.get(r.0 as usize))
+			.filter_map(|attr| {
+				let elements: Vec<&[u8]> = group.get_buffer_data(attr).collect();
+				if elements.len() != num_vertices {
+					warn!(
+						"dropping {:?} buffer with {} elements from a group with {num_vertices} vertices",
+						attr.binding.binding_type,
+						elements.len()
+					);
+					return None;
+				}
+				let data = vertices
+					.iter()
+					.flat_map(|v| elements[*v as usize])
+					.copied()
+					.collect();
+				Some((
+					attr.binding.binding_type,
+					attr.binding.binding_slot,
+					attr.block_format.clone(),
+					attr.index_set.clone(),
+					data,
+				))
+			})
+			.collect();
+
+		let attributes = buffers
+			.into_iter()
+			.map(
+				|(binding_type, binding_slot, block_format, index_set, data)| {
+					self.push_attribute_buffer(
+						binding_type,
+						binding_slot,
+						block_format,
+						index_set,
+						data,
+					)
+				},
+			)
+			.collect();
+
+		let group = &mut self.attribute_groups[group_index];
+		group.attributes = SizedVec::new(attributes);
+		group.referenced_active = group.attributes.len() as u32;
+		group.number_elements = vertices.len() as u32;
+		group.vertex_indices = Default::default();
+		group.normal_indices = Default::default();
+		group.uv_indices = Default::default();
+	}
+
+	/// apply the index sets of a group to its buffers, so new per-vertex buffers can be added directly
+	pub(super) fn flatten_group(&mut self, group_index: usize) {
+		let group = &self.attribute_groups[group_index];
+		if group.is_indexed() {
+			let num_vertices = self
+				.group_geometry(group)
+				.map(|geometry| geometry.positions.len())
+				.unwrap_or(group.number_elements as usize);
+			self.remap_group_vertices(group_index, &(0..num_vertices as u32).collect::<Vec<_>>());
+		}
+	}
+
+	/// replace the slot 0 buffer of the given type in a group, or add it if the group does not have one
+	fn set_group_attribute(
+		&mut self,
+		group_index: usize,
+		binding_type: AttributeType,
+		block_format: BlockFormat,
+		index_set: IndexSet,
+		data: Vec<u8>,
+	) {
+		let buffer = self.push_attribute_buffer(binding_type, 0, block_format, index_set, data);
+		let group = &mut self.attribute_groups[group_index];
+		let existing = group.attributes.iter_mut().find(|attr| {
+			self.attribute_buffers
+				.get(attr.0 as usize)
+				.is_some_and(|attr| {
+					attr.binding.binding_type == binding_type && attr.binding.binding_slot == 0
+				})
+		});
+		match existing {
+			Some(existing) => *existing = buffer,
+			None => {
+				group.attributes.push(buffer);
+				group.referenced_active = group.attributes.len() as u32;
+			}
+		}
+	}
+
+	fn group_triangles(&self, meshes: &[usize]) -> Vec<[usize; 3]> {
+		meshes
+			.iter()
+			.flat_map(|mesh| self.meshes[*mesh].indices.chunks_exact(3))
+			.map(|triangle| [0, 1, 2].map(|i| triangle[i].0 as usize))
+			.collect()
+	}
+
+	/// Recompute the normals of all triangle meshes from their positions
+	///
+	/// Flat normals need a separate vertex for every triangle corner,
+	/// so all other vertex data is duplicated to match.
+	pub fn recompute_normals(&mut self, mode: NormalMode) {
+		for (group, meshes) in self.triangle_groups() {
+			self.flatten_group(group);
+
+			let Some(num_vertices) = self
+				.group_geometry(&self.attribute_groups[group])
+				.map(|geometry| geometry.positions.len())
+			else {
+				continue;
+			};
+			if self
+				.group_triangles(&meshes)
+				.iter()
+				.flatten()
+				.any(|i| *i >= num_vertices)
+			{
+				warn!("skipping normals of attribute group {group}, it has indices out of range");
+				continue;
+			}
+
+			if mode == NormalMode::Flat {
+				let corners: Vec<u32> = meshes
+					.iter()
+					.flat_map(|mesh| self.meshes[*mesh].indices.iter().map(|i| i.0))
+					.collect();
+				self.remap_group_vertices(group, &corners);
+				let mut next = 0;
+				for mesh in &meshes {
+					let mesh = &mut self.meshes[*mesh];
+					let len = mesh.indices.len() as u32;
+					mesh.indices = SizedVec::new((next..next + len).map(Reference).collect());
+					next += len;
+				}
+			}
+
+			let Some(positions) = self
+				.group_geometry(&self.attribute_groups[group])
+				.map(|geometry| geometry.positions)
+			else {
+				continue;
+			};
+			let triangles = self.group_triangles(&meshes);
+
+			// unnormalized cross products weigh the normals by the area of the triangle
+			let mut normals = vec![Vertex::identity(); positions.len()];
+			match mode {
+				NormalMode::Smooth => {
+					let mut by_position: HashMap<[u32; 3], Vertex> = HashMap::new();
+					for [a, b, c] in &triangles {
+						let normal =
+							(positions[*b] - positions[*a]).cross(positions[*c] - positions[*a]);
+						for corner in [a, b, c] {
+							*by_position
+								.entry(position_key(positions[*corner]))
+								.or_insert(Vertex::identity()) += normal;
+						}
+					}
+					for (normal, position) in normals.iter_mut().zip(&positions) {
+						if let Some(sum) = by_position.get(&position_key(*position)) {
+							*normal = sum.normalize();
+						}
+					}
+				}
+				NormalMode::Flat => {
+					for [a, b, c] in &triangles {
+						let normal = (positions[*b] - positions[*a])
+							.cross(positions[*c] - positions[*a])
+							.normalize();
+						for corner in [a, b, c] {
+							normals[*corner] = normal;
+						}
+					}
+				}
+			}
+
+			self.set_group_attribute(
+				group,
+				AttributeType::Normals,
+				BlockFormat::F32Vec3,
+				IndexSet::Norms,
+				vertex_bytes(&normals),
+			);
+		}
+		self.remove_unused_attributes();
+	}
+
+	/// Generate tangents for all triangle meshes from their positions, normals and first uv set
+	///
+	/// Groups without normals or texture coordinates are skipped.
+	pub fn generate_tangents(&mut self) {
+		for (group, meshes) in self.triangle_groups() {
+			self.flatten_group(group);
+
+			let Some(geometry) = self.group_geometry(&self.attribute_groups[group]) else {
+				continue;
+			};
+			let num_vertices = geometry.positions.len();
+			if geometry.normals.len() != num_vertices || geometry.tex_coords.len() != num_vertices {
+				warn!("skipping tangents of attribute group {group}, it needs normals and texture coordinates");
+				continue;
+			}
+			let triangles = self.group_triangles(&meshes);
+			if triangles.iter().flatten().any(|i| *i >= num_vertices) {
+				warn!("skipping tangents of attribute group {group}, it has indices out of range");
+				continue;
+			}
+
+			let mut tangents = vec![Vertex::identity(); num_vertices];
+			for [a, b, c] in triangles {
+				let p = &geometry.positions;
+				let uv = &geometry.tex_coords;
+				let (e1, e2) = (p[b] - p[a], p[c] - p[a]);
+				let (du1, dv1) = (uv[b][0] - uv[a][0], uv[b][1] - uv[a][1]);
+				let (du2, dv2) = (uv[c][0] - uv[a][0], uv[c][1] - uv[a][1]);
+				let det = du1 * dv2 - du2 * dv1;
+				if det.abs() < f32::EPSILON {
+					continue;
+				}
+				let tangent = (e1 * dv2 - e2 * dv1) * (1.0 / det);
+				for corner in [a, b, c] {
+					tangents[corner] += tangent;
+				}
+			}
+			// make the tangents perpendicular to the normals
+			for (tangent, normal) in tangents.iter_mut().zip(&geometry.normals) {
+				let projected = (*tangent - *normal * normal.dot(*tangent)).normalize();
+				*tangent = if projected.length() > 0.5 {
+					projected
+				} else {
+					perpendicular(*normal)
+				};
+			}
+
+			self.set_group_attribute(
+				group,
+				AttributeType::Tangents,
+				BlockFormat::F32Vec3,
+				IndexSet::Main,
+				vertex_bytes(&tangents),
+			);
+		}
+		self.remove_unused_attributes();
+	}
+
+	/// Merge vertices that have exactly the same data in every attribute buffer
+	pub fn weld_vertices(&mut self) {
+		for group_index in 0..self.attribute_groups.len() {
+			let group = &self.attribute_groups[group_index];
+			let buffers: Vec<Vec<&[u8]>> = group
+				.attributes
+				.iter()
+				.filter_map(|r| self.attribute_buffers.get(r.0 as usize))
+				.map(|attr| group.get_buffer_data(attr).collect())
+				.collect();
+			let num_vertices = buffers.iter().map(Vec::len).min().unwrap_or(0);
+
+			let mut unique = vec![];
+			let mut lookup: HashMap<Vec<u8>, u32> = HashMap::new();
+			let mapping: Vec<u32> = (0..num_vertices)
+				.map(|v| {
+					let key = buffers.iter().flat_map(|b| b[v]).copied().collect();
+					*lookup.entry(key).or_insert_with(|| {
+						unique.push(v as u32);
+						unique.len() as u32 - 1
+					})
+				})
+				.collect();
+			if unique.len() == num_vertices {
+				continue;
+			}
+
+			self.remap_group_vertices(group_index, &unique);
+			for mesh in self
+				.meshes
+				.iter_mut()
+				.filter(|mesh| mesh.attribute_group_index as usize == group_index)
+			{
+				for index in mesh.indices.iter_mut() {
+					if let Some(new) = mapping.get(index.0 as usize) {
+						index.0 = *new;
+					}
+				}
+			}
+		}
+		self.remove_unused_attributes();
+	}
+
+	/// Check that every attribute group references existing buffers with the number of elements it expects
+	pub fn validate_attribute_groups(&self) -> Vec<AttributeGroupError> {
+		let mut errors = vec![];
+
+		for (buffer, attr) in self.attribute_buffers.iter().enumerate() {
+			let actual = attr.data.len() / attr.element_size();
+			if actual != attr.number_elements as usize {
+				errors.push(AttributeGroupError::BufferSize {
+					buffer,
+					declared: attr.number_elements,
+					actual,
+				});
+			}
+		}
+
+		for (group_index, group) in self.attribute_groups.iter().enumerate() {
+			for r in group.attributes.iter() {
+				let Some(attr) = self.attribute_buffers.get(r.0 as usize) else {
+					errors.push(AttributeGroupError::MissingBuffer {
+						group: group_index,
+						buffer: r.0,
+					});
+					continue;
+				};
+				let elements = attr.data.len() / attr.element_size();
+				let indices = group.index_list(&attr.index_set);
+				if let Some(index) = indices.iter().find(|i| i.0 as usize >= elements) {
+					errors.push(AttributeGroupError::IndexOutOfRange {
+						group: group_index,
+						buffer: r.0,
+						index: index.0,
+						elements,
+					});
+				}
+				let resolved = if indices.is_empty() {
+					elements
+				} else {
+					indices.len()
+				};
+				if resolved != group.number_elements as usize {
+					errors.push(AttributeGroupError::ElementCount {
+						group: group_index,
+						buffer: r.0,
+						elements: resolved,
+						expected: group.number_elements,
+					});
+				}
+			}
+		}
+
+		errors
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::internal_file::resource_collection::geometric_data_container::read_floats;
+
+	fn whole_vertices(positions: &[[i8; 3]]) -> Vec<Vertex> {
+		positions
+			.iter()
+			.map(|[x, y, z]| Vertex {
+				x: *x as f32,
+				y: *y as f32,
+				z: *z as f32,
+			})
+			.collect()
+	}
+
+	#[proptest]
+	fn weld_and_normals_keep_geometry(
+		#[strategy(proptest::collection::vec([-10i8..10, -10i8..10, -10i8..10], 3..30))]
+		positions: Vec<[i8; 3]>,
+	) {
+		// whole numbers make exact duplicates likely
+		let positions = whole_vertices(&positions);
+		let num_triangles = positions.len() / 3;
+
+		let mut gmdc = GeometricDataContainer::default();
+		gmdc.push_test_mesh("mesh", &positions, vec![], 0..num_triangles as u32 * 3);
+		let triangles = |gmdc: &GeometricDataContainer| -> Vec<Vertex> {
+			let positions = gmdc.mesh_geometry(&gmdc.meshes[0]).unwrap().positions;
+			gmdc.meshes[0]
+				.indices
+				.iter()
+				.map(|i| positions[i.0 as usize])
+				.collect()
+		};
+		let original = triangles(&gmdc);
+
+		gmdc.weld_vertices();
+		prop_assert_eq!(&triangles(&gmdc), &original);
+		prop_assert!(gmdc.validate_attribute_groups().is_empty());
+
+		gmdc.recompute_normals(NormalMode::Flat);
+		prop_assert_eq!(&triangles(&gmdc), &original);
+		let geometry = gmdc.mesh_geometry(&gmdc.meshes[0]).unwrap();
+		prop_assert_eq!(geometry.normals.len(), geometry.positions.len());
+		prop_assert!(gmdc.validate_attribute_groups().is_empty());
+	}
+
+	#[proptest]
+	fn tangents_follow_uv_on_quad(
+		#[strategy(0.0f32..std::f32::consts::TAU)] angle: f32,
+		#[strategy(-0.5f32..0.5)] tilt: f32,
+		mirrored: bool,
+	) {
+		// a unit quad in the xy plane, rotated around z, with u along its local x axis
+		let (sin, cos) = angle.sin_cos();
+		let u_axis = Vertex {
+			x: cos,
+			y: sin,
+			z: 0.0,
+		};
+		let v_axis = Vertex {
+			x: -sin,
+			y: cos,
+			z: 0.0,
+		};
+		let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+		let positions: Vec<Vertex> = corners
+			.iter()
+			.map(|&(u, v)| u_axis * u + v_axis * v)
+			.collect();
+		let tex_coords: Vec<u8> = corners
+			.iter()
+			.flat_map(|&(u, v)| [if mirrored { -u } else { u }, v])
+			.flat_map(f32::to_le_bytes)
+			.collect();
+		// tilted normals, so the tangents have to be projected to be perpendicular
+		let normal = (u_axis * tilt
+			+ Vertex {
+				x: 0.0,
+				y: 0.0,
+				z: 1.0,
+			})
+		.normalize();
+
+		let mut gmdc = GeometricDataContainer::default();
+		gmdc.push_test_mesh(
+			"quad",
+			&positions,
+			vec![
+				(
+					AttributeType::Normals,
+					BlockFormat::F32Vec3,
+					vertex_bytes(&[normal; 4]),
+				),
+				(AttributeType::TexCoords, BlockFormat::F32Vec2, tex_coords),
+			],
+			[0, 1, 2, 0, 2, 3],
+		);
+		gmdc.generate_tangents();
+
+		let group = &gmdc.attribute_groups[gmdc.meshes[0].attribute_group_index as usize];
+		let tangents: Vec<Vec<f32>> = group
+			.attributes
+			.iter()
+			.map(|r| &gmdc.attribute_buffers[r.0 as usize])
+			.find(|attr| attr.binding.binding_type == AttributeType::Tangents)
+			.map(|attr| read_floats(group, attr).collect())
+			.unwrap_or_default();
+		prop_assert_eq!(tangents.len(), positions.len());
+
+		let expected = if mirrored { u_axis * -1.0 } else { u_axis };
+		for tangent in tangents {
+			let tangent = Vertex {
+				x: tangent[0],
+				y: tangent[1],
+				z: tangent[2],
+			};
+			prop_assert!((tangent.length() - 1.0).abs() < 1e-4, "{tangent:?}");
+			prop_assert!(tangent.dot(normal).abs() < 1e-4, "{tangent:?} {normal:?}");
+			prop_assert!(tangent.dot(expected) > 0.8, "{tangent:?} {expected:?}");
+		}
+	}
+
+	#[proptest]
+	fn bounding_mesh_contains_every_vertex(
+		#[strategy(proptest::collection::vec([-10i8..10, -10i8..10, -10i8..10], 3..30))]
+		positions: Vec<[i8; 3]>,
+	) {
+		let positions = whole_vertices(&positions);
+		let num_triangles = positions.len() / 3;
+
+		let mut gmdc = GeometricDataContainer::default();
+		gmdc.push_test_mesh("mesh", &positions, vec![], 0..num_triangles as u32 * 3);
+		gmdc.recompute_bounding_mesh();
+
+		let bounding_mesh = &gmdc.bounding_mesh;
+		prop_assert!(gmdc.dynamic_bounding_mesh.is_empty());
+		prop_assert_eq!(bounding_mesh.faces.len(), num_triangles * 3);
+		for position in &positions[..num_triangles * 3] {
+			prop_assert!(bounding_mesh.vertices.contains(position), "{position:?}");
+		}
+		let faces: Vec<Vertex> = bounding_mesh
+			.faces
+			.iter()
+			.map(|i| bounding_mesh.vertices[i.0 as usize])
+			.collect();
+		prop_assert_eq!(&faces[..], &positions[..num_triangles * 3]);
+	}
+}
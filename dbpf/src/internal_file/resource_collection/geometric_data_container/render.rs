@@ -0,0 +1,606 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	collections::HashMap,
+	io::{Read, Seek},
+};
+
+use crate::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		resource_collection::{
+			geometric_data_container::{
+				math::Vertex, AttributeType, GeometricDataContainer, PrimitiveType,
+			},
+			material_definition::{
+				find_material_textures, material_key, texture_key, MaterialDefinition,
+			},
+			texture_resource::decoded_texture::DecodedTexture,
+			ResourceCollection, ResourceData,
+		},
+		CompressionError, DecodedFile,
+	},
+	IndexEntry,
+};
+
+/// the fraction of light that reaches surfaces facing away from the light
+const AMBIENT: f32 = 0.35;
+/// the color of meshes without a texture
+const UNTEXTURED: [u8; 4] = [200, 200, 200, 255];
+/// texels with a lower alpha are not drawn
+const ALPHA_CUTOFF: u8 = 128;
+/// pixels this close outside a triangle are still drawn, so shared edges do not leave gaps
+const EDGE_TOLERANCE: f32 = 1e-4;
+/// triangles with a corner closer to the camera than this are skipped
+const NEAR: f32 = 1e-3;
+
+/// A perspective camera in gmdc space
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+	pub eye: Vertex,
+	pub target: Vertex,
+	pub up: Vertex,
+	/// field of view over the shortest side of the image, in radians
+	pub fov: f32,
+}
+
+impl Camera {
+	/// look at the center of the meshes from `direction`, from far enough away to fit all of them
+	pub fn framing(gmdc: &GeometricDataContainer, direction: Vertex, fov: f32) -> Self {
+		let positions: Vec<Vertex> = gmdc
+			.meshes
+			.iter()
+			.filter_map(|mesh| gmdc.mesh_geometry(mesh))
+			.flat_map(|geometry| geometry.positions)
+			.collect();
+		let (min, max) = positions.iter().fold(
+			(
+				Vertex {
+					x: f32::MAX,
+					y: f32::MAX,
+					z: f32::MAX,
+				},
+				Vertex {
+					x: f32::MIN,
+					y: f32::MIN,
+					z: f32::MIN,
+				},
+			),
+			|(min, max), p| {
+				(
+					Vertex {
+						x: min.x.min(p.x),
+						y: min.y.min(p.y),
+						z: min.z.min(p.z),
+					},
+					Vertex {
+						x: max.x.max(p.x),
+						y: max.y.max(p.y),
+						z: max.z.max(p.z),
+					},
+				)
+			},
+		);
+		let (target, radius) = if positions.is_empty() {
+			(Vertex::default(), 1.0)
+		} else {
+			((min + max) * 0.5, ((max - min) * 0.5).length().max(1e-3))
+		};
+		let direction = if direction.length() > f32::EPSILON {
+			direction.normalize()
+		} else {
+			Vertex {
+				x: 0.0,
+				y: 0.0,
+				z: 1.0,
+			}
+		};
+		// the y axis is up, unless we are looking straight along it
+		let up = if direction.y.abs() > 0.99 {
+			Vertex {
+				x: 0.0,
+				y: 0.0,
+				z: -1.0,
+			}
+		} else {
+			Vertex {
+				x: 0.0,
+				y: 1.0,
+				z: 0.0,
+			}
+		};
+		Self {
+			eye: target + direction * (radius / (fov / 2.0).sin()),
+			target,
+			up,
+			fov,
+		}
+	}
+}
+
+/// Everything besides the mesh that decides how a rendered image looks
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+	pub width: usize,
+	pub height: usize,
+	pub camera: Camera,
+	/// RGBA color of the pixels that are not covered by a mesh
+	pub background: [u8; 4],
+	/// the direction the light shines in, `None` to light the meshes from the camera
+	pub light_direction: Option<Vertex>,
+	/// the weight of every morph, indexed like the blend group bindings, missing morphs are not applied
+	pub blend_weights: Vec<f32>,
+	/// the diffuse texture of every mesh group, indexed like the meshes
+	///
+	/// meshes without a texture are drawn in a plain color
+	pub textures: Vec<Option<DecodedTexture>>,
+}
+
+impl RenderOptions {
+	pub fn new(width: usize, height: usize, camera: Camera) -> Self {
+		Self {
+			width,
+			height,
+			camera,
+			background: [0, 0, 0, 0],
+			light_direction: None,
+			blend_weights: vec![],
+			textures: vec![],
+		}
+	}
+}
+
+/// a vertex after projection, with its attributes divided by the depth for perspective correct interpolation
+#[derive(Copy, Clone, Debug)]
+struct ScreenVertex {
+	x: f32,
+	y: f32,
+	inv_depth: f32,
+	normal: Vertex,
+	uv: [f32; 2],
+}
+
+fn edge(a: &ScreenVertex, b: &ScreenVertex, x: f32, y: f32) -> f32 {
+	(b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+fn sample(texture: &DecodedTexture, [u, v]: [f32; 2]) -> [u8; 4] {
+	if texture.width == 0 || texture.height == 0 {
+		return UNTEXTURED;
+	}
+	let x = ((u.rem_euclid(1.0) * texture.width as f32) as usize).min(texture.width - 1);
+	let y = ((v.rem_euclid(1.0) * texture.height as f32) as usize).min(texture.height - 1);
+	let i = (y * texture.width + x) * 4;
+	texture
+		.data
+		.get(i..i + 4)
+		.and_then(|texel| texel.try_into().ok())
+		.unwrap_or(UNTEXTURED)
+}
+
+impl GeometricDataContainer {
+	/// Draw all triangle meshes into an RGBA image
+	///
+	/// The meshes are posed with the morph weights in `options` and lit by a single directional light.
+	/// Both sides of every triangle are drawn.
+	pub fn render(&self, options: &RenderOptions) -> DecodedTexture {
+		let (width, height) = (options.width, options.height);
+		let mut image = DecodedTexture {
+			width,
+			height,
+			data: options.background.repeat(width * height),
+		};
+		let mut depth = vec![f32::INFINITY; width * height];
+
+		let camera = &options.camera;
+		let forward = (camera.target - camera.eye).normalize();
+		let right = forward.cross(camera.up).normalize();
+		let up = right.cross(forward);
+		let focal = width.min(height) as f32 / 2.0 / (camera.fov / 2.0).tan();
+		let light = options.light_direction.unwrap_or(forward).normalize();
+
+		for (mesh_index, mesh) in self.meshes.iter().enumerate() {
+			if mesh.primitive_type != PrimitiveType::Triangles {
+				continue;
+			}
+			let (Some(group), Some(mut geometry)) = (
+				self.attribute_groups
+					.get(mesh.attribute_group_index as usize),
+				self.mesh_geometry(mesh),
+			) else {
+				continue;
+			};
+			let num_vertices = geometry.positions.len();
+			let weight = |morph: u8| options.blend_weights.get(morph as usize).copied();
+			for (delta_type, values) in [
+				(AttributeType::PositionDeltas, &mut geometry.positions),
+				(AttributeType::NormalDeltas, &mut geometry.normals),
+			] {
+				if values.len() != num_vertices || options.blend_weights.is_empty() {
+					continue;
+				}
+				let deltas = self.group_morph_deltas(group, num_vertices, delta_type);
+				for (value, deltas) in values.iter_mut().zip(deltas) {
+					for (morph, delta) in deltas {
+						if let Some(weight) = weight(morph) {
+							*value += delta * weight;
+						}
+					}
+				}
+			}
+			let texture = options.textures.get(mesh_index).and_then(Option::as_ref);
+
+			let project = |v: usize| {
+				let relative = geometry.positions[v] - camera.eye;
+				let z = relative.dot(forward);
+				(z >= NEAR).then(|| ScreenVertex {
+					x: width as f32 / 2.0 + relative.dot(right) * focal / z,
+					y: height as f32 / 2.0 - relative.dot(up) * focal / z,
+					inv_depth: 1.0 / z,
+					normal: geometry.normals.get(v).copied().unwrap_or_default() * (1.0 / z),
+					uv: geometry
+						.tex_coords
+						.get(v)
+						.map_or([0.0; 2], |[u, v]| [u / z, v / z]),
+				})
+			};
+
+			for triangle in mesh.indices.chunks_exact(3) {
+				let corners = [triangle[0].0, triangle[1].0, triangle[2].0].map(|i| i as usize);
+				if corners.iter().any(|&i| i >= num_vertices) {
+					continue;
+				}
+				let [Some(a), Some(b), Some(c)] = corners.map(project) else {
+					continue;
+				};
+				let area = edge(&a, &b, c.x, c.y);
+				if area.abs() < f32::EPSILON {
+					continue;
+				}
+
+				let [p0, p1, p2] = corners.map(|i| geometry.positions[i]);
+				let mut face_normal = (p1 - p0).cross(p2 - p0).normalize();
+				// draw the back side as if it were the front side
+				if face_normal.dot(camera.eye - p0) < 0.0 {
+					face_normal = face_normal * -1.0;
+				}
+
+				let x_range = a.x.min(b.x).min(c.x).floor().max(0.0) as usize
+					..(a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(width);
+				let y_range = a.y.min(b.y).min(c.y).floor().max(0.0) as usize
+					..(a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(height);
+				for y in y_range {
+					for x in x_range.clone() {
+						let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+						let w = [
+							edge(&b, &c, px, py) / area,
+							edge(&c, &a, px, py) / area,
+							edge(&a, &b, px, py) / area,
+						];
+						if w.iter().any(|&w| w < -EDGE_TOLERANCE) {
+							continue;
+						}
+						let inv_depth =
+							w[0] * a.inv_depth + w[1] * b.inv_depth + w[2] * c.inv_depth;
+						let z = 1.0 / inv_depth;
+						let pixel = y * width + x;
+						if z >= depth[pixel] {
+							continue;
+						}
+
+						let color = match texture {
+							Some(texture) => sample(
+								texture,
+								[0, 1].map(|i| {
+									(w[0] * a.uv[i] + w[1] * b.uv[i] + w[2] * c.uv[i]) * z
+								}),
+							),
+							None => UNTEXTURED,
+						};
+						if color[3] < ALPHA_CUTOFF {
+							continue;
+						}
+
+						let mut normal = (a.normal * w[0] + b.normal * w[1] + c.normal * w[2]) * z;
+						normal = if normal.length() > f32::EPSILON {
+							normal.normalize()
+						} else {
+							face_normal
+						};
+						if normal.dot(face_normal) < 0.0 {
+							normal = normal * -1.0;
+						}
+						let intensity =
+							AMBIENT + (1.0 - AMBIENT) * normal.dot(light * -1.0).max(0.0);
+
+						depth[pixel] = z;
+						image.data[pixel * 4..pixel * 4 + 4].copy_from_slice(&[
+							(color[0] as f32 * intensity) as u8,
+							(color[1] as f32 * intensity) as u8,
+							(color[2] as f32 * intensity) as u8,
+							255,
+						]);
+					}
+				}
+			}
+		}
+
+		image
+	}
+}
+
+/// Find the diffuse texture of every mesh group of a gmdc in the same package
+///
+/// The textures are found through the SHPE that uses the mesh, its TXMTs and their base texture,
+/// which is decoded by [find_material_textures].
+/// The result is indexed like the meshes of `gmdc`, and can be used as [RenderOptions::textures].
+/// Only decoded copies of the resources are used, so the package is left unchanged.
+pub fn find_mesh_textures<'a, R: Read + Seek>(
+	entries: impl IntoIterator<Item = &'a mut IndexEntry>,
+	reader: &mut R,
+	mesh_group_id: u32,
+	mesh_instance_id: u64,
+	gmdc: &GeometricDataContainer,
+) -> Vec<Option<DecodedTexture>> {
+	let mut entries: Vec<&mut IndexEntry> = entries.into_iter().collect();
+	let mut shapes = vec![];
+	let mut nodes = vec![];
+	let mut materials = HashMap::new();
+	for entry in entries.iter_mut() {
+		let DBPFFileType::Known(
+			KnownDBPFFileType::Shape
+			| KnownDBPFFileType::GeometricNode
+			| KnownDBPFFileType::MaterialDefinition,
+		) = entry.type_id
+		else {
+			continue;
+		};
+		let (group_id, instance_id) = (entry.group_id, entry.instance_id.id);
+		let Ok(Some(DecodedFile::ResourceCollection(rcol))) = entry
+			.data(reader)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded_copy())
+		else {
+			continue;
+		};
+		for resource in &rcol.entries {
+			match &resource.data {
+				ResourceData::Shape(shape) => shapes.push((rcol.clone(), shape.clone())),
+				ResourceData::Material(txmt) => {
+					materials.insert(material_key(&txmt.file_name.name.0), txmt.clone());
+				}
+				ResourceData::GeometricNode(_) => {
+					nodes.push((group_id, instance_id, rcol.clone()));
+				}
+				_ => {}
+			}
+		}
+	}
+
+	let uses_mesh = |shape_rcol: &ResourceCollection| {
+		shape_rcol
+			.links_of_type(KnownDBPFFileType::GeometricNode)
+			.flat_map(|link| {
+				nodes.iter().filter(move |(group_id, instance_id, _)| {
+					link.links_to(*group_id, *instance_id)
+				})
+			})
+			.any(|(_, _, gmnd)| {
+				gmnd.links_of_type(KnownDBPFFileType::GeometricDataContainer)
+					.any(|link| link.links_to(mesh_group_id, mesh_instance_id))
+			})
+	};
+	// fall back to every shape in the package if none of them is linked to this mesh
+	let linked_shapes: Vec<_> = shapes.iter().filter(|(rcol, _)| uses_mesh(rcol)).collect();
+	let shapes = if linked_shapes.is_empty() {
+		shapes.iter().collect()
+	} else {
+		linked_shapes
+	};
+
+	let mesh_materials: Vec<Option<&MaterialDefinition>> = gmdc
+		.meshes
+		.iter()
+		.map(|mesh| {
+			let part = shapes
+				.iter()
+				.flat_map(|(_, shape)| shape.parts.iter())
+				.find(|part| part.subset.0.eq_ignore_ascii_case(&mesh.name.0))?;
+			materials.get(&material_key(&part.material.0))
+		})
+		.collect();
+
+	let textures = find_material_textures(
+		entries.iter_mut().map(|entry| &mut **entry),
+		reader,
+		mesh_materials.iter().flatten().copied(),
+	);
+
+	mesh_materials
+		.into_iter()
+		.map(|txmt| textures.get(&texture_key(txmt?.base_texture()?)).cloned())
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use proptest::{prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::{
+		common::BigString,
+		internal_file::resource_collection::{
+			geometric_node::GeometricNode,
+			material_definition::{PropertyValue, BASE_TEXTURE_PROPERTY},
+			shape::{Shape, ShapePart},
+			texture_resource::{ImageImportOptions, TextureFormat, TextureResource},
+			FileLink, ResourceBlockVersion, ResourceEntry, LOCAL_GROUP,
+		},
+	};
+
+	/// a package entry for a resource collection with a single block
+	fn package_entry(
+		type_id: KnownDBPFFileType,
+		instance_id: u64,
+		links: Vec<FileLink>,
+		data: ResourceData,
+	) -> IndexEntry {
+		IndexEntry::from_decoded(
+			DBPFFileType::Known(type_id),
+			0,
+			instance_id,
+			DecodedFile::ResourceCollection(ResourceCollection {
+				links,
+				entries: vec![ResourceEntry {
+					name: BigString::default(),
+					type_id: DBPFFileType::Known(type_id),
+					version: ResourceBlockVersion::V9,
+					data,
+				}],
+				..ResourceCollection::default()
+			}),
+		)
+	}
+
+	fn local_link(type_id: KnownDBPFFileType, instance_id: u32) -> FileLink {
+		FileLink {
+			group_id: LOCAL_GROUP,
+			instance_id,
+			resource_id: 0,
+			type_id: DBPFFileType::Known(type_id),
+		}
+	}
+
+	#[proptest]
+	fn render_square_covers_center_but_not_corners(
+		#[strategy(-1.0f32..1.0)] x: f32,
+		#[strategy(-1.0f32..1.0)] y: f32,
+		#[strategy(0.3f32..1.0)] z: f32,
+		#[strategy(8usize..64)] size: usize,
+	) {
+		let positions = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]].map(|[x, y]| Vertex {
+			x,
+			y,
+			z: 0.0,
+		});
+		let mut gmdc = GeometricDataContainer::default();
+		gmdc.push_test_mesh("square", &positions, vec![], [0, 1, 2, 0, 2, 3]);
+
+		let camera = Camera::framing(&gmdc, Vertex { x, y, z }, 1.0);
+		let image = gmdc.render(&RenderOptions::new(size, size, camera));
+		prop_assert_eq!(image.data.len(), size * size * 4);
+
+		let pixel = |x: usize, y: usize| &image.data[(y * size + x) * 4..(y * size + x) * 4 + 4];
+		// the square is lit from the camera, so it is brighter than the ambient light alone
+		prop_assert_eq!(pixel(size / 2, size / 2)[3], 255);
+		prop_assert!(pixel(size / 2, size / 2)[0] as f32 > UNTEXTURED[0] as f32 * AMBIENT);
+		// the square fits in the circle that touches the sides of the image
+		for (x, y) in [(0, 0), (size - 1, 0), (0, size - 1), (size - 1, size - 1)] {
+			prop_assert_eq!(pixel(x, y), &[0, 0, 0, 0]);
+		}
+	}
+
+	#[proptest]
+	fn find_mesh_textures_follows_local_group_links(
+		#[strategy(1usize..20)] width: usize,
+		mesh_group_id: u32,
+		mesh_instance_id: u32,
+	) {
+		let shape = |instance_id, material: &str, links| {
+			package_entry(
+				KnownDBPFFileType::Shape,
+				instance_id,
+				links,
+				ResourceData::Shape(Shape {
+					parts: vec![ShapePart {
+						subset: BigString::from("body"),
+						material: BigString::from(format!("{material}_txmt")),
+						..ShapePart::default()
+					}],
+					..Shape::default()
+				}),
+			)
+		};
+		let material = |instance_id, name: &str| {
+			let mut txmt = MaterialDefinition::default();
+			txmt.file_name.name = BigString::from(format!("{name}_txmt"));
+			txmt.set_value(
+				BASE_TEXTURE_PROPERTY,
+				&PropertyValue::Texture(format!("{name}_texture")),
+			);
+			package_entry(
+				KnownDBPFFileType::MaterialDefinition,
+				instance_id,
+				vec![],
+				ResourceData::Material(txmt),
+			)
+		};
+		let texture = |instance_id, name: &str, width: usize| {
+			let mut txtr = TextureResource::from_decoded(
+				DecodedTexture {
+					width,
+					height: 1,
+					data: vec![0x80; width * 4],
+				},
+				&ImageImportOptions {
+					format: Some(TextureFormat::RawARGB32),
+					..ImageImportOptions::default()
+				},
+			)
+			.unwrap();
+			txtr.file_name.name = BigString::from(format!("{name}_texture_txtr"));
+			let width = txtr.width as usize;
+			let entry = package_entry(
+				KnownDBPFFileType::TextureResource,
+				instance_id,
+				vec![],
+				ResourceData::Texture(txtr),
+			);
+			(entry, width)
+		};
+		let (wrong_texture, _) = texture(6, "wrong", width + 1);
+		let (right_texture, right_width) = texture(7, "right", width);
+		// the unlinked shape comes first, so it is used if the local group links are not followed
+		let mut entries = vec![
+			shape(1, "wrong", vec![]),
+			shape(
+				2,
+				"right",
+				vec![local_link(KnownDBPFFileType::GeometricNode, 3)],
+			),
+			package_entry(
+				KnownDBPFFileType::GeometricNode,
+				3,
+				vec![local_link(
+					KnownDBPFFileType::GeometricDataContainer,
+					mesh_instance_id,
+				)],
+				ResourceData::GeometricNode(GeometricNode::default()),
+			),
+			material(4, "wrong"),
+			material(5, "right"),
+			wrong_texture,
+			right_texture,
+		];
+
+		let mut gmdc = super::GeometricDataContainer::default();
+		gmdc.push_test_mesh("body", &[], vec![], []);
+		let textures = find_mesh_textures(
+			&mut entries,
+			&mut Cursor::new(vec![]),
+			mesh_group_id,
+			mesh_instance_id as u64,
+			&gmdc,
+		);
+		prop_assert_eq!(textures.len(), 1);
+		prop_assert_eq!(
+			textures[0].as_ref().map(|texture| texture.width),
+			Some(right_width)
+		);
+	}
+}
@@ -0,0 +1,572 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	cmp::Ordering,
+	collections::{BTreeSet, BinaryHeap, HashMap},
+};
+
+use crate::{
+	common::SizedVec,
+	internal_file::resource_collection::geometric_data_container::{
+		math::Vertex, processing::position_key, AttributeGroup, AttributeType,
+		GeometricDataContainer, Reference,
+	},
+};
+
+/// How far [simplified](GeometricDataContainer::simplified) should reduce a mesh
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SimplifyTarget {
+	/// the number of triangles to keep, spread over all meshes by their size
+	Triangles(usize),
+	/// the fraction of the triangles of every mesh to keep, between 0 and 1
+	Ratio(f32),
+}
+
+/// cost of fully moving one vertex onto another with a different bone, relative to the mesh size squared
+const SKIN_PENALTY: f64 = 0.01;
+
+/// the lowest cosine of the angle a triangle may be turned by a collapse
+const MIN_NORMAL_DOT: f32 = 0.5;
+
+/// symmetric 4x4 matrix that sums the squared distances to a set of planes
+#[derive(Copy, Clone, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+	fn plane(normal: Vertex, point: Vertex) -> Self {
+		let [a, b, c] = [normal.x, normal.y, normal.z].map(f64::from);
+		let d = -f64::from(normal.dot(point));
+		Self([
+			a * a,
+			a * b,
+			a * c,
+			a * d,
+			b * b,
+			b * c,
+			b * d,
+			c * c,
+			c * d,
+			d * d,
+		])
+	}
+
+	fn add(&mut self, other: &Self) {
+		for (a, b) in self.0.iter_mut().zip(other.0) {
+			*a += b;
+		}
+	}
+
+	fn error(&self, p: Vertex) -> f64 {
+		let [x, y, z] = [p.x, p.y, p.z].map(f64::from);
+		let q = &self.0;
+		q[0] * x * x
+			+ q[4] * y * y
+			+ q[7] * z * z
+			+ 2.0 * (q[1] * x * y + q[2] * x * z + q[5] * y * z)
+			+ 2.0 * (q[3] * x + q[6] * y + q[8] * z)
+			+ q[9]
+	}
+}
+
+/// the bone weights and morph deltas of a vertex, which should not change much when it is removed
+#[derive(Clone, Debug, Default)]
+struct Deformation {
+	/// bone slot and weight
+	bones: Vec<(u8, f32)>,
+	/// morph id and position delta
+	morphs: Vec<(u8, Vertex)>,
+}
+
+impl Deformation {
+	fn dominant_bone(&self) -> Option<u8> {
+		self.bones
+			.iter()
+			.max_by(|a, b| a.1.total_cmp(&b.1))
+			.map(|(bone, _)| *bone)
+	}
+
+	fn weight(&self, bone: u8) -> f32 {
+		self.bones
+			.iter()
+			.filter(|(b, _)| *b == bone)
+			.map(|(_, w)| w)
+			.sum()
+	}
+
+	fn delta(&self, morph: u8) -> Vertex {
+		self.morphs
+			.iter()
+			.find(|(m, _)| *m == morph)
+			.map_or(Vertex::identity(), |(_, d)| *d)
+	}
+
+	/// extra cost of moving a vertex with this deformation onto one with `other`
+	fn penalty(&self, other: &Self, scale: f64) -> f64 {
+		let bones: BTreeSet<u8> = self.bones.iter().chain(&other.bones).map(|b| b.0).collect();
+		let skin: f32 = bones
+			.into_iter()
+			.map(|b| (self.weight(b) - other.weight(b)).abs())
+			.sum();
+		let morphs: BTreeSet<u8> = self
+			.morphs
+			.iter()
+			.chain(&other.morphs)
+			.map(|m| m.0)
+			.collect();
+		let morph: f32 = morphs
+			.into_iter()
+			.map(|m| {
+				let d = self.delta(m) - other.delta(m);
+				d.dot(d)
+			})
+			.sum();
+		f64::from(skin) * scale * SKIN_PENALTY + f64::from(morph)
+	}
+}
+
+/// a half edge collapse that removes vertex `from` by moving it onto vertex `to`
+struct Collapse {
+	cost: f64,
+	from: u32,
+	to: u32,
+	versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Collapse {
+	// reversed, so the binary heap pops the cheapest collapse first
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.cost.total_cmp(&self.cost)
+	}
+}
+
+fn triangle_normal(positions: &[Vertex], [a, b, c]: [u32; 3]) -> Vertex {
+	let [a, b, c] = [a, b, c].map(|i| positions[i as usize]);
+	(b - a).cross(c - a)
+}
+
+/// remove vertices with half edge collapses until at most `target` triangles are left,
+/// or no collapse is possible anymore
+fn simplify_triangles(
+	positions: &[Vertex],
+	deformation: &[Deformation],
+	mut triangles: Vec<[u32; 3]>,
+	target: usize,
+) -> Vec<[u32; 3]> {
+	let num_vertices = positions.len();
+	let mut alive = vec![true; triangles.len()];
+	let mut live = triangles.len();
+	let mut vertex_triangles = vec![vec![]; num_vertices];
+	let mut quadrics = vec![Quadric::default(); num_vertices];
+	let mut locked = vec![false; num_vertices];
+	let original_normals: Vec<Vertex> = triangles
+		.iter()
+		.map(|triangle| triangle_normal(positions, *triangle).normalize())
+		.collect();
+
+	// different vertices at the same position are texture or normal seams, which have to stay
+	let mut at_position: HashMap<[u32; 3], BTreeSet<u32>> = HashMap::new();
+	// edges with only one triangle are on the open border of the mesh
+	let mut edges: HashMap<[[u32; 3]; 2], Vec<[u32; 2]>> = HashMap::new();
+	for (t, triangle) in triangles.iter().enumerate() {
+		let normal = triangle_normal(positions, *triangle).normalize();
+		let plane = Quadric::plane(normal, positions[triangle[0] as usize]);
+		for (i, v) in triangle.iter().enumerate() {
+			vertex_triangles[*v as usize].push(t);
+			quadrics[*v as usize].add(&plane);
+			at_position
+				.entry(position_key(positions[*v as usize]))
+				.or_default()
+				.insert(*v);
+
+			let w = triangle[(i + 1) % 3];
+			let (kv, kw) = (
+				position_key(positions[*v as usize]),
+				position_key(positions[w as usize]),
+			);
+			edges
+				.entry([kv.min(kw), kv.max(kw)])
+				.or_default()
+				.push([*v, w]);
+		}
+	}
+	for vertices in at_position.values().filter(|vertices| vertices.len() > 1) {
+		for v in vertices {
+			locked[*v as usize] = true;
+		}
+	}
+	for edge in edges.values().filter(|edge| edge.len() == 1) {
+		for v in edge[0] {
+			locked[v as usize] = true;
+		}
+	}
+
+	let (min, max) = positions
+		.iter()
+		.fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), p| {
+			let p = [p.x, p.y, p.z];
+			(
+				std::array::from_fn(|i| min[i].min(p[i])),
+				std::array::from_fn(|i| max[i].max(p[i])),
+			)
+		});
+	let scale: f64 = (0..3)
+		.map(|i| f64::from(max[i] - min[i]).powi(2))
+		.sum::<f64>()
+		.max(f64::EPSILON);
+
+	let mut versions = vec![0u32; num_vertices];
+	let mut removed = vec![false; num_vertices];
+	let candidate =
+		|from: u32, to: u32, quadrics: &[Quadric], versions: &[u32]| -> Option<Collapse> {
+			let (f, t) = (from as usize, to as usize);
+			if locked[f] || deformation[f].dominant_bone() != deformation[t].dominant_bone() {
+				return None;
+			}
+			let mut quadric = quadrics[f];
+			quadric.add(&quadrics[t]);
+			Some(Collapse {
+				cost: quadric.error(positions[t]) + deformation[f].penalty(&deformation[t], scale),
+				from,
+				to,
+				versions: (versions[f], versions[t]),
+			})
+		};
+
+	let mut heap: BinaryHeap<Collapse> = triangles
+		.iter()
+		.flat_map(|[a, b, c]| [(*a, *b), (*b, *c), (*c, *a)])
+		.flat_map(|(v, w)| [(v, w), (w, v)])
+		.filter_map(|(from, to)| candidate(from, to, &quadrics, &versions))
+		.collect();
+
+	while live > target {
+		let Some(collapse) = heap.pop() else {
+			break;
+		};
+		let (from, to) = (collapse.from as usize, collapse.to as usize);
+		if removed[from] || removed[to] || collapse.versions != (versions[from], versions[to]) {
+			continue;
+		}
+		let live_triangles: Vec<usize> = vertex_triangles[from]
+			.iter()
+			.copied()
+			.filter(|t| alive[*t])
+			.collect();
+		if !live_triangles
+			.iter()
+			.any(|t| triangles[*t].contains(&collapse.to))
+		{
+			continue;
+		}
+		// moving the vertex must not turn any of the remaining triangles inside out,
+		// neither compared to how it is now nor to how it started
+		let flips = live_triangles
+			.iter()
+			.filter(|t| !triangles[**t].contains(&collapse.to))
+			.any(|t| {
+				let old = triangles[*t];
+				let new = old.map(|v| if v == collapse.from { collapse.to } else { v });
+				let (old, new) = (
+					triangle_normal(positions, old).normalize(),
+					triangle_normal(positions, new),
+				);
+				let new = new.normalize();
+				new.length() < 0.5
+					|| old.dot(new) < MIN_NORMAL_DOT
+					|| original_normals[*t].dot(new) < MIN_NORMAL_DOT
+			});
+		if flips {
+			continue;
+		}
+
+		for t in live_triangles {
+			if triangles[t].contains(&collapse.to) {
+				alive[t] = false;
+				live -= 1;
+			} else {
+				for v in triangles[t].iter_mut().filter(|v| **v == collapse.from) {
+					*v = collapse.to;
+				}
+				vertex_triangles[to].push(t);
+			}
+		}
+		removed[from] = true;
+		let quadric = quadrics[from];
+		quadrics[to].add(&quadric);
+		versions[to] += 1;
+
+		let neighbours: BTreeSet<u32> = vertex_triangles[to]
+			.iter()
+			.filter(|t| alive[**t])
+			.flat_map(|t| triangles[*t])
+			.filter(|v| *v != collapse.to)
+			.collect();
+		for v in neighbours {
+			heap.extend(candidate(v, collapse.to, &quadrics, &versions));
+			heap.extend(candidate(collapse.to, v, &quadrics, &versions));
+		}
+	}
+
+	triangles
+		.into_iter()
+		.zip(alive)
+		.filter_map(|(triangle, alive)| alive.then_some(triangle))
+		.collect()
+}
+
+impl GeometricDataContainer {
+	/// read the first bone weights and all morph deltas of a flattened group
+	fn group_deformation(&self, group: &AttributeGroup, num_vertices: usize) -> Vec<Deformation> {
+		let mut deformation = vec![Deformation::default(); num_vertices];
+		let buffers: Vec<_> = group
+			.attributes
+			.iter()
+			.filter_map(|r| self.attribute_buffers.get(r.0 as usize))
+			.collect();
+		let find = |binding_type: AttributeType, slot: u32| {
+			buffers
+				.iter()
+				.find(|attr| {
+					attr.binding.binding_type == binding_type && attr.binding.binding_slot == slot
+				})
+				.map(|attr| group.get_buffer_data(attr).collect::<Vec<_>>())
+				.filter(|elements| elements.len() == num_vertices)
+		};
+		let floats = |element: &[u8]| -> Vec<f32> {
+			element
+				.chunks_exact(4)
+				.map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+				.collect()
+		};
+
+		if let (Some(keys), Some(weights)) = (
+			find(AttributeType::BoneKeys, 0),
+			find(AttributeType::BoneWeights, 0),
+		) {
+			for (v, (keys, weights)) in keys.iter().zip(weights).enumerate() {
+				let weights = floats(weights);
+				// the weight of the last bone is left out when it is implied
+				let rest = 1.0 - weights.iter().sum::<f32>();
+				deformation[v].bones = keys
+					.iter()
+					.enumerate()
+					.filter(|(_, key)| **key != 0xff)
+					.filter_map(|(c, key)| {
+						let weight = weights
+							.get(c)
+							.copied()
+							.or((c == weights.len()).then_some(rest))?;
+						Some((*key, weight))
+					})
+					.collect();
+			}
+		}
+
+		for (deformation, morphs) in deformation.iter_mut().zip(self.group_morph_deltas(
+			group,
+			num_vertices,
+			AttributeType::PositionDeltas,
+		)) {
+			deformation.morphs = morphs;
+		}
+
+		deformation
+	}
+
+	/// Return a copy with fewer triangles, made by repeatedly removing the vertex that changes
+	/// the shape the least (quadric edge collapse)
+	///
+	/// Vertices are only removed, never moved, so the texture coordinates, bone weights and morph
+	/// deltas of the remaining vertices are kept as they are.
+	/// Vertices on texture seams and open edges are never removed, vertices are never merged into
+	/// one that mostly follows a different bone, and differences in skinning or morph deltas make
+	/// a collapse more expensive.
+	pub fn simplified(&self, target: SimplifyTarget) -> Self {
+		let total: usize = self
+			.triangle_groups()
+			.iter()
+			.flat_map(|(_, meshes)| meshes)
+			.map(|mesh| self.meshes[*mesh].poly_count())
+			.sum();
+		let ratio = match target {
+			SimplifyTarget::Triangles(triangles) => triangles as f32 / total.max(1) as f32,
+			SimplifyTarget::Ratio(ratio) => ratio,
+		}
+		.clamp(0.0, 1.0);
+		// nothing will be removed, so the vertices don't need to be welded either
+		if ratio >= 1.0 {
+			return self.clone();
+		}
+
+		let mut gmdc = self.clone();
+		gmdc.weld_vertices();
+
+		let groups = gmdc.triangle_groups();
+
+		for (group, meshes) in groups {
+			gmdc.flatten_group(group);
+			let Some(positions) = gmdc
+				.group_geometry(&gmdc.attribute_groups[group])
+				.map(|geometry| geometry.positions)
+			else {
+				continue;
+			};
+			let num_vertices = positions.len();
+			let deformation = gmdc.group_deformation(&gmdc.attribute_groups[group], num_vertices);
+
+			for mesh in &meshes {
+				let mesh = &mut gmdc.meshes[*mesh];
+				let triangles: Vec<[u32; 3]> = mesh
+					.indices
+					.chunks_exact(3)
+					.map(|triangle| [0, 1, 2].map(|i| triangle[i].0))
+					.filter(|triangle| triangle.iter().all(|v| (*v as usize) < num_vertices))
+					.collect();
+				let target = (triangles.len() as f32 * ratio).round() as usize;
+				let triangles = simplify_triangles(&positions, &deformation, triangles, target);
+				mesh.indices =
+					SizedVec::new(triangles.into_iter().flatten().map(Reference).collect());
+			}
+
+			// drop the vertices that no triangle uses anymore
+			let used: Vec<u32> = meshes
+				.iter()
+				.flat_map(|mesh| gmdc.meshes[*mesh].indices.iter().map(|i| i.0))
+				.collect::<BTreeSet<_>>()
+				.into_iter()
+				.collect();
+			if used.len() < num_vertices {
+				let mut mapping = vec![0; num_vertices];
+				for (new, old) in used.iter().enumerate() {
+					mapping[*old as usize] = new as u32;
+				}
+				gmdc.remap_group_vertices(group, &used);
+				for mesh in &meshes {
+					for index in gmdc.meshes[*mesh].indices.iter_mut() {
+						index.0 = mapping[index.0 as usize];
+					}
+				}
+			}
+		}
+
+		gmdc.remove_unused_attributes();
+		gmdc.recompute_bounding_mesh();
+		gmdc
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+
+	/// a square grid of `size` by `size` vertices, returned together with its positions
+	fn grid(size: usize, heights: &[f32]) -> (GeometricDataContainer, Vec<Vertex>) {
+		let positions: Vec<Vertex> = (0..size * size)
+			.map(|i| Vertex {
+				x: (i % size) as f32,
+				y: (i / size) as f32,
+				z: heights[i],
+			})
+			.collect();
+		let indices: Vec<u32> = (0..size - 1)
+			.flat_map(|y| (0..size - 1).map(move |x| (x, y)))
+			.flat_map(|(x, y)| {
+				let v = (y * size + x) as u32;
+				let s = size as u32;
+				[v, v + 1, v + s + 1, v, v + s + 1, v + s]
+			})
+			.collect();
+
+		let mut gmdc = GeometricDataContainer::default();
+		gmdc.push_test_mesh("grid", &positions, vec![], indices);
+		(gmdc, positions)
+	}
+
+	#[proptest]
+	fn simplify_grid_keeps_border_and_orientation(
+		#[strategy(3usize..12)] size: usize,
+		#[strategy(0.0f32..1.0)] ratio: f32,
+		#[strategy(proptest::collection::vec(-0.1f32..0.1, 144))] heights: Vec<f32>,
+	) {
+		let (gmdc, positions) = grid(size, &heights);
+		let num_triangles = gmdc.meshes[0].poly_count();
+
+		let simplified = gmdc.simplified(SimplifyTarget::Ratio(ratio));
+		prop_assert!(simplified.validate_attribute_groups().is_empty());
+		let mesh = &simplified.meshes[0];
+		prop_assert!(mesh.poly_count() <= num_triangles);
+		// the interior can always lose at least one vertex
+		if ((num_triangles as f32 * ratio).round() as usize) < num_triangles {
+			prop_assert!(mesh.poly_count() < num_triangles);
+		}
+		prop_assert!(mesh.poly_count() > 0);
+
+		let new_positions = simplified.mesh_geometry(mesh).unwrap().positions;
+		// every remaining vertex is an original one, and the border is untouched
+		let border = positions
+			.iter()
+			.filter(|p| {
+				let last = (size - 1) as f32;
+				p.x == 0.0 || p.y == 0.0 || p.x == last || p.y == last
+			})
+			.count();
+		prop_assert!(new_positions.iter().all(|p| positions.contains(p)));
+		prop_assert_eq!(
+			new_positions
+				.iter()
+				.filter(|p| {
+					let last = (size - 1) as f32;
+					p.x == 0.0 || p.y == 0.0 || p.x == last || p.y == last
+				})
+				.count(),
+			border
+		);
+		// no triangle is turned upside down
+		for triangle in mesh.indices.chunks_exact(3) {
+			let normal = triangle_normal(&new_positions, [0, 1, 2].map(|i| triangle[i].0));
+			prop_assert!(normal.z > 0.0);
+		}
+	}
+
+	#[proptest]
+	fn simplify_keeping_everything_changes_nothing(
+		#[strategy(3usize..12)] size: usize,
+		#[strategy(proptest::collection::vec(-0.1f32..0.1, 144))] heights: Vec<f32>,
+		extra_triangles: u8,
+	) {
+		let (mut gmdc, _) = grid(size, &heights);
+		// a duplicate vertex that welding would merge
+		gmdc.attribute_buffers[0]
+			.data
+			.extend_from_within(0..3 * size_of::<f32>());
+		gmdc.attribute_groups[0].number_elements += 1;
+		let num_triangles = gmdc.meshes[0].poly_count();
+
+		prop_assert_eq!(&gmdc.simplified(SimplifyTarget::Ratio(1.0)), &gmdc);
+		prop_assert_eq!(
+			&gmdc.simplified(SimplifyTarget::Triangles(
+				num_triangles + extra_triangles as usize
+			)),
+			&gmdc
+		);
+	}
+}
@@ -0,0 +1,453 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	collections::HashMap,
+	io::{Read, Seek},
+};
+
+use crate::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		resource_collection::{
+			geometric_data_container::{math::Transform, GeometricDataContainer},
+			ResourceCollection, ResourceData,
+		},
+		CompressionError, DecodedFile,
+	},
+	IndexEntry,
+};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Joint {
+	pub name: String,
+	/// index of the parent joint, `None` for the roots of the hierarchy
+	pub parent: Option<usize>,
+	/// transform relative to the parent joint
+	pub transform: Transform,
+	/// the GMDC bone this joint drives, intermediate transform nodes do not have one
+	pub bone: Option<usize>,
+}
+
+/// The joint hierarchy of a model, taken from the transform nodes of its CRES
+///
+/// Parents always come before their children in [joints](Self::joints).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Skeleton {
+	pub joints: Vec<Joint>,
+}
+
+impl Skeleton {
+	/// Collect all transform nodes reachable from the root of a CRES
+	///
+	/// Returns `None` if none of the nodes drive a bone.
+	pub fn from_creation_resource(cres: &ResourceCollection) -> Option<Self> {
+		fn walk(
+			cres: &ResourceCollection,
+			block: usize,
+			parent: Option<usize>,
+			visited: &mut [bool],
+			joints: &mut Vec<Joint>,
+		) {
+			let Some(entry) = cres.entries.get(block) else {
+				return;
+			};
+			// guard against broken files that reference a parent block as a child
+			if std::mem::replace(&mut visited[block], true) {
+				return;
+			}
+			let parent = match &entry.data {
+				ResourceData::TransformNode(node) => {
+					joints.push(Joint {
+						name: node.name().to_string(),
+						parent,
+						transform: Transform {
+							rotation: node.rotation,
+							translation: node.translation,
+						},
+						bone: node.is_joint().then_some(node.joint_reference as usize),
+					});
+					Some(joints.len() - 1)
+				}
+				_ => parent,
+			};
+			for child in entry.data.child_blocks() {
+				walk(cres, child, parent, visited, joints);
+			}
+		}
+
+		let mut joints = vec![];
+		let mut visited = vec![false; cres.entries.len()];
+		// the resource node is always the first block
+		walk(cres, 0, None, &mut visited, &mut joints);
+
+		joints
+			.iter()
+			.any(|joint| joint.bone.is_some())
+			.then_some(Self { joints })
+	}
+
+	pub fn joint_for_bone(&self, bone: usize) -> Option<usize> {
+		self.joints
+			.iter()
+			.position(|joint| joint.bone == Some(bone))
+	}
+
+	/// true if every bone of the mesh is driven by a joint in this skeleton
+	pub fn covers(&self, gmdc: &GeometricDataContainer) -> bool {
+		(0..gmdc.bones.len()).all(|bone| self.joint_for_bone(bone).is_some())
+	}
+}
+
+/// Find the skeleton of a mesh among the resources of a package
+///
+/// The mesh is identified by the group and instance id of its GMDC resource.
+/// A CRES that uses the mesh through its SHPE and GMND links is preferred,
+/// otherwise the first skeleton that has a joint for every bone of the mesh is used.
+/// When several resources have the same type, group and instance, the last one is used, like the game does.
+/// Only copies of the resources are decoded, so the entries are written back unchanged.
+/// Resources which fail to decode are skipped.
+pub fn find_skeleton<'a, R: Read + Seek>(
+	entries: impl IntoIterator<Item = &'a mut IndexEntry>,
+	reader: &mut R,
+	mesh_group_id: u32,
+	mesh_instance_id: u64,
+	gmdc: &GeometricDataContainer,
+) -> Option<Skeleton> {
+	let mut creation_resources: Vec<(_, ResourceCollection)> = vec![];
+	let mut linked_resources = HashMap::new();
+	for entry in entries {
+		let type_id = entry.type_id;
+		let DBPFFileType::Known(
+			known @ (KnownDBPFFileType::CreationResource
+			| KnownDBPFFileType::Shape
+			| KnownDBPFFileType::GeometricNode),
+		) = type_id
+		else {
+			continue;
+		};
+		let key = (type_id, entry.group_id, entry.instance_id.id);
+		let Ok(Some(DecodedFile::ResourceCollection(rcol))) = entry
+			.data(reader)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded_copy())
+		else {
+			continue;
+		};
+		if known == KnownDBPFFileType::CreationResource {
+			match creation_resources.iter_mut().find(|(k, _)| *k == key) {
+				Some((_, cres)) => *cres = rcol,
+				None => creation_resources.push((key, rcol)),
+			}
+		} else {
+			linked_resources.insert(key, rcol);
+		}
+	}
+
+	let linked = |rcol: &ResourceCollection, type_id: KnownDBPFFileType| {
+		rcol.links_of_type(type_id)
+			.flat_map(|link| {
+				linked_resources
+					.iter()
+					.filter(move |((type_id, group_id, instance_id), _)| {
+						*type_id == link.type_id && link.links_to(*group_id, *instance_id)
+					})
+					.map(|(_, rcol)| rcol)
+			})
+			.collect::<Vec<_>>()
+	};
+	// CRES -> SHPE -> GMND -> GMDC
+	let uses_mesh = |cres: &ResourceCollection| {
+		linked(cres, KnownDBPFFileType::Shape)
+			.into_iter()
+			.flat_map(|shpe| linked(shpe, KnownDBPFFileType::GeometricNode))
+			.any(|gmnd| {
+				gmnd.links_of_type(KnownDBPFFileType::GeometricDataContainer)
+					.any(|link| link.links_to(mesh_group_id, mesh_instance_id))
+			})
+	};
+
+	let mut fallback = None;
+	for (_, cres) in &creation_resources {
+		let Some(skeleton) = Skeleton::from_creation_resource(cres) else {
+			continue;
+		};
+		if uses_mesh(cres) {
+			return Some(skeleton);
+		}
+		if fallback.is_none() && skeleton.covers(gmdc) {
+			fallback = Some(skeleton);
+		}
+	}
+	fallback
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use proptest::{collection::vec, prelude::*, prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::{
+		common::{BigString, SizedVec},
+		internal_file::resource_collection::{
+			creation_resource::{ResourceNode, ResourceNodeData, ResourceNodeItem},
+			geometric_data_container::math::{Quaternion, Vertex},
+			object_graph::{TransformNode, TransformNodeChild},
+			FileLink, FileName, ResourceBlockVersion, ResourceEntry, LOCAL_GROUP,
+		},
+	};
+
+	fn entry(data: ResourceData) -> ResourceEntry {
+		ResourceEntry {
+			name: BigString::default(),
+			type_id: DBPFFileType::default(),
+			version: ResourceBlockVersion::V7,
+			data,
+		}
+	}
+
+	/// A CRES with a transform node for every item in `nodes`, given as (parent, translation, bone).
+	///
+	/// Parents are indices into `nodes` and always come before their children,
+	/// nodes without a parent are children of the resource node.
+	fn creation_resource(nodes: &[(Option<usize>, Vertex, Option<usize>)]) -> ResourceCollection {
+		let children_of = |parent: Option<usize>| {
+			nodes
+				.iter()
+				.enumerate()
+				.filter(move |(_, (p, _, _))| *p == parent)
+				.map(|(i, _)| i as i32 + 1)
+		};
+		let mut entries = vec![entry(ResourceData::ResourceNode(ResourceNode {
+			data: ResourceNodeData::Resource {
+				file_name: FileName::default(),
+				composition_tree_node: Default::default(),
+				items: children_of(None)
+					.map(|child_node| ResourceNodeItem {
+						unknown: 0,
+						child_node,
+					})
+					.collect(),
+				unknown: 0,
+			},
+			unknown: 0,
+		}))];
+		for (i, (_, translation, bone)) in nodes.iter().enumerate() {
+			let mut node = TransformNode {
+				children: children_of(Some(i))
+					.map(|child_node| TransformNodeChild {
+						unknown: 0,
+						child_node,
+					})
+					.collect(),
+				translation: *translation,
+				rotation: Quaternion::identity(),
+				joint_reference: bone.map_or(0x7FFFFFFF, |bone| bone as i32),
+				..TransformNode::default()
+			};
+			node.composition_tree_node.object_graph_node.file_name =
+				BigString::from(format!("joint{i}"));
+			entries.push(entry(ResourceData::TransformNode(node)));
+		}
+		ResourceCollection {
+			entries,
+			..ResourceCollection::default()
+		}
+	}
+
+	/// every node gets a random earlier node or the resource node as its parent,
+	/// and the first one is always bone 0
+	fn nodes() -> impl Strategy<Value = Vec<(Option<usize>, Vertex, Option<usize>)>> {
+		vec((any::<u16>(), any::<[i16; 3]>(), any::<bool>()), 1..12).prop_map(|nodes| {
+			let mut bones = 0..;
+			nodes
+				.into_iter()
+				.enumerate()
+				.map(|(i, (parent, [x, y, z], is_bone))| {
+					let parent = (parent as usize % (i + 1)).checked_sub(1);
+					let translation = Vertex {
+						x: x.into(),
+						y: y.into(),
+						z: z.into(),
+					};
+					let bone = (i == 0 || is_bone).then(|| bones.next().unwrap());
+					(parent, translation, bone)
+				})
+				.collect()
+		})
+	}
+
+	#[proptest]
+	fn skeleton_keeps_hierarchy(
+		#[strategy(nodes())] nodes: Vec<(Option<usize>, Vertex, Option<usize>)>,
+	) {
+		let skeleton = Skeleton::from_creation_resource(&creation_resource(&nodes)).unwrap();
+		prop_assert_eq!(skeleton.joints.len(), nodes.len());
+
+		// joints are in depth-first order, so map them back to the nodes by name
+		let node_of = |joint: usize| -> usize {
+			skeleton.joints[joint].name["joint".len()..]
+				.parse()
+				.unwrap()
+		};
+		for (joint_index, joint) in skeleton.joints.iter().enumerate() {
+			let (parent, translation, bone) = nodes[node_of(joint_index)];
+			prop_assert_eq!(joint.parent.map(node_of), parent);
+			prop_assert!(joint.parent.is_none_or(|parent| parent < joint_index));
+			prop_assert_eq!(joint.transform.translation, translation);
+			prop_assert_eq!(joint.bone, bone);
+			if let Some(bone) = bone {
+				prop_assert_eq!(skeleton.joint_for_bone(bone), Some(joint_index));
+			}
+		}
+
+		let bones = nodes.iter().filter(|(_, _, bone)| bone.is_some()).count();
+		let gmdc = |bones: usize| GeometricDataContainer {
+			bones: SizedVec::new(vec![Transform::identity(); bones]),
+			..GeometricDataContainer::default()
+		};
+		prop_assert!(skeleton.covers(&gmdc(bones)));
+		prop_assert!(!skeleton.covers(&gmdc(bones + 1)));
+	}
+
+	#[proptest]
+	fn skeleton_needs_a_bone(
+		#[strategy(nodes())] nodes: Vec<(Option<usize>, Vertex, Option<usize>)>,
+	) {
+		let nodes: Vec<_> = nodes
+			.into_iter()
+			.map(|(parent, translation, _)| (parent, translation, None))
+			.collect();
+		prop_assert_eq!(
+			Skeleton::from_creation_resource(&creation_resource(&nodes)),
+			None
+		);
+	}
+
+	fn link(type_id: KnownDBPFFileType, group_id: u32, instance_id: u32) -> FileLink {
+		FileLink {
+			group_id,
+			instance_id,
+			resource_id: 0,
+			type_id: DBPFFileType::Known(type_id),
+		}
+	}
+
+	/// a package entry for a resource with the given links and blocks
+	fn package_entry(
+		type_id: KnownDBPFFileType,
+		instance_id: u64,
+		links: Vec<FileLink>,
+		rcol: ResourceCollection,
+	) -> IndexEntry {
+		IndexEntry::from_decoded(
+			DBPFFileType::Known(type_id),
+			0,
+			instance_id,
+			DecodedFile::ResourceCollection(ResourceCollection { links, ..rcol }),
+		)
+	}
+
+	#[proptest]
+	fn find_skeleton_follows_the_last_links(
+		#[strategy(nodes())] fallback: Vec<(Option<usize>, Vertex, Option<usize>)>,
+		#[strategy(nodes())] nodes: Vec<(Option<usize>, Vertex, Option<usize>)>,
+		mesh_group_id: u32,
+		mesh_instance_id: u32,
+		overridden: bool,
+	) {
+		use KnownDBPFFileType::*;
+
+		let to_mesh = link(GeometricDataContainer, mesh_group_id, mesh_instance_id);
+		let elsewhere = link(
+			GeometricDataContainer,
+			mesh_group_id,
+			mesh_instance_id.wrapping_add(1),
+		);
+		// the GMND that comes last replaces the other one with the same instance
+		let (first, last) = if overridden {
+			(to_mesh, elsewhere)
+		} else {
+			(elsewhere, to_mesh)
+		};
+		let mut entries = vec![
+			package_entry(CreationResource, 1, vec![], creation_resource(&fallback)),
+			package_entry(
+				CreationResource,
+				2,
+				vec![link(Shape, 0, 1)],
+				creation_resource(&nodes),
+			),
+			package_entry(
+				Shape,
+				1,
+				vec![link(GeometricNode, 0, 1)],
+				ResourceCollection::default(),
+			),
+			package_entry(GeometricNode, 1, vec![first], ResourceCollection::default()),
+			package_entry(GeometricNode, 1, vec![last], ResourceCollection::default()),
+		];
+
+		// a mesh without bones is covered by every skeleton, so the first CRES is the fallback
+		let skeleton = find_skeleton(
+			&mut entries,
+			&mut Cursor::new(vec![]),
+			mesh_group_id,
+			mesh_instance_id as u64,
+			&super::GeometricDataContainer::default(),
+		);
+		let expected = if overridden { &fallback } else { &nodes };
+		prop_assert_eq!(
+			skeleton,
+			Skeleton::from_creation_resource(&creation_resource(expected))
+		);
+	}
+
+	#[proptest]
+	fn find_skeleton_follows_local_group_links(
+		#[strategy(nodes())] fallback: Vec<(Option<usize>, Vertex, Option<usize>)>,
+		#[strategy(nodes())] nodes: Vec<(Option<usize>, Vertex, Option<usize>)>,
+		mesh_group_id: u32,
+		mesh_instance_id: u32,
+	) {
+		use KnownDBPFFileType::*;
+
+		let mut entries = vec![
+			package_entry(CreationResource, 1, vec![], creation_resource(&fallback)),
+			package_entry(
+				CreationResource,
+				2,
+				vec![link(Shape, LOCAL_GROUP, 1)],
+				creation_resource(&nodes),
+			),
+			package_entry(
+				Shape,
+				1,
+				vec![link(GeometricNode, LOCAL_GROUP, 1)],
+				ResourceCollection::default(),
+			),
+			package_entry(
+				GeometricNode,
+				1,
+				vec![link(GeometricDataContainer, LOCAL_GROUP, mesh_instance_id)],
+				ResourceCollection::default(),
+			),
+		];
+
+		let skeleton = find_skeleton(
+			&mut entries,
+			&mut Cursor::new(vec![]),
+			mesh_group_id,
+			mesh_instance_id as u64,
+			&super::GeometricDataContainer::default(),
+		);
+		prop_assert_eq!(
+			skeleton,
+			Skeleton::from_creation_resource(&creation_resource(&nodes))
+		);
+	}
+}
@@ -0,0 +1,50 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::internal_file::resource_collection::geometric_data_container::{
+	math::Vertex, GeometricDataContainer, Mesh, PrimitiveType,
+};
+
+const HEADER_SIZE: usize = 80;
+/// normal, three corners and a two byte attribute count
+const TRIANGLE_SIZE: usize = 4 * 3 * 4 + 2;
+
+impl GeometricDataContainer {
+	/// Export the triangles of a single mesh group to a binary STL file
+	///
+	/// Returns `None` if the group does not have vertex positions or is not made of triangles.
+	pub fn export_stl(&self, mesh: &Mesh) -> Option<Vec<u8>> {
+		if mesh.primitive_type != PrimitiveType::Triangles {
+			return None;
+		}
+		let positions = self.mesh_geometry(mesh)?.positions;
+		let triangles: Vec<[Vertex; 3]> = mesh
+			.indices
+			.chunks_exact(3)
+			.filter_map(|triangle| {
+				Some([
+					*positions.get(triangle[0].0 as usize)?,
+					*positions.get(triangle[1].0 as usize)?,
+					*positions.get(triangle[2].0 as usize)?,
+				])
+			})
+			.collect();
+
+		let mut out = Vec::with_capacity(HEADER_SIZE + 4 + triangles.len() * TRIANGLE_SIZE);
+		let mut header = mesh.name.0.to_vec();
+		header.resize(HEADER_SIZE, 0);
+		out.extend_from_slice(&header);
+		out.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+
+		for [a, b, c] in triangles {
+			let normal = (b - a).cross(c - a).normalize();
+			for v in [normal, a, b, c] {
+				out.extend([v.x, v.y, v.z].iter().flat_map(|f| f.to_le_bytes()));
+			}
+			out.extend_from_slice(&0u16.to_le_bytes());
+		}
+
+		Some(out)
+	}
+}
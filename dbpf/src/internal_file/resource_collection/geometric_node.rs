@@ -0,0 +1,37 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use binrw::binrw;
+
+use crate::internal_file::resource_collection::{
+	object_graph::ObjectGraphNode, ResourceBlockVersion, ResourceEntry,
+};
+
+#[binrw]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeometricNodeBlock {
+	pub block_id: u32,
+	pub entry: ResourceEntry,
+}
+
+/// cGeometryNode, the GMND which links a GMDC into the scenegraph
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeometricNode {
+	pub object_graph_node: ObjectGraphNode,
+
+	#[brw(if(version == ResourceBlockVersion::V11))]
+	pub unknown1: i16,
+	#[brw(if(version == ResourceBlockVersion::V11 || version == ResourceBlockVersion::V12))]
+	pub unknown2: i16,
+	#[brw(if(version == ResourceBlockVersion::V11 || version == ResourceBlockVersion::V12))]
+	pub unknown3: u8,
+
+	#[br(temp)]
+	#[bw(calc = blocks.len() as u32)]
+	block_count: u32,
+	#[br(count = block_count)]
+	pub blocks: Vec<GeometricNodeBlock>,
+}
@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	collections::HashMap,
+	io::{Read, Seek},
+};
+
+use binrw::binrw;
+
+use crate::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		resource_collection::{FileName, ResourceBlockVersion, ResourceData},
+		CompressionError, DecodedFile,
+	},
+	DBPFFile, IndexEntry,
+};
+
+/// cLevelInfo, a single mipmap level of a texture that is stored outside of its TXTR.
+///
+/// The data is encoded in the format of the texture that references it.
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LevelInformation {
+	pub file_name: FileName,
+
+	pub width: u32,
+	pub height: u32,
+	pub z_level: u32,
+
+	#[br(temp)]
+	#[bw(calc = data.len() as u32)]
+	data_len: u32,
+	#[br(count = data_len)]
+	pub data: Vec<u8>,
+}
+
+/// Normalise a LIFO file name so that references from a TXTR can be compared against the name in the LIFO.
+///
+/// The comparison is case-insensitive and ignores the `##0x<group>!` prefix that is sometimes added.
+pub fn lifo_key(name: &[u8]) -> Vec<u8> {
+	let name = match name.iter().position(|&c| c == b'!') {
+		Some(pos) if name.starts_with(b"##") => &name[pos + 1..],
+		_ => name,
+	};
+	name.to_ascii_lowercase()
+}
+
+/// Decode all LIFO resources in a package, indexed by their [lifo_key]
+///
+/// Resources which fail to decode are skipped.
+pub fn find_level_information<R: Read + Seek>(
+	package: &mut DBPFFile,
+	reader: &mut R,
+) -> HashMap<Vec<u8>, LevelInformation> {
+	collect_level_information(package.index.iter_mut(), reader)
+}
+
+/// Decode all LIFO resources among the given entries, indexed by their [lifo_key]
+///
+/// Only copies of the resources are decoded, so the entries are written back unchanged.
+/// Resources which fail to decode are skipped.
+pub fn collect_level_information<'a, R: Read + Seek>(
+	entries: impl IntoIterator<Item = &'a mut IndexEntry>,
+	reader: &mut R,
+) -> HashMap<Vec<u8>, LevelInformation> {
+	entries
+		.into_iter()
+		.filter(|entry| entry.type_id == DBPFFileType::Known(KnownDBPFFileType::LevelInformation))
+		.filter_map(|entry| {
+			let decoded = entry
+				.data(reader)
+				.map_err(CompressionError::from)
+				.and_then(|data| data.decoded_copy())
+				.ok()??;
+			level_information_from_decoded(&decoded)
+		})
+		.map(|lifo| (lifo_key(&lifo.file_name.name.0), lifo))
+		.collect()
+}
+
+/// Get the level information block out of a decoded LIFO resource
+pub fn level_information_from_decoded(decoded: &DecodedFile) -> Option<LevelInformation> {
+	match decoded {
+		DecodedFile::ResourceCollection(rcol) => {
+			rcol.entries.iter().find_map(|entry| match &entry.data {
+				ResourceData::LevelInformation(lifo) => Some(lifo.clone()),
+				_ => None,
+			})
+		}
+		_ => None,
+	}
+}
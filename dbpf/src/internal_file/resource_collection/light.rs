@@ -0,0 +1,77 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use binrw::binrw;
+
+use crate::{
+	common::BigString,
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::resource_collection::{
+		object_graph::ObjectGraphNode, FileName, ResourceBlockVersion,
+	},
+};
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StandardLightBase {
+	#[br(assert("cStandardLightBase".as_bytes() == c_standard_light_base.0.as_slice()))]
+	#[bw(calc(BigString::from("cStandardLightBase".to_string())))]
+	pub c_standard_light_base: BigString,
+
+	pub block_id: u32,
+	pub version: u32,
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LightT {
+	#[br(assert("cLightT".as_bytes() == c_light_t.0.as_slice()))]
+	#[bw(calc(BigString::from("cLightT".to_string())))]
+	pub c_light_t: BigString,
+
+	pub block_id: u32,
+	pub version: u32,
+
+	pub object_graph_node: ObjectGraphNode,
+}
+
+/// An ambient, directional, point or spot light, the extra fields depend on the block type
+#[binrw]
+#[brw(import {type_id: DBPFFileType, version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Light {
+	pub standard_light_base: StandardLightBase,
+	pub file_name: FileName,
+	pub light_t: LightT,
+
+	pub name: BigString,
+
+	pub unknown1: f32,
+	pub unknown2: f32,
+	pub red: f32,
+	pub green: f32,
+	pub blue: f32,
+
+	/// range of point and spot lights
+	#[brw(if(Light::has_range(type_id)))]
+	pub range: [f32; 2],
+	/// cone angles of spot lights
+	#[brw(if(Light::has_cone(type_id)))]
+	pub cone: [f32; 2],
+}
+
+impl Light {
+	/// Whether lights of this block type store a [range](Light::range)
+	pub fn has_range(type_id: DBPFFileType) -> bool {
+		matches!(
+			type_id,
+			DBPFFileType::Known(KnownDBPFFileType::PointLight | KnownDBPFFileType::Spotlight)
+		)
+	}
+
+	/// Whether lights of this block type store a [cone](Light::cone)
+	pub fn has_cone(type_id: DBPFFileType) -> bool {
+		matches!(type_id, DBPFFileType::Known(KnownDBPFFileType::Spotlight))
+	}
+}
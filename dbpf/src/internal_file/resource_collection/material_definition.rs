@@ -2,13 +2,31 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::{
+	collections::HashMap,
+	fmt::{Display, Formatter},
+	io::{Read, Seek},
+};
+
 use binrw::binrw;
 
 use crate::{
 	common::BigString,
-	internal_file::resource_collection::{FileName, ResourceBlockVersion},
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		resource_collection::{
+			level_information::{level_information_from_decoded, lifo_key},
+			texture_resource::decoded_texture::DecodedTexture,
+			FileLink, FileName, ResourceBlockVersion, ResourceData, LOCAL_GROUP,
+		},
+		CompressionError, DecodedFile,
+	},
+	IndexEntry,
 };
 
+/// the property that names the diffuse texture
+pub const BASE_TEXTURE_PROPERTY: &str = "stdMatBaseTextureName";
+
 #[binrw]
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Property {
@@ -39,3 +57,456 @@ pub struct MaterialDefinition {
 	#[brw(if(version as u32 > 8))]
 	pub names: Vec<BigString>,
 }
+
+/// The shader a material is made for, which decides the properties it uses
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MaterialType {
+	StandardMaterial,
+	SimStandardMaterial,
+	SimSkin,
+	Floor,
+	Wall,
+	Other(String),
+}
+
+impl MaterialType {
+	pub const KNOWN: &'static [MaterialType] = &[
+		MaterialType::StandardMaterial,
+		MaterialType::SimStandardMaterial,
+		MaterialType::SimSkin,
+		MaterialType::Floor,
+		MaterialType::Wall,
+	];
+
+	pub fn from_name(name: &[u8]) -> Self {
+		Self::KNOWN
+			.iter()
+			.find(|known| known.name().as_bytes().eq_ignore_ascii_case(name))
+			.cloned()
+			.unwrap_or_else(|| Self::Other(String::from_utf8_lossy(name).to_string()))
+	}
+
+	pub fn name(&self) -> &str {
+		match self {
+			MaterialType::StandardMaterial => "StandardMaterial",
+			MaterialType::SimStandardMaterial => "SimStandardMaterial",
+			MaterialType::SimSkin => "SimSkin",
+			MaterialType::Floor => "Floor",
+			MaterialType::Wall => "Wall",
+			MaterialType::Other(name) => name,
+		}
+	}
+}
+
+/// How the value of a property should be read
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PropertyKind {
+	Bool,
+	Int,
+	Float,
+	/// comma separated rgb or rgba floats
+	Color,
+	/// the name of a TXTR, without the `_txtr` suffix
+	Texture,
+	Text,
+}
+
+/// the kinds of the properties used by the materials of the game
+const KNOWN_PROPERTIES: &[(&str, PropertyKind)] = &[
+	("stdMatAlphaBlendMode", PropertyKind::Text),
+	("stdMatAlphaMultiplier", PropertyKind::Float),
+	("stdMatAlphaRefValue", PropertyKind::Int),
+	("stdMatAlphaTestEnabled", PropertyKind::Bool),
+	("stdMatBaseTextureEnabled", PropertyKind::Bool),
+	(BASE_TEXTURE_PROPERTY, PropertyKind::Texture),
+	("stdMatCullMode", PropertyKind::Text),
+	("stdMatDiffCoef", PropertyKind::Color),
+	("stdMatEmissiveCoef", PropertyKind::Color),
+	("stdMatEnvCubeCoef", PropertyKind::Color),
+	("stdMatEnvCubeLockedToCamera", PropertyKind::Int),
+	("stdMatEnvCubeMode", PropertyKind::Text),
+	("stdMatEnvCubeTextureName", PropertyKind::Texture),
+	("stdMatLayer", PropertyKind::Int),
+	("stdMatLightingEnabled", PropertyKind::Bool),
+	("stdMatNormalMapTextureEnabled", PropertyKind::Bool),
+	("stdMatNormalMapTextureName", PropertyKind::Texture),
+	("stdMatSpecCoef", PropertyKind::Color),
+	("stdMatSpecPower", PropertyKind::Float),
+	("stdMatUntexturedDiffAlpha", PropertyKind::Float),
+	("numTexturesToComposite", PropertyKind::Int),
+	("reflectivity", PropertyKind::Float),
+];
+
+impl PropertyKind {
+	/// look up the kind of a property by its name, unknown properties are guessed from the naming conventions
+	pub fn of(name: &[u8]) -> Self {
+		if let Some((_, kind)) = KNOWN_PROPERTIES
+			.iter()
+			.find(|(known, _)| known.as_bytes().eq_ignore_ascii_case(name))
+		{
+			return *kind;
+		}
+		let name = name.to_ascii_lowercase();
+		// numbered textures like baseTexture0 are used by composited materials
+		let digits = name.iter().rev().take_while(|c| c.is_ascii_digit()).count();
+		let without_number = &name[..name.len() - digits];
+		if without_number.ends_with(b"texturename") || without_number.ends_with(b"texture") {
+			PropertyKind::Texture
+		} else if name.ends_with(b"coef") || name.ends_with(b"color") {
+			PropertyKind::Color
+		} else if name.ends_with(b"enabled") {
+			PropertyKind::Bool
+		} else {
+			PropertyKind::Text
+		}
+	}
+}
+
+/// A parsed property value
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+	Bool(bool),
+	Int(i32),
+	Float(f32),
+	Color(Vec<f32>),
+	Texture(String),
+	/// also used for values that could not be parsed as their kind
+	Text(String),
+}
+
+impl PropertyValue {
+	pub fn parse(kind: PropertyKind, value: &[u8]) -> Self {
+		let text = String::from_utf8_lossy(value).to_string();
+		let trimmed = text.trim();
+		let parsed = match kind {
+			PropertyKind::Bool => match trimmed.to_ascii_lowercase().as_str() {
+				"1" | "true" => Some(PropertyValue::Bool(true)),
+				"0" | "false" => Some(PropertyValue::Bool(false)),
+				_ => None,
+			},
+			PropertyKind::Int => trimmed.parse().ok().map(PropertyValue::Int),
+			PropertyKind::Float => trimmed.parse().ok().map(PropertyValue::Float),
+			PropertyKind::Color => trimmed
+				.split(',')
+				.map(|c| c.trim().parse().ok())
+				.collect::<Option<Vec<f32>>>()
+				.filter(|c| (3..=4).contains(&c.len()))
+				.map(PropertyValue::Color),
+			PropertyKind::Texture => Some(PropertyValue::Texture(text.clone())),
+			PropertyKind::Text => None,
+		};
+		parsed.unwrap_or(PropertyValue::Text(text))
+	}
+}
+
+impl Display for PropertyValue {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PropertyValue::Bool(b) => write!(f, "{}", *b as u8),
+			PropertyValue::Int(i) => write!(f, "{i}"),
+			PropertyValue::Float(x) => write!(f, "{x}"),
+			PropertyValue::Color(c) => write!(
+				f,
+				"{}",
+				c.iter().map(f32::to_string).collect::<Vec<_>>().join(",")
+			),
+			PropertyValue::Texture(s) | PropertyValue::Text(s) => write!(f, "{s}"),
+		}
+	}
+}
+
+/// the name used to compare texture names, without group prefix and type suffix
+pub fn texture_key(name: &[u8]) -> Vec<u8> {
+	let key = lifo_key(name.trim_ascii());
+	match key.strip_suffix(b"_txtr") {
+		Some(stripped) => stripped.to_vec(),
+		None => key,
+	}
+}
+
+/// the name used to compare material names, without group prefix and type suffix
+pub fn material_key(name: &[u8]) -> Vec<u8> {
+	let key = lifo_key(name.trim_ascii());
+	match key.strip_suffix(b"_txmt") {
+		Some(stripped) => stripped.to_vec(),
+		None => key,
+	}
+}
+
+impl Property {
+	pub fn kind(&self) -> PropertyKind {
+		PropertyKind::of(&self.name.0)
+	}
+
+	pub fn typed_value(&self) -> PropertyValue {
+		PropertyValue::parse(self.kind(), &self.value.0)
+	}
+
+	pub fn set_typed_value(&mut self, value: &PropertyValue) {
+		self.value = BigString::from(value.to_string());
+	}
+}
+
+impl MaterialDefinition {
+	pub fn typed_material_type(&self) -> MaterialType {
+		MaterialType::from_name(&self.material_type.0)
+	}
+
+	pub fn property(&self, name: &str) -> Option<&Property> {
+		self.properties
+			.iter()
+			.find(|property| property.name.0.eq_ignore_ascii_case(name.as_bytes()))
+	}
+
+	pub fn value(&self, name: &str) -> Option<PropertyValue> {
+		self.property(name).map(Property::typed_value)
+	}
+
+	/// set the value of a property, adding it if it does not exist yet
+	pub fn set_value(&mut self, name: &str, value: &PropertyValue) {
+		match self
+			.properties
+			.iter_mut()
+			.find(|property| property.name.0.eq_ignore_ascii_case(name.as_bytes()))
+		{
+			Some(property) => property.set_typed_value(value),
+			None => self.properties.push(Property {
+				name: BigString::from(name.to_string()),
+				value: BigString::from(value.to_string()),
+			}),
+		}
+	}
+
+	pub fn base_texture(&self) -> Option<&[u8]> {
+		self.property(BASE_TEXTURE_PROPERTY)
+			.map(|property| property.value.0.as_slice())
+	}
+
+	/// The TXTR every texture property refers to, as (property name, link) pairs
+	///
+	/// Names without a group prefix link to [LOCAL_GROUP].
+	pub fn texture_links(&self) -> Vec<(String, FileLink)> {
+		self.properties
+			.iter()
+			.filter(|property| property.kind() == PropertyKind::Texture)
+			.filter(|property| !property.value.0.trim_ascii().is_empty())
+			.map(|property| {
+				let mut name = property.value.0.trim_ascii().to_vec();
+				if !name.to_ascii_lowercase().ends_with(b"_txtr") {
+					name.extend_from_slice(b"_txtr");
+				}
+				(
+					String::from_utf8_lossy(&property.name.0).to_string(),
+					FileLink::from_name(
+						&name,
+						DBPFFileType::Known(KnownDBPFFileType::TextureResource),
+					),
+				)
+			})
+			.collect()
+	}
+}
+
+/// Decode the largest mip level of every texture a material refers to, from the same package
+///
+/// Textures are found by their TGI, or by the name inside the TXTR if the TGI does not match.
+/// The result is indexed by [texture_key]. Only decoded copies of the resources are used, so the
+/// package is left unchanged.
+pub fn find_textures<'a, R: Read + Seek>(
+	entries: impl IntoIterator<Item = &'a mut IndexEntry>,
+	reader: &mut R,
+	material: &MaterialDefinition,
+) -> HashMap<Vec<u8>, DecodedTexture> {
+	find_material_textures(entries, reader, [material])
+}
+
+/// Decode the textures of several materials at once, see [find_textures]
+pub fn find_material_textures<'a, 'm, R: Read + Seek>(
+	entries: impl IntoIterator<Item = &'a mut IndexEntry>,
+	reader: &mut R,
+	materials: impl IntoIterator<Item = &'m MaterialDefinition>,
+) -> HashMap<Vec<u8>, DecodedTexture> {
+	let mut links = vec![];
+	let mut wanted: Vec<Vec<u8>> = vec![];
+	for material in materials {
+		links.extend(material.texture_links());
+		wanted.extend(
+			material
+				.properties
+				.iter()
+				.filter(|property| property.kind() == PropertyKind::Texture)
+				.map(|property| texture_key(&property.value.0)),
+		);
+	}
+
+	let mut lifos = HashMap::new();
+	let mut texture_entries = vec![];
+	for entry in entries {
+		match entry.type_id {
+			DBPFFileType::Known(KnownDBPFFileType::TextureResource) => {
+				let linked = links.iter().any(|(_, link)| {
+					link.instance_id() == entry.instance_id.id
+						&& (link.group_id == LOCAL_GROUP || link.group_id == entry.group_id)
+				});
+				texture_entries.push((linked, entry));
+			}
+			DBPFFileType::Known(KnownDBPFFileType::LevelInformation) => {
+				if let Some(lifo) = entry
+					.data(reader)
+					.map_err(CompressionError::from)
+					.and_then(|data| data.decoded_copy())
+					.ok()
+					.flatten()
+					.and_then(|decoded| level_information_from_decoded(&decoded))
+				{
+					lifos.insert(lifo_key(&lifo.file_name.name.0), lifo);
+				}
+			}
+			_ => {}
+		}
+	}
+	// try the linked textures first, so other textures only need to be decoded if a link did not match
+	texture_entries.sort_by_key(|(linked, _)| !linked);
+
+	let mut textures = HashMap::new();
+	for (_, entry) in texture_entries {
+		if wanted.iter().all(|key| textures.contains_key(key)) {
+			break;
+		}
+		let Ok(Some(DecodedFile::ResourceCollection(rcol))) = entry
+			.data(reader)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded_copy())
+		else {
+			continue;
+		};
+		for resource in &rcol.entries {
+			let ResourceData::Texture(txtr) = &resource.data else {
+				continue;
+			};
+			let key = texture_key(&txtr.file_name.name.0);
+			if textures.contains_key(&key) || !wanted.contains(&key) {
+				continue;
+			}
+			let mut txtr = txtr.clone();
+			txtr.embed_lifo(&lifos);
+			let Some(largest) = txtr
+				.textures
+				.first()
+				.and_then(|texture| texture.entries.len().checked_sub(1))
+			else {
+				continue;
+			};
+			if let Ok(decoded) = txtr.decompress(0, largest) {
+				textures.insert(key, decoded);
+			}
+		}
+	}
+	textures
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use proptest::{prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::internal_file::resource_collection::{
+		texture_resource::{ImageImportOptions, TextureFormat, TextureResource},
+		ResourceCollection, ResourceEntry,
+	};
+
+	#[proptest]
+	fn typed_values_roundtrip(
+		#[strategy(proptest::collection::vec(-1000.0f32..1000.0, 3..=4))] color: Vec<f32>,
+		value: f32,
+		int: i32,
+		flag: bool,
+		#[strategy("[a-zA-Z0-9_]{1,20}")] texture: String,
+	) {
+		let mut material = MaterialDefinition::default();
+		let values = [
+			("stdMatDiffCoef", PropertyValue::Color(color)),
+			("stdMatSpecPower", PropertyValue::Float(value)),
+			("stdMatLayer", PropertyValue::Int(int)),
+			("stdMatLightingEnabled", PropertyValue::Bool(flag)),
+			(
+				BASE_TEXTURE_PROPERTY,
+				PropertyValue::Texture(texture.clone()),
+			),
+		];
+		for (name, value) in &values {
+			material.set_value(name, value);
+		}
+		prop_assert_eq!(material.properties.len(), values.len());
+		for (name, value) in &values {
+			prop_assert_eq!(material.value(name), Some(value.clone()));
+		}
+
+		let links = material.texture_links();
+		prop_assert_eq!(links.len(), 1);
+		let (property, link) = &links[0];
+		prop_assert_eq!(property, BASE_TEXTURE_PROPERTY);
+		prop_assert_eq!(link.group_id, LOCAL_GROUP);
+		prop_assert_eq!(link.instance_id >> 24, 0xFF);
+
+		// the prefix only changes the group, and case does not matter for the hash
+		let prefixed = FileLink::from_name(
+			format!("##0x1C050000!{}_TXTR", texture.to_uppercase()).as_bytes(),
+			DBPFFileType::Known(KnownDBPFFileType::TextureResource),
+		);
+		prop_assert_eq!(prefixed.group_id, 0x1C050000);
+		prop_assert_eq!(prefixed.instance_id(), link.instance_id());
+		prop_assert!(texture_key(b"##0x1C050000!Foo_txtr") == b"foo");
+	}
+
+	#[proptest]
+	fn find_textures_decodes_the_largest_mip(
+		#[strategy("[a-zA-Z0-9_]{1,20}")] texture: String,
+		#[strategy(1usize..40)] width: usize,
+		#[strategy(1usize..40)] height: usize,
+		instance_id: u64,
+	) {
+		let mut txtr = TextureResource::from_decoded(
+			DecodedTexture {
+				width,
+				height,
+				data: vec![0x80; width * height * 4],
+			},
+			&ImageImportOptions {
+				format: Some(TextureFormat::RawARGB32),
+				..ImageImportOptions::default()
+			},
+		)?;
+		txtr.file_name.name = BigString::from(format!("{texture}_txtr"));
+		let rcol = ResourceCollection {
+			entries: vec![ResourceEntry {
+				name: BigString::default(),
+				type_id: DBPFFileType::Known(KnownDBPFFileType::TextureResource),
+				version: ResourceBlockVersion::V9,
+				data: ResourceData::Texture(txtr.clone()),
+			}],
+			..ResourceCollection::default()
+		};
+		let mut entries = vec![IndexEntry::from_decoded(
+			DBPFFileType::Known(KnownDBPFFileType::TextureResource),
+			LOCAL_GROUP,
+			instance_id,
+			DecodedFile::ResourceCollection(rcol),
+		)];
+
+		let mut material = MaterialDefinition::default();
+		material.set_value(BASE_TEXTURE_PROPERTY, &PropertyValue::Texture(texture.clone()));
+		let textures = find_textures(&mut entries, &mut Cursor::new(vec![]), &material);
+
+		let decoded = textures.get(&texture_key(texture.as_bytes()));
+		prop_assert!(decoded.is_some());
+		let decoded = decoded.unwrap();
+		prop_assert_eq!(
+			(decoded.width, decoded.height),
+			(txtr.width as usize, txtr.height as usize)
+		);
+	}
+}
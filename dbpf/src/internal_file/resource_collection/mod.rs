@@ -2,19 +2,41 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::io::{Read, Seek};
+
 use binrw::{args, binrw};
 
 use crate::{
 	common::BigString,
 	filetypes::{DBPFFileType, KnownDBPFFileType},
-	internal_file::resource_collection::{
-		geometric_data_container::GeometricDataContainer, material_definition::MaterialDefinition,
-		texture_resource::TextureResource,
+	internal_file::{
+		resource_collection::{
+			creation_resource::{LightRefNode, ResourceNode, ShapeRefNode},
+			geometric_data_container::GeometricDataContainer,
+			geometric_node::GeometricNode,
+			level_information::LevelInformation,
+			light::Light,
+			material_definition::MaterialDefinition,
+			object_graph::{
+				BoneDataExtension, DataListExtension, TransformNode, BONE_DATA_EXTENSION_ID,
+				DATA_LIST_EXTENSION_ID, LIGHT_REF_NODE_ID, SHAPE_REF_NODE_ID, TRANSFORM_NODE_ID,
+			},
+			shape::Shape,
+			texture_resource::TextureResource,
+		},
+		CompressionError, DecodedFile,
 	},
+	DBPFFile,
 };
 
+pub mod creation_resource;
 pub mod geometric_data_container;
+pub mod geometric_node;
+pub mod level_information;
+pub mod light;
 pub mod material_definition;
+pub mod object_graph;
+pub mod shape;
 pub mod texture_resource;
 
 #[binrw]
@@ -27,6 +49,61 @@ pub enum ResourceData {
 	Material(#[brw(args{ version })] MaterialDefinition),
 	#[br(pre_assert(matches ! (type_id, DBPFFileType::Known(KnownDBPFFileType::GeometricDataContainer))))]
 	Mesh(#[brw(args{ version })] GeometricDataContainer),
+	#[br(pre_assert(matches ! (type_id, DBPFFileType::Known(KnownDBPFFileType::CreationResource))))]
+	ResourceNode(#[brw(args{ version })] ResourceNode),
+	#[br(pre_assert(type_id.code() == TRANSFORM_NODE_ID))]
+	TransformNode(#[brw(args{ version })] TransformNode),
+	#[br(pre_assert(type_id.code() == SHAPE_REF_NODE_ID))]
+	ShapeRefNode(#[brw(args{ version })] ShapeRefNode),
+	#[br(pre_assert(type_id.code() == LIGHT_REF_NODE_ID))]
+	LightRefNode(#[brw(args{ version })] LightRefNode),
+	#[br(pre_assert(type_id.code() == DATA_LIST_EXTENSION_ID))]
+	DataListExtension(#[brw(args{ version })] DataListExtension),
+	#[br(pre_assert(type_id.code() == BONE_DATA_EXTENSION_ID))]
+	BoneDataExtension(#[brw(args{ version })] BoneDataExtension),
+	#[br(pre_assert(matches ! (type_id, DBPFFileType::Known(KnownDBPFFileType::Shape))))]
+	Shape(#[brw(args{ version })] Shape),
+	#[br(pre_assert(matches ! (type_id, DBPFFileType::Known(KnownDBPFFileType::GeometricNode))))]
+	GeometricNode(#[brw(args{ version })] GeometricNode),
+	#[br(pre_assert(matches ! (type_id, DBPFFileType::Known(
+		KnownDBPFFileType::AmbientLight
+		| KnownDBPFFileType::DirectionalLight
+		| KnownDBPFFileType::PointLight
+		| KnownDBPFFileType::Spotlight))))]
+	Light(#[brw(args{ type_id, version })] Light),
+	#[br(pre_assert(matches ! (type_id, DBPFFileType::Known(KnownDBPFFileType::LevelInformation))))]
+	LevelInformation(#[brw(args{ version })] LevelInformation),
+}
+
+impl ResourceData {
+	/// Indices of the blocks in the same resource collection which are children of this block in the scenegraph
+	pub fn child_blocks(&self) -> Vec<usize> {
+		let children = match self {
+			ResourceData::ResourceNode(node) => node.children(),
+			ResourceData::TransformNode(node) => {
+				node.children.iter().map(|c| c.child_node).collect()
+			}
+			ResourceData::ShapeRefNode(node) => node
+				.transform_node
+				.transform_node
+				.children
+				.iter()
+				.map(|c| c.child_node)
+				.collect(),
+			ResourceData::LightRefNode(node) => node
+				.transform_node
+				.transform_node
+				.children
+				.iter()
+				.map(|c| c.child_node)
+				.collect(),
+			_ => vec![],
+		};
+		children
+			.into_iter()
+			.filter_map(|c| usize::try_from(c).ok())
+			.collect()
+	}
 }
 
 #[binrw]
@@ -67,6 +144,16 @@ pub enum ResourceBlockVersion {
 	V9 = 9,
 	V10 = 10,
 	V11 = 11,
+	V12 = 12,
+	V13 = 13,
+	V14 = 14,
+	V15 = 15,
+	V16 = 16,
+	V17 = 17,
+	V18 = 18,
+	V19 = 19,
+	V20 = 20,
+	V21 = 21,
 }
 
 #[binrw]
@@ -80,6 +167,77 @@ pub struct FileLink {
 	pub type_id: DBPFFileType,
 }
 
+/// The group of resource names without a `##0x<group>!` prefix, which stands for the group of the package itself
+pub const LOCAL_GROUP: u32 = 0xFFFFFFFF;
+
+/// bitwise crc without reflection, as used for the names of scenegraph resources
+fn name_crc(data: &[u8], width: u32, poly: u32, init: u32, xor_out: u32) -> u32 {
+	let top = 1 << (width - 1);
+	let mask = if width == 32 {
+		u32::MAX
+	} else {
+		(1 << width) - 1
+	};
+	let mut crc = init;
+	for &byte in data {
+		crc ^= (byte as u32) << (width - 8);
+		for _ in 0..8 {
+			crc = if crc & top != 0 {
+				(crc << 1) ^ poly
+			} else {
+				crc << 1
+			};
+		}
+		crc &= mask;
+	}
+	crc ^ xor_out
+}
+
+impl FileLink {
+	/// The full instance id, with the resource id as the high 32 bits
+	pub fn instance_id(&self) -> u64 {
+		self.instance_id as u64 | ((self.resource_id as u64) << 32)
+	}
+
+	/// Whether the link points to the resource with the given group and instance id
+	///
+	/// Links to [LOCAL_GROUP] match any group, as they refer to the group of the package they are in.
+	pub fn links_to(&self, group_id: u32, instance_id: u64) -> bool {
+		self.instance_id() == instance_id
+			&& (self.group_id == LOCAL_GROUP || self.group_id == group_id)
+	}
+
+	/// The link to a scenegraph resource with the given name, the way the game looks it up
+	///
+	/// The group comes from the `##0x<group>!` prefix, or is [LOCAL_GROUP] without one.
+	/// The instance and resource id are hashes of the rest of the name, ignoring case.
+	pub fn from_name(name: &[u8], type_id: DBPFFileType) -> Self {
+		let (group_id, name) = match name.iter().position(|&c| c == b'!') {
+			Some(pos) if name.starts_with(b"##0x") || name.starts_with(b"##0X") => (
+				std::str::from_utf8(&name[4..pos])
+					.ok()
+					.and_then(|group| u32::from_str_radix(group, 16).ok())
+					.unwrap_or(LOCAL_GROUP),
+				&name[pos + 1..],
+			),
+			_ => (LOCAL_GROUP, name),
+		};
+		let name = name.trim_ascii().to_ascii_lowercase();
+		Self {
+			group_id,
+			instance_id: name_crc(&name, 24, 0x864CFB, 0xB704CE, 0) | 0xFF000000,
+			resource_id: name_crc(&name, 32, 0x04C11DB7, 0xFFFFFFFF, 0xFFFFFFFF),
+			type_id,
+		}
+	}
+}
+
+/// The group id the game gives to packages and semi-globals with the given name, ignoring case
+pub fn group_hash(name: &[u8]) -> u32 {
+	let name = name.trim_ascii().to_ascii_lowercase();
+	name_crc(&name, 24, 0x864CFB, 0xB704CE, 0) | 0x7F000000
+}
+
 #[binrw]
 #[derive(Clone, Debug, PartialEq)]
 pub struct ResourceEntry {
@@ -120,3 +278,668 @@ pub struct ResourceCollection {
 	#[br(count = item_count)]
 	pub entries: Vec<ResourceEntry>,
 }
+
+impl ResourceCollection {
+	pub fn links_of_type(&self, type_id: KnownDBPFFileType) -> impl Iterator<Item = &FileLink> {
+		self.links
+			.iter()
+			.filter(move |link| link.type_id == DBPFFileType::Known(type_id))
+	}
+
+	/// Decode all resources of the given type that are linked from this resource and can be found in `package`.
+	///
+	/// Links to resources in other packages are skipped.
+	/// This can be used to follow a chain of references, for example SHPE -> GMND -> GMDC.
+	pub fn resolve_links<R: Read + Seek>(
+		&self,
+		type_id: KnownDBPFFileType,
+		package: &mut DBPFFile,
+		reader: &mut R,
+	) -> Vec<Result<ResourceCollection, CompressionError>> {
+		self.links_of_type(type_id)
+			.filter_map(|link| {
+				let entry = package.find_entry(link.type_id, link.group_id, link.instance_id())?;
+				entry
+					.data(reader)
+					.map_err(CompressionError::from)
+					.and_then(|data| data.decoded())
+					.map(|decoded| match decoded {
+						Some(DecodedFile::ResourceCollection(res)) => Some(res.clone()),
+						_ => None,
+					})
+					.transpose()
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+
+	use binrw::{BinRead, BinWrite};
+	use proptest::{collection::vec, option, prelude::*, prop_assert_eq, prop_oneof};
+	use test_strategy::proptest;
+
+	use super::*;
+	use crate::internal_file::resource_collection::{
+		creation_resource::{ResourceNodeData, ResourceNodeItem, ShapeRefNodeItem},
+		geometric_data_container::math::{Quaternion, Vertex},
+		geometric_node::GeometricNodeBlock,
+		light::{LightT, StandardLightBase},
+		object_graph::{
+			BoundedNode, CompositionTreeNode, EmbeddedTransformNode, Extension, ExtensionData,
+			ExtensionItem, ExtensionValue, ObjectGraphExtension, ObjectGraphNode, RenderableNode,
+			TransformNodeChild,
+		},
+		shape::{ShapeItem, ShapePart},
+	};
+
+	fn name() -> impl Strategy<Value = BigString> {
+		"[a-zA-Z0-9_]{0,12}".prop_map(BigString::from)
+	}
+
+	fn file_name() -> impl Strategy<Value = FileName> {
+		name().prop_map(|name| FileName {
+			block_id: 0,
+			version: 2,
+			name,
+		})
+	}
+
+	fn vertex() -> impl Strategy<Value = Vertex> {
+		any::<[i16; 3]>().prop_map(|[x, y, z]| Vertex {
+			x: x.into(),
+			y: y.into(),
+			z: z.into(),
+		})
+	}
+
+	fn quaternion() -> impl Strategy<Value = Quaternion> {
+		any::<[i16; 4]>().prop_map(|[x, y, z, w]| Quaternion {
+			x: x.into(),
+			y: y.into(),
+			z: z.into(),
+			w: w.into(),
+		})
+	}
+
+	fn object_graph_node() -> impl Strategy<Value = ObjectGraphNode> {
+		(
+			any::<u32>(),
+			prop_oneof![Just(3u32), Just(4)],
+			vec(any::<(u8, u8, u32)>(), 0..4),
+			name(),
+		)
+			.prop_map(|(block_id, version, extensions, name)| ObjectGraphNode {
+				block_id,
+				version,
+				extensions: extensions
+					.into_iter()
+					.map(|(enabled, dependent, index)| ObjectGraphExtension {
+						enabled,
+						dependent,
+						index,
+					})
+					.collect(),
+				file_name: if version == 4 {
+					name
+				} else {
+					BigString::default()
+				},
+			})
+	}
+
+	fn composition_tree_node() -> impl Strategy<Value = CompositionTreeNode> {
+		(any::<(u32, u32)>(), object_graph_node()).prop_map(
+			|((block_id, version), object_graph_node)| CompositionTreeNode {
+				block_id,
+				version,
+				object_graph_node,
+			},
+		)
+	}
+
+	fn transform_node() -> impl Strategy<Value = TransformNode> {
+		(
+			composition_tree_node(),
+			vec(any::<(u16, i32)>(), 0..4),
+			vertex(),
+			quaternion(),
+			any::<i32>(),
+		)
+			.prop_map(
+				|(composition_tree_node, children, translation, rotation, joint_reference)| {
+					TransformNode {
+						composition_tree_node,
+						children: children
+							.into_iter()
+							.map(|(unknown, child_node)| TransformNodeChild {
+								unknown,
+								child_node,
+							})
+							.collect(),
+						translation,
+						rotation,
+						joint_reference,
+					}
+				},
+			)
+	}
+
+	fn embedded_transform_node() -> impl Strategy<Value = EmbeddedTransformNode> {
+		(any::<u32>(), transform_node()).prop_map(|(block_id, transform_node)| {
+			EmbeddedTransformNode {
+				block_id,
+				version: ResourceBlockVersion::V7,
+				transform_node,
+			}
+		})
+	}
+
+	fn renderable_and_bounded_node() -> impl Strategy<Value = (RenderableNode, BoundedNode)> {
+		any::<[u32; 4]>().prop_map(|[a, b, c, d]| {
+			(
+				RenderableNode {
+					block_id: a,
+					version: b,
+				},
+				BoundedNode {
+					block_id: c,
+					version: d,
+				},
+			)
+		})
+	}
+
+	fn extension_value() -> impl Strategy<Value = ExtensionValue> {
+		let leaf = prop_oneof![
+			any::<i32>().prop_map(ExtensionValue::Int),
+			any::<i16>().prop_map(|f| ExtensionValue::Float(f.into())),
+			vertex().prop_map(ExtensionValue::Translation),
+			name().prop_map(ExtensionValue::String),
+			quaternion().prop_map(ExtensionValue::Rotation),
+			vec(any::<u8>(), 0..8).prop_map(|data| ExtensionValue::Binary { data }),
+		];
+		leaf.prop_recursive(2, 8, 4, |inner| {
+			vec((name(), inner), 0..4).prop_map(|items| ExtensionValue::Array {
+				items: items
+					.into_iter()
+					.map(|(name, value)| ExtensionItem { name, value })
+					.collect(),
+			})
+		})
+	}
+
+	fn extension() -> impl Strategy<Value = Extension> {
+		(
+			any::<(u32, u32)>(),
+			prop_oneof![
+				(name(), vec((name(), extension_value()), 0..4)).prop_map(|(name, items)| {
+					ExtensionData::Items {
+						name,
+						items: items
+							.into_iter()
+							.map(|(name, value)| ExtensionItem { name, value })
+							.collect(),
+					}
+				}),
+				(0u8..7, vec(any::<u8>(), 0..8))
+					.prop_map(|(type_code, data)| ExtensionData::Raw { type_code, data }),
+			],
+		)
+			.prop_map(|((block_id, version), data)| Extension {
+				block_id,
+				version,
+				data,
+			})
+	}
+
+	fn entry(
+		name: &str,
+		type_id: u32,
+		version: ResourceBlockVersion,
+		data: ResourceData,
+	) -> ResourceEntry {
+		ResourceEntry {
+			name: name.into(),
+			type_id: type_id.into(),
+			version,
+			data,
+		}
+	}
+
+	fn data_list_entry() -> impl Strategy<Value = ResourceEntry> {
+		extension().prop_map(|extension| {
+			entry(
+				"cDataListExtension",
+				DATA_LIST_EXTENSION_ID,
+				ResourceBlockVersion::V1,
+				ResourceData::DataListExtension(DataListExtension { extension }),
+			)
+		})
+	}
+
+	fn shape_entry() -> impl Strategy<Value = ResourceEntry> {
+		prop_oneof![
+			Just(ResourceBlockVersion::V6),
+			Just(ResourceBlockVersion::V7),
+			Just(ResourceBlockVersion::V8),
+		]
+		.prop_flat_map(|version| {
+			(
+				Just(version),
+				object_graph_node(),
+				vec(any::<u32>(), 0..4),
+				vec((any::<(i32, u8, i32, u8)>(), name()), 0..4),
+				vec((name(), name(), any::<[u8; 9]>()), 0..4),
+			)
+		})
+		.prop_map(|(version, object_graph_node, lod_levels, items, parts)| {
+			let shape = Shape {
+				object_graph_node,
+				lod_levels: if version == ResourceBlockVersion::V6 {
+					vec![]
+				} else {
+					lod_levels
+				},
+				items: items
+					.into_iter()
+					.map(
+						|((unknown1, unknown2, unknown3, unknown4), geometric_node)| {
+							let (unknown3, unknown4) = if version == ResourceBlockVersion::V8 {
+								(0, 0)
+							} else {
+								(unknown3, unknown4)
+							};
+							ShapeItem {
+								unknown1,
+								unknown2,
+								unknown3,
+								unknown4,
+								geometric_node,
+							}
+						},
+					)
+					.collect(),
+				parts: parts
+					.into_iter()
+					.map(|(subset, material, unknown)| ShapePart {
+						subset,
+						material,
+						unknown,
+					})
+					.collect(),
+			};
+			entry(
+				"cShape",
+				KnownDBPFFileType::Shape as u32,
+				version,
+				ResourceData::Shape(shape),
+			)
+		})
+	}
+
+	fn geometric_node_entry() -> impl Strategy<Value = ResourceEntry> {
+		(
+			prop_oneof![
+				Just(ResourceBlockVersion::V11),
+				Just(ResourceBlockVersion::V12),
+				Just(ResourceBlockVersion::V13),
+			],
+			object_graph_node(),
+			any::<(i16, i16, u8)>(),
+			vec((any::<u32>(), data_list_entry()), 0..3),
+		)
+			.prop_map(
+				|(version, object_graph_node, (unknown1, unknown2, unknown3), blocks)| {
+					let v11 = version == ResourceBlockVersion::V11;
+					let v11_or_12 = v11 || version == ResourceBlockVersion::V12;
+					let node = GeometricNode {
+						object_graph_node,
+						unknown1: if v11 { unknown1 } else { 0 },
+						unknown2: if v11_or_12 { unknown2 } else { 0 },
+						unknown3: if v11_or_12 { unknown3 } else { 0 },
+						blocks: blocks
+							.into_iter()
+							.map(|(block_id, entry)| GeometricNodeBlock { block_id, entry })
+							.collect(),
+					};
+					entry(
+						"cGeometryNode",
+						KnownDBPFFileType::GeometricNode as u32,
+						version,
+						ResourceData::GeometricNode(node),
+					)
+				},
+			)
+	}
+
+	fn resource_node_entry() -> impl Strategy<Value = ResourceEntry> {
+		let item = any::<(i16, i32)>().prop_map(|(unknown, child_node)| ResourceNodeItem {
+			unknown,
+			child_node,
+		});
+		let data = prop_oneof![
+			(
+				file_name(),
+				composition_tree_node(),
+				vec(item.clone(), 0..4),
+				any::<i32>()
+			)
+				.prop_map(|(file_name, composition_tree_node, items, unknown)| {
+					ResourceNodeData::Resource {
+						file_name,
+						composition_tree_node,
+						items,
+						unknown,
+					}
+				}),
+			(object_graph_node(), item).prop_map(|(object_graph_node, item)| {
+				ResourceNodeData::Graph {
+					object_graph_node,
+					item,
+				}
+			}),
+		];
+		(data, any::<i32>()).prop_map(|(data, unknown)| {
+			entry(
+				"cResourceNode",
+				KnownDBPFFileType::CreationResource as u32,
+				ResourceBlockVersion::V7,
+				ResourceData::ResourceNode(ResourceNode { data, unknown }),
+			)
+		})
+	}
+
+	fn shape_ref_node_entry() -> impl Strategy<Value = ResourceEntry> {
+		(
+			prop_oneof![
+				Just(ResourceBlockVersion::V20),
+				Just(ResourceBlockVersion::V21)
+			],
+			renderable_and_bounded_node(),
+			embedded_transform_node(),
+			any::<(i16, i32, i32, u8, i32, i32)>(),
+			name(),
+			vec(any::<(u16, i32)>(), 0..4),
+			vec((any::<i32>(), name()), 0..4),
+			vec(any::<u8>(), 0..8),
+		)
+			.prop_map(
+				|(
+					version,
+					(renderable_node, bounded_node),
+					transform_node,
+					(unknown1, unknown2, unknown3, unknown4, unknown5, unknown6),
+					name,
+					shapes,
+					blends,
+					data,
+				)| {
+					let (blend_indices, blend_names): (Vec<_>, Vec<_>) = blends.into_iter().unzip();
+					let node = ShapeRefNode {
+						renderable_node,
+						bounded_node,
+						transform_node,
+						unknown1,
+						unknown2,
+						name,
+						unknown3,
+						unknown4,
+						shapes: shapes
+							.into_iter()
+							.map(|(unknown1, shape_link)| ShapeRefNodeItem {
+								unknown1,
+								shape_link,
+							})
+							.collect(),
+						unknown5,
+						blend_indices,
+						blend_names: if version == ResourceBlockVersion::V21 {
+							blend_names
+						} else {
+							vec![]
+						},
+						data,
+						unknown6,
+					};
+					entry(
+						"cShapeRefNode",
+						SHAPE_REF_NODE_ID,
+						version,
+						ResourceData::ShapeRefNode(node),
+					)
+				},
+			)
+	}
+
+	fn light_ref_node_entry() -> impl Strategy<Value = ResourceEntry> {
+		(
+			renderable_and_bounded_node(),
+			embedded_transform_node(),
+			any::<(u16, [u8; 4])>(),
+		)
+			.prop_map(
+				|((renderable_node, bounded_node), transform_node, (light_link, unknown))| {
+					entry(
+						"cLightRefNode",
+						LIGHT_REF_NODE_ID,
+						ResourceBlockVersion::V10,
+						ResourceData::LightRefNode(LightRefNode {
+							renderable_node,
+							bounded_node,
+							transform_node,
+							light_link,
+							unknown,
+						}),
+					)
+				},
+			)
+	}
+
+	fn light_entry() -> impl Strategy<Value = ResourceEntry> {
+		(
+			prop_oneof![
+				Just(KnownDBPFFileType::AmbientLight),
+				Just(KnownDBPFFileType::DirectionalLight),
+				Just(KnownDBPFFileType::PointLight),
+				Just(KnownDBPFFileType::Spotlight),
+			],
+			any::<[u32; 4]>(),
+			file_name(),
+			object_graph_node(),
+			name(),
+			any::<[i16; 9]>(),
+		)
+			.prop_map(
+				|(type_id, [a, b, c, d], file_name, object_graph_node, name, values)| {
+					let [unknown1, unknown2, red, green, blue, r0, r1, c0, c1] =
+						values.map(f32::from);
+					let has_range = matches!(
+						type_id,
+						KnownDBPFFileType::PointLight | KnownDBPFFileType::Spotlight
+					);
+					let has_cone = type_id == KnownDBPFFileType::Spotlight;
+					let light = Light {
+						standard_light_base: StandardLightBase {
+							block_id: a,
+							version: b,
+						},
+						file_name,
+						light_t: LightT {
+							block_id: c,
+							version: d,
+							object_graph_node,
+						},
+						name,
+						unknown1,
+						unknown2,
+						red,
+						green,
+						blue,
+						range: if has_range { [r0, r1] } else { [0.0; 2] },
+						cone: if has_cone { [c0, c1] } else { [0.0; 2] },
+					};
+					entry(
+						"cLight",
+						type_id as u32,
+						ResourceBlockVersion::V1,
+						ResourceData::Light(light),
+					)
+				},
+			)
+	}
+
+	fn transform_node_entry() -> impl Strategy<Value = ResourceEntry> {
+		transform_node().prop_map(|node| {
+			entry(
+				"cTransformNode",
+				TRANSFORM_NODE_ID,
+				ResourceBlockVersion::V7,
+				ResourceData::TransformNode(node),
+			)
+		})
+	}
+
+	fn bone_data_entry() -> impl Strategy<Value = ResourceEntry> {
+		(extension(), any::<(u32, u32, i16)>(), quaternion()).prop_map(
+			|(extension, (unknown1, unknown2, unknown3), rotation)| {
+				entry(
+					"cBoneDataExtension",
+					BONE_DATA_EXTENSION_ID,
+					ResourceBlockVersion::V5,
+					ResourceData::BoneDataExtension(BoneDataExtension {
+						extension,
+						unknown1,
+						unknown2,
+						unknown3: unknown3.into(),
+						rotation,
+					}),
+				)
+			},
+		)
+	}
+
+	fn level_information_entry() -> impl Strategy<Value = ResourceEntry> {
+		(file_name(), any::<[u32; 3]>(), vec(any::<u8>(), 0..64)).prop_map(
+			|(file_name, [width, height, z_level], data)| {
+				entry(
+					"cLevelInfo",
+					KnownDBPFFileType::LevelInformation as u32,
+					ResourceBlockVersion::V4,
+					ResourceData::LevelInformation(LevelInformation {
+						file_name,
+						width,
+						height,
+						z_level,
+						data,
+					}),
+				)
+			},
+		)
+	}
+
+	fn resource_collection(
+		entries: impl Strategy<Value = Vec<ResourceEntry>>,
+	) -> impl Strategy<Value = ResourceCollection> {
+		(entries, option::of(vec(any::<(u32, u32, u32)>(), 0..4))).prop_map(|(entries, links)| {
+			let version = links.is_some();
+			ResourceCollection {
+				version,
+				links: links
+					.unwrap_or_default()
+					.into_iter()
+					.map(|(group_id, instance_id, resource_id)| FileLink {
+						group_id,
+						instance_id,
+						resource_id: if version { resource_id } else { 0 },
+						type_id: DBPFFileType::Known(KnownDBPFFileType::GeometricNode),
+					})
+					.collect(),
+				entries,
+			}
+		})
+	}
+
+	fn write_read_same(rcol: ResourceCollection) -> Result<(), TestCaseError> {
+		let mut cur = Cursor::new(vec![]);
+		rcol.write(&mut cur)?;
+		cur.set_position(0);
+		prop_assert_eq!(ResourceCollection::read(&mut cur)?, rcol);
+		Ok(())
+	}
+
+	#[proptest]
+	fn shape_write_read_same(
+		#[strategy(resource_collection(vec(shape_entry(), 1..3)))] rcol: ResourceCollection,
+	) {
+		write_read_same(rcol)?;
+	}
+
+	#[proptest]
+	fn geometric_node_write_read_same(
+		#[strategy(resource_collection(vec(geometric_node_entry(), 1..3)))]
+		rcol: ResourceCollection,
+	) {
+		write_read_same(rcol)?;
+	}
+
+	#[proptest]
+	fn creation_resource_write_read_same(
+		#[strategy(resource_collection(vec(prop_oneof![
+			resource_node_entry(),
+			transform_node_entry(),
+			shape_ref_node_entry(),
+			light_ref_node_entry(),
+			data_list_entry(),
+			bone_data_entry(),
+		], 1..6)))]
+		rcol: ResourceCollection,
+	) {
+		write_read_same(rcol)?;
+	}
+
+	#[proptest]
+	fn light_write_read_same(
+		#[strategy(resource_collection(vec(light_entry(), 1..3)))] rcol: ResourceCollection,
+	) {
+		write_read_same(rcol)?;
+	}
+
+	#[proptest]
+	fn level_information_write_read_same(
+		#[strategy(resource_collection(vec(level_information_entry(), 1..2)))]
+		rcol: ResourceCollection,
+	) {
+		write_read_same(rcol)?;
+	}
+
+	#[proptest]
+	fn names_hash_like_the_game(#[strategy(vec(any::<bool>(), 6))] upper: Vec<bool>) {
+		let name: Vec<u8> = b"global"
+			.iter()
+			.zip(&upper)
+			.map(|(c, &upper)| if upper { c.to_ascii_uppercase() } else { *c })
+			.collect();
+		// the group of the global BHAVs in objects.package is the hash of "Global"
+		prop_assert_eq!(group_hash(&name), 0x7FD46CD0);
+
+		let mut prefixed = b"##0x1C050000!".to_vec();
+		prefixed.extend_from_slice(&name);
+		let link = FileLink::from_name(
+			&prefixed,
+			DBPFFileType::Known(KnownDBPFFileType::TextureResource),
+		);
+		prop_assert_eq!(link.group_id, 0x1C050000);
+		prop_assert_eq!(link.instance_id, 0xFFD46CD0);
+
+		// the resource id is CRC-32/BZIP2, which has this check value
+		let link = FileLink::from_name(
+			b"123456789",
+			DBPFFileType::Known(KnownDBPFFileType::TextureResource),
+		);
+		prop_assert_eq!(link.group_id, LOCAL_GROUP);
+		prop_assert_eq!(link.resource_id, 0xFC891918);
+	}
+}
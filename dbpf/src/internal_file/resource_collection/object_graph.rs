@@ -0,0 +1,284 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Building blocks shared by the scenegraph resources (CRES, SHPE, GMND, LGHT).
+//! Most of these only appear embedded in other blocks, prefixed by their block name and id.
+
+use binrw::binrw;
+
+use crate::{
+	common::BigString,
+	internal_file::resource_collection::{
+		geometric_data_container::math::{Quaternion, Vertex},
+		ResourceBlockVersion,
+	},
+};
+
+pub const TRANSFORM_NODE_ID: u32 = 0x65246462;
+pub const SHAPE_REF_NODE_ID: u32 = 0x65245517;
+pub const LIGHT_REF_NODE_ID: u32 = 0x253D2018;
+pub const DATA_LIST_EXTENSION_ID: u32 = 0x6A836D56;
+pub const BONE_DATA_EXTENSION_ID: u32 = 0xE9075BC5;
+
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ObjectGraphExtension {
+	pub enabled: u8,
+	pub dependent: u8,
+	/// index of the extension block inside the resource collection
+	pub index: u32,
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ObjectGraphNode {
+	#[br(assert("cObjectGraphNode".as_bytes() == c_object_graph_node.0.as_slice()))]
+	#[bw(calc(BigString::from("cObjectGraphNode".to_string())))]
+	pub c_object_graph_node: BigString,
+
+	pub block_id: u32,
+	pub version: u32,
+
+	#[br(temp)]
+	#[bw(calc = extensions.len() as u32)]
+	extension_count: u32,
+	#[br(count = extension_count)]
+	pub extensions: Vec<ObjectGraphExtension>,
+
+	#[br(if(version == 4))]
+	#[bw(if(*version == 4))]
+	pub file_name: BigString,
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CompositionTreeNode {
+	#[br(assert("cCompositionTreeNode".as_bytes() == c_composition_tree_node.0.as_slice()))]
+	#[bw(calc(BigString::from("cCompositionTreeNode".to_string())))]
+	pub c_composition_tree_node: BigString,
+
+	pub block_id: u32,
+	pub version: u32,
+
+	pub object_graph_node: ObjectGraphNode,
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RenderableNode {
+	#[br(assert("cRenderableNode".as_bytes() == c_renderable_node.0.as_slice()))]
+	#[bw(calc(BigString::from("cRenderableNode".to_string())))]
+	pub c_renderable_node: BigString,
+
+	pub block_id: u32,
+	pub version: u32,
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BoundedNode {
+	#[br(assert("cBoundedNode".as_bytes() == c_bounded_node.0.as_slice()))]
+	#[bw(calc(BigString::from("cBoundedNode".to_string())))]
+	pub c_bounded_node: BigString,
+
+	pub block_id: u32,
+	pub version: u32,
+}
+
+#[binrw]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TransformNodeChild {
+	pub unknown: u16,
+	/// index of the child block inside the resource collection
+	pub child_node: i32,
+}
+
+/// cTransformNode, a node in the scenegraph hierarchy that places its children relative to its parent
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransformNode {
+	pub composition_tree_node: CompositionTreeNode,
+
+	#[br(temp)]
+	#[bw(calc = children.len() as u32)]
+	child_count: u32,
+	#[br(count = child_count)]
+	pub children: Vec<TransformNodeChild>,
+
+	pub translation: Vertex,
+	pub rotation: Quaternion,
+
+	/// index of the bone in the GMDC this node drives, or -1 (0x7FFFFFFF) when it is not a joint
+	pub joint_reference: i32,
+}
+
+impl TransformNode {
+	/// The name of the node, as used by CRES bone lookups
+	pub fn name(&self) -> &BigString {
+		&self.composition_tree_node.object_graph_node.file_name
+	}
+
+	pub fn is_joint(&self) -> bool {
+		self.joint_reference >= 0 && self.joint_reference != 0x7FFFFFFF
+	}
+}
+
+/// A cTransformNode embedded in another block
+#[binrw]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EmbeddedTransformNode {
+	#[br(assert("cTransformNode".as_bytes() == c_transform_node.0.as_slice()))]
+	#[bw(calc(BigString::from("cTransformNode".to_string())))]
+	pub c_transform_node: BigString,
+
+	pub block_id: u32,
+	pub version: ResourceBlockVersion,
+
+	#[br(args{ version })]
+	#[bw(args{ version: *version })]
+	pub transform_node: TransformNode,
+}
+
+#[binrw]
+#[br(import(type_code: u8))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExtensionValue {
+	#[br(pre_assert(type_code == 2))]
+	Int(i32),
+	#[br(pre_assert(type_code == 3))]
+	Float(f32),
+	#[br(pre_assert(type_code == 5))]
+	Translation(Vertex),
+	#[br(pre_assert(type_code == 6))]
+	String(BigString),
+	#[br(pre_assert(type_code == 7))]
+	Array {
+		#[br(temp)]
+		#[bw(calc = items.len() as u32)]
+		count: u32,
+		#[br(count = count)]
+		items: Vec<ExtensionItem>,
+	},
+	#[br(pre_assert(type_code == 8))]
+	Rotation(Quaternion),
+	#[br(pre_assert(type_code == 9))]
+	Binary {
+		#[br(temp)]
+		#[bw(calc = data.len() as u32)]
+		count: u32,
+		#[br(count = count)]
+		data: Vec<u8>,
+	},
+}
+
+impl Default for ExtensionValue {
+	fn default() -> Self {
+		Self::Int(0)
+	}
+}
+
+/// A single named value in an extension, the value type code is stored before the name
+#[binrw]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtensionItem {
+	#[br(temp)]
+	#[bw(calc = value.type_code())]
+	type_code: u8,
+	pub name: BigString,
+	#[br(args(type_code))]
+	pub value: ExtensionValue,
+}
+
+impl ExtensionValue {
+	pub fn type_code(&self) -> u8 {
+		match self {
+			ExtensionValue::Int(_) => 2,
+			ExtensionValue::Float(_) => 3,
+			ExtensionValue::Translation(_) => 5,
+			ExtensionValue::String(_) => 6,
+			ExtensionValue::Array { .. } => 7,
+			ExtensionValue::Rotation(_) => 8,
+			ExtensionValue::Binary { .. } => 9,
+		}
+	}
+}
+
+#[binrw]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExtensionData {
+	#[brw(magic = 7u8)]
+	Items {
+		name: BigString,
+		#[br(temp)]
+		#[bw(calc = items.len() as u32)]
+		count: u32,
+		#[br(count = count)]
+		items: Vec<ExtensionItem>,
+	},
+	Raw {
+		type_code: u8,
+		#[br(temp)]
+		#[bw(calc = data.len() as u32)]
+		count: u32,
+		#[br(count = count)]
+		data: Vec<u8>,
+	},
+}
+
+impl Default for ExtensionData {
+	fn default() -> Self {
+		Self::Items {
+			name: BigString::default(),
+			items: vec![],
+		}
+	}
+}
+
+#[binrw]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Extension {
+	#[br(assert("cExtension".as_bytes() == c_extension.0.as_slice()))]
+	#[bw(calc(BigString::from("cExtension".to_string())))]
+	pub c_extension: BigString,
+
+	pub block_id: u32,
+	pub version: u32,
+
+	pub data: ExtensionData,
+}
+
+impl Extension {
+	/// Look up a top-level item by name
+	pub fn get(&self, name: &str) -> Option<&ExtensionValue> {
+		match &self.data {
+			ExtensionData::Items { items, .. } => items
+				.iter()
+				.find(|item| item.name.0.as_slice() == name.as_bytes())
+				.map(|item| &item.value),
+			ExtensionData::Raw { .. } => None,
+		}
+	}
+}
+
+/// cDataListExtension, a tree of named values attached to scenegraph nodes
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataListExtension {
+	pub extension: Extension,
+}
+
+/// cBoneDataExtension
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoneDataExtension {
+	pub extension: Extension,
+
+	pub unknown1: u32,
+	pub unknown2: u32,
+	pub unknown3: f32,
+	pub rotation: Quaternion,
+}
@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use binrw::{args, binrw};
+
+use crate::{
+	common::BigString,
+	internal_file::resource_collection::{object_graph::ObjectGraphNode, ResourceBlockVersion},
+};
+
+/// A level of detail of the shape, referring to a GMND by its resource name
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ShapeItem {
+	pub unknown1: i32,
+	pub unknown2: u8,
+	#[brw(if(version != ResourceBlockVersion::V8))]
+	pub unknown3: i32,
+	#[brw(if(version != ResourceBlockVersion::V8))]
+	pub unknown4: u8,
+	pub geometric_node: BigString,
+}
+
+/// Maps a mesh group (subset) in the GMDC to the TXMT it is rendered with
+#[binrw]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ShapePart {
+	pub subset: BigString,
+	pub material: BigString,
+	pub unknown: [u8; 9],
+}
+
+#[binrw]
+#[brw(import {version: ResourceBlockVersion})]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Shape {
+	pub object_graph_node: ObjectGraphNode,
+
+	#[br(temp)]
+	#[bw(calc = lod_levels.len() as u32)]
+	#[brw(if(version != ResourceBlockVersion::V6))]
+	lod_count: u32,
+	#[br(count = lod_count)]
+	#[brw(if(version != ResourceBlockVersion::V6))]
+	pub lod_levels: Vec<u32>,
+
+	#[br(temp)]
+	#[bw(calc = items.len() as u32)]
+	item_count: u32,
+	#[br(count = item_count, args {inner: args! {version}})]
+	#[bw(args {version})]
+	pub items: Vec<ShapeItem>,
+
+	#[br(temp)]
+	#[bw(calc = parts.len() as u32)]
+	part_count: u32,
+	#[br(count = part_count)]
+	pub parts: Vec<ShapePart>,
+}
+
+impl Shape {
+	/// The material name used for the given GMDC mesh group
+	pub fn material_for(&self, subset: &str) -> Option<&BigString> {
+		self.parts
+			.iter()
+			.find(|part| part.subset.0.as_slice() == subset.as_bytes())
+			.map(|part| &part.material)
+	}
+}
@@ -2,9 +2,16 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::cmp::max;
+use std::{
+	cmp::{max, min},
+	io::{Seek, Write},
+};
 
-#[derive(Clone, Debug, Default)]
+use crate::internal_file::resource_collection::texture_resource::{
+	ImageExportFormat, TextureFormat,
+};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct DecodedTexture {
 	pub width: usize,
 	pub height: usize,
@@ -190,3 +197,64 @@ impl DecodedTexture {
 		ShrinkResult::Ok
 	}
 }
+
+impl DecodedTexture {
+	pub fn write_image<W: Write + Seek>(
+		&self,
+		format: ImageExportFormat,
+		writer: &mut W,
+	) -> Result<(), image::ImageError> {
+		image::write_buffer_with_format(
+			writer,
+			&self.data,
+			self.width as u32,
+			self.height as u32,
+			image::ExtendedColorType::Rgba8,
+			format.image_format(),
+		)
+	}
+
+	/// copy another image into this one with its top left corner at the given position,
+	/// anything that falls outside of this image is cut off
+	pub fn blit(&mut self, other: &DecodedTexture, x: usize, y: usize) {
+		let copy_width = min(other.width, self.width.saturating_sub(x));
+		for row in 0..min(other.height, self.height.saturating_sub(y)) {
+			let src = row * other.width * 4;
+			let dst = ((y + row) * self.width + x) * 4;
+			self.data[dst..dst + copy_width * 4]
+				.copy_from_slice(&other.data[src..src + copy_width * 4]);
+		}
+	}
+
+	/// pick the smallest texture format that can represent this image without visibly losing
+	/// its alpha channel
+	///
+	/// - opaque grayscale images become [Grayscale](TextureFormat::Grayscale)
+	/// - images with at most on/off transparency become [DXT1](TextureFormat::DXT1)
+	/// - alpha with only 16 evenly spaced levels becomes [DXT3](TextureFormat::DXT3),
+	///   which stores those exactly
+	/// - any other alpha becomes [DXT5](TextureFormat::DXT5)
+	/// - images smaller than a single 4x4 block are kept uncompressed
+	pub fn best_format(&self) -> TextureFormat {
+		let pixels = || self.data.chunks_exact(4);
+		let opaque = pixels().all(|p| p[3] == 0xFF);
+		let small = self.width < 4 || self.height < 4;
+		if opaque {
+			if pixels().all(|p| p[0] == p[1] && p[1] == p[2]) {
+				TextureFormat::Grayscale
+			} else if small {
+				TextureFormat::RawRGB24
+			} else {
+				TextureFormat::DXT1
+			}
+		} else if small {
+			TextureFormat::RawARGB32
+		} else if pixels().all(|p| p[3] == 0 || p[3] == 0xFF) {
+			TextureFormat::DXT1
+		} else if pixels().all(|p| p[3] % 17 == 0) {
+			TextureFormat::DXT3
+		} else {
+			TextureFormat::DXT5
+		}
+	}
+}
@@ -3,11 +3,13 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 pub mod decoded_texture;
+pub mod resample;
 
 use std::{
 	cmp::max,
+	collections::HashMap,
 	fmt::Debug,
-	io::{Cursor, Read, Write},
+	io::{BufRead, Cursor, Read, Seek, Write},
 };
 
 use binrw::{args, binrw, BinResult, Error};
@@ -20,7 +22,11 @@ use thiserror::Error;
 use crate::{
 	common::BigString,
 	internal_file::resource_collection::{
-		texture_resource::decoded_texture::{ShrinkDirection, ShrinkResult},
+		level_information::{lifo_key, LevelInformation},
+		texture_resource::{
+			decoded_texture::{ShrinkDirection, ShrinkResult},
+			resample::ResampleOptions,
+		},
 		FileName, ResourceBlockVersion,
 	},
 };
@@ -357,6 +363,71 @@ pub enum DdsError {
 	LifoExport,
 }
 
+#[derive(Debug, Error)]
+pub enum ImageImportError {
+	#[error(transparent)]
+	Image(#[from] image::ImageError),
+	#[error(transparent)]
+	IoError(#[from] std::io::Error),
+	#[error(transparent)]
+	BinRW(#[from] binrw::Error),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ImageImportOptions {
+	/// the format to store the texture in, if not set it is picked from the image contents using
+	/// [DecodedTexture::best_format]
+	pub format: Option<TextureFormat>,
+	/// generate the full mipmap chain
+	pub generate_mip_levels: bool,
+	/// scale the image down, keeping its aspect ratio, so that neither side is larger than this
+	pub max_size: Option<usize>,
+	/// filter used for scaling the image and generating mipmaps
+	pub resample: ResampleOptions,
+}
+
+#[derive(Debug, Error)]
+pub enum ImageExportError {
+	#[error(transparent)]
+	Image(#[from] image::ImageError),
+	#[error(transparent)]
+	BinRW(#[from] binrw::Error),
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Sequence)]
+pub enum ImageExportFormat {
+	#[default]
+	Png,
+	Tga,
+}
+
+impl ImageExportFormat {
+	pub fn extension(&self) -> &'static str {
+		match self {
+			ImageExportFormat::Png => "png",
+			ImageExportFormat::Tga => "tga",
+		}
+	}
+
+	fn image_format(&self) -> image::ImageFormat {
+		match self {
+			ImageExportFormat::Png => image::ImageFormat::Png,
+			ImageExportFormat::Tga => image::ImageFormat::Tga,
+		}
+	}
+}
+
+impl Default for ImageImportOptions {
+	fn default() -> Self {
+		Self {
+			format: None,
+			generate_mip_levels: true,
+			max_size: None,
+			resample: ResampleOptions::default(),
+		}
+	}
+}
+
 impl TextureResource {
 	/// guess the best matching size for the given mipmap level
 	pub fn mip_size(&self, mip_level: usize) -> (usize, usize) {
@@ -536,9 +607,130 @@ impl TextureResource {
 		Ok(())
 	}
 
+	/// import a PNG, TGA or JPEG image
+	///
+	/// the file format is guessed from the contents of the file
+	pub fn import_image<R: BufRead + Seek>(
+		reader: R,
+		options: &ImageImportOptions,
+	) -> Result<Self, ImageImportError> {
+		let image = image::ImageReader::new(reader)
+			.with_guessed_format()?
+			.decode()?;
+		let decoded = DecodedTexture {
+			width: image.width() as usize,
+			height: image.height() as usize,
+			data: image.into_rgba8().into_raw(),
+		};
+		Ok(Self::from_decoded(decoded, options)?)
+	}
+
+	/// export a single mip level as a PNG or TGA image
+	///
+	/// texture and mip index work the same as in [decompress](Self::decompress)
+	pub fn export_image<W: Write + Seek>(
+		&self,
+		texture_index: usize,
+		mip_index: usize,
+		format: ImageExportFormat,
+		writer: &mut W,
+	) -> Result<(), ImageExportError> {
+		self.decompress(texture_index, mip_index)?
+			.write_image(format, writer)?;
+		Ok(())
+	}
+
+	/// tile every mip level next to each other, from largest to smallest,
+	/// with one row for every texture
+	pub fn contact_sheet(&self) -> BinResult<DecodedTexture> {
+		let rows = (0..self.textures.len())
+			.map(|texture_index| {
+				(0..self.textures[texture_index].entries.len())
+					.rev()
+					.map(|mip_index| self.decompress(texture_index, mip_index))
+					.collect::<BinResult<Vec<_>>>()
+			})
+			.collect::<BinResult<Vec<_>>>()?;
+
+		let row_width = |row: &Vec<DecodedTexture>| row.iter().map(|mip| mip.width).sum::<usize>();
+		let row_height =
+			|row: &Vec<DecodedTexture>| row.iter().map(|mip| mip.height).max().unwrap_or(0);
+		let width = rows.iter().map(row_width).max().unwrap_or(0);
+		let height = rows.iter().map(row_height).sum();
+
+		let mut sheet = DecodedTexture {
+			width,
+			height,
+			data: vec![0; width * height * 4],
+		};
+		let mut y = 0;
+		for row in &rows {
+			let mut x = 0;
+			for mip in row {
+				sheet.blit(mip, x, y);
+				x += mip.width;
+			}
+			y += row_height(row);
+		}
+		Ok(sheet)
+	}
+
+	/// export the [contact_sheet](Self::contact_sheet) as a PNG or TGA image
+	pub fn export_contact_sheet<W: Write + Seek>(
+		&self,
+		format: ImageExportFormat,
+		writer: &mut W,
+	) -> Result<(), ImageExportError> {
+		self.contact_sheet()?.write_image(format, writer)?;
+		Ok(())
+	}
+
+	/// create a new single texture resource from raw image data
+	pub fn from_decoded(
+		mut decoded: DecodedTexture,
+		options: &ImageImportOptions,
+	) -> BinResult<Self> {
+		// pick the format before scaling, which turns on/off transparency into gradients
+		let format = options.format.unwrap_or_else(|| decoded.best_format());
+
+		if let Some(max_size) = options.max_size {
+			let max_size = max(max_size, 1);
+			let largest = max(decoded.width, decoded.height);
+			if largest > max_size {
+				let width = max(decoded.width * max_size / largest, 1);
+				let height = max(decoded.height * max_size / largest, 1);
+				decoded = decoded.resize(width, height, &options.resample);
+			}
+		}
+
+		// mipmaps are generated from the uncompressed image, compression happens last
+		let mut texture = Self {
+			width: decoded.width as u32,
+			height: decoded.height as u32,
+			format: TextureFormat::RawARGB32,
+			textures: vec![TextureResourceTexture {
+				entries: vec![TextureResourceData::Embedded(
+					EmbeddedTextureResourceMipLevel::new(
+						decoded.width,
+						decoded.height,
+						TextureFormat::RawARGB32,
+						&decoded.data,
+					),
+				)],
+				creator_id: 0xFFFFFFFF,
+			}],
+			..Default::default()
+		};
+		if options.generate_mip_levels {
+			texture.add_max_mip_levels(&options.resample);
+		}
+		texture.recompress_with_format(format)
+	}
+
 	/// decompress a single texture given by its texture and mip index
-	/// the mip index goes from 0 (largest) to mip_levels (smallest)
-	/// if a mip level is represented as a LIFOStream then this function will return None
+	/// the mip index goes from 0 (smallest) to mip_levels - 1 (largest)
+	/// if a mip level is represented as a LIFO file then this function will return an error,
+	/// use [embed_lifo](Self::embed_lifo) first to resolve it
 	pub fn decompress(&self, texture_index: usize, mip_index: usize) -> BinResult<DecodedTexture> {
 		let TextureResourceData::Embedded(ref data) =
 			self.textures[texture_index].entries[mip_index]
@@ -628,6 +820,26 @@ impl TextureResource {
 		self.format
 	}
 
+	/// true if the texture stores values rather than colors, like bump, normal and transparency maps
+	///
+	/// These should be resampled without [gamma correction](ResampleOptions::gamma_correct),
+	/// which is only correct for sRGB colors.
+	pub fn stores_linear_data(&self) -> bool {
+		let name = self.file_name.name.0.to_ascii_lowercase();
+		matches!(self.format, TextureFormat::Alpha | TextureFormat::Grayscale)
+			|| [b"normal".as_slice(), b"bump"]
+				.iter()
+				.any(|word| name.windows(word.len()).any(|w| w == *word))
+	}
+
+	/// the resample options that fit this texture
+	pub fn default_resample_options(&self) -> ResampleOptions {
+		ResampleOptions {
+			gamma_correct: !self.stores_linear_data(),
+			..ResampleOptions::default()
+		}
+	}
+
 	/// remove n mipmap levels, starting from the largest first, effectively decreasing the size of the texture
 	/// the number of mipmap levels will be reduced by mip_levels
 	pub fn remove_largest_mip_levels(&mut self, mip_levels: usize) {
@@ -654,52 +866,32 @@ impl TextureResource {
 	}
 
 	/// attempt to do a certain number of mipmap additions in one go, returns the actual amount added
-	pub fn add_extra_mip_levels(&mut self, mip_levels: usize, preserve_alpha: Option<u8>) -> usize {
+	///
+	/// every new level is resampled directly from the largest mipmap, so any size can be used
+	pub fn add_extra_mip_levels(&mut self, mip_levels: usize, options: &ResampleOptions) -> usize {
 		assert!(mip_levels >= 1);
 		let cur_mip_levels = self.mip_levels();
-		let (smallest_width, smallest_height) = self.mip_size(cur_mip_levels - 1);
-		let cur_smallest_textures = self
-			.textures
-			.iter()
-			.map(|tex| {
-				let smallest_mipmap = tex.entries.first().cloned();
-				match smallest_mipmap {
-					Some(TextureResourceData::Embedded(e)) => Ok(DecodedTexture {
-						data: e
-							.decompress(smallest_width, smallest_height, self.format)
-							.map_err(|_| ())?,
-						width: smallest_width,
-						height: smallest_height,
-					}),
-					_ => Err(()),
-				}
-			})
-			.collect::<Result<Vec<_>, _>>();
-		let Ok(mut cur_textures) = cur_smallest_textures else {
+		if cur_mip_levels == 0 {
+			return 0;
+		}
+		let mip_levels = mip_levels.min(self.max_mip_levels().saturating_sub(cur_mip_levels));
+		if mip_levels == 0 || self.any_lifo() {
+			return 0;
+		}
+		let Ok(largest_textures) = (0..self.textures.len())
+			.map(|texture| self.decompress(texture, self.textures[texture].entries.len() - 1))
+			.collect::<BinResult<Vec<_>>>()
+		else {
 			return 0;
 		};
 
 		for i in 0..mip_levels {
-			if cur_textures
-				.iter_mut()
-				.try_for_each(|tex| {
-					if matches!(
-						tex.shrink(preserve_alpha, ShrinkDirection::Both),
-						ShrinkResult::Ok
-					) {
-						Ok(())
-					} else {
-						Err(())
-					}
-				})
-				.is_err()
-			{
-				return i;
-			}
+			let (width, height) = self.mip_size(cur_mip_levels + i);
 			self.textures
 				.iter_mut()
-				.zip(cur_textures.iter())
-				.for_each(|(tex, new)| {
+				.zip(largest_textures.iter())
+				.for_each(|(tex, largest)| {
+					let new = largest.resize(width, height, options);
 					tex.entries.insert(
 						0,
 						TextureResourceData::Embedded(EmbeddedTextureResourceMipLevel::new(
@@ -714,12 +906,12 @@ impl TextureResource {
 		mip_levels
 	}
 
-	pub fn add_max_mip_levels(&mut self, preserve_alpha: Option<u8>) {
+	pub fn add_max_mip_levels(&mut self, options: &ResampleOptions) {
 		let max = self.max_mip_levels();
 		let cur = self.mip_levels();
-		let extra = max - cur;
+		let extra = max.saturating_sub(cur);
 		if extra > 0 {
-			self.add_extra_mip_levels(extra, preserve_alpha);
+			self.add_extra_mip_levels(extra, options);
 		}
 	}
 
@@ -727,6 +919,7 @@ impl TextureResource {
 		self.textures.first().map(|t| t.entries.len()).unwrap_or(0)
 	}
 
+	/// the length of the full mipmap chain, down to a single pixel
 	pub fn max_mip_levels(&self) -> usize {
 		let mut width = self.width;
 		let mut height = self.height;
@@ -735,14 +928,6 @@ impl TextureResource {
 		}
 		let mut i = 1;
 		while (width > 1) || (height > 1) {
-			if DecodedTexture::can_shrink_dimensions(
-				width as usize,
-				height as usize,
-				ShrinkDirection::Both,
-			) == ShrinkResult::Unable
-			{
-				return 1;
-			}
 			i += 1;
 			width = max(width >> 1, 1);
 			height = max(height >> 1, 1);
@@ -750,7 +935,44 @@ impl TextureResource {
 		i
 	}
 
-	fn any_lifo(&self) -> bool {
+	/// resample all textures to a new size, regenerating the same number of mipmap levels
+	///
+	/// returns an error if the texture contains LIFO references
+	pub fn resize(
+		&mut self,
+		width: usize,
+		height: usize,
+		options: &ResampleOptions,
+	) -> BinResult<()> {
+		let width = width.max(1);
+		let height = height.max(1);
+		let mip_levels = self.mip_levels();
+		if mip_levels == 0 {
+			return Ok(());
+		}
+		let largest_textures = (0..self.textures.len())
+			.map(|texture| self.decompress(texture, self.textures[texture].entries.len() - 1))
+			.collect::<BinResult<Vec<_>>>()?;
+
+		self.width = width as u32;
+		self.height = height as u32;
+		let format = self.format;
+		self.textures
+			.iter_mut()
+			.zip(largest_textures)
+			.for_each(|(tex, largest)| {
+				let new = largest.resize(width, height, options);
+				tex.entries = vec![TextureResourceData::Embedded(
+					EmbeddedTextureResourceMipLevel::new(width, height, format, &new.data),
+				)];
+			});
+		if mip_levels > 1 {
+			self.add_extra_mip_levels(mip_levels - 1, options);
+		}
+		Ok(())
+	}
+
+	pub fn any_lifo(&self) -> bool {
 		self.textures.iter().any(|t| {
 			t.entries
 				.iter()
@@ -758,65 +980,334 @@ impl TextureResource {
 		})
 	}
 
+	/// replace all mipmap levels that reference a LIFO file with the data of that LIFO,
+	/// after which the texture can be decompressed, shrunk and converted like any embedded texture
+	///
+	/// `lifos` should be indexed by [lifo_key], as returned by
+	/// [find_level_information](crate::internal_file::resource_collection::level_information::find_level_information)
+	///
+	/// returns false if any of the references could not be resolved
+	pub fn embed_lifo(&mut self, lifos: &HashMap<Vec<u8>, LevelInformation>) -> bool {
+		let mut all_resolved = true;
+		for entry in self.textures.iter_mut().flat_map(|t| t.entries.iter_mut()) {
+			if let TextureResourceData::LIFOFile { file_name } = entry {
+				match lifos.get(&lifo_key(&file_name.0)) {
+					Some(lifo) => {
+						*entry = TextureResourceData::Embedded(EmbeddedTextureResourceMipLevel {
+							data: lifo.data.clone(),
+						});
+					}
+					None => all_resolved = false,
+				}
+			}
+		}
+		all_resolved
+	}
+
 	pub fn can_shrink(&self, shrink_direction: ShrinkDirection) -> bool {
-		let mut results = self.textures.iter().map(|t| {
-			let (mip_width, mip_height) = self.mip_size(t.entries.len());
-			DecodedTexture::can_shrink_dimensions(mip_width, mip_height, shrink_direction)
-		});
-		!results.any(|r| r == ShrinkResult::Unable)
-			&& DecodedTexture::can_shrink_dimensions(
-				self.width as usize,
-				self.height as usize,
-				shrink_direction,
-			) != ShrinkResult::Small
+		DecodedTexture::can_shrink_dimensions(
+			self.width as usize,
+			self.height as usize,
+			shrink_direction,
+		) != ShrinkResult::Small
 			&& !self.any_lifo()
 	}
 
+	/// halve the texture in the given direction, odd sizes are rounded down
+	///
+	/// returns true if a mipmap level had to be removed
 	pub fn shrink(
 		&mut self,
-		preserve_alpha: Option<u8>,
+		options: &ResampleOptions,
 		shrink_direction: ShrinkDirection,
 	) -> BinResult<bool> {
 		if !self.can_shrink(shrink_direction) {
 			return Ok(false);
 		}
 
-		let format = self.format;
-		for texture in 0..self.textures.len() {
-			let num_mip = self.textures[texture].entries.len();
-			for i in 0..num_mip {
-				let mip_level = num_mip - i - 1;
-				let (mip_width, mip_height) = self.mip_size(mip_level);
-				let texture = &mut self.textures[texture];
-				if let TextureResourceData::Embedded(mip) = &mut texture.entries[i] {
-					let mut decoded = DecodedTexture {
-						data: mip.decompress(mip_width, mip_height, format)?,
-						width: mip_width,
-						height: mip_height,
-					};
-					decoded.shrink(preserve_alpha, shrink_direction);
-					mip.compress(decoded.width, decoded.height, format, &decoded.data);
-				}
-			}
-		}
-
-		let old_width = self.width;
-		let old_height = self.height;
+		let mut width = self.width as usize;
+		let mut height = self.height as usize;
 		if shrink_direction != ShrinkDirection::Vertical {
-			self.width /= 2;
+			width = max(width / 2, 1);
 		}
 		if shrink_direction != ShrinkDirection::Horizontal {
-			self.height /= 2;
+			height = max(height / 2, 1);
 		}
 
-		let mut mip_removed = false;
-		let max_mip = self.max_mip_levels();
-		for texture in &mut self.textures {
-			if texture.entries.len() > max_mip {
-				texture.entries.remove(0);
-				mip_removed = true;
+		let mip_levels = self.mip_levels();
+		self.resize(width, height, options)?;
+		Ok(self.mip_levels() < mip_levels)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{prelude::*, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+
+	fn format() -> impl Strategy<Value = TextureFormat> {
+		prop_oneof![
+			Just(TextureFormat::RawARGB32),
+			Just(TextureFormat::RawRGB24),
+			Just(TextureFormat::Alpha),
+			Just(TextureFormat::DXT1),
+			Just(TextureFormat::DXT3),
+			Just(TextureFormat::Grayscale),
+			Just(TextureFormat::DXT5),
+		]
+	}
+
+	#[proptest]
+	fn only_color_textures_are_gamma_corrected(
+		#[strategy("[a-z]{0,8}")] name: String,
+		#[strategy(prop_oneof![Just(""), Just("_normal"), Just("-Bump")])] suffix: &'static str,
+		#[strategy(format())] format: TextureFormat,
+	) {
+		let name = format!("{name}{suffix}_txtr");
+		let texture = TextureResource {
+			file_name: FileName {
+				name: BigString::from(name.clone()),
+				..FileName::default()
+			},
+			format,
+			..TextureResource::default()
+		};
+		let lower = name.to_lowercase();
+		let linear = matches!(format, TextureFormat::Alpha | TextureFormat::Grayscale)
+			|| lower.contains("normal")
+			|| lower.contains("bump");
+		prop_assert_eq!(texture.stores_linear_data(), linear);
+		prop_assert_eq!(texture.default_resample_options().gamma_correct, !linear);
+	}
+
+	/// an image with random colors where the alpha of every pixel is picked by `alpha`,
+	/// the first pixel is pure red so the image is never grayscale
+	fn image(
+		width: usize,
+		height: usize,
+		colors: &[[u8; 3]],
+		alpha: impl Fn(usize) -> u8,
+	) -> DecodedTexture {
+		let data = (0..width * height)
+			.flat_map(|i| {
+				let [r, g, b] = if i == 0 {
+					[0xFF, 0, 0]
+				} else {
+					colors[i % colors.len()]
+				};
+				[r, g, b, alpha(i)]
+			})
+			.collect();
+		DecodedTexture {
+			width,
+			height,
+			data,
+		}
+	}
+
+	fn import_png(decoded: &DecodedTexture) -> Result<TextureResource, TestCaseError> {
+		let mut png = Cursor::new(vec![]);
+		image::RgbaImage::from_raw(
+			decoded.width as u32,
+			decoded.height as u32,
+			decoded.data.clone(),
+		)
+		.unwrap()
+		.write_to(&mut png, image::ImageFormat::Png)
+		.map_err(|e| TestCaseError::fail(e.to_string()))?;
+		png.set_position(0);
+		TextureResource::import_image(png, &ImageImportOptions::default())
+			.map_err(|e| TestCaseError::fail(e.to_string()))
+	}
+
+	/// imports the image and checks that it keeps its size and gets the expected format
+	fn import_format(
+		decoded: DecodedTexture,
+		expected: TextureFormat,
+	) -> Result<(), TestCaseError> {
+		prop_assert_eq!(decoded.best_format(), expected);
+
+		let texture = import_png(&decoded)?;
+		prop_assert_eq!(texture.get_format(), expected);
+		prop_assert_eq!(
+			(texture.width as usize, texture.height as usize),
+			(decoded.width, decoded.height)
+		);
+		prop_assert_eq!(texture.mip_levels(), texture.max_mip_levels());
+		let largest = texture.decompress(0, texture.mip_levels() - 1)?;
+		prop_assert_eq!(
+			(largest.width, largest.height),
+			(decoded.width, decoded.height)
+		);
+		if matches!(
+			expected,
+			TextureFormat::RawARGB32 | TextureFormat::Grayscale
+		) {
+			prop_assert_eq!(largest.data, decoded.data);
+		}
+		Ok(())
+	}
+
+	#[proptest]
+	fn import_opaque(
+		#[strategy(1usize..20)] width: usize,
+		#[strategy(1usize..20)] height: usize,
+		#[strategy(proptest::collection::vec(any::<[u8; 3]>(), 1..8))] colors: Vec<[u8; 3]>,
+	) {
+		let expected = if width < 4 || height < 4 {
+			TextureFormat::RawRGB24
+		} else {
+			TextureFormat::DXT1
+		};
+		import_format(image(width, height, &colors, |_| 0xFF), expected)?;
+	}
+
+	/// an uncompressed texture with its full mipmap chain, so every mip keeps its exact colours
+	fn mipmapped(decoded: DecodedTexture) -> Result<TextureResource, TestCaseError> {
+		TextureResource::from_decoded(
+			decoded,
+			&ImageImportOptions {
+				format: Some(TextureFormat::RawARGB32),
+				..ImageImportOptions::default()
+			},
+		)
+		.map_err(|e| TestCaseError::fail(e.to_string()))
+	}
+
+	#[proptest]
+	fn export_mip_as_png(
+		#[strategy(1usize..20)] width: usize,
+		#[strategy(1usize..20)] height: usize,
+		#[strategy(proptest::collection::vec(any::<[u8; 3]>(), 1..8))] colors: Vec<[u8; 3]>,
+		#[strategy(proptest::collection::vec(any::<u8>(), 1..8))] alphas: Vec<u8>,
+		mip: usize,
+	) {
+		let texture = mipmapped(image(width, height, &colors, |i| alphas[i % alphas.len()]))?;
+		let mip_index = mip % texture.mip_levels();
+
+		let mut png = Cursor::new(vec![]);
+		texture
+			.export_image(0, mip_index, ImageExportFormat::Png, &mut png)
+			.map_err(|e| TestCaseError::fail(e.to_string()))?;
+		let exported = image::load_from_memory_with_format(png.get_ref(), image::ImageFormat::Png)
+			.map_err(|e| TestCaseError::fail(e.to_string()))?
+			.into_rgba8();
+
+		let expected = texture.decompress(0, mip_index)?;
+		prop_assert_eq!(
+			(exported.width() as usize, exported.height() as usize),
+			(expected.width, expected.height)
+		);
+		prop_assert_eq!(exported.into_raw(), expected.data);
+	}
+
+	#[proptest]
+	fn contact_sheet_places_mips_side_by_side(
+		#[strategy(1usize..20)] width: usize,
+		#[strategy(1usize..20)] height: usize,
+		#[strategy(proptest::collection::vec(any::<[u8; 3]>(), 1..8))] colors: Vec<[u8; 3]>,
+	) {
+		let texture = mipmapped(image(width, height, &colors, |_| 0xFF))?;
+		let mips = (0..texture.mip_levels())
+			.rev()
+			.map(|mip_index| texture.decompress(0, mip_index))
+			.collect::<BinResult<Vec<_>>>()?;
+
+		let sheet = texture.contact_sheet()?;
+		prop_assert_eq!(sheet.width, mips.iter().map(|mip| mip.width).sum::<usize>());
+		prop_assert_eq!(sheet.height, height);
+		prop_assert_eq!(sheet.data.len(), sheet.width * sheet.height * 4);
+
+		let mut x = 0;
+		for mip in &mips {
+			for row in 0..mip.height {
+				let sheet_start = (row * sheet.width + x) * 4;
+				prop_assert_eq!(
+					&sheet.data[sheet_start..sheet_start + mip.width * 4],
+					&mip.data[row * mip.width * 4..(row + 1) * mip.width * 4]
+				);
+			}
+			// everything below a mip is left empty
+			for row in mip.height..sheet.height {
+				let sheet_start = (row * sheet.width + x) * 4;
+				prop_assert!(sheet.data[sheet_start..sheet_start + mip.width * 4]
+					.iter()
+					.all(|&c| c == 0));
 			}
+			x += mip.width;
 		}
-		Ok(mip_removed)
+	}
+
+	#[proptest]
+	fn import_grayscale(
+		#[strategy(1usize..20)] width: usize,
+		#[strategy(1usize..20)] height: usize,
+		#[strategy(proptest::collection::vec(any::<u8>(), 1..8))] grays: Vec<u8>,
+	) {
+		let data = (0..width * height)
+			.flat_map(|i| {
+				let gray = grays[i % grays.len()];
+				[gray, gray, gray, 0xFF]
+			})
+			.collect();
+		let decoded = DecodedTexture {
+			width,
+			height,
+			data,
+		};
+		import_format(decoded, TextureFormat::Grayscale)?;
+	}
+
+	#[proptest]
+	fn import_transparent(
+		#[strategy(4usize..20)] width: usize,
+		#[strategy(4usize..20)] height: usize,
+		#[strategy(proptest::collection::vec(any::<[u8; 3]>(), 1..8))] colors: Vec<[u8; 3]>,
+		#[strategy(proptest::collection::vec(any::<u8>(), 1..8))] alphas: Vec<u8>,
+	) {
+		// the second pixel makes sure the image uses the kind of transparency being tested
+		let alpha = |first: u8, map: fn(u8) -> u8| {
+			let alphas = alphas.clone();
+			move |i: usize| {
+				if i == 1 {
+					first
+				} else {
+					map(alphas[i % alphas.len()])
+				}
+			}
+		};
+		import_format(
+			image(
+				width,
+				height,
+				&colors,
+				alpha(0, |a| if a < 0x80 { 0 } else { 0xFF }),
+			),
+			TextureFormat::DXT1,
+		)?;
+		import_format(
+			image(width, height, &colors, alpha(0x11, |a| a / 17 * 17)),
+			TextureFormat::DXT3,
+		)?;
+		import_format(
+			image(width, height, &colors, alpha(0x80, |a| a)),
+			TextureFormat::DXT5,
+		)?;
+	}
+
+	#[proptest]
+	fn import_small_transparent(
+		#[strategy(1usize..4)] width: usize,
+		#[strategy(1usize..20)] height: usize,
+		#[strategy(proptest::collection::vec(any::<[u8; 3]>(), 1..8))] colors: Vec<[u8; 3]>,
+		alpha: u8,
+	) {
+		let alpha = if alpha == 0xFF { 0 } else { alpha };
+		import_format(
+			image(width, height, &colors, |_| alpha),
+			TextureFormat::RawARGB32,
+		)?;
 	}
 }
@@ -0,0 +1,419 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{f32::consts::PI, sync::OnceLock};
+
+use enum_iterator::Sequence;
+
+use crate::internal_file::resource_collection::texture_resource::decoded_texture::DecodedTexture;
+
+/// the reconstruction filter used when resizing a texture
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Sequence)]
+pub enum ResampleFilter {
+	/// averages all pixels that fall within the target pixel, the same as the old mipmap generator
+	#[default]
+	Box,
+	/// linear falloff, slightly softer than box but without blocky artifacts when upscaling
+	Triangle,
+	/// windowed sinc with three lobes, sharp but may ring around high contrast edges
+	Lanczos,
+	/// Kaiser-windowed sinc, sharper than triangle with less ringing than Lanczos
+	Kaiser,
+}
+
+impl ResampleFilter {
+	/// radius of the filter kernel in source pixels when not downscaling
+	fn support(self) -> f32 {
+		match self {
+			ResampleFilter::Box => 0.5,
+			ResampleFilter::Triangle => 1.0,
+			ResampleFilter::Lanczos | ResampleFilter::Kaiser => 3.0,
+		}
+	}
+
+	fn weight(self, x: f32) -> f32 {
+		let x = x.abs();
+		match self {
+			ResampleFilter::Box => {
+				if x <= 0.5 {
+					1.0
+				} else {
+					0.0
+				}
+			}
+			ResampleFilter::Triangle => (1.0 - x).max(0.0),
+			ResampleFilter::Lanczos => {
+				if x < 3.0 {
+					sinc(x) * sinc(x / 3.0)
+				} else {
+					0.0
+				}
+			}
+			ResampleFilter::Kaiser => {
+				const ALPHA: f32 = 4.0;
+				let t = x / 3.0;
+				if t < 1.0 {
+					sinc(x) * bessel_i0(ALPHA * (1.0 - t * t).sqrt()) / bessel_i0(ALPHA)
+				} else {
+					0.0
+				}
+			}
+		}
+	}
+}
+
+fn sinc(x: f32) -> f32 {
+	if x.abs() < 1e-6 {
+		1.0
+	} else {
+		let x = x * PI;
+		x.sin() / x
+	}
+}
+
+/// zeroth order modified Bessel function of the first kind, used by the Kaiser window
+fn bessel_i0(x: f32) -> f32 {
+	let mut sum = 1.0;
+	let mut term = 1.0;
+	let half_x = x / 2.0;
+	for k in 1..32 {
+		term *= half_x / k as f32;
+		let term_sq = term * term;
+		sum += term_sq;
+		if term_sq < sum * 1e-8 {
+			break;
+		}
+	}
+	sum
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ResampleOptions {
+	pub filter: ResampleFilter,
+	/// average colours in linear light instead of directly on the sRGB values,
+	/// which keeps bright details from darkening in smaller mipmaps
+	pub gamma_correct: bool,
+	/// scale the alpha of the result so that the same fraction of pixels passes an alpha test
+	/// against this reference value as in the source image;
+	/// keeps alpha-tested foliage and hair from thinning out in smaller mipmaps
+	pub alpha_coverage: Option<u8>,
+}
+
+impl Default for ResampleOptions {
+	fn default() -> Self {
+		Self {
+			filter: ResampleFilter::Box,
+			gamma_correct: true,
+			alpha_coverage: None,
+		}
+	}
+}
+
+/// premultiplied rgb, alpha, and straight rgb as a fallback for fully transparent areas
+const CHANNELS: usize = 7;
+
+struct Contribution {
+	start: usize,
+	weights: Vec<f32>,
+}
+
+fn contributions(src_size: usize, dst_size: usize, filter: ResampleFilter) -> Vec<Contribution> {
+	let scale = src_size as f32 / dst_size as f32;
+	// widen the filter when downscaling so every source pixel contributes
+	let filter_scale = scale.max(1.0);
+	let support = filter.support() * filter_scale;
+	(0..dst_size)
+		.map(|i| {
+			let center = (i as f32 + 0.5) * scale;
+			let start = (center - support).floor().max(0.0) as usize;
+			let end = ((center + support).ceil() as usize).clamp(start + 1, src_size);
+			let mut weights: Vec<f32> = (start..end)
+				.map(|j| filter.weight((j as f32 + 0.5 - center) / filter_scale))
+				.collect();
+			let sum: f32 = weights.iter().sum();
+			if sum.abs() > f32::EPSILON {
+				weights.iter_mut().for_each(|w| *w /= sum);
+			} else {
+				// fall back to nearest neighbour
+				weights.iter_mut().for_each(|w| *w = 0.0);
+				let nearest = (center as usize).clamp(start, end - 1);
+				weights[nearest - start] = 1.0;
+			}
+			Contribution { start, weights }
+		})
+		.collect()
+}
+
+fn srgb_to_linear_table() -> &'static [f32; 256] {
+	static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+	TABLE.get_or_init(|| {
+		std::array::from_fn(|i| {
+			let c = i as f32 / 255.0;
+			if c <= 0.04045 {
+				c / 12.92
+			} else {
+				((c + 0.055) / 1.055).powf(2.4)
+			}
+		})
+	})
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+	if c <= 0.0031308 {
+		c * 12.92
+	} else {
+		1.055 * c.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+fn to_u8(c: f32) -> u8 {
+	(c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// fraction of the alpha values that pass an alpha test against the reference value
+fn coverage(alpha: impl Iterator<Item = f32>, reference: f32) -> f32 {
+	let (passed, total) = alpha.fold((0usize, 0usize), |(passed, total), a| {
+		(passed + (a > reference) as usize, total + 1)
+	});
+	if total == 0 {
+		0.0
+	} else {
+		passed as f32 / total as f32
+	}
+}
+
+impl DecodedTexture {
+	/// resample the image to an arbitrary size, both up and down
+	///
+	/// the width and height are clamped to at least one pixel
+	pub fn resize(&self, width: usize, height: usize, options: &ResampleOptions) -> DecodedTexture {
+		let width = width.max(1);
+		let height = height.max(1);
+		if self.width == 0 || self.height == 0 {
+			return DecodedTexture {
+				width,
+				height,
+				data: vec![0; width * height * 4],
+			};
+		}
+
+		let to_linear = srgb_to_linear_table();
+		let decode = |c: u8| {
+			if options.gamma_correct {
+				to_linear[c as usize]
+			} else {
+				c as f32 / 255.0
+			}
+		};
+
+		let source: Vec<f32> = self
+			.data
+			.chunks_exact(4)
+			.flat_map(|p| {
+				let [r, g, b] = [decode(p[0]), decode(p[1]), decode(p[2])];
+				let a = p[3] as f32 / 255.0;
+				[r * a, g * a, b * a, a, r, g, b]
+			})
+			.collect();
+
+		// horizontal pass
+		let horizontal = contributions(self.width, width, options.filter);
+		let mut intermediate = vec![0.0f32; width * self.height * CHANNELS];
+		for y in 0..self.height {
+			let src_row = &source[y * self.width * CHANNELS..(y + 1) * self.width * CHANNELS];
+			let dst_row = &mut intermediate[y * width * CHANNELS..(y + 1) * width * CHANNELS];
+			for (x, contribution) in horizontal.iter().enumerate() {
+				let dst = &mut dst_row[x * CHANNELS..(x + 1) * CHANNELS];
+				for (i, weight) in contribution.weights.iter().enumerate() {
+					let src_i = (contribution.start + i) * CHANNELS;
+					dst.iter_mut()
+						.zip(&src_row[src_i..src_i + CHANNELS])
+						.for_each(|(d, s)| *d += s * weight);
+				}
+			}
+		}
+
+		// vertical pass
+		let vertical = contributions(self.height, height, options.filter);
+		let mut resampled = vec![0.0f32; width * height * CHANNELS];
+		for (y, contribution) in vertical.iter().enumerate() {
+			let dst_row = &mut resampled[y * width * CHANNELS..(y + 1) * width * CHANNELS];
+			for (i, weight) in contribution.weights.iter().enumerate() {
+				let src_y = contribution.start + i;
+				let src_row =
+					&intermediate[src_y * width * CHANNELS..(src_y + 1) * width * CHANNELS];
+				dst_row
+					.iter_mut()
+					.zip(src_row)
+					.for_each(|(d, s)| *d += s * weight);
+			}
+		}
+
+		let mut alpha_scale = 1.0;
+		if let Some(reference) = options.alpha_coverage {
+			let reference = reference as f32 / 255.0;
+			let target = coverage(
+				self.data.chunks_exact(4).map(|p| p[3] as f32 / 255.0),
+				reference,
+			);
+			// measured on the rounded alpha that ends up in the image,
+			// otherwise values just above the reference can round down onto it
+			let scaled_coverage = |scale: f32| {
+				coverage(
+					resampled
+						.chunks_exact(CHANNELS)
+						.map(|p| to_u8(p[3].clamp(0.0, 1.0) * scale) as f32 / 255.0),
+					reference,
+				)
+			};
+			// coverage only grows with the scale, so a binary search finds the best fit
+			let current = scaled_coverage(1.0);
+			if current < target {
+				let (mut low, mut high) = (1.0f32, 64.0f32);
+				for _ in 0..16 {
+					let mid = (low + high) / 2.0;
+					if scaled_coverage(mid) < target {
+						low = mid;
+					} else {
+						high = mid;
+					}
+				}
+				alpha_scale = high;
+			} else if current > target {
+				let (mut low, mut high) = (0.0f32, 1.0f32);
+				for _ in 0..16 {
+					let mid = (low + high) / 2.0;
+					if scaled_coverage(mid) > target {
+						high = mid;
+					} else {
+						low = mid;
+					}
+				}
+				alpha_scale = low;
+			}
+		}
+
+		let encode = |c: f32| {
+			if options.gamma_correct {
+				to_u8(linear_to_srgb(c.clamp(0.0, 1.0)))
+			} else {
+				to_u8(c)
+			}
+		};
+		let data = resampled
+			.chunks_exact(CHANNELS)
+			.flat_map(|p| {
+				let a = p[3].clamp(0.0, 1.0);
+				let rgb = if a > 1.0 / 512.0 {
+					[p[0] / a, p[1] / a, p[2] / a]
+				} else {
+					[p[4], p[5], p[6]]
+				};
+				[
+					encode(rgb[0]),
+					encode(rgb[1]),
+					encode(rgb[2]),
+					to_u8(a * alpha_scale),
+				]
+			})
+			.collect();
+
+		DecodedTexture {
+			width,
+			height,
+			data,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use proptest::{prelude::*, prop_assert, prop_assert_eq};
+	use test_strategy::proptest;
+
+	use super::*;
+
+	#[proptest]
+	fn resize_solid_color_keeps_color(
+		#[strategy(1usize..64)] width: usize,
+		#[strategy(1usize..64)] height: usize,
+		#[strategy(1usize..64)] new_width: usize,
+		#[strategy(1usize..64)] new_height: usize,
+		color: [u8; 4],
+		filter: u8,
+		gamma_correct: bool,
+	) {
+		let filter = enum_iterator::all::<ResampleFilter>()
+			.nth(filter as usize % enum_iterator::cardinality::<ResampleFilter>())
+			.unwrap();
+		let texture = DecodedTexture {
+			width,
+			height,
+			data: color.repeat(width * height),
+		};
+		let resized = texture.resize(
+			new_width,
+			new_height,
+			&ResampleOptions {
+				filter,
+				gamma_correct,
+				alpha_coverage: None,
+			},
+		);
+		prop_assert_eq!(resized.width, new_width);
+		prop_assert_eq!(resized.height, new_height);
+		for pixel in resized.data.chunks_exact(4) {
+			prop_assert_eq!(pixel[3], color[3]);
+			if color[3] > 0 {
+				for c in 0..3 {
+					prop_assert!(pixel[c].abs_diff(color[c]) <= 1);
+				}
+			}
+		}
+	}
+
+	/// fraction of the pixels of the image whose alpha is above the reference value
+	fn alpha_share(texture: &DecodedTexture, reference: u8) -> f32 {
+		let passed = texture
+			.data
+			.chunks_exact(4)
+			.filter(|p| p[3] > reference)
+			.count();
+		passed as f32 / (texture.width * texture.height) as f32
+	}
+
+	#[proptest]
+	fn downscale_keeps_alpha_coverage(
+		#[strategy(8usize..32)] half_width: usize,
+		#[strategy(8usize..32)] half_height: usize,
+		#[strategy(proptest::collection::vec(any::<u8>(), 64 * 64))] alphas: Vec<u8>,
+		#[strategy(32u8..224)] reference: u8,
+		gamma_correct: bool,
+	) {
+		let (width, height) = (half_width * 2, half_height * 2);
+		let texture = DecodedTexture {
+			width,
+			height,
+			data: alphas[..width * height]
+				.iter()
+				.flat_map(|&a| [0x40, 0x80, 0x20, a])
+				.collect(),
+		};
+		let resized = texture.resize(
+			half_width,
+			half_height,
+			&ResampleOptions {
+				filter: ResampleFilter::Box,
+				gamma_correct,
+				alpha_coverage: Some(reference),
+			},
+		);
+		let expected = alpha_share(&texture, reference);
+		let actual = alpha_share(&resized, reference);
+		prop_assert!(
+			(expected - actual).abs() <= 0.05,
+			"source coverage {expected}, resized coverage {actual}"
+		);
+	}
+}
@@ -73,6 +73,24 @@ pub struct TextList {
 	pub data: VersionedTextList,
 }
 
+impl TextList {
+	/// The strings in one language in order, other resources refer to a string by its index in this list
+	///
+	/// Untagged lists have no languages, so all their strings are returned.
+	pub fn strings(&self, language: LanguageCode) -> Vec<String> {
+		match &self.data {
+			VersionedTextList::Tagged { sets, .. } => sets
+				.iter()
+				.filter(|set| set.language_code == language)
+				.map(|set| set.value.to_string())
+				.collect(),
+			VersionedTextList::Untagged { sets } => {
+				sets.iter().map(|set| set.value.to_string()).collect()
+			}
+		}
+	}
+}
+
 impl From<UntaggedString> for TaggedString {
 	fn from(value: UntaggedString) -> Self {
 		Self {
@@ -79,6 +79,11 @@ where
 	pub fn is_read(&self) -> bool {
 		self.data.is_some()
 	}
+
+	/// The parsed data, if this pointer has been read already
+	pub fn get_read(&self) -> Option<&T> {
+		self.data.as_ref()
+	}
 }
 
 impl<'a, Ptr: IntoSeekFrom + Debug, T: BinRead + Debug> Debug for LazyFilePtr<Ptr, T, T::Args<'a>>
@@ -0,0 +1,214 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	collections::HashMap,
+	ffi::OsStr,
+	fs::File,
+	io::{BufReader, Cursor},
+	path::{Path, PathBuf},
+};
+
+use binrw::BinRead;
+use clap::{Parser, Subcommand};
+use dbpf::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		behaviour::{behaviour_function::BehaviourFunction, listing::ListingError},
+		CompressionError, DecodedFile,
+	},
+	DBPFFile,
+};
+use dbpf_utils::unique_names::UniqueNames;
+use thiserror::Error;
+use walkdir::WalkDir;
+
+#[derive(Parser)]
+#[command(
+	version,
+	about = "Convert the behaviour functions (BHAV) in DBPF .package files to and from text listings"
+)]
+struct Args {
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Write every BHAV to a listing named `<group>_<instance>.bhav`
+	Disassemble {
+		/// Directory to write to, every package gets its own subdirectory
+		#[arg(short, long, default_value = ".")]
+		output: PathBuf,
+
+		#[arg(required = true)]
+		file_or_directory: Vec<PathBuf>,
+	},
+	/// Replace the BHAVs in a package with listings named like the ones from `disassemble`
+	Assemble {
+		/// Write the package to this file instead of overwriting it
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+
+		package: PathBuf,
+
+		#[arg(required = true)]
+		listings: Vec<PathBuf>,
+	},
+}
+
+#[derive(Error, Debug)]
+enum Error {
+	#[error(transparent)]
+	IO(#[from] std::io::Error),
+	#[error(transparent)]
+	Compression(#[from] CompressionError),
+	#[error("{0}: {1}")]
+	Listing(PathBuf, ListingError),
+	#[error("{0}: the file name should be <group>_<instance>.bhav")]
+	ListingName(PathBuf),
+	#[error("{0}: there is no BHAV with group {1:08X} and instance {2:016X}")]
+	Missing(PathBuf, u32, u64),
+}
+
+const BHAV: DBPFFileType = DBPFFileType::Known(KnownDBPFFileType::SimanticsBehaviourFunction);
+const TPRP: DBPFFileType = DBPFFileType::Known(KnownDBPFFileType::EdithSimanticsBehaviourLabels);
+
+fn disassemble_package(path: &Path, dir: &Path) -> Result<usize, Error> {
+	let mut reader = BufReader::new(File::open(path)?);
+	let mut file = DBPFFile::read(&mut reader).map_err(CompressionError::from)?;
+
+	let mut labels = HashMap::new();
+	for entry in file.index.iter_mut().filter(|entry| entry.type_id == TPRP) {
+		let key = (entry.group_id, entry.instance_id.id);
+		match entry
+			.data(&mut reader)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded())
+		{
+			Ok(Some(DecodedFile::BehaviourFunctionLabels(tprp))) => {
+				labels.insert(key, tprp.clone());
+			}
+			Ok(_) => {}
+			Err(err) => eprintln!(
+				"{}: labels {:08X}_{:016X}: {err}",
+				path.display(),
+				key.0,
+				key.1
+			),
+		}
+	}
+
+	let mut written = 0;
+	for entry in file.index.iter_mut().filter(|entry| entry.type_id == BHAV) {
+		let (group_id, instance_id) = (entry.group_id, entry.instance_id.id);
+		let bhav = match entry
+			.data(&mut reader)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded())
+		{
+			Ok(Some(DecodedFile::BehaviourFunction(bhav))) => bhav,
+			Ok(_) => continue,
+			Err(err) => {
+				eprintln!(
+					"{}: {group_id:08X}_{instance_id:016X}: {err}",
+					path.display()
+				);
+				continue;
+			}
+		};
+		let listing = bhav.disassemble(labels.get(&(group_id, instance_id)));
+		std::fs::create_dir_all(dir)?;
+		std::fs::write(
+			dir.join(format!("{group_id:08X}_{instance_id:016X}.bhav")),
+			listing,
+		)?;
+		written += 1;
+	}
+	Ok(written)
+}
+
+/// the group and instance id from a listing name like `7FD46CD0_0000000000001000.bhav`
+fn listing_key(path: &Path) -> Option<(u32, u64)> {
+	let (group, instance) = path.file_stem()?.to_str()?.split_once('_')?;
+	Some((
+		u32::from_str_radix(group, 16).ok()?,
+		u64::from_str_radix(instance, 16).ok()?,
+	))
+}
+
+fn assemble_package(package: &Path, listings: &[PathBuf], output: &Path) -> Result<usize, Error> {
+	let mut reader = Cursor::new(std::fs::read(package)?);
+	let mut file = DBPFFile::read(&mut reader).map_err(CompressionError::from)?;
+
+	for listing in listings {
+		let (group_id, instance_id) =
+			listing_key(listing).ok_or_else(|| Error::ListingName(listing.clone()))?;
+		let bhav = BehaviourFunction::assemble(&std::fs::read_to_string(listing)?)
+			.map_err(|err| Error::Listing(listing.clone(), err))?;
+		let entry = file
+			.find_entry(BHAV, group_id, instance_id)
+			.ok_or_else(|| Error::Missing(listing.clone(), group_id, instance_id))?;
+		let data = entry.data(&mut reader).map_err(CompressionError::from)?;
+		match data.decoded()? {
+			Some(DecodedFile::BehaviourFunction(old)) => *old = bhav,
+			_ => return Err(Error::Missing(listing.clone(), group_id, instance_id)),
+		}
+	}
+
+	let mut out_buf = Cursor::new(vec![]);
+	file.write(&mut out_buf, &mut reader)?;
+	std::fs::write(output, out_buf.into_inner())?;
+	Ok(listings.len())
+}
+
+fn main() {
+	let args = Args::parse();
+
+	match args.command {
+		Command::Disassemble {
+			output,
+			file_or_directory,
+		} => {
+			let mut total = 0;
+			// packages with the same name in different directories would overwrite each other
+			let mut dirs = UniqueNames::default();
+			for path in file_or_directory.iter().flat_map(|arg| {
+				WalkDir::new(arg).into_iter().filter_map(|entry| {
+					let path = entry.ok()?.path().to_path_buf();
+					(path.extension() == Some(OsStr::new("package"))).then_some(path)
+				})
+			}) {
+				let dir = output.join(dirs.claim_package(&path));
+
+				match disassemble_package(&path, &dir) {
+					Ok(written) => {
+						println!(
+							"{}: wrote {written} listings to {}",
+							path.display(),
+							dir.display()
+						);
+						total += written;
+					}
+					Err(err) => eprintln!("{}: {err}", path.display()),
+				}
+			}
+			println!("Wrote {total} listings");
+		}
+		Command::Assemble {
+			output,
+			package,
+			listings,
+		} => {
+			let output = output.unwrap_or_else(|| package.clone());
+			match assemble_package(&package, &listings, &output) {
+				Ok(assembled) => println!("{}: assembled {assembled} BHAVs", output.display()),
+				Err(err) => {
+					eprintln!("{}: {err}", package.display());
+					std::process::exit(1);
+				}
+			}
+		}
+	}
+}
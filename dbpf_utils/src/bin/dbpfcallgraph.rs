@@ -0,0 +1,153 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	ffi::OsStr,
+	fs::File,
+	io::BufReader,
+	path::{Path, PathBuf},
+};
+
+use binrw::BinRead;
+use clap::Parser;
+use dbpf::{
+	internal_file::{
+		behaviour::call_graph::{CallGraph, TreeId, GLOBAL_GROUP},
+		CompressionError,
+	},
+	DBPFFile,
+};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+fn parse_hex<T: TryFrom<u32>>(s: &str) -> Result<T, String> {
+	u32::from_str_radix(s.trim_start_matches("0x"), 16)
+		.ok()
+		.and_then(|n| T::try_from(n).ok())
+		.ok_or_else(|| format!("{s} is not a valid hexadecimal id"))
+}
+
+#[derive(Parser)]
+#[command(
+	version,
+	about = "Find which behaviour functions (BHAV) call each other in a set of DBPF .package files"
+)]
+struct Args {
+	/// List the calls to the tree with this instance id
+	#[arg(short, long, value_parser = parse_hex::<u16>)]
+	callers: Option<u16>,
+
+	/// The group of the tree given with --callers
+	#[arg(short, long, value_parser = parse_hex::<u32>, default_value = "7FD46CD0")]
+	group: u32,
+
+	/// List the trees that are in more than one package
+	#[arg(long)]
+	overrides: bool,
+
+	/// Write the call graph to this file in the Graphviz DOT format
+	#[arg(long)]
+	dot: Option<PathBuf>,
+
+	/// The packages, later packages override earlier ones
+	#[arg(required = true)]
+	file_or_directory: Vec<PathBuf>,
+}
+
+#[derive(Error, Debug)]
+enum Error {
+	#[error(transparent)]
+	IO(#[from] std::io::Error),
+	#[error(transparent)]
+	Compression(#[from] CompressionError),
+}
+
+fn add_package(graph: &mut CallGraph, path: &Path) -> Result<(), Error> {
+	let mut reader = BufReader::new(File::open(path)?);
+	let mut file = DBPFFile::read(&mut reader).map_err(CompressionError::from)?;
+	let name = path
+		.file_stem()
+		.unwrap_or_default()
+		.to_string_lossy()
+		.to_string();
+	for undecoded in graph.add_package(name, &mut file, &mut reader) {
+		eprintln!(
+			"{}: {} {:08X} {:016X}: {}",
+			path.display(),
+			undecoded.type_id.abbreviation(),
+			undecoded.group_id,
+			undecoded.instance_id,
+			undecoded.error
+		);
+	}
+	Ok(())
+}
+
+fn main() {
+	let args = Args::parse();
+
+	let mut graph = CallGraph::default();
+	for path in args.file_or_directory.iter().flat_map(|arg| {
+		WalkDir::new(arg)
+			.sort_by_file_name()
+			.into_iter()
+			.filter_map(|entry| {
+				let path = entry.ok()?.path().to_path_buf();
+				(path.extension() == Some(OsStr::new("package"))).then_some(path)
+			})
+	}) {
+		if let Err(err) = add_package(&mut graph, &path) {
+			eprintln!("{}: {err}", path.display());
+		}
+	}
+	println!(
+		"{} trees and {} calls in {} packages",
+		graph.trees.len(),
+		graph.calls.len(),
+		graph.packages.len()
+	);
+
+	if let Some(instance_id) = args.callers {
+		let tree = TreeId {
+			group_id: args.group,
+			instance_id,
+		};
+		for call in graph.callers_of(tree) {
+			let name = graph
+				.definitions(call.caller)
+				.last()
+				.map_or("", |definition| definition.name.as_str());
+			println!(
+				"{}: {:08X} {:04X} \"{name}\" instruction {}",
+				graph.packages[call.package],
+				call.caller.group_id,
+				call.caller.instance_id,
+				call.instruction
+			);
+		}
+	}
+
+	if args.overrides {
+		for (tree, definitions) in graph.overridden() {
+			let kind = if tree.group_id == GLOBAL_GROUP {
+				"global"
+			} else {
+				"tree"
+			};
+			println!("{kind} {:08X} {:04X}:", tree.group_id, tree.instance_id);
+			for definition in definitions {
+				println!(
+					"\t{}: \"{}\"",
+					graph.packages[definition.package], definition.name
+				);
+			}
+		}
+	}
+
+	if let Some(dot) = args.dot {
+		if let Err(err) = std::fs::write(&dot, graph.to_dot()) {
+			eprintln!("{}: {err}", dot.display());
+		}
+	}
+}
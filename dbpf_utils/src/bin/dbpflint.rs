@@ -0,0 +1,98 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	ffi::OsStr,
+	fs::File,
+	io::BufReader,
+	path::{Path, PathBuf},
+};
+
+use binrw::BinRead;
+use clap::Parser;
+use dbpf::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{CompressionError, DecodedFile},
+	DBPFFile,
+};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+#[derive(Parser)]
+#[command(
+	version,
+	about = "Check the behaviour functions (BHAV) in DBPF .package files for possible problems"
+)]
+struct Args {
+	#[arg(required = true)]
+	file_or_directory: Vec<PathBuf>,
+}
+
+#[derive(Error, Debug)]
+enum Error {
+	#[error(transparent)]
+	IO(#[from] std::io::Error),
+	#[error(transparent)]
+	Compression(#[from] CompressionError),
+}
+
+/// print the findings of every BHAV in a package, returns the number of findings
+///
+/// BHAVs that fail to decode are reported and skipped
+fn lint_package(path: &Path) -> Result<usize, Error> {
+	let mut reader = BufReader::new(File::open(path)?);
+	let mut file = DBPFFile::read(&mut reader).map_err(CompressionError::from)?;
+
+	let mut total = 0;
+	for entry in file.index.iter_mut().filter(|entry| {
+		entry.type_id == DBPFFileType::Known(KnownDBPFFileType::SimanticsBehaviourFunction)
+	}) {
+		let (group_id, instance_id) = (entry.group_id, entry.instance_id.id);
+		let bhav = match entry
+			.data(&mut reader)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded())
+		{
+			Ok(Some(DecodedFile::BehaviourFunction(bhav))) => bhav,
+			Ok(_) => continue,
+			Err(err) => {
+				eprintln!(
+					"{}: {group_id:08X} {instance_id:016X}: {err}",
+					path.display()
+				);
+				continue;
+			}
+		};
+		for finding in bhav.analyze() {
+			println!(
+				"{}: {group_id:08X} {instance_id:016X} \"{}\": {finding}",
+				path.display(),
+				bhav.name.name
+			);
+			total += 1;
+		}
+	}
+	Ok(total)
+}
+
+fn main() {
+	let args = Args::parse();
+
+	let mut total = 0;
+	for path in args.file_or_directory.iter().flat_map(|arg| {
+		WalkDir::new(arg).into_iter().filter_map(|entry| {
+			let path = entry.ok()?.path().to_path_buf();
+			(path.extension() == Some(OsStr::new("package"))).then_some(path)
+		})
+	}) {
+		match lint_package(&path) {
+			Ok(findings) => total += findings,
+			Err(err) => eprintln!("{}: {err}", path.display()),
+		}
+	}
+	println!("Found {total} possible problems");
+	if total > 0 {
+		std::process::exit(1);
+	}
+}
@@ -0,0 +1,215 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	ffi::OsStr,
+	io::Cursor,
+	path::{Path, PathBuf},
+};
+
+use binrw::BinRead;
+use clap::Parser;
+use dbpf::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		resource_collection::{
+			geometric_data_container::{
+				math::Vertex,
+				render::{find_mesh_textures, Camera, RenderOptions},
+			},
+			texture_resource::ImageExportFormat,
+			ResourceData,
+		},
+		CompressionError, DecodedFile,
+	},
+	DBPFFile,
+};
+use dbpf_utils::{application_main, unique_names::UniqueNames};
+use futures::{stream, StreamExt};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+#[derive(Parser)]
+#[command(
+	version,
+	about = "Render the meshes in DBPF .package files to png images"
+)]
+struct Args {
+	#[arg(long, default_value_t = 256)]
+	width: usize,
+
+	#[arg(long, default_value_t = 256)]
+	height: usize,
+
+	/// the direction from the center of the mesh towards the camera
+	#[arg(short, long, num_args = 3, default_values_t = [1.0, 0.75, 2.0], allow_hyphen_values = true)]
+	direction: Vec<f32>,
+
+	/// the field of view in degrees
+	#[arg(short, long, default_value_t = 40.0)]
+	fov: f32,
+
+	/// render without looking up the textures of the meshes
+	#[arg(long)]
+	untextured: bool,
+
+	/// the directory to write the images to
+	#[arg(short, long, default_value = ".")]
+	output: PathBuf,
+
+	#[arg(required = true)]
+	file_or_directory: Vec<PathBuf>,
+}
+
+#[derive(Error, Debug)]
+enum Error {
+	#[error(transparent)]
+	IO(#[from] std::io::Error),
+	#[error(transparent)]
+	Compression(#[from] CompressionError),
+	#[error(transparent)]
+	Image(#[from] image::ImageError),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct RenderSettings {
+	width: usize,
+	height: usize,
+	direction: Vertex,
+	fov: f32,
+	textured: bool,
+}
+
+/// render every mesh in a package, returns the name and png data of every image
+///
+/// meshes that fail to decode are reported and skipped
+fn render_package(
+	path: &Path,
+	data: Vec<u8>,
+	name: &str,
+	settings: RenderSettings,
+) -> Result<Vec<(String, Vec<u8>)>, Error> {
+	let mut cursor = Cursor::new(data);
+	let mut file = DBPFFile::read(&mut cursor).map_err(CompressionError::from)?;
+
+	let mut meshes = vec![];
+	for entry in file.index.iter_mut().filter(|entry| {
+		entry.type_id == DBPFFileType::Known(KnownDBPFFileType::GeometricDataContainer)
+	}) {
+		let (group_id, instance_id) = (entry.group_id, entry.instance_id.id);
+		let rcol = match entry
+			.data(&mut cursor)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded())
+		{
+			Ok(Some(DecodedFile::ResourceCollection(rcol))) => rcol,
+			Ok(_) => continue,
+			Err(err) => {
+				eprintln!("{}: {instance_id:X}: {err}", path.display());
+				continue;
+			}
+		};
+		for entry in rcol.entries.iter() {
+			if let ResourceData::Mesh(gmdc) = &entry.data {
+				meshes.push((group_id, instance_id, gmdc.clone()));
+			}
+		}
+	}
+
+	let mut images = vec![];
+	for (group_id, instance_id, gmdc) in meshes {
+		let camera = Camera::framing(&gmdc, settings.direction, settings.fov);
+		let mut options = RenderOptions::new(settings.width, settings.height, camera);
+		if settings.textured {
+			options.textures =
+				find_mesh_textures(&mut file.index, &mut cursor, group_id, instance_id, &gmdc);
+		}
+		let image = gmdc.render(&options);
+		let mut png = Cursor::new(vec![]);
+		image.write_image(ImageExportFormat::Png, &mut png)?;
+		images.push((
+			format!("{name}_{group_id:08X}_{instance_id:016X}.png"),
+			png.into_inner(),
+		));
+	}
+	Ok(images)
+}
+
+async fn render_file(
+	path: PathBuf,
+	name: String,
+	settings: RenderSettings,
+	output: PathBuf,
+) -> Result<usize, Error> {
+	let data = tokio::fs::read(&path).await?;
+	let images = tokio_rayon::spawn(move || render_package(&path, data, &name, settings)).await?;
+	tokio::fs::create_dir_all(&output).await?;
+	for (name, png) in &images {
+		tokio::fs::write(output.join(name), png).await?;
+	}
+	Ok(images.len())
+}
+
+#[tokio::main]
+async fn main() {
+	let args = Args::parse();
+	let settings = RenderSettings {
+		width: args.width,
+		height: args.height,
+		direction: Vertex {
+			x: args.direction[0],
+			y: args.direction[1],
+			z: args.direction[2],
+		},
+		fov: args.fov.to_radians(),
+		textured: !args.untextured,
+	};
+
+	application_main(|| async {
+		let mut names = UniqueNames::default();
+		let flattened = stream::iter(
+			args.file_or_directory
+				.into_iter()
+				.flat_map(|arg| {
+					WalkDir::new(arg).into_iter().filter_map(|entry| {
+						let path = entry.unwrap().path().to_path_buf();
+						if path.extension() == Some(OsStr::new("package")) {
+							Some(path)
+						} else {
+							None
+						}
+					})
+				})
+				.map(|path| {
+					let output = args.output.clone();
+					let name = names.claim_package(&path);
+					async move {
+						(
+							path.clone(),
+							render_file(path, name, settings, output).await,
+						)
+					}
+				}),
+		)
+		.buffer_unordered(num_cpus::get());
+
+		let rendered = flattened
+			.fold(0, |state, item| async move {
+				match item.1 {
+					Ok(rendered) => {
+						println!("{}: {rendered} meshes", item.0.display());
+						state + rendered
+					}
+					Err(err) => {
+						eprintln!("{}: {err}", item.0.display());
+						state
+					}
+				}
+			})
+			.await;
+
+		println!("Rendered {rendered} meshes");
+	})
+	.await;
+}
@@ -0,0 +1,191 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	ffi::OsStr,
+	io::Cursor,
+	path::{Path, PathBuf},
+};
+
+use binrw::BinRead;
+use clap::Parser;
+use dbpf::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		resource_collection::{geometric_data_container::simplify::SimplifyTarget, ResourceData},
+		CompressionError, DecodedFile,
+	},
+	DBPFFile,
+};
+use dbpf_utils::{application_main, unique_names::UniqueNames};
+use futures::{stream, StreamExt};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+#[derive(Parser)]
+#[command(
+	version,
+	about = "Reduce the triangle count of the meshes in DBPF .package files"
+)]
+struct Args {
+	/// the fraction of triangles to keep in every mesh
+	#[arg(short, long, default_value_t = 0.5, conflicts_with = "triangles")]
+	ratio: f32,
+
+	/// the number of triangles to keep in every mesh resource
+	#[arg(short, long)]
+	triangles: Option<usize>,
+
+	/// only simplify mesh resources with more than this many triangles
+	#[arg(short, long, default_value_t = 0)]
+	above: usize,
+
+	/// write the changed packages to this directory
+	#[arg(short, long, required_unless_present = "in_place")]
+	output: Option<PathBuf>,
+
+	/// overwrite the original packages with the changed ones
+	#[arg(long, conflicts_with = "output")]
+	in_place: bool,
+
+	#[arg(required = true)]
+	file_or_directory: Vec<PathBuf>,
+}
+
+#[derive(Error, Debug)]
+enum Error {
+	#[error(transparent)]
+	IOError(#[from] std::io::Error),
+	#[error(transparent)]
+	CompressionError(#[from] CompressionError),
+}
+
+/// simplify all meshes in a package, returns the number of triangles before and after
+///
+/// meshes that fail to decode are reported and left as they are
+fn simplify_package(
+	path: &Path,
+	data: Vec<u8>,
+	target: SimplifyTarget,
+	above: usize,
+) -> Result<(usize, usize, Option<Vec<u8>>), Error> {
+	let mut cursor = Cursor::new(data);
+	let mut file = DBPFFile::read(&mut cursor).map_err(CompressionError::from)?;
+
+	let (mut before, mut after) = (0, 0);
+	for entry in file.index.iter_mut().filter(|entry| {
+		entry.type_id == DBPFFileType::Known(KnownDBPFFileType::GeometricDataContainer)
+	}) {
+		let instance_id = entry.instance_id.id;
+		let rcol = match entry
+			.data(&mut cursor)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded())
+		{
+			Ok(Some(DecodedFile::ResourceCollection(rcol))) => rcol,
+			Ok(_) => continue,
+			Err(err) => {
+				eprintln!("{}: {instance_id:X}: {err}", path.display());
+				continue;
+			}
+		};
+		for entry in rcol.entries.iter_mut() {
+			if let ResourceData::Mesh(gmdc) = &mut entry.data {
+				let triangles: usize = gmdc.meshes.iter().map(|mesh| mesh.poly_count()).sum();
+				before += triangles;
+				if triangles > above {
+					*gmdc = gmdc.simplified(target);
+				}
+				after += gmdc
+					.meshes
+					.iter()
+					.map(|mesh| mesh.poly_count())
+					.sum::<usize>();
+			}
+		}
+	}
+
+	if before == after {
+		return Ok((before, after, None));
+	}
+	let mut out_buf = Cursor::new(vec![]);
+	file.write(&mut out_buf, &mut cursor)?;
+	Ok((before, after, Some(out_buf.into_inner())))
+}
+
+async fn simplify_file(
+	path: PathBuf,
+	out_path: PathBuf,
+	target: SimplifyTarget,
+	above: usize,
+) -> Result<(usize, usize), Error> {
+	let data = tokio::fs::read(&path).await?;
+	let (before, after, out_bytes) =
+		tokio_rayon::spawn(move || simplify_package(&path, data, target, above)).await?;
+	if let Some(out_bytes) = out_bytes {
+		if let Some(dir) = out_path.parent() {
+			tokio::fs::create_dir_all(dir).await?;
+		}
+		tokio::fs::write(out_path, &out_bytes).await?;
+	}
+	Ok((before, after))
+}
+
+#[tokio::main]
+async fn main() {
+	let args = Args::parse();
+	let target = args
+		.triangles
+		.map_or(SimplifyTarget::Ratio(args.ratio), SimplifyTarget::Triangles);
+
+	application_main(|| async {
+		let mut names = UniqueNames::default();
+		let flattened = stream::iter(
+			args.file_or_directory
+				.into_iter()
+				.flat_map(|arg| {
+					WalkDir::new(arg).into_iter().filter_map(|entry| {
+						let path = entry.unwrap().path().to_path_buf();
+						if path.extension() == Some(OsStr::new("package")) {
+							Some(path)
+						} else {
+							None
+						}
+					})
+				})
+				.map(|path| {
+					let out_path = match &args.output {
+						Some(dir) => dir.join(format!("{}.package", names.claim_package(&path))),
+						None => path.clone(),
+					};
+					async move {
+						(
+							path.clone(),
+							simplify_file(path, out_path, target, args.above).await,
+						)
+					}
+				}),
+		)
+		.buffer_unordered(num_cpus::get());
+
+		let (before, after) = flattened
+			.fold((0, 0), |state, item| async move {
+				match item.1 {
+					Ok((before, after)) => {
+						println!("{:?}: {before} -> {after} triangles", item.0);
+						(state.0 + before, state.1 + after)
+					}
+					Err(err) => {
+						eprintln!("{:?}: {err}", item.0);
+						state
+					}
+				}
+			})
+			.await;
+
+		println!("Before: {before} triangles");
+		println!("After: {after} triangles");
+	})
+	.await;
+}
@@ -0,0 +1,232 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+	ffi::OsStr,
+	fs::File,
+	io::{BufReader, BufWriter},
+	path::{Path, PathBuf},
+};
+
+use binrw::BinRead;
+use clap::{Parser, ValueEnum};
+use dbpf::{
+	filetypes::{DBPFFileType, KnownDBPFFileType},
+	internal_file::{
+		resource_collection::{
+			level_information::find_level_information,
+			texture_resource::{ImageExportError, ImageExportFormat, TextureResource},
+			ResourceData,
+		},
+		CompressionError, DecodedFile,
+	},
+	DBPFFile,
+};
+use dbpf_utils::unique_names::UniqueNames;
+use thiserror::Error;
+use walkdir::WalkDir;
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Format {
+	Png,
+	Tga,
+}
+
+impl From<Format> for ImageExportFormat {
+	fn from(value: Format) -> Self {
+		match value {
+			Format::Png => ImageExportFormat::Png,
+			Format::Tga => ImageExportFormat::Tga,
+		}
+	}
+}
+
+#[derive(Parser)]
+#[command(
+	version,
+	about = "Export all textures (TXTR) in DBPF .package files to PNG or TGA images"
+)]
+struct Args {
+	#[arg(short, long, value_enum, default_value_t = Format::Png)]
+	format: Format,
+
+	/// Export every mipmap level instead of only the largest one
+	#[arg(short, long)]
+	mip_levels: bool,
+
+	/// Also export an image with all mipmap levels tiled next to each other
+	#[arg(short, long)]
+	contact_sheet: bool,
+
+	/// Directory to export to, every package gets its own subdirectory named after it
+	#[arg(short, long, default_value = ".")]
+	output: PathBuf,
+
+	#[arg(required = true)]
+	file_or_directory: Vec<PathBuf>,
+}
+
+#[derive(Error, Debug)]
+enum Error {
+	#[error(transparent)]
+	IO(#[from] std::io::Error),
+	#[error(transparent)]
+	Compression(#[from] CompressionError),
+	#[error(transparent)]
+	ImageExport(#[from] ImageExportError),
+}
+
+/// turn a scenegraph resource name into something that is safe to use as a file name
+fn sanitize_name(name: &[u8]) -> String {
+	String::from_utf8_lossy(name)
+		.chars()
+		.map(|c| match c {
+			'/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '!' => '_',
+			c if c.is_control() => '_',
+			c => c,
+		})
+		.collect()
+}
+
+fn export_texture(
+	texture: &TextureResource,
+	name: &str,
+	dir: &Path,
+	args: &Args,
+) -> Result<usize, Error> {
+	let format = ImageExportFormat::from(args.format);
+	let multiple_textures = texture.textures.len() > 1;
+	let mut exported = 0;
+
+	for (texture_index, tex) in texture.textures.iter().enumerate() {
+		let texture_suffix = if multiple_textures {
+			format!("_{texture_index}")
+		} else {
+			String::new()
+		};
+		let Some(largest) = tex.entries.len().checked_sub(1) else {
+			continue;
+		};
+		let mip_indices = if args.mip_levels {
+			0..=largest
+		} else {
+			largest..=largest
+		};
+		for mip_index in mip_indices {
+			let mip_suffix = if args.mip_levels {
+				let (width, height) = texture.mip_size(largest - mip_index);
+				format!("_{width}x{height}")
+			} else {
+				String::new()
+			};
+			let path = dir.join(format!(
+				"{name}{texture_suffix}{mip_suffix}.{}",
+				format.extension()
+			));
+			let mut writer = BufWriter::new(File::create(&path)?);
+			texture.export_image(texture_index, mip_index, format, &mut writer)?;
+			exported += 1;
+		}
+	}
+
+	if args.contact_sheet {
+		let path = dir.join(format!("{name}_sheet.{}", format.extension()));
+		let mut writer = BufWriter::new(File::create(&path)?);
+		texture.export_contact_sheet(format, &mut writer)?;
+		exported += 1;
+	}
+
+	Ok(exported)
+}
+
+fn export_package(path: &Path, dir: &Path, args: &Args) -> Result<usize, Error> {
+	let mut reader = BufReader::new(File::open(path)?);
+	let mut file = DBPFFile::read(&mut reader).map_err(CompressionError::from)?;
+	let lifos = find_level_information(&mut file, &mut reader);
+
+	let mut exported = 0;
+	let mut names = UniqueNames::default();
+
+	for entry in file
+		.index
+		.iter_mut()
+		.filter(|entry| entry.type_id == DBPFFileType::Known(KnownDBPFFileType::TextureResource))
+	{
+		let instance_id = entry.instance_id.id;
+		let rcol = match entry
+			.data(&mut reader)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded())
+		{
+			Ok(Some(DecodedFile::ResourceCollection(rcol))) => rcol,
+			Ok(_) => continue,
+			Err(err) => {
+				eprintln!("{}: {instance_id:X}: {err}", path.display());
+				continue;
+			}
+		};
+		for resource in &rcol.entries {
+			let ResourceData::Texture(texture) = &resource.data else {
+				continue;
+			};
+			let mut texture = texture.clone();
+			if texture.any_lifo() && !texture.embed_lifo(&lifos) {
+				eprintln!(
+					"{}: {:X}: skipping texture with missing LIFO mipmaps",
+					path.display(),
+					instance_id
+				);
+				continue;
+			}
+
+			let mut name = sanitize_name(&texture.file_name.name.0);
+			if name.is_empty() {
+				name = format!("{instance_id:016X}");
+			}
+			// textures with the same name would overwrite each other
+			let name = if names.is_used(&name) {
+				names.claim(&format!("{name}_{instance_id:016X}"))
+			} else {
+				names.claim(&name)
+			};
+
+			std::fs::create_dir_all(dir)?;
+			match export_texture(&texture, &name, dir, args) {
+				Ok(n) => exported += n,
+				Err(err) => eprintln!("{}: {name}: {err}", path.display()),
+			}
+		}
+	}
+
+	Ok(exported)
+}
+
+fn main() {
+	let args = Args::parse();
+
+	let mut total = 0;
+	// packages with the same name in different directories would overwrite each other
+	let mut dirs = UniqueNames::default();
+	for path in args.file_or_directory.iter().flat_map(|arg| {
+		WalkDir::new(arg).into_iter().filter_map(|entry| {
+			let path = entry.ok()?.path().to_path_buf();
+			(path.extension() == Some(OsStr::new("package"))).then_some(path)
+		})
+	}) {
+		let dir = args.output.join(dirs.claim_package(&path));
+
+		match export_package(&path, &dir, &args) {
+			Ok(exported) => {
+				println!(
+					"{}: exported {exported} images to {}",
+					path.display(),
+					dir.display()
+				);
+				total += exported;
+			}
+			Err(err) => eprintln!("{}: {err}", path.display()),
+		}
+	}
+	println!("Exported {total} images");
+}
@@ -0,0 +1,259 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use dbpf::internal_file::behaviour::{
+	behaviour_constants::BehaviourConstants,
+	behaviour_constants_labels::{
+		BehaviourConstantsLabels, Header, Label, LabelV1, LabelV2, Version,
+	},
+};
+use eframe::{
+	egui,
+	egui::{Color32, DragValue, Response, Ui},
+};
+use futures::channel::oneshot;
+use rfd::FileHandle;
+use tracing::error;
+
+use crate::{
+	async_execute,
+	editor::{drag_hex_fn, numbered_combo_box, Editor},
+};
+
+#[derive(Debug, Default)]
+pub struct BehaviourConstantsEditorState {
+	save_file_picker: Option<oneshot::Receiver<Option<FileHandle>>>,
+	open_file_picker: Option<oneshot::Receiver<Option<Vec<u8>>>>,
+	import_error: Option<String>,
+	/// the TRCN with the same instance, which names the constants
+	labels: Option<BehaviourConstantsLabels>,
+}
+
+impl BehaviourConstantsEditorState {
+	/// Set the labels of the TRCN with the same instance, `None` when there is none
+	pub fn set_labels(&mut self, labels: Option<BehaviourConstantsLabels>) {
+		self.labels = labels;
+	}
+}
+
+impl Editor for BehaviourConstants {
+	type EditorState = BehaviourConstantsEditorState;
+
+	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		if let Some(picker) = &mut state.save_file_picker {
+			if let Ok(Some(handle)) = picker.try_recv() {
+				state.save_file_picker = None;
+				if let Some(handle) = handle {
+					let res = futures::executor::block_on(
+						handle.write(self.to_csv(state.labels.as_ref()).as_bytes()),
+					);
+					if let Err(error) = res {
+						error!(?error);
+					}
+				}
+			}
+		}
+		let mut res = ui
+			.horizontal_wrapped(|ui| {
+				ui.label("file name") | self.file_name.name.show_editor(&mut 500.0, ui)
+			})
+			.inner;
+		if let Some(picker) = &mut state.open_file_picker {
+			if let Ok(Some(bytes)) = picker.try_recv() {
+				state.open_file_picker = None;
+				if let Some(bytes) = bytes {
+					state.import_error = self
+						.import_csv(&String::from_utf8_lossy(&bytes))
+						.err()
+						.map(|err| err.to_string());
+					res.mark_changed();
+				}
+			}
+		}
+
+		res |= egui::Grid::new("bcon header")
+			.num_columns(2)
+			.show(ui, |ui| drag_hex_fn("flag", &mut self.flag, ui))
+			.inner;
+
+		ui.horizontal(|ui| {
+			let name = self.file_name.name.to_string();
+			if ui
+				.button("Export CSV")
+				.on_hover_text("export the constants with their labels to a .csv file")
+				.clicked() && state.save_file_picker.is_none()
+			{
+				let (tx, rx) = oneshot::channel();
+				let dialog = rfd::AsyncFileDialog::new()
+					.set_file_name(format!("{name}.csv"))
+					.add_filter("Comma-separated values", &["csv"])
+					.save_file();
+				async_execute(async move {
+					let _ = tx.send(dialog.await);
+				});
+				state.save_file_picker = Some(rx);
+			}
+			if ui
+				.button("Import CSV")
+				.on_hover_text(
+					"set the constants from the index and value columns of a .csv file, \
+					the other columns are ignored",
+				)
+				.clicked() && state.open_file_picker.is_none()
+			{
+				let (tx, rx) = oneshot::channel();
+				let dialog = rfd::AsyncFileDialog::new()
+					.add_filter("Comma-separated values", &["csv"])
+					.pick_file();
+				async_execute(async move {
+					let bytes = match dialog.await {
+						Some(handle) => Some(handle.read().await),
+						None => None,
+					};
+					let _ = tx.send(bytes);
+				});
+				state.open_file_picker = Some(rx);
+			}
+		});
+		if let Some(err) = &state.import_error {
+			ui.colored_label(Color32::RED, err);
+		}
+		if state.labels.is_none() {
+			ui.label("no labels (TRCN) with the same instance were found");
+		}
+
+		egui::Grid::new("constants")
+			.num_columns(4)
+			.striped(true)
+			.show(ui, |ui| {
+				ui.label("constant");
+				ui.label("value");
+				ui.label("default");
+				ui.label("range");
+				ui.end_row();
+
+				let labels = state.labels.as_ref();
+				for (index, value) in self.constants.iter_mut().enumerate() {
+					let label = labels.and_then(|labels| labels.label(index));
+					let name = ui.label(format!(
+						"{index}: {}",
+						label.map(Label::name).unwrap_or_default()
+					));
+					if let Some(description) = label.and_then(Label::description) {
+						name.on_hover_text(description);
+					}
+					res |= ui.add(DragValue::new(value));
+					ui.label(
+						label
+							.and_then(Label::default_value)
+							.map(|v| v.to_string())
+							.unwrap_or_default(),
+					);
+					match label.and_then(Label::range) {
+						Some(range) if !range.contains(value) => {
+							ui.colored_label(
+								Color32::RED,
+								format!("{} to {}, out of range", range.start(), range.end()),
+							);
+						}
+						Some(range) => {
+							ui.label(format!("{} to {}", range.start(), range.end()));
+						}
+						None => {
+							ui.label("");
+						}
+					}
+					ui.end_row();
+				}
+			});
+
+		ui.horizontal(|ui| {
+			if ui
+				.add_enabled(
+					self.constants.len() < u8::MAX as usize,
+					egui::Button::new("➕"),
+				)
+				.clicked()
+			{
+				self.constants.push(0);
+				res.mark_changed();
+			}
+			if ui
+				.add_enabled(!self.constants.is_empty(), egui::Button::new("🗑"))
+				.clicked()
+			{
+				self.constants.pop();
+				res.mark_changed();
+			}
+		});
+		res
+	}
+}
+
+fn show_label(label: &mut Label, ui: &mut Ui) -> Response {
+	match label {
+		Label::V1(label) => {
+			let mut res = ui.add(DragValue::new(&mut label.id).prefix("id: "));
+			res |= label.name.show_editor(&mut 200.0, ui);
+			res |= ui.add(DragValue::new(&mut label.default).prefix("default: "));
+			res |= ui.add(DragValue::new(&mut label.min).prefix("min: "));
+			res | ui.add(DragValue::new(&mut label.max).prefix("max: "))
+		}
+		Label::V2(label) => {
+			let res = ui.add(DragValue::new(&mut label.id).prefix("id: "));
+			res | label.name.show_editor(&mut 200.0, ui)
+				| label.description.show_editor(&mut 300.0, ui)
+		}
+	}
+}
+
+impl Editor for BehaviourConstantsLabels {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = ui
+			.horizontal_wrapped(|ui| {
+				ui.label("file name") | self.file_name.name.show_editor(&mut 500.0, ui)
+			})
+			.inner;
+		ui.horizontal(|ui| {
+			ui.label("version");
+			let version = match &mut self.header {
+				Header::Normal(version, _) | Header::ExtraNulls(_, version) => version,
+			};
+			res |= numbered_combo_box("version", version, Version::KNOWN, Version::name, ui);
+		});
+
+		let mut delete = None;
+		for (i, label) in self.labels.iter_mut().enumerate() {
+			ui.push_id(i, |ui| {
+				ui.horizontal(|ui| {
+					if ui.button("🗑").clicked() {
+						delete = Some(i);
+					}
+					res |= show_label(label, ui);
+				});
+			});
+		}
+		if let Some(i) = delete {
+			self.labels.remove(i);
+			res.mark_changed();
+		}
+		if ui.button("➕").clicked() {
+			let id = self.labels.len() as u32;
+			self.labels.push(match self.header {
+				Header::Normal(..) => Label::V1(LabelV1 {
+					id,
+					..Default::default()
+				}),
+				Header::ExtraNulls(..) => Label::V2(LabelV2 {
+					id,
+					..Default::default()
+				}),
+			});
+			res.mark_changed();
+		}
+		res
+	}
+}
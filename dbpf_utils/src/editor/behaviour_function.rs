@@ -4,23 +4,26 @@
 
 use std::{
 	cmp::max,
-	collections::{BTreeSet, VecDeque},
+	collections::{BTreeSet, HashMap, VecDeque},
+	fmt::Debug,
 	sync::Arc,
 };
 
-use dbpf::internal_file::behaviour::behaviour_function::{
-	BehaviourFunction, Goto, Instruction, Signature,
+use dbpf::internal_file::behaviour::{
+	behaviour_function::{BehaviourFunction, Goto, Instruction, Signature},
+	primitive::{primitive_name, DataOwner, ExpressionOperator, Function, FIRST_CALL_OPCODE},
 };
 use eframe::{
-	egui::{Color32, ComboBox, Context, DragValue, Grid, Pos2, Response, Ui},
+	egui::{Button, Color32, ComboBox, Context, DragValue, Grid, Pos2, Response, Ui},
 	glow,
 };
 use egui_snarl::{
-	ui::{PinInfo, PinPlacement, SnarlPin, SnarlStyle, SnarlViewer, WireLayer, WireStyle},
+	ui::{AnyPins, PinInfo, PinPlacement, SnarlPin, SnarlStyle, SnarlViewer, WireLayer, WireStyle},
 	InPin, InPinId, NodeId, OutPin, OutPinId, Snarl,
 };
 
 use crate::editor::{
+	numbered_combo_box,
 	r#enum::{EnumEditor, EnumEditorState},
 	Editor,
 };
@@ -91,7 +94,7 @@ impl Editor for Instruction {
 		res |= self.false_target.show_editor(state, ui);
 		res |= ui.add(DragValue::new(&mut self.node_version).hexadecimal(1, false, false));
 
-		res | ui
+		res |= ui
 			.horizontal(|ui| {
 				ui.label("(");
 				ui.style_mut().spacing.interact_size.x = 25.0;
@@ -104,25 +107,144 @@ impl Editor for Instruction {
 				ui.label(")");
 				res
 			})
-			.inner
+			.inner;
+		ui.label(self.function().to_string());
+		res
 	}
 }
 
+fn data_editor(id_salt: &str, owner: &mut DataOwner, data: &mut u16, ui: &mut Ui) -> Response {
+	numbered_combo_box(id_salt, owner, DataOwner::KNOWN, DataOwner::name, ui)
+		| ui.add(DragValue::new(data))
+}
+
+/// edit the operands of the primitives that have typed operands, `None` for the others
+fn show_function_editor(function: &mut Function, ui: &mut Ui) -> Option<Response> {
+	Some(match function {
+		Function::Sleep(sleep) => {
+			ui.label("ticks in Param") | ui.add(DragValue::new(&mut sleep.ticks_parameter))
+		}
+		Function::Expression(e) => {
+			data_editor("lhs", &mut e.lhs_owner, &mut e.lhs_data, ui)
+				| numbered_combo_box(
+					"operator",
+					&mut e.operator,
+					ExpressionOperator::KNOWN,
+					ExpressionOperator::name,
+					ui,
+				) | data_editor("rhs", &mut e.rhs_owner, &mut e.rhs_data, ui)
+		}
+		Function::RandomNumber(r) => {
+			let res = data_editor(
+				"destination",
+				&mut r.destination_owner,
+				&mut r.destination_data,
+				ui,
+			);
+			ui.label(":= Random(");
+			let res = res | data_editor("range", &mut r.range_owner, &mut r.range_data, ui);
+			ui.label(")");
+			res
+		}
+		Function::TestObjectType(t) => {
+			let res = data_editor("object", &mut t.id_owner, &mut t.id_data, ui);
+			ui.label("is of type");
+			res | ui.add(DragValue::new(&mut t.guid).hexadecimal(8, false, true))
+		}
+		Function::Call(_, call) => {
+			let mut res = ui.label("arguments");
+			for argument in &mut call.arguments {
+				res |= ui.add(DragValue::new(argument));
+			}
+			res
+		}
+		_ => return None,
+	})
+}
+
+fn target_mut(instruction: &mut Instruction, output: usize) -> &mut Goto {
+	if output == 0 {
+		&mut instruction.true_target
+	} else {
+		&mut instruction.false_target
+	}
+}
+
+/// write the wires of the graph into the gotos, and return the instructions in the order they are saved
+fn sync_instructions(
+	snarl: &mut Snarl<Instruction>,
+	order: &[NodeId],
+	signature: Signature,
+) -> Vec<Instruction> {
+	let indices: HashMap<NodeId, u16> = order
+		.iter()
+		.enumerate()
+		.map(|(i, node)| (*node, i as u16))
+		.collect();
+	order
+		.iter()
+		.map(|&node| {
+			for output in 0..2 {
+				let target = snarl
+					.out_pin(OutPinId { node, output })
+					.remotes
+					.first()
+					.and_then(|remote| indices.get(&remote.node))
+					.copied();
+				if let Some(target) = target {
+					*target_mut(&mut snarl[node], output) = Goto::Instr(target);
+				}
+			}
+			let instruction = &mut snarl[node];
+			instruction.operands.resize(signature.operand_count(), 0);
+			instruction.clone()
+		})
+		.collect()
+}
+
 #[derive(Debug, Default)]
 pub struct BhavEditorState {
 	snarl: Snarl<Instruction>,
+	/// the node of every instruction, in the order the instructions are saved
+	order: Vec<NodeId>,
 	enum_editor_state: EnumEditorState,
 }
 
 #[derive(Debug)]
-struct BhavViewer<'a, 'b> {
-	_bhav: &'a mut BehaviourFunction,
-	enum_editor_state: &'b mut EnumEditorState,
+struct BhavViewer<'a> {
+	order: &'a mut Vec<NodeId>,
+	signature: Signature,
+	enum_editor_state: &'a mut EnumEditorState,
 }
 
-impl<'a, 'b> SnarlViewer<Instruction> for BhavViewer<'a, 'b> {
-	fn title(&mut self, _node: &Instruction) -> String {
-		"Instruction".to_string()
+impl BhavViewer<'_> {
+	fn insert_instruction(&mut self, pos: Pos2, snarl: &mut Snarl<Instruction>) -> NodeId {
+		let node = snarl.insert_node(
+			pos,
+			Instruction {
+				operands: vec![0; self.signature.operand_count()],
+				..Default::default()
+			},
+		);
+		self.order.push(node);
+		node
+	}
+
+	/// gotos without a wire point to nothing
+	fn clear_target(&mut self, pin: OutPinId, snarl: &mut Snarl<Instruction>) {
+		if let Some(instruction) = snarl.get_node_mut(pin.node) {
+			*target_mut(instruction, pin.output) = Goto::Error;
+		}
+	}
+}
+
+impl SnarlViewer<Instruction> for BhavViewer<'_> {
+	fn title(&mut self, node: &Instruction) -> String {
+		match primitive_name(node.opcode) {
+			Some(name) => name.to_string(),
+			None if node.opcode >= FIRST_CALL_OPCODE => "Call".to_string(),
+			None => "Primitive".to_string(),
+		}
 	}
 
 	fn show_header(
@@ -133,7 +255,13 @@ impl<'a, 'b> SnarlViewer<Instruction> for BhavViewer<'a, 'b> {
 		ui: &mut Ui,
 		snarl: &mut Snarl<Instruction>,
 	) {
-		ui.label("Instruction");
+		let index = self
+			.order
+			.iter()
+			.position(|n| *n == node)
+			.unwrap_or_default();
+		let title = self.title(&snarl[node]);
+		ui.label(format!("{index}: {title}"));
 		ui.add(DragValue::new(&mut snarl[node].opcode).hexadecimal(1, false, false));
 	}
 
@@ -160,12 +288,21 @@ impl<'a, 'b> SnarlViewer<Instruction> for BhavViewer<'a, 'b> {
 		ui: &mut Ui,
 		snarl: &mut Snarl<Instruction>,
 	) -> impl SnarlPin + 'static {
-		let (target, color) = if pin.id.output == 0 {
-			(&mut snarl[pin.id.node].true_target, Color32::GREEN)
+		let color = if pin.id.output == 0 {
+			Color32::GREEN
 		} else {
-			(&mut snarl[pin.id.node].false_target, Color32::RED)
+			Color32::RED
 		};
-		target.show_editor(self.enum_editor_state, ui);
+		let target = target_mut(&mut snarl[pin.id.node], pin.id.output);
+		if target.show_editor(self.enum_editor_state, ui).changed() {
+			let target = *target;
+			snarl.drop_outputs(pin.id);
+			if let Goto::Instr(i) = target {
+				if let Some(&node) = self.order.get(i as usize) {
+					snarl.connect(pin.id, InPinId { node, input: 0 });
+				}
+			}
+		}
 		PinInfo::circle().with_fill(color).with_wire_color(color)
 	}
 
@@ -183,25 +320,136 @@ impl<'a, 'b> SnarlViewer<Instruction> for BhavViewer<'a, 'b> {
 	) {
 		let instr = &mut snarl[node];
 		ui.vertical_centered(|ui| {
+			ui.label(instr.function().to_string());
+			let mut function = instr.function();
+			let typed = ui.horizontal(|ui| show_function_editor(&mut function, ui));
+			if typed.inner.is_some_and(|res| res.changed()) {
+				instr.set_function(&function);
+			}
 			ui.horizontal(|ui| {
 				ui.label("version");
 				ui.add(DragValue::new(&mut instr.node_version).hexadecimal(1, false, false));
 			});
 
-			ui.collapsing("parameters", |ui| {
-				ui.horizontal(|ui| {
-					for u in &mut instr.operands[..8] {
-						ui.add(DragValue::new(u).hexadecimal(2, false, false));
-					}
-				});
-				ui.horizontal(|ui| {
-					for u in &mut instr.operands[8..] {
-						ui.add(DragValue::new(u).hexadecimal(2, false, false));
-					}
-				});
+			ui.collapsing("operands", |ui| {
+				for row in instr.operands.chunks_mut(8) {
+					ui.horizontal(|ui| {
+						for u in row {
+							ui.add(DragValue::new(u).hexadecimal(2, false, false));
+						}
+					});
+				}
 			});
 		});
 	}
+
+	fn has_graph_menu(&mut self, _pos: Pos2, _snarl: &mut Snarl<Instruction>) -> bool {
+		true
+	}
+
+	fn show_graph_menu(&mut self, pos: Pos2, ui: &mut Ui, snarl: &mut Snarl<Instruction>) {
+		if ui.button("Add instruction").clicked() {
+			self.insert_instruction(pos, snarl);
+			ui.close();
+		}
+	}
+
+	fn has_dropped_wire_menu(
+		&mut self,
+		src_pins: AnyPins,
+		_snarl: &mut Snarl<Instruction>,
+	) -> bool {
+		matches!(src_pins, AnyPins::Out(_))
+	}
+
+	fn show_dropped_wire_menu(
+		&mut self,
+		pos: Pos2,
+		ui: &mut Ui,
+		src_pins: AnyPins,
+		snarl: &mut Snarl<Instruction>,
+	) {
+		let AnyPins::Out(pins) = src_pins else {
+			return;
+		};
+		if ui.button("Add instruction").clicked() {
+			let node = self.insert_instruction(pos, snarl);
+			for pin in pins {
+				snarl.drop_outputs(*pin);
+				snarl.connect(*pin, InPinId { node, input: 0 });
+			}
+			ui.close();
+		}
+	}
+
+	fn has_node_menu(&mut self, _node: &Instruction) -> bool {
+		true
+	}
+
+	fn show_node_menu(
+		&mut self,
+		node: NodeId,
+		inputs: &[InPin],
+		_outputs: &[OutPin],
+		ui: &mut Ui,
+		snarl: &mut Snarl<Instruction>,
+	) {
+		let Some(index) = self.order.iter().position(|n| *n == node) else {
+			return;
+		};
+		if ui
+			.add_enabled(index > 0, Button::new("Make entry point"))
+			.clicked()
+		{
+			self.order.remove(index);
+			self.order.insert(0, node);
+			ui.close();
+		}
+		if ui.add_enabled(index > 0, Button::new("Move up")).clicked() {
+			self.order.swap(index, index - 1);
+			ui.close();
+		}
+		if ui
+			.add_enabled(index + 1 < self.order.len(), Button::new("Move down"))
+			.clicked()
+		{
+			self.order.swap(index, index + 1);
+			ui.close();
+		}
+		if ui.button("Delete").clicked() {
+			for input in inputs {
+				for remote in &input.remotes {
+					self.clear_target(*remote, snarl);
+				}
+			}
+			snarl.remove_node(node);
+			self.order.remove(index);
+			ui.close();
+		}
+	}
+
+	fn connect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<Instruction>) {
+		// a goto has a single target
+		snarl.drop_outputs(from.id);
+		snarl.connect(from.id, to.id);
+	}
+
+	fn disconnect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<Instruction>) {
+		snarl.disconnect(from.id, to.id);
+		self.clear_target(from.id, snarl);
+	}
+
+	fn drop_outputs(&mut self, pin: &OutPin, snarl: &mut Snarl<Instruction>) {
+		snarl.drop_outputs(pin.id);
+		self.clear_target(pin.id, snarl);
+	}
+
+	fn drop_inputs(&mut self, pin: &InPin, snarl: &mut Snarl<Instruction>) {
+		for remote in &pin.remotes {
+			self.clear_target(*remote, snarl);
+		}
+		snarl.drop_inputs(pin.id);
+	}
 }
 
 impl Editor for BehaviourFunction {
@@ -275,45 +523,43 @@ impl Editor for BehaviourFunction {
 			}
 		}
 
-		for (instr, pos) in self.instructions.iter().zip(node_positons) {
-			snarl.insert_node(pos, instr.clone());
-		}
-
-		for (i, instr) in self.instructions.iter().enumerate() {
-			if let Goto::Instr(target) = instr.true_target {
-				snarl.connect(
-					OutPinId {
-						node: NodeId(i),
-						output: 0,
-					},
-					InPinId {
-						node: NodeId(target as usize),
-						input: 0,
-					},
-				);
-			}
-			if let Goto::Instr(target) = instr.false_target {
-				snarl.connect(
-					OutPinId {
-						node: NodeId(i),
-						output: 1,
-					},
-					InPinId {
-						node: NodeId(target as usize),
-						input: 0,
-					},
-				);
+		let order: Vec<NodeId> = self
+			.instructions
+			.iter()
+			.zip(node_positons)
+			.map(|(instr, pos)| snarl.insert_node(pos, instr.clone()))
+			.collect();
+
+		for (instr, &node) in self.instructions.iter().zip(&order) {
+			for (output, target) in [instr.true_target, instr.false_target]
+				.into_iter()
+				.enumerate()
+			{
+				// gotos to instructions that do not exist stay unconnected
+				if let Some(&target) = match target {
+					Goto::Instr(target) => order.get(target as usize),
+					_ => None,
+				} {
+					snarl.connect(
+						OutPinId { node, output },
+						InPinId {
+							node: target,
+							input: 0,
+						},
+					);
+				}
 			}
 		}
 
 		BhavEditorState {
 			snarl,
+			order,
 			enum_editor_state: EnumEditorState::default(),
 		}
 	}
 
 	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
-		let res = Grid::new("bhav edit grid")
+		let mut res = Grid::new("bhav edit grid")
 			.num_columns(2)
 			.show(ui, |ui| {
 				ui.label("filename");
@@ -368,9 +614,26 @@ impl Editor for BehaviourFunction {
 		snarl_style.downscale_wire_frame = Some(true);
 		snarl_style.pin_placement = Some(PinPlacement::Edge {});
 
+		if let Err(err) = self.validate() {
+			ui.colored_label(
+				ui.visuals().error_fg_color,
+				format!("this BHAV can not be saved: {err}"),
+			);
+		}
+
+		let findings = self.analyze();
+		if !findings.is_empty() {
+			ui.collapsing(format!("{} possible problems", findings.len()), |ui| {
+				for finding in &findings {
+					ui.colored_label(ui.visuals().warn_fg_color, finding.to_string());
+				}
+			});
+		}
+
 		state.snarl.show(
 			&mut BhavViewer {
-				_bhav: self,
+				order: &mut state.order,
+				signature: self.signature,
 				enum_editor_state: &mut state.enum_editor_state,
 			},
 			&snarl_style,
@@ -378,6 +641,12 @@ impl Editor for BehaviourFunction {
 			ui,
 		);
 
+		let instructions = sync_instructions(&mut state.snarl, &state.order, self.signature);
+		if instructions != self.instructions {
+			self.instructions = instructions;
+			res.mark_changed();
+		}
+
 		res
 	}
 }
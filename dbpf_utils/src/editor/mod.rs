@@ -11,17 +11,24 @@ use dbpf::internal_file::material_shader::MaterialShader;
 use dbpf::{
 	filetypes::{DBPFFileType, KnownDBPFFileType},
 	internal_file::{
-		behaviour::behaviour_function::BehaviourFunction, sim_outfits::SimOutfits,
-		text_list::TextList, DecodedFile,
+		behaviour::{
+			behaviour_constants::BehaviourConstants, behaviour_function::BehaviourFunction,
+			object_functions::ObjectFunctions,
+			pie_menu_functions::PieMenuFunctions,
+		},
+		sim_outfits::SimOutfits,
+		text_list::TextList,
+		DecodedFile,
 	},
 };
 use eframe::{
 	egui,
-	egui::{Align, DragValue, Response, Ui},
+	egui::{Align, ComboBox, DragValue, Response, Ui},
 	emath::Numeric,
 	glow,
 };
 
+pub mod behaviour_constants;
 pub mod behaviour_function;
 pub mod common_ui;
 pub mod cpf;
@@ -31,6 +38,9 @@ mod image;
 pub mod instance_id;
 pub mod matshad;
 pub mod object_data;
+pub mod object_functions;
+pub mod object_slot;
+pub mod pie_menu_functions;
 pub mod resource_collection;
 pub mod sim_description;
 pub mod sim_outfits;
@@ -58,6 +68,9 @@ pub enum DecodedFileEditorState {
 	SimOutfits(<SimOutfits as Editor>::EditorState),
 	TextList(<TextList as Editor>::EditorState),
 	BehaviourFunction(<BehaviourFunction as Editor>::EditorState),
+	BehaviourConstants(<BehaviourConstants as Editor>::EditorState),
+	PieMenuFunctions(<PieMenuFunctions as Editor>::EditorState),
+	ObjectFunctions(<ObjectFunctions as Editor>::EditorState),
 	MaterialShader(<MaterialShader as Editor>::EditorState),
 	Image(<Image as Editor>::EditorState),
 	PropertySet(<PropertySet as Editor>::EditorState),
@@ -86,6 +99,15 @@ impl Editor for DecodedFile {
 			DecodedFile::BehaviourFunction(bhav) => {
 				DecodedFileEditorState::BehaviourFunction(bhav.new_editor(context, gl_context))
 			}
+			DecodedFile::BehaviourConstants(bcon) => {
+				DecodedFileEditorState::BehaviourConstants(bcon.new_editor(context, gl_context))
+			}
+			DecodedFile::PieMenuFunctions(ttab) => {
+				DecodedFileEditorState::PieMenuFunctions(ttab.new_editor(context, gl_context))
+			}
+			DecodedFile::ObjectFunctions(objf) => {
+				DecodedFileEditorState::ObjectFunctions(objf.new_editor(context, gl_context))
+			}
 			DecodedFile::MaterialShader(matshad) => {
 				DecodedFileEditorState::MaterialShader(matshad.new_editor(context, gl_context))
 			}
@@ -118,6 +140,20 @@ impl Editor for DecodedFile {
 				DecodedFile::BehaviourFunction(bhav),
 				DecodedFileEditorState::BehaviourFunction(state),
 			) => bhav.show_editor(state, ui),
+			(
+				DecodedFile::BehaviourConstants(bcon),
+				DecodedFileEditorState::BehaviourConstants(state),
+			) => bcon.show_editor(state, ui),
+			(DecodedFile::BehaviourConstantsLabels(trcn), _) => trcn.show_editor(&mut (), ui),
+			(
+				DecodedFile::PieMenuFunctions(ttab),
+				DecodedFileEditorState::PieMenuFunctions(state),
+			) => ttab.show_editor(state, ui),
+			(
+				DecodedFile::ObjectFunctions(objf),
+				DecodedFileEditorState::ObjectFunctions(state),
+			) => objf.show_editor(state, ui),
+			(DecodedFile::ObjectSlot(slot), _) => slot.show_editor(&mut (), ui),
 			(
 				DecodedFile::MaterialShader(matshad),
 				DecodedFileEditorState::MaterialShader(state),
@@ -134,6 +170,51 @@ impl Editor for DecodedFile {
 	}
 }
 
+/// the state of editors that refer to BHAVs
+#[derive(Clone, Debug, Default)]
+pub struct OpenTreeState {
+	/// the BHAV the user asked to open, taken by the application
+	pub open_tree: Option<u16>,
+}
+
+impl DecodedFileEditorState {
+	/// The instance of a BHAV the editor asked to open, this clears the request
+	pub fn take_open_tree(&mut self) -> Option<u16> {
+		match self {
+			DecodedFileEditorState::PieMenuFunctions(state) => state.open_tree.take(),
+			DecodedFileEditorState::ObjectFunctions(state) => state.open_tree.take(),
+			_ => None,
+		}
+	}
+
+	/// The type of the resource with the same group and instance that names the contents of this one,
+	/// like the TTAs of a TTAB or the TRCN of a BCON
+	pub fn label_type(&self) -> Option<KnownDBPFFileType> {
+		match self {
+			DecodedFileEditorState::PieMenuFunctions(_) => Some(KnownDBPFFileType::PieMenuStrings),
+			DecodedFileEditorState::BehaviourConstants(_) => {
+				Some(KnownDBPFFileType::BehaviourConstantsLabels)
+			}
+			_ => None,
+		}
+	}
+
+	/// Set the decoded resource of [DecodedFileEditorState::label_type], `None` when the package does not have it
+	pub fn set_labels(&mut self, labels: Option<DecodedFile>) {
+		match self {
+			DecodedFileEditorState::PieMenuFunctions(state) => state.set_strings(match &labels {
+				Some(DecodedFile::TextList(strings)) => Some(strings),
+				_ => None,
+			}),
+			DecodedFileEditorState::BehaviourConstants(state) => state.set_labels(match labels {
+				Some(DecodedFile::BehaviourConstantsLabels(trcn)) => Some(trcn),
+				_ => None,
+			}),
+			_ => {}
+		}
+	}
+}
+
 pub fn editor_supported(file_type: DBPFFileType) -> bool {
 	match file_type {
         DBPFFileType::Known(
@@ -167,6 +248,14 @@ pub fn editor_supported(file_type: DBPFFileType) -> bool {
             KnownDBPFFileType::TextureResource |
             KnownDBPFFileType::MaterialDefinition |
             KnownDBPFFileType::GeometricDataContainer |
+            KnownDBPFFileType::CreationResource |
+            KnownDBPFFileType::Shape |
+            KnownDBPFFileType::GeometricNode |
+            KnownDBPFFileType::AmbientLight |
+            KnownDBPFFileType::DirectionalLight |
+            KnownDBPFFileType::PointLight |
+            KnownDBPFFileType::Spotlight |
+            KnownDBPFFileType::LevelInformation |
 
             // STR
             KnownDBPFFileType::TextList |
@@ -175,8 +264,13 @@ pub fn editor_supported(file_type: DBPFFileType) -> bool {
 
             // BHAV
             KnownDBPFFileType::SimanticsBehaviourFunction |
+            KnownDBPFFileType::PieMenuFunctions |
+            KnownDBPFFileType::SimanticsBehaviourConstants |
+            KnownDBPFFileType::BehaviourConstantsLabels |
+            KnownDBPFFileType::ObjectFunctions |
 
             KnownDBPFFileType::ObjectData |
+            KnownDBPFFileType::ObjectSlot |
 
             KnownDBPFFileType::SimDescription |
 
@@ -199,6 +293,59 @@ pub(crate) fn drag_hex_fn<T: Numeric>(name: &str, value: &mut T, ui: &mut Ui) ->
 	res
 }
 
+/// a dropdown for the enums that are stored as a number, like [DataOwner](dbpf::internal_file::behaviour::primitive::DataOwner)
+pub(crate) fn numbered_combo_box<T: Copy + PartialEq + Debug>(
+	id_salt: &str,
+	value: &mut T,
+	known: &[T],
+	name: impl Fn(&T) -> Option<&'static str>,
+	ui: &mut Ui,
+) -> Response {
+	let mut changed = false;
+	let mut res = ComboBox::from_id_salt(id_salt)
+		.selected_text(name(value).map_or_else(|| format!("{value:?}"), str::to_string))
+		.show_ui(ui, |ui| {
+			for k in known {
+				changed |= ui
+					.selectable_value(value, *k, name(k).unwrap_or_default())
+					.changed();
+			}
+		})
+		.response;
+	if changed {
+		res.mark_changed();
+	}
+	res
+}
+
+/// a BHAV id with a button to open that BHAV
+pub(crate) fn tree_edit(id: &mut u16, open_tree: &mut Option<u16>, ui: &mut Ui) -> Response {
+	ui.horizontal(|ui| {
+		let res = ui.add(DragValue::new(id).hexadecimal(4, false, true).prefix("0x"));
+		if ui
+			.add_enabled(*id != 0, egui::Button::new("open"))
+			.on_hover_text("open this BHAV in a new tab")
+			.clicked()
+		{
+			*open_tree = Some(*id);
+		}
+		res
+	})
+	.inner
+}
+
+pub(crate) fn tree_fn(
+	name: &str,
+	id: &mut u16,
+	open_tree: &mut Option<u16>,
+	ui: &mut Ui,
+) -> Response {
+	ui.label(name);
+	let res = tree_edit(id, open_tree, ui);
+	ui.end_row();
+	res
+}
+
 pub(crate) fn drag_decimal_fn<T: Numeric>(name: &str, value: &mut T, ui: &mut Ui) -> Response {
 	ui.label(name);
 	let res = ui.add(DragValue::new(value));
@@ -3,13 +3,23 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use crate::editor::Align;
-use dbpf::internal_file::object_data::{ObjectData, Version};
+use dbpf::internal_file::object_data::{ObjectData, ObjectType, ShadowType, Version};
 use eframe::{
 	egui,
 	egui::{ComboBox, DragValue, Response, Ui},
 };
 
-use crate::editor::{drag_checkbox_fn, Editor};
+use crate::editor::{drag_checkbox_fn, numbered_combo_box, Editor};
+
+/// a hex drag value for a field that is typed, but stored as a u16
+fn drag_raw<T: Copy + From<u16> + Into<u16>>(value: &mut T, ui: &mut Ui) -> Response {
+	let mut raw = (*value).into();
+	let res = ui.add(DragValue::new(&mut raw).hexadecimal(1, false, false));
+	if res.changed() {
+		*value = T::from(raw);
+	}
+	res
+}
 
 impl Editor for ObjectData {
 	type EditorState = ();
@@ -150,6 +160,41 @@ impl Editor for ObjectData {
 					res
 				}};
 			}
+			macro_rules! drag_raw {
+				($name:expr, $key:ident) => {{
+					ui.with_layout(egui::Layout::left_to_right(Align::TOP), |ui| {
+						ui.label($name);
+					});
+					let res = drag_raw($key, ui);
+					ui.end_row();
+					res
+				}};
+			}
+			// checkboxes for the named bits of a typed bitfield, the hex value also shows the other bits
+			macro_rules! bits_checkbox {
+				($name:expr, $key:ident, $($c_name:expr => $get:ident / $set:ident),* $(,)?) => {{
+					ui.with_layout(egui::Layout::left_to_right(Align::TOP), |ui| {
+						ui.label($name);
+					});
+					let res = ui.with_layout(
+						egui::Layout::left_to_right(Align::TOP).with_main_wrap(true),
+						|ui| {
+							let mut res = drag_raw($key, ui);
+							$(
+								let mut checked = $key.$get();
+								let c_res = ui.checkbox(&mut checked, $c_name);
+								if c_res.changed() {
+									$key.$set(checked);
+								}
+								res |= c_res;
+							)*
+							res
+						},
+					);
+					ui.end_row();
+					res.response | res.inner
+				}};
+			}
 			macro_rules! drag_checkbox {
                     ($name:expr, $key:ident, $($c_name:expr),*) => {
                         drag_checkbox_fn($name, $key, [$($c_name),*], ui)
@@ -184,107 +229,98 @@ impl Editor for ObjectData {
 
 			separator!("Catalog sort");
 
-			res |= drag_checkbox!(
+			res |= bits_checkbox!(
 				"catalog use",
 				catalog_use,
-				"adults",
-				"children",
-				"group",
-				"teens",
-				"elders",
-				"toddlers"
+				"adults" => adults / set_adults,
+				"children" => children / set_children,
+				"group" => group / set_group,
+				"teens" => teens / set_teens,
+				"elders" => elders / set_elders,
+				"toddlers" => toddlers / set_toddlers,
 			);
-			res |= drag_checkbox!(
+			res |= bits_checkbox!(
 				"room",
 				room_sort,
-				"kitchen",
-				"bedroom",
-				"bathroom",
-				"living room",
-				"outside",
-				"dining room",
-				"misc",
-				"study",
-				"kids"
+				"kitchen" => kitchen / set_kitchen,
+				"bedroom" => bedroom / set_bedroom,
+				"bathroom" => bathroom / set_bathroom,
+				"living room" => living_room / set_living_room,
+				"outside" => outside / set_outside,
+				"dining room" => dining_room / set_dining_room,
+				"misc" => misc / set_misc,
+				"study" => study / set_study,
+				"kids" => kids / set_kids,
 			);
-			res |= drag_checkbox!(
+			res |= bits_checkbox!(
 				"function",
 				function_sort,
-				"seating",
-				"surfaces",
-				"appliances",
-				"electronics",
-				"plumbing",
-				"decorative",
-				"general",
-				"lighting",
-				"hobbies",
-				"",
-				"aspiration rewards",
-				"career rewards"
-			);
-			res |= drag_checkbox!(
-				"valid ep",
-				expansion_flags_1,
-				"base",
-				"university",
-				"night life",
-				"business",
-				"family",
-				"glamour",
-				"pets",
-				"seasons",
-				"celebration",
-				"HM fashion",
-				"bon voyage",
-				"teen style",
-				"",
-				"free time",
-				"kitchen & bath",
-				"ikea"
+				"seating" => seating / set_seating,
+				"surfaces" => surfaces / set_surfaces,
+				"appliances" => appliances / set_appliances,
+				"electronics" => electronics / set_electronics,
+				"plumbing" => plumbing / set_plumbing,
+				"decorative" => decorative / set_decorative,
+				"general" => general / set_general,
+				"lighting" => lighting / set_lighting,
+				"hobbies" => hobbies / set_hobbies,
+				"aspiration rewards" => aspiration_rewards / set_aspiration_rewards,
+				"career rewards" => career_rewards / set_career_rewards,
 			);
-			res |= drag_checkbox!(
-				"valid ep",
-				expansion_flags_2,
-				"apartment life",
-				"mansion & garden"
-			); // TODO store edition
-			res |= drag_checkbox!(
+			res |= bits_checkbox!(
 				"build mode type",
 				build_mode_type,
-				"miscellaneous",
-				"",
-				"garden",
-				"doors/windows"
-			);
-			res |= drag_checkbox!(
-				"build mode subsort",
-				build_mode_subsort,
-				"/trees/doors",
-				"/shrubs/multi-story windows",
-				"/flowers/windows",
-				"columns//gates",
-				"/gardening/archways",
-				"stairs//",
-				"swimming pool//",
-				"",
-				"multi-story columns//multi-story doors",
-				"column arches//",
-				"garage//",
-				"elevators//",
-				"architecture//"
+				"miscellaneous" => miscellaneous / set_miscellaneous,
+				"garden" => garden / set_garden,
+				"doors/windows" => doors_windows / set_doors_windows,
 			);
+			// the subsort bits have a different meaning for every build mode type
+			let subsort_type = *build_mode_type;
+			res |= if subsort_type.garden() {
+				bits_checkbox!(
+					"build mode subsort",
+					build_mode_subsort,
+					"trees" => trees_doors / set_trees_doors,
+					"shrubs" => shrubs_multi_story_windows / set_shrubs_multi_story_windows,
+					"flowers" => flowers_windows / set_flowers_windows,
+					"gardening" => gardening_archways / set_gardening_archways,
+				)
+			} else if subsort_type.doors_windows() {
+				bits_checkbox!(
+					"build mode subsort",
+					build_mode_subsort,
+					"doors" => trees_doors / set_trees_doors,
+					"multi-story windows" => shrubs_multi_story_windows / set_shrubs_multi_story_windows,
+					"windows" => flowers_windows / set_flowers_windows,
+					"gates" => columns_gates / set_columns_gates,
+					"archways" => gardening_archways / set_gardening_archways,
+					"multi-story doors" => multi_story_columns_doors / set_multi_story_columns_doors,
+				)
+			} else {
+				bits_checkbox!(
+					"build mode subsort",
+					build_mode_subsort,
+					"columns" => columns_gates / set_columns_gates,
+					"stairs" => stairs / set_stairs,
+					"swimming pool" => swimming_pool / set_swimming_pool,
+					"multi-story columns" => multi_story_columns_doors / set_multi_story_columns_doors,
+					"column arches" => column_arches / set_column_arches,
+					"garage" => garage / set_garage,
+					"elevators" => elevators / set_elevators,
+					"architecture" => architecture / set_architecture,
+				)
+			};
 			res |= drag_hex!("function sub sort", function_sub_sort);
 			res |= drag_hex!("downtown sort", downtown_sort);
 			res |= drag_hex!("holiday sort", vacation_sort);
-			res |= drag_checkbox!(
+			res |= bits_checkbox!(
 				"community sort",
 				community_sort,
-				"food",
-				"shopping",
-				"outdoor",
-				"street",
-				"miscellaneous"
+				"food" => food / set_food,
+				"shopping" => shopping / set_shopping,
+				"outdoor" => outdoor / set_outdoor,
+				"street" => street / set_street,
+				"miscellaneous" => miscellaneous / set_miscellaneous,
 			);
 
 			separator!("Catalog ratings");
@@ -296,16 +332,16 @@ impl Editor for ObjectData {
 			res |= drag!("energy", energy_rating);
 			res |= drag!("fun", fun_rating);
 			res |= drag!("room", room_rating);
-			res |= drag_checkbox!(
+			res |= bits_checkbox!(
 				"gives skill",
 				gives_skill,
-				"cooking",
-				"mechanical",
-				"logic",
-				"body",
-				"creativity",
-				"charisma",
-				"cleaning"
+				"cooking" => cooking / set_cooking,
+				"mechanical" => mechanical / set_mechanical,
+				"logic" => logic / set_logic,
+				"body" => body / set_body,
+				"creativity" => creativity / set_creativity,
+				"charisma" => charisma / set_charisma,
+				"cleaning" => cleaning / set_cleaning,
 			);
 			res |= drag!("scratch", scratch_rating);
 			res |= drag!("chew", chew_rating);
@@ -331,7 +367,19 @@ impl Editor for ObjectData {
 			res |= drag_hex!("multi-tile sub index", multi_tile_sub_index);
 			res |= drag_hex!("multi-tile lead object", multi_tile_lead);
 			res |= drag_hex!("level offset", level_offset);
-			res |= drag_hex!("shadow type", shadow_type);
+			ui.label("shadow type");
+			res |= ui
+				.horizontal(|ui| {
+					numbered_combo_box(
+						"shadow type",
+						shadow_type,
+						ShadowType::KNOWN,
+						ShadowType::name,
+						ui,
+					) | drag_raw(shadow_type, ui)
+				})
+				.inner;
+			ui.end_row();
 			res |= drag_hex!("front direction", front_direction);
 			res |= drag_hex!("chair entry flags", chair_entry_flags);
 			res |= drag_hex!("tile width", tile_width);
@@ -377,7 +425,19 @@ impl Editor for ObjectData {
 
 			separator!("Miscellaneous");
 
-			res |= drag_hex!("type", object_type);
+			ui.label("type");
+			res |= ui
+				.horizontal(|ui| {
+					numbered_combo_box(
+						"object type",
+						object_type,
+						ObjectType::KNOWN,
+						ObjectType::name,
+						ui,
+					) | drag_raw(object_type, ui)
+				})
+				.inner;
+			ui.end_row();
 			res |= drag!("look at score", look_at_score);
 			res |= drag_checkbox!("unlockable", unlockable, "");
 			res |= drag_hex!("object ownership flags", object_ownership);
@@ -391,7 +451,11 @@ impl Editor for ObjectData {
 			res |= drag_checkbox!("no new name from template", no_new_name_from_template, "");
 			res |= drag_hex!("object version", object_version);
 			res |= drag_checkbox!("ignore model index in icon", ignore_current_model_index, "");
-			res |= drag_hex!("for sale flags", for_sale_flags);
+			res |= bits_checkbox!(
+				"for sale flags",
+				for_sale_flags,
+				"for sale" => for_sale / set_for_sale,
+			);
 			res |= drag_checkbox!("inhibit suit copying", inhibit_suit_copying, "");
 			res |= drag_hex!("selector category", selector_category);
 			res |= drag_hex!("selector sub-category", selector_sub_category);
@@ -401,10 +465,39 @@ impl Editor for ObjectData {
 			res |= drag_hex!("thumbnail flags", thumbnail_flags);
 			res |= drag_hex!("requirements", requirements);
 
+			separator!("Sims 2 expansion packs");
+
+			res |= bits_checkbox!(
+				"valid ep",
+				expansion_flags_1,
+				"base" => base / set_base,
+				"university" => university / set_university,
+				"night life" => nightlife / set_nightlife,
+				"business" => business / set_business,
+				"family" => family / set_family,
+				"glamour" => glamour / set_glamour,
+				"pets" => pets / set_pets,
+				"seasons" => seasons / set_seasons,
+				"celebration" => celebration / set_celebration,
+				"HM fashion" => hm_fashion / set_hm_fashion,
+				"bon voyage" => bon_voyage / set_bon_voyage,
+				"teen style" => teen_style / set_teen_style,
+				"free time" => free_time / set_free_time,
+				"kitchen & bath" => kitchen_bath / set_kitchen_bath,
+				"ikea" => ikea / set_ikea,
+			);
+			res |= bits_checkbox!(
+				"valid ep",
+				expansion_flags_2,
+				"apartment life" => apartment_life / set_apartment_life,
+				"mansion & garden" => mansion_garden / set_mansion_garden,
+			); // TODO store edition
+
 			separator!("Sims 1");
 
-			res |= drag_hex!("dynamic sprite base id", expansion_flags_1);
-			res |= drag_hex!("number of dynamic sprites", expansion_flags_2);
+			res |= drag_raw!("dynamic sprite base id", expansion_flags_1);
+			res |= drag_raw!("number of dynamic sprites", expansion_flags_2);
+
 			res |= drag_hex!("thumbnail graphic", selector_category);
 			res |= drag_hex!("shadow flags", selector_sub_category);
 			res |= drag_hex!("shadow brightness", object_size);
@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use dbpf::internal_file::behaviour::object_functions::{function_name, Entry, ObjectFunctions};
+use eframe::{
+	egui,
+	egui::{Response, Ui},
+};
+
+use crate::editor::{tree_edit, Editor, OpenTreeState};
+
+impl Editor for ObjectFunctions {
+	type EditorState = OpenTreeState;
+
+	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = ui
+			.horizontal_wrapped(|ui| {
+				ui.label("file name") | self.file_name.name.show_editor(&mut 500.0, ui)
+			})
+			.inner;
+
+		egui::Grid::new("object functions")
+			.num_columns(3)
+			.striped(true)
+			.show(ui, |ui| {
+				ui.label("function");
+				ui.label("guard");
+				ui.label("action");
+				ui.end_row();
+
+				for (i, entry) in self.entries.iter_mut().enumerate() {
+					ui.label(format!("{i}: {}", function_name(i).unwrap_or("unknown")));
+					res |= ui
+						.push_id((i, "guard"), |ui| {
+							tree_edit(&mut entry.guardian_id, &mut state.open_tree, ui)
+						})
+						.inner;
+					res |= ui
+						.push_id((i, "action"), |ui| {
+							tree_edit(&mut entry.action_id, &mut state.open_tree, ui)
+						})
+						.inner;
+					ui.end_row();
+				}
+			});
+
+		ui.horizontal(|ui| {
+			if ui.button("➕").clicked() {
+				self.entries.push(Entry::default());
+				res.mark_changed();
+			}
+			if ui
+				.add_enabled(!self.entries.is_empty(), egui::Button::new("🗑"))
+				.clicked()
+			{
+				self.entries.pop();
+				res.mark_changed();
+			}
+		});
+		res
+	}
+}
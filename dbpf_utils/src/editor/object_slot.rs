@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use dbpf::internal_file::object_slot::{ObjectSlot, Slot, SlotType, SLOT_FLAG_NAMES};
+use eframe::{
+	egui,
+	egui::{DragValue, Response, Ui},
+};
+
+use crate::editor::{drag_checkbox_fn, drag_decimal_fn, drag_hex_fn, numbered_combo_box, Editor};
+
+fn show_slot(slot: &mut Slot, version: u32, ui: &mut Ui) -> Response {
+	egui::Grid::new("slot")
+		.num_columns(2)
+		.show(ui, |ui| {
+			ui.label("type");
+			let mut res = numbered_combo_box(
+				"type",
+				&mut slot.slot_type,
+				SlotType::KNOWN,
+				SlotType::name,
+				ui,
+			);
+			ui.end_row();
+
+			ui.label("position");
+			ui.horizontal(|ui| {
+				for (axis, value) in ["x", "y", "z"].into_iter().zip(&mut slot.position) {
+					res |= ui.add(
+						DragValue::new(value)
+							.speed(0.01)
+							.prefix(format!("{axis}: ")),
+					);
+				}
+			});
+			ui.end_row();
+
+			res |= drag_decimal_fn("standing", &mut slot.standing, ui);
+			res |= drag_decimal_fn("sitting", &mut slot.sitting, ui);
+			res |= drag_decimal_fn("ground", &mut slot.ground, ui);
+			res |= drag_checkbox_fn("flags", &mut slot.flags, SLOT_FLAG_NAMES, ui);
+			res |= drag_decimal_fn("snap target slot", &mut slot.snap_target_slot, ui);
+			if version >= 6 {
+				res |= drag_decimal_fn("min proximity", &mut slot.min_proximity, ui);
+				res |= drag_decimal_fn("max proximity", &mut slot.max_proximity, ui);
+				res |= drag_decimal_fn("optimal proximity", &mut slot.optimal_proximity, ui);
+				res |= drag_hex_fn("unknown 1", &mut slot.unknown[0], ui);
+				res |= drag_hex_fn("unknown 2", &mut slot.unknown[1], ui);
+			}
+			if version >= 7 {
+				res |= drag_decimal_fn("gradient", &mut slot.gradient, ui);
+			}
+			if version >= 8 {
+				res |= drag_decimal_fn("height", &mut slot.height, ui);
+			}
+			if version >= 9 {
+				res |= drag_decimal_fn("facing", &mut slot.facing, ui);
+			}
+			if version >= 10 {
+				res |= drag_decimal_fn("resolution", &mut slot.resolution, ui);
+			}
+			res
+		})
+		.inner
+}
+
+impl Editor for ObjectSlot {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = ui
+			.horizontal_wrapped(|ui| {
+				ui.label("file name") | self.file_name.name.show_editor(&mut 500.0, ui)
+			})
+			.inner;
+		res |= egui::Grid::new("slot header")
+			.num_columns(2)
+			.show(ui, |ui| {
+				drag_hex_fn("unknown", &mut self.unknown, ui)
+					| drag_decimal_fn("version", &mut self.version, ui)
+			})
+			.inner;
+
+		let version = self.version;
+		let mut delete = None;
+		for (i, slot) in self.slots.iter_mut().enumerate() {
+			let title = format!(
+				"{i}: {}",
+				slot.slot_type
+					.name()
+					.map_or_else(|| format!("{:?}", slot.slot_type), str::to_string)
+			);
+			ui.push_id(i, |ui| {
+				ui.collapsing(title, |ui| {
+					res |= show_slot(slot, version, ui);
+					if ui.button("🗑 delete slot").clicked() {
+						delete = Some(i);
+					}
+				});
+			});
+		}
+		if let Some(i) = delete {
+			self.slots.remove(i);
+			res.mark_changed();
+		}
+		if ui.button("➕").clicked() {
+			self.slots.push(Slot::default());
+			res.mark_changed();
+		}
+		res
+	}
+}
@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use dbpf::internal_file::{
+	behaviour::pie_menu_functions::{
+		Attenuation, Interaction, MotiveAdvertisement, PieMenuFunctions, FORMAT_MOTIVE_TABLES,
+		INTERACTION_FLAG_NAMES, OLD_MOTIVE_COUNT,
+	},
+	text_list::{LanguageCode, TextList},
+};
+use eframe::{
+	egui,
+	egui::{DragValue, Response, Ui},
+};
+
+use crate::editor::{
+	drag_checkbox_fn, drag_decimal_fn, drag_hex_fn, numbered_combo_box, tree_fn, Editor,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct PieMenuFunctionsEditorState {
+	/// the BHAV the user asked to open, taken by the application
+	pub open_tree: Option<u16>,
+	/// the English interaction names from the TTAs with the same instance
+	strings: Vec<String>,
+}
+
+impl PieMenuFunctionsEditorState {
+	/// Take the interaction names from the TTAs that belong to the TTAB, `None` when there are none
+	pub fn set_strings(&mut self, strings: Option<&TextList>) {
+		self.strings = strings
+			.map(|strings| strings.strings(LanguageCode::default()))
+			.unwrap_or_default();
+	}
+}
+
+fn show_interaction(
+	interaction: &mut Interaction,
+	format: u32,
+	open_tree: &mut Option<u16>,
+	ui: &mut Ui,
+) -> Response {
+	let mut res = egui::Grid::new("interaction")
+		.num_columns(2)
+		.show(ui, |ui| {
+			let mut res = tree_fn("action", &mut interaction.action_id, open_tree, ui);
+			res |= tree_fn("guard", &mut interaction.guardian_id, open_tree, ui);
+			res |= drag_decimal_fn("string index", &mut interaction.string_index, ui);
+			res |= drag_checkbox_fn("flags", &mut interaction.flags, INTERACTION_FLAG_NAMES, ui);
+			res |= drag_hex_fn("flags 2", &mut interaction.flags2, ui);
+			res |= drag_decimal_fn("autonomy", &mut interaction.autonomy, ui);
+
+			ui.label("attenuation");
+			ui.horizontal(|ui| {
+				let attenuation = &mut interaction.attenuation;
+				res |= numbered_combo_box(
+					"attenuation",
+					attenuation,
+					Attenuation::KNOWN,
+					Attenuation::name,
+					ui,
+				);
+				if *attenuation == Attenuation::Custom {
+					res |= ui.add(DragValue::new(&mut interaction.attenuation_value).speed(0.01));
+				}
+			});
+			ui.end_row();
+
+			res |= drag_hex_fn("joining index", &mut interaction.joining_index, ui);
+			if format >= 0x45 {
+				res |= drag_decimal_fn("ui display type", &mut interaction.ui_display_type, ui);
+			}
+			if format >= 0x46 {
+				res |= drag_hex_fn("facial animation", &mut interaction.facial_animation, ui);
+			}
+			if format >= 0x4A {
+				res |= drag_decimal_fn("memory multiplier", &mut interaction.memory_multiplier, ui);
+				res |= drag_hex_fn("object type", &mut interaction.object_type, ui);
+			}
+			if format >= 0x4C {
+				res |= drag_hex_fn("model table", &mut interaction.model_table_id, ui);
+			}
+			res
+		})
+		.inner;
+
+	for (i, table) in interaction.motive_tables.iter_mut().enumerate() {
+		ui.collapsing(
+			format!("motive table {i} ({} motives)", table.len()),
+			|ui| {
+				egui::Grid::new(("motives", i))
+					.num_columns(4)
+					.striped(true)
+					.show(ui, |ui| {
+						ui.label("motive");
+						ui.label("min");
+						ui.label("delta");
+						ui.label("personality");
+						ui.end_row();
+						for (
+							motive,
+							MotiveAdvertisement {
+								min,
+								delta,
+								personality,
+							},
+						) in table.iter_mut().enumerate()
+						{
+							ui.label(motive.to_string());
+							res |= ui.add(DragValue::new(min));
+							res |= ui.add(DragValue::new(delta));
+							res |= ui.add(DragValue::new(personality).hexadecimal(1, false, false));
+							ui.end_row();
+						}
+					});
+				// older formats always have the same number of motives
+				if format >= FORMAT_MOTIVE_TABLES {
+					ui.horizontal(|ui| {
+						if ui.button("➕").clicked() {
+							table.push(MotiveAdvertisement::default());
+							res.mark_changed();
+						}
+						if ui
+							.add_enabled(!table.is_empty(), egui::Button::new("🗑"))
+							.clicked()
+						{
+							table.pop();
+							res.mark_changed();
+						}
+					});
+				}
+			},
+		);
+	}
+	if format >= FORMAT_MOTIVE_TABLES {
+		ui.horizontal(|ui| {
+			if ui.button("add motive table").clicked() {
+				interaction.motive_tables.push(vec![]);
+				res.mark_changed();
+			}
+			if ui
+				.add_enabled(
+					!interaction.motive_tables.is_empty(),
+					egui::Button::new("remove motive table"),
+				)
+				.clicked()
+			{
+				interaction.motive_tables.pop();
+				res.mark_changed();
+			}
+		});
+	}
+	res
+}
+
+impl Editor for PieMenuFunctions {
+	type EditorState = PieMenuFunctionsEditorState;
+
+	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = ui
+			.horizontal_wrapped(|ui| {
+				ui.label("file name") | self.file_name.name.show_editor(&mut 500.0, ui)
+			})
+			.inner;
+		res |= egui::Grid::new("ttab header")
+			.num_columns(2)
+			.show(ui, |ui| {
+				drag_hex_fn("unknown", &mut self.unknown, ui)
+					| drag_hex_fn("format", &mut self.format, ui)
+			})
+			.inner;
+		if state.strings.is_empty() {
+			ui.label("no pie menu strings (TTAs) with the same instance were found");
+		}
+
+		let format = self.format;
+		let mut delete = None;
+		for (i, interaction) in self.interactions.iter_mut().enumerate() {
+			let name = interaction
+				.name(&state.strings)
+				.unwrap_or("(no name)")
+				.to_string();
+			ui.push_id(i, |ui| {
+				ui.collapsing(format!("{i}: {name}"), |ui| {
+					res |= show_interaction(interaction, format, &mut state.open_tree, ui);
+					if ui.button("🗑 delete interaction").clicked() {
+						delete = Some(i);
+					}
+				});
+			});
+		}
+		if let Some(i) = delete {
+			self.interactions.remove(i);
+			res.mark_changed();
+		}
+		if ui.button("➕").clicked() {
+			self.interactions.push(Interaction {
+				joining_index: 0xFFFFFFFF,
+				motive_tables: if format >= FORMAT_MOTIVE_TABLES {
+					vec![vec![]]
+				} else {
+					vec![vec![MotiveAdvertisement::default(); OLD_MOTIVE_COUNT]]
+				},
+				..Default::default()
+			});
+			res.mark_changed();
+		}
+		res
+	}
+}
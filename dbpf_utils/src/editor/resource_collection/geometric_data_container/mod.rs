@@ -5,6 +5,7 @@
 use std::collections::BTreeMap;
 use std::iter;
 use std::ops::Deref;
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
@@ -14,10 +15,13 @@ use crate::editor::resource_collection::geometric_data_container::GMDCEditorCrea
 	Create, GetAttrib, GetUniformBlock, IncompleteFramebuffer, NoContext, OpenGL, ProgramLink,
 	ShaderCompile,
 };
-use crate::editor::Editor;
+use crate::{async_execute, editor::Editor};
 use dbpf::internal_file::resource_collection::geometric_data_container::math::Transform;
 use dbpf::internal_file::resource_collection::geometric_data_container::{
 	math::{Mat4, Vertex},
+	processing::NormalMode,
+	simplify::SimplifyTarget,
+	skeleton::Skeleton,
 	AttributeType, GeometricDataContainer, PrimitiveType,
 };
 use eframe::{
@@ -164,23 +168,105 @@ pub struct GMDCEditorStateData {
 pub struct GMDCEditorState {
 	data: Result<GMDCEditorStateData, GMDCEditorCreationError>,
 
-	save_file_picker: Option<oneshot::Receiver<Option<FileHandle>>>,
+	save_file_picker: Option<(MeshExport, oneshot::Receiver<Option<FileHandle>>)>,
+	open_file_picker: Option<(MeshImport, oneshot::Receiver<Option<FileHandle>>)>,
+	/// keep the bones, blend groups and name of the current mesh when importing
+	import_keep_original: bool,
+	import_error: Option<String>,
+	/// problems found by the last validation, `None` if it was not run
+	validation: Option<Vec<String>>,
+	/// the fraction of triangles to keep when simplifying
+	simplify_ratio: f32,
+	/// the joint hierarchy of the CRES that uses this mesh, if it was found in the open package
+	skeleton: Option<Skeleton>,
 
 	total_polys: usize,
 	total_memory: usize,
 }
 
+impl GMDCEditorState {
+	/// Set the skeleton that is exported to glTF together with the mesh, see
+	/// [find_skeleton](dbpf::internal_file::resource_collection::geometric_data_container::skeleton::find_skeleton)
+	pub fn set_skeleton(&mut self, skeleton: Option<Skeleton>) {
+		self.skeleton = skeleton;
+	}
+}
+
 impl Default for GMDCEditorState {
 	fn default() -> Self {
 		Self {
 			data: Err(NoContext),
 			save_file_picker: None,
+			open_file_picker: None,
+			import_keep_original: true,
+			import_error: None,
+			validation: None,
+			simplify_ratio: 0.5,
+			skeleton: None,
 			total_polys: 0,
 			total_memory: 0,
 		}
 	}
 }
 
+/// the format a file picker was opened for
+#[derive(Copy, Clone, Debug)]
+enum MeshExport {
+	Gltf,
+	Obj,
+	/// a single mesh group
+	Stl(usize),
+}
+
+#[derive(Copy, Clone, Debug)]
+enum MeshImport {
+	Gltf,
+	/// replace a single mesh group
+	Obj(usize),
+}
+
+fn export_mesh_file(
+	gmdc: &GeometricDataContainer,
+	skeleton: Option<&Skeleton>,
+	export: MeshExport,
+	handle: &FileHandle,
+) -> Result<(), String> {
+	let bytes = match export {
+		MeshExport::Gltf => gmdc.export_gltf(skeleton).map_err(|e| e.to_string())?.0,
+		MeshExport::Obj => {
+			let mtl_name = Path::new(&handle.file_name())
+				.with_extension("mtl")
+				.to_string_lossy()
+				.to_string();
+			let export = gmdc.export_obj(&mtl_name, |_| None);
+			#[cfg(not(target_arch = "wasm32"))]
+			std::fs::write(handle.path().with_extension("mtl"), export.mtl)
+				.map_err(|e| e.to_string())?;
+			export.obj.into_bytes()
+		}
+		MeshExport::Stl(mesh) => gmdc
+			.meshes
+			.get(mesh)
+			.and_then(|mesh| gmdc.export_stl(mesh))
+			.ok_or("only mesh groups made of triangles can be exported to STL")?,
+	};
+	futures::executor::block_on(handle.write(&bytes)).map_err(|e| e.to_string())
+}
+
+/// recreate the editor state after the mesh data changed, keeping the options of the user
+fn refresh_state(gmdc: &GeometricDataContainer, state: &mut GMDCEditorState, ctx: &egui::Context) {
+	let gl = state
+		.data
+		.as_ref()
+		.ok()
+		.map(|data| data.gl_state.gl.clone());
+	let keep_original = state.import_keep_original;
+	let simplify_ratio = state.simplify_ratio;
+	*state = gmdc.new_editor(ctx, &gl);
+	state.import_keep_original = keep_original;
+	state.simplify_ratio = simplify_ratio;
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum GMDCEditorCreationError {
 	#[error("Could not get a OpenGL context, does this device support OpenGL?")]
@@ -822,27 +908,67 @@ impl Editor for GeometricDataContainer {
 		GMDCEditorState {
 			data,
 			save_file_picker: None,
+			open_file_picker: None,
+			import_keep_original: true,
+			import_error: None,
+			validation: None,
+			simplify_ratio: 0.5,
+			skeleton: None,
 			total_memory,
 			total_polys,
 		}
 	}
 
 	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
-		if let Some(picker) = &mut state.save_file_picker {
+		if let Some((export, picker)) = &mut state.save_file_picker {
 			if let Ok(Some(handle)) = picker.try_recv() {
+				let export = *export;
 				state.save_file_picker = None;
 				if let Some(handle) = handle {
-					let gltf = self.export_gltf();
-					if let Some(gltf) = gltf {
-						let res = futures::executor::block_on(handle.write(&gltf.0));
-						if let Err(e) = res {
-							error!(?e);
+					if let Err(error) =
+						export_mesh_file(self, state.skeleton.as_ref(), export, &handle)
+					{
+						error!(%error);
+					}
+				}
+			}
+		}
+
+		let mut changed = false;
+		if let Some((import, picker)) = &mut state.open_file_picker {
+			if let Ok(Some(handle)) = picker.try_recv() {
+				let import = *import;
+				state.open_file_picker = None;
+				if let Some(handle) = handle {
+					let bytes = futures::executor::block_on(handle.read());
+					let result = match import {
+						MeshImport::Gltf => {
+							let original = state.import_keep_original.then_some(&*self);
+							GeometricDataContainer::import_gltf(&bytes, original)
+								.map(|new| *self = new)
+								.map_err(|e| e.to_string())
+						}
+						MeshImport::Obj(mesh) => self
+							.import_obj(mesh, &String::from_utf8_lossy(&bytes))
+							.map_err(|e| e.to_string()),
+					};
+					match result {
+						Ok(()) => {
+							refresh_state(self, state, ui.ctx());
+							changed = true;
+						}
+						Err(error) => {
+							error!(%error);
+							state.import_error = Some(error);
 						}
 					}
 				}
 			}
 		}
 
+		let mut export_request = None;
+		let mut import_request = None;
+
 		let available = ui.available_size_before_wrap();
 
 		egui::ScrollArea::vertical()
@@ -875,16 +1001,23 @@ impl Editor for GeometricDataContainer {
 					// TODO name in tooltip
 					ui.group(|ui| {
 						ui.vertical(|ui| {
-							for (mesh, visible) in self.meshes.iter_mut().zip(
-								state
-									.data
-									.as_mut()
-									.map(|state| {
-										Either::Left(state.display_state.meshes_visible.iter_mut())
-									})
-									.unwrap_or(Either::Right(iter::repeat(())))
-									.factor_into_iter(),
-							) {
+							for (i, (mesh, visible)) in self
+								.meshes
+								.iter_mut()
+								.zip(
+									state
+										.data
+										.as_mut()
+										.map(|state| {
+											Either::Left(
+												state.display_state.meshes_visible.iter_mut(),
+											)
+										})
+										.unwrap_or(Either::Right(iter::repeat(())))
+										.factor_into_iter(),
+								)
+								.enumerate()
+							{
 								ui.horizontal(|ui| {
 									if let Either::Left(visible) = visible {
 										ui.add(egui::Checkbox::without_text(visible));
@@ -919,6 +1052,25 @@ impl Editor for GeometricDataContainer {
 
 									ui.add(egui::DragValue::new(&mut mesh.opacity))
 										.on_hover_text("opacity");
+
+									if ui
+										.small_button("STL")
+										.on_hover_text("export this group to a .stl file")
+										.clicked()
+									{
+										export_request = Some(MeshExport::Stl(i));
+									}
+									if ui
+										.small_button("OBJ")
+										.on_hover_text(
+											"replace this group with an object from a .obj file\n\
+											bone weights and morphs are only kept if the vertices and faces \
+											are still in the order they were exported in",
+										)
+										.clicked()
+									{
+										import_request = Some(MeshImport::Obj(i));
+									}
 								});
 							}
 						});
@@ -986,31 +1138,135 @@ impl Editor for GeometricDataContainer {
 			});
 
 		ui.horizontal_wrapped(|ui| {
-			/*if ui
+			if ui
 				.button("Export glTF")
-				.on_hover_text("export the mesh to a .gltf file")
-				.clicked() && state.save_file_picker.is_none()
+				.on_hover_text(if state.skeleton.is_some() {
+					"export the mesh and the skeleton of the model that uses it to a .glb file"
+				} else {
+					"export the mesh to a .glb file\n\
+					no model that uses this mesh was found in the package, so bones are exported without their hierarchy"
+				})
+				.clicked()
 			{
-				let (tx, rx) = oneshot::channel();
-				let dialog = rfd::AsyncFileDialog::new()
-					.set_file_name(format!(
-						"{}.glb",
-						String::from_utf8_lossy(&self.file_name.name.0.data)
-					))
-					.add_filter("OpenGL Transfer Format", &["gltf", "glb"]);
-				// TODO global options open file path set directory
-				let dialog = dialog.save_file();
-				async_execute(async move {
-					let file = dialog.await;
-					let _ = if let Some(handle) = file {
-						tx.send(Some(handle))
-					} else {
-						tx.send(None)
-					};
-				});
-				state.save_file_picker = Some(rx);
-			}*/
+				export_request = Some(MeshExport::Gltf);
+			}
+			if ui
+				.button("Export OBJ")
+				.on_hover_text("export all groups to a .obj file with a .mtl material library")
+				.clicked()
+			{
+				export_request = Some(MeshExport::Obj);
+			}
+
+			if ui
+				.button("Import glTF")
+				.on_hover_text("replace the mesh with the contents of a .glb or .gltf file")
+				.clicked()
+			{
+				import_request = Some(MeshImport::Gltf);
+			}
+			ui.checkbox(
+				&mut state.import_keep_original,
+				"Keep bones and blend groups",
+			)
+			.on_hover_text(
+				"keep the bones, blend groups and resource name of the current mesh when importing",
+			);
+
+			if let Some(error) = &state.import_error {
+				ui.colored_label(ui.visuals().error_fg_color, error);
+			}
+		});
+
+		let mut processed = false;
+		ui.horizontal_wrapped(|ui| {
+			if ui
+				.button("Smooth normals")
+				.on_hover_text("recompute the normals, averaging them where triangles meet")
+				.clicked()
+			{
+				self.recompute_normals(NormalMode::Smooth);
+				processed = true;
+			}
+			if ui
+				.button("Flat normals")
+				.on_hover_text("recompute the normals, giving every triangle its own vertices")
+				.clicked()
+			{
+				self.recompute_normals(NormalMode::Flat);
+				processed = true;
+			}
+			if ui
+				.button("Tangents")
+				.on_hover_text("generate tangents for bump mapped materials")
+				.clicked()
+			{
+				self.generate_tangents();
+				processed = true;
+			}
+			if ui
+				.button("Weld")
+				.on_hover_text("merge vertices that are exactly the same")
+				.clicked()
+			{
+				self.weld_vertices();
+				processed = true;
+			}
+			if ui
+				.button("Bounding mesh")
+				.on_hover_text("regenerate the bounding meshes from the triangles of the mesh")
+				.clicked()
+			{
+				self.recompute_bounding_mesh();
+				processed = true;
+			}
+			if ui
+				.button("Simplify")
+				.on_hover_text(
+					"remove triangles where it changes the shape the least, \
+					keeping texture seams, bone weights and morphs intact",
+				)
+				.clicked()
+			{
+				*self = self.simplified(SimplifyTarget::Ratio(state.simplify_ratio));
+				processed = true;
+			}
+			ui.add(
+				egui::DragValue::new(&mut state.simplify_ratio)
+					.range(0.0..=1.0)
+					.speed(0.01),
+			)
+			.on_hover_text("the fraction of triangles to keep");
+			if ui
+				.button("Validate")
+				.on_hover_text("check that the attribute groups and buffers are consistent")
+				.clicked()
+			{
+				state.validation = Some(
+					self.validate_attribute_groups()
+						.iter()
+						.map(ToString::to_string)
+						.collect(),
+				);
+			}
+		});
+		match &state.validation {
+			Some(problems) if problems.is_empty() => {
+				ui.label("no problems found");
+			}
+			Some(problems) => {
+				for problem in problems {
+					ui.colored_label(ui.visuals().warn_fg_color, problem);
+				}
+			}
+			None => {}
+		}
+		if processed {
+			refresh_state(self, state, ui.ctx());
+			changed = true;
+		}
 
+		ui.horizontal_wrapped(|ui| {
 			if let Ok(state_data) = &mut state.data {
 				for mode in all::<DisplayMode>() {
 					ui.radio_value(
@@ -1022,7 +1278,48 @@ impl Editor for GeometricDataContainer {
 			}
 		});
 
-		match &mut state.data {
+		if let Some(export) = export_request.filter(|_| state.save_file_picker.is_none()) {
+			let name = String::from_utf8_lossy(&self.file_name.name.0.data).to_string();
+			let (file_name, filter, extension) = match export {
+				MeshExport::Gltf => (format!("{name}.glb"), "glTF Binary", "glb"),
+				MeshExport::Obj => (format!("{name}.obj"), "Wavefront OBJ", "obj"),
+				MeshExport::Stl(mesh) => (
+					format!("{}.stl", String::from_utf8_lossy(&self.meshes[mesh].name.0)),
+					"STL",
+					"stl",
+				),
+			};
+			// TODO global options open file path set directory
+			let dialog = rfd::AsyncFileDialog::new()
+				.set_file_name(file_name)
+				.add_filter(filter, &[extension])
+				.save_file();
+			let (tx, rx) = oneshot::channel();
+			async_execute(async move {
+				let file = dialog.await;
+				let _ = tx.send(file);
+			});
+			state.save_file_picker = Some((export, rx));
+		}
+		if let Some(import) = import_request.filter(|_| state.open_file_picker.is_none()) {
+			let dialog = match import {
+				MeshImport::Gltf => {
+					rfd::AsyncFileDialog::new().add_filter("glTF", &["glb", "gltf"])
+				}
+				MeshImport::Obj(_) => {
+					rfd::AsyncFileDialog::new().add_filter("Wavefront OBJ", &["obj"])
+				}
+			}
+			.pick_file();
+			let (tx, rx) = oneshot::channel();
+			async_execute(async move {
+				let file = dialog.await;
+				let _ = tx.send(file);
+			});
+			state.open_file_picker = Some((import, rx));
+		}
+
+		let mut response = match &mut state.data {
 			Ok(state_data) => {
 				let display_data = &mut state_data.display_state;
 
@@ -1407,7 +1704,11 @@ impl Editor for GeometricDataContainer {
 					.response
 			}
 			Err(err) => ui.label(format!("{err}")),
+		};
+		if changed {
+			response.mark_changed();
 		}
+		response
 	}
 }
 
@@ -2,43 +2,158 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::sync::Arc;
+use std::{
+	collections::HashMap,
+	fmt::{Debug, Formatter},
+};
 
 use dbpf::{
 	common::BigString,
-	internal_file::resource_collection::material_definition::{MaterialDefinition, Property},
+	internal_file::resource_collection::{
+		material_definition::{
+			texture_key, MaterialDefinition, MaterialType, Property, PropertyValue,
+		},
+		texture_resource::decoded_texture::DecodedTexture,
+	},
 };
 use eframe::{
-	egui::{Context, Response, Ui},
-	glow,
+	egui,
+	egui::{ColorImage, ComboBox, Context, DragValue, Response, TextureHandle, TextureOptions, Ui},
 };
 
 use crate::editor::vector::VecEditorState;
 use crate::editor::Editor;
 
+/// size of the texture previews next to texture properties
+const PREVIEW_SIZE: f32 = 64.0;
+
+#[derive(Clone, Default, PartialEq)]
+pub struct PropertyEditorState {
+	/// the textures of the material, indexed by their texture key
+	previews: HashMap<Vec<u8>, TextureHandle>,
+}
+
+impl Debug for PropertyEditorState {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_list()
+			.entries(
+				self.previews
+					.values()
+					.map(|img| format!("{}: {:?}", img.name(), img.size_vec2())),
+			)
+			.finish()
+	}
+}
+
 impl Editor for Property {
-	type EditorState = ();
+	type EditorState = PropertyEditorState;
+
+	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = self.name.show_editor(&mut 300.0, ui);
+		let mut value = self.typed_value();
+		let typed_res = match &mut value {
+			PropertyValue::Bool(b) => ui.checkbox(b, ""),
+			PropertyValue::Int(i) => ui.add(DragValue::new(i)),
+			PropertyValue::Float(f) => ui.add(DragValue::new(f).speed(0.01)),
+			PropertyValue::Color(c) => {
+				if let Ok(rgb) = <&mut [f32; 3]>::try_from(c.as_mut_slice()) {
+					ui.color_edit_button_rgb(rgb)
+				} else if let Ok(rgba) = <&mut [f32; 4]>::try_from(c.as_mut_slice()) {
+					ui.color_edit_button_rgba_unmultiplied(rgba)
+				} else {
+					ui.label("")
+				}
+			}
+			PropertyValue::Texture(_) | PropertyValue::Text(_) => {
+				res |= self.value.show_editor(&mut 300.0, ui);
+				let preview = matches!(value, PropertyValue::Texture(_))
+					.then(|| state.previews.get(&texture_key(&self.value.0)))
+					.flatten();
+				if let Some(texture) = preview {
+					ui.add(
+						egui::Image::new(texture)
+							.fit_to_exact_size(egui::Vec2::splat(PREVIEW_SIZE))
+							.maintain_aspect_ratio(true),
+					)
+					.on_hover_ui(|ui| {
+						let size = texture.size();
+						ui.add(
+							egui::Image::new(texture)
+								.fit_to_exact_size(egui::Vec2::new(size[0] as f32, size[1] as f32)),
+						);
+					});
+				}
+				return res;
+			}
+		};
+		if typed_res.changed() {
+			self.set_typed_value(&value);
+		}
+		res |= typed_res;
+		res
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct MaterialDefinitionEditorState {
+	properties: VecEditorState<Property>,
+}
 
-	fn new_editor(
-		&self,
-		_context: &Context,
-		_gl: &Option<Arc<glow::Context>>,
-	) -> Self::EditorState {
+impl Default for MaterialDefinitionEditorState {
+	fn default() -> Self {
+		Self {
+			properties: VecEditorState::Shared(PropertyEditorState::default()),
+		}
 	}
+}
 
-	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
-		self.name.show_editor(&mut 300.0, ui) | self.value.show_editor(&mut 300.0, ui)
+impl MaterialDefinitionEditorState {
+	/// Show previews of the textures the material refers to, indexed by their texture key
+	pub fn set_textures(&mut self, textures: HashMap<Vec<u8>, DecodedTexture>, context: &Context) {
+		let max_texture_side = context.input(|input| input.max_texture_side);
+		let previews = textures
+			.into_iter()
+			.filter(|(_, decoded)| {
+				decoded.width <= max_texture_side && decoded.height <= max_texture_side
+			})
+			.map(|(key, decoded)| {
+				let texture = context.load_texture(
+					format!("material_texture_{}", String::from_utf8_lossy(&key)),
+					ColorImage::from_rgba_unmultiplied(
+						[decoded.width, decoded.height],
+						&decoded.data,
+					),
+					TextureOptions::LINEAR,
+				);
+				(key, texture)
+			})
+			.collect();
+		self.properties = VecEditorState::Shared(PropertyEditorState { previews });
 	}
 }
 
 impl Editor for MaterialDefinition {
-	type EditorState = ();
+	type EditorState = MaterialDefinitionEditorState;
 
-	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
 		let mut res = self.file_name.name.show_editor(&mut 500.0, ui);
 		ui.horizontal(|ui| {
 			ui.label("Material Type");
-			res |= self.material_type.show_editor(&mut 500.0, ui);
+			let material_type = self.typed_material_type();
+			ComboBox::from_id_salt("material_type")
+				.selected_text(material_type.name())
+				.show_ui(ui, |ui| {
+					for known in MaterialType::KNOWN {
+						if ui
+							.selectable_label(&material_type == known, known.name())
+							.clicked()
+						{
+							self.material_type = BigString::from(known.name().to_string());
+							res.mark_changed();
+						}
+					}
+				});
+			res |= self.material_type.show_editor(&mut 300.0, ui);
 		});
 		ui.horizontal(|ui| {
 			ui.label("Material Description");
@@ -47,11 +162,21 @@ impl Editor for MaterialDefinition {
 
 		ui.push_id("Properties", |ui| {
 			res |= ui.label("Properties");
-			res |= self
-				.properties
-				.show_editor(&mut VecEditorState::Shared(()), ui);
+			res |= self.properties.show_editor(&mut state.properties, ui);
 		});
 
+		let links = self.texture_links();
+		if !links.is_empty() {
+			ui.label("Texture links");
+			for (property, link) in links {
+				ui.label(format!(
+					"{property}: TXTR {:08X} {:016X}",
+					link.group_id,
+					link.instance_id()
+				));
+			}
+		}
+
 		ui.push_id("Names", |ui| {
 			ui.label("Names");
 			res |= self
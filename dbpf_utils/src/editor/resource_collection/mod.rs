@@ -2,10 +2,19 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::sync::Arc;
+use std::{
+	io::{Read, Seek},
+	sync::Arc,
+};
 
-use dbpf::internal_file::resource_collection::{
-	geometric_data_container::GeometricDataContainer, ResourceCollection, ResourceData,
+use dbpf::{
+	internal_file::resource_collection::{
+		geometric_data_container::{skeleton::find_skeleton, GeometricDataContainer},
+		level_information::collect_level_information,
+		material_definition::{find_textures, MaterialDefinition},
+		ResourceCollection, ResourceData,
+	},
+	IndexEntry,
 };
 use eframe::{
 	egui,
@@ -18,12 +27,14 @@ use crate::editor::Editor;
 
 mod geometric_data_container;
 mod material_definition;
+mod scenegraph;
 mod texture_resource;
 
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum ResourceEditorState {
 	TextureResource(TextureResourceEditorState),
+	Material(<MaterialDefinition as Editor>::EditorState),
 	Mesh(<GeometricDataContainer as Editor>::EditorState),
 	None,
 }
@@ -33,6 +44,55 @@ pub struct ResourceCollectionEditorState {
 	pub resource_editor_states: Vec<ResourceEditorState>,
 }
 
+impl ResourceCollectionEditorState {
+	/// Look up the data that the blocks of the collection take from other resources in the open package
+	///
+	/// Only decoded copies of the other resources are used, so they are written back unchanged.
+	///
+	/// `group_id` and `instance_id` identify the resource the collection was loaded from.
+	pub fn resolve_links<R: Read + Seek>(
+		&mut self,
+		rcol: &ResourceCollection,
+		group_id: u32,
+		instance_id: u64,
+		entries: &mut [&mut IndexEntry],
+		reader: &mut R,
+		context: &egui::Context,
+	) {
+		for (entry, state) in rcol.entries.iter().zip(&mut self.resource_editor_states) {
+			match (&entry.data, state) {
+				(ResourceData::Texture(texture), ResourceEditorState::TextureResource(state))
+					if texture.any_lifo() =>
+				{
+					let lifos = collect_level_information(
+						entries.iter_mut().map(|entry| &mut **entry),
+						reader,
+					);
+					state.set_level_information(texture, lifos, context);
+				}
+				(ResourceData::Material(material), ResourceEditorState::Material(state)) => {
+					let textures = find_textures(
+						entries.iter_mut().map(|entry| &mut **entry),
+						reader,
+						material,
+					);
+					state.set_textures(textures, context);
+				}
+				(ResourceData::Mesh(mesh), ResourceEditorState::Mesh(state)) => {
+					state.set_skeleton(find_skeleton(
+						entries.iter_mut().map(|entry| &mut **entry),
+						reader,
+						group_id,
+						instance_id,
+						mesh,
+					));
+				}
+				_ => {}
+			}
+		}
+	}
+}
+
 impl Editor for ResourceCollection {
 	type EditorState = ResourceCollectionEditorState;
 
@@ -49,10 +109,13 @@ impl Editor for ResourceCollection {
 					ResourceData::Texture(texture) => {
 						ResourceEditorState::TextureResource(texture.new_editor(context, gl))
 					}
-					ResourceData::Material(_material) => ResourceEditorState::None,
+					ResourceData::Material(material) => {
+						ResourceEditorState::Material(material.new_editor(context, gl))
+					}
 					ResourceData::Mesh(mesh) => {
 						ResourceEditorState::Mesh(mesh.new_editor(context, gl))
 					}
+					_ => ResourceEditorState::None,
 				})
 				.collect(),
 		}
@@ -61,6 +124,13 @@ impl Editor for ResourceCollection {
 	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
 		ScrollArea::vertical()
 			.show(ui, |ui| {
+				if matches!(
+					self.entries.first().map(|e| &e.data),
+					Some(ResourceData::ResourceNode(_))
+				) {
+					scenegraph::scenegraph_tree(self, ui);
+				}
+
 				let mut res = ui.checkbox(&mut self.version, "Has resource id");
 
 				ui.separator();
@@ -101,8 +171,8 @@ impl Editor for ResourceCollection {
 						}
 						ResourceData::Material(material) => {
 							match &mut state.resource_editor_states[num] {
-								ResourceEditorState::None => {
-									res |= material.show_editor(&mut (), ui);
+								ResourceEditorState::Material(material_state) => {
+									res |= material.show_editor(material_state, ui);
 								}
 								_ => {
 									panic!()
@@ -117,6 +187,20 @@ impl Editor for ResourceCollection {
 								panic!()
 							}
 						},
+						ResourceData::ResourceNode(node) => res |= node.show_editor(&mut (), ui),
+						ResourceData::TransformNode(node) => res |= node.show_editor(&mut (), ui),
+						ResourceData::ShapeRefNode(node) => res |= node.show_editor(&mut (), ui),
+						ResourceData::LightRefNode(node) => res |= node.show_editor(&mut (), ui),
+						ResourceData::DataListExtension(ext) => res |= ext.show_editor(&mut (), ui),
+						ResourceData::BoneDataExtension(ext) => res |= ext.show_editor(&mut (), ui),
+						ResourceData::Shape(shape) => res |= shape.show_editor(&mut (), ui),
+						ResourceData::GeometricNode(node) => res |= node.show_editor(&mut (), ui),
+						ResourceData::Light(light) => {
+							res |= light.show_editor(&mut entry.type_id.clone(), ui)
+						}
+						ResourceData::LevelInformation(lifo) => {
+							res |= lifo.show_editor(&mut (), ui)
+						}
 					}
 				}
 
@@ -0,0 +1,386 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use dbpf::{
+	filetypes::DBPFFileType,
+	internal_file::resource_collection::{
+		creation_resource::{LightRefNode, ResourceNode, ShapeRefNode},
+		geometric_data_container::math::{Quaternion, Vertex},
+		geometric_node::GeometricNode,
+		light::Light,
+		object_graph::{
+			BoneDataExtension, DataListExtension, Extension, ExtensionData, ExtensionItem,
+			ExtensionValue, ObjectGraphNode, TransformNode,
+		},
+		shape::{Shape, ShapePart},
+		ResourceCollection, ResourceData,
+	},
+};
+use eframe::egui::{CollapsingHeader, DragValue, Grid, Response, Ui};
+
+use crate::editor::{drag_decimal_fn, drag_hex_fn, vector::VecEditorState, Editor};
+
+fn vertex_editor(name: &str, v: &mut Vertex, ui: &mut Ui) -> Response {
+	ui.label(name);
+	let res = ui
+		.horizontal(|ui| {
+			ui.add(DragValue::new(&mut v.x).speed(0.01))
+				| ui.add(DragValue::new(&mut v.y).speed(0.01))
+				| ui.add(DragValue::new(&mut v.z).speed(0.01))
+		})
+		.inner;
+	ui.end_row();
+	res
+}
+
+fn quaternion_editor(name: &str, q: &mut Quaternion, ui: &mut Ui) -> Response {
+	ui.label(name);
+	let res = ui
+		.horizontal(|ui| {
+			ui.add(DragValue::new(&mut q.x).speed(0.01))
+				| ui.add(DragValue::new(&mut q.y).speed(0.01))
+				| ui.add(DragValue::new(&mut q.z).speed(0.01))
+				| ui.add(DragValue::new(&mut q.w).speed(0.01))
+		})
+		.inner;
+	ui.end_row();
+	res
+}
+
+fn object_graph_node_editor(node: &mut ObjectGraphNode, ui: &mut Ui) -> Response {
+	ui.label("Name");
+	let mut res = node.file_name.show_editor(&mut 300.0, ui);
+	ui.end_row();
+	ui.label("Extensions");
+	res |= ui.label(format!(
+		"{:?}",
+		node.extensions.iter().map(|e| e.index).collect::<Vec<_>>()
+	));
+	ui.end_row();
+	res
+}
+
+fn transform_node_grid(node: &mut TransformNode, ui: &mut Ui) -> Response {
+	let mut res = object_graph_node_editor(&mut node.composition_tree_node.object_graph_node, ui);
+	res |= vertex_editor("Translation", &mut node.translation, ui);
+	res |= quaternion_editor("Rotation", &mut node.rotation, ui);
+	res |= drag_decimal_fn("Joint reference", &mut node.joint_reference, ui);
+	ui.label("Children");
+	res |= ui.label(format!(
+		"{:?}",
+		node.children
+			.iter()
+			.map(|c| c.child_node)
+			.collect::<Vec<_>>()
+	));
+	ui.end_row();
+	res
+}
+
+impl Editor for TransformNode {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		Grid::new("TransformNode")
+			.num_columns(2)
+			.show(ui, |ui| transform_node_grid(self, ui))
+			.inner
+	}
+}
+
+impl Editor for ResourceNode {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		Grid::new("ResourceNode")
+			.num_columns(2)
+			.show(ui, |ui| {
+				ui.label("Children");
+				let res = ui.label(format!("{:?}", self.children()));
+				ui.end_row();
+				res
+			})
+			.inner
+	}
+}
+
+impl Editor for ShapeRefNode {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		Grid::new("ShapeRefNode")
+			.num_columns(2)
+			.show(ui, |ui| {
+				let mut res = transform_node_grid(&mut self.transform_node.transform_node, ui);
+				ui.label("Shape links");
+				res |= ui.label(format!(
+					"{:?}",
+					self.shapes.iter().map(|s| s.shape_link).collect::<Vec<_>>()
+				));
+				ui.end_row();
+				if !self.blend_names.is_empty() {
+					ui.label("Blends");
+					res |= ui.label(
+						self.blend_names
+							.iter()
+							.map(|n| n.to_string())
+							.collect::<Vec<_>>()
+							.join(", "),
+					);
+					ui.end_row();
+				}
+				res
+			})
+			.inner
+	}
+}
+
+impl Editor for LightRefNode {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		Grid::new("LightRefNode")
+			.num_columns(2)
+			.show(ui, |ui| {
+				transform_node_grid(&mut self.transform_node.transform_node, ui)
+					| drag_decimal_fn("Light link", &mut self.light_link, ui)
+			})
+			.inner
+	}
+}
+
+fn extension_items_editor(items: &mut [ExtensionItem], ui: &mut Ui) -> Response {
+	let mut res = ui.label("");
+	for (i, item) in items.iter_mut().enumerate() {
+		ui.push_id(i, |ui| match &mut item.value {
+			ExtensionValue::Array { items } => {
+				CollapsingHeader::new(item.name.to_string()).show(ui, |ui| {
+					res |= extension_items_editor(items, ui);
+				});
+			}
+			value => {
+				ui.horizontal(|ui| {
+					res |= item.name.show_editor(&mut 150.0, ui);
+					res |= match value {
+						ExtensionValue::Int(v) => ui.add(DragValue::new(v)),
+						ExtensionValue::Float(v) => ui.add(DragValue::new(v).speed(0.01)),
+						ExtensionValue::Translation(v) => {
+							ui.add(DragValue::new(&mut v.x).speed(0.01))
+								| ui.add(DragValue::new(&mut v.y).speed(0.01))
+								| ui.add(DragValue::new(&mut v.z).speed(0.01))
+						}
+						ExtensionValue::String(s) => s.show_editor(&mut 200.0, ui),
+						ExtensionValue::Rotation(q) => {
+							ui.add(DragValue::new(&mut q.x).speed(0.01))
+								| ui.add(DragValue::new(&mut q.y).speed(0.01))
+								| ui.add(DragValue::new(&mut q.z).speed(0.01))
+								| ui.add(DragValue::new(&mut q.w).speed(0.01))
+						}
+						ExtensionValue::Binary { data } => {
+							ui.label(format!("{} bytes", data.len()))
+						}
+						ExtensionValue::Array { .. } => unreachable!(),
+					};
+				});
+			}
+		});
+	}
+	res
+}
+
+fn extension_editor(extension: &mut Extension, ui: &mut Ui) -> Response {
+	match &mut extension.data {
+		ExtensionData::Items { name, items } => {
+			let mut res = name.show_editor(&mut 300.0, ui);
+			res |= extension_items_editor(items, ui);
+			res
+		}
+		ExtensionData::Raw { type_code, data } => ui.label(format!(
+			"raw extension type {type_code:#x}, {} bytes",
+			data.len()
+		)),
+	}
+}
+
+impl Editor for DataListExtension {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		extension_editor(&mut self.extension, ui)
+	}
+}
+
+impl Editor for BoneDataExtension {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = extension_editor(&mut self.extension, ui);
+		res |= Grid::new("BoneDataExtension")
+			.num_columns(2)
+			.show(ui, |ui| {
+				drag_hex_fn("Unknown 1", &mut self.unknown1, ui)
+					| drag_hex_fn("Unknown 2", &mut self.unknown2, ui)
+					| drag_decimal_fn("Unknown 3", &mut self.unknown3, ui)
+					| quaternion_editor("Rotation", &mut self.rotation, ui)
+			})
+			.inner;
+		res
+	}
+}
+
+impl Editor for ShapePart {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		self.subset
+			.show_editor(&mut 150.0, ui)
+			.on_hover_text("Subset")
+			| self
+				.material
+				.show_editor(&mut 300.0, ui)
+				.on_hover_text("Material")
+	}
+}
+
+impl Editor for Shape {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = Grid::new("Shape")
+			.num_columns(2)
+			.show(ui, |ui| {
+				let mut res = object_graph_node_editor(&mut self.object_graph_node, ui);
+				ui.label("LOD levels");
+				res |= ui.label(format!("{:?}", self.lod_levels));
+				ui.end_row();
+				for (i, item) in self.items.iter_mut().enumerate() {
+					ui.label(format!("Geometric node #{i}"));
+					res |= ui
+						.push_id(i, |ui| item.geometric_node.show_editor(&mut 300.0, ui))
+						.inner;
+					ui.end_row();
+				}
+				res
+			})
+			.inner;
+
+		ui.push_id("Parts", |ui| {
+			ui.label("Parts");
+			res |= self.parts.show_editor(&mut VecEditorState::Shared(()), ui);
+		});
+
+		res
+	}
+}
+
+impl Editor for GeometricNode {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = Grid::new("GeometricNode")
+			.num_columns(2)
+			.show(ui, |ui| {
+				object_graph_node_editor(&mut self.object_graph_node, ui)
+			})
+			.inner;
+		for (i, block) in self.blocks.iter_mut().enumerate() {
+			CollapsingHeader::new(block.entry.name.to_string())
+				.id_salt(i)
+				.show(ui, |ui| {
+					res |= match &mut block.entry.data {
+						ResourceData::DataListExtension(ext) => ext.show_editor(&mut (), ui),
+						data => ui.label(format!("{data:?}")),
+					};
+				});
+		}
+		res
+	}
+}
+
+/// the state is the block type of the light, which decides which fields are stored
+impl Editor for Light {
+	type EditorState = DBPFFileType;
+
+	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		Grid::new("Light")
+			.num_columns(2)
+			.show(ui, |ui| {
+				ui.label("Name");
+				let mut res = self.name.show_editor(&mut 300.0, ui);
+				ui.end_row();
+
+				ui.label("Colour");
+				let mut colour = [self.red, self.green, self.blue];
+				let colour_res = ui.color_edit_button_rgb(&mut colour);
+				if colour_res.changed() {
+					[self.red, self.green, self.blue] = colour;
+				}
+				res |= colour_res;
+				ui.end_row();
+
+				res |= drag_decimal_fn("Unknown 1", &mut self.unknown1, ui);
+				res |= drag_decimal_fn("Unknown 2", &mut self.unknown2, ui);
+				if Light::has_range(*state) {
+					res |= drag_decimal_fn("Range start", &mut self.range[0], ui);
+					res |= drag_decimal_fn("Range end", &mut self.range[1], ui);
+				}
+				if Light::has_cone(*state) {
+					res |= drag_decimal_fn("Cone inner", &mut self.cone[0], ui);
+					res |= drag_decimal_fn("Cone outer", &mut self.cone[1], ui);
+				}
+				res
+			})
+			.inner
+	}
+}
+
+fn scenegraph_node(rcol: &ResourceCollection, index: usize, depth: usize, ui: &mut Ui) {
+	let Some(entry) = rcol.entries.get(index) else {
+		ui.label(format!("missing block #{index}"));
+		return;
+	};
+	let children = entry.data.child_blocks();
+	let type_name = entry
+		.type_id
+		.properties()
+		.map(|prop| prop.abbreviation.to_string())
+		.unwrap_or_else(|| entry.name.to_string());
+	let mut label = format!("#{index} {type_name}");
+	if let ResourceData::TransformNode(node) = &entry.data {
+		label += &format!(" {}", node.name());
+	}
+	if let ResourceData::ShapeRefNode(node) = &entry.data {
+		for shape in &node.shapes {
+			if let Some(link) = usize::try_from(shape.shape_link)
+				.ok()
+				.and_then(|i| rcol.links.get(i))
+			{
+				label += &format!(" -> {:08X}:{:016X}", link.group_id, link.instance_id());
+			}
+		}
+	}
+
+	// guard against malformed cyclic graphs
+	if children.is_empty() || depth > 64 {
+		ui.label(label);
+	} else {
+		CollapsingHeader::new(label)
+			.id_salt(index)
+			.default_open(depth < 2)
+			.show(ui, |ui| {
+				for child in children {
+					scenegraph_node(rcol, child, depth + 1, ui);
+				}
+			});
+	}
+}
+
+/// Show the hierarchy of a CRES, starting from the cResourceNode at block 0
+pub fn scenegraph_tree(rcol: &ResourceCollection, ui: &mut Ui) {
+	CollapsingHeader::new("Scenegraph")
+		.default_open(true)
+		.show(ui, |ui| {
+			scenegraph_node(rcol, 0, 0, ui);
+		});
+}
@@ -10,9 +10,14 @@ use crate::{
 	},
 };
 use binrw::{BinRead, BinResult};
-use dbpf::internal_file::resource_collection::texture_resource::{
-	decoded_texture::{DecodedTexture, ShrinkDirection},
-	KnownPurpose, Purpose, TextureFormat, TextureResource, TextureResourceData,
+use dbpf::internal_file::resource_collection::{
+	level_information::LevelInformation,
+	texture_resource::{
+		decoded_texture::ShrinkDirection,
+		resample::{ResampleFilter, ResampleOptions},
+		ImageExportFormat, ImageImportOptions, KnownPurpose, Purpose, TextureFormat,
+		TextureResource, TextureResourceData,
+	},
 };
 use eframe::egui::{Color32, Vec2};
 use eframe::{
@@ -24,11 +29,12 @@ use eframe::{
 };
 use enum_iterator::all;
 use futures::channel::oneshot;
-use image::ImageReader;
 use rfd::FileHandle;
 use std::cmp::max;
 use std::{
+	borrow::Cow,
 	cmp::min,
+	collections::HashMap,
 	fmt::{Debug, Formatter, Write},
 	io::Cursor,
 	sync::Arc,
@@ -99,14 +105,29 @@ enum TextureLoadError {
 	TooBig(usize),
 }
 
+#[derive(Copy, Clone, Debug)]
+enum ExportKind {
+	Dds,
+	/// texture and mip index of the exported image
+	Image(usize, usize),
+	ContactSheet,
+}
+
 pub struct TextureResourceEditorState {
 	textures: Vec<Vec<Result<egui::TextureHandle, TextureLoadError>>>,
 	zoom_state: Vec<(Rect, usize)>,
 	original_texture_bgra: BinResult<TextureResource>,
 	preserve_transparency: u8,
-	save_file_picker: Option<oneshot::Receiver<Option<FileHandle>>>,
+	resample_filter: ResampleFilter,
+	gamma_correct: bool,
+	resize_width: u32,
+	resize_height: u32,
+	import_max_size: u32,
+	save_file_picker: Option<(ExportKind, oneshot::Receiver<Option<FileHandle>>)>,
 	enum_editor_state: EnumEditorState,
 	alpha_texture_color: Color32,
+	/// the LIFO resources of the open package, used to show and export the mipmaps that are not stored in the texture
+	lifos: HashMap<Vec<u8>, LevelInformation>,
 }
 
 impl Default for TextureResourceEditorState {
@@ -116,9 +137,15 @@ impl Default for TextureResourceEditorState {
 			zoom_state: vec![],
 			original_texture_bgra: Ok(TextureResource::default()),
 			preserve_transparency: 0,
+			resample_filter: ResampleFilter::default(),
+			gamma_correct: true,
+			resize_width: 0,
+			resize_height: 0,
+			import_max_size: 0,
 			save_file_picker: None,
 			enum_editor_state: EnumEditorState::default(),
 			alpha_texture_color: Color32::from_rgb(127, 125, 120),
+			lifos: HashMap::new(),
 		}
 	}
 }
@@ -193,8 +220,36 @@ impl TextureResourceEditorState {
 			.collect()
 	}
 
+	/// Set the LIFO resources of the open package, indexed by their
+	/// [lifo_key](dbpf::internal_file::resource_collection::level_information::lifo_key)
+	///
+	/// The texture itself is not changed, the LIFOs are only embedded in copies for the preview and exports,
+	/// or when the user asks for it.
+	pub fn set_level_information(
+		&mut self,
+		res: &TextureResource,
+		lifos: HashMap<Vec<u8>, LevelInformation>,
+		context: &egui::Context,
+	) {
+		self.lifos = lifos;
+		self.refresh_textures_from(res, context);
+	}
+
+	/// the texture with the mipmaps from the LIFO resources embedded, if it references any
+	fn with_lifo<'a>(&self, res: &'a TextureResource) -> Cow<'a, TextureResource> {
+		if res.any_lifo() && !self.lifos.is_empty() {
+			let mut embedded = res.clone();
+			embedded.embed_lifo(&self.lifos);
+			Cow::Owned(embedded)
+		} else {
+			Cow::Borrowed(res)
+		}
+	}
+
 	fn refresh_textures_from(&mut self, res: &TextureResource, context: &egui::Context) {
-		self.textures = Self::load_textures(res, context);
+		self.textures = Self::load_textures(&self.with_lifo(res), context);
+		self.resize_width = res.width;
+		self.resize_height = res.height;
 		self.zoom_state.resize(self.textures.len(), (Rect::ZERO, 0));
 		let mip_levels = res.mip_levels() - 1;
 		self.zoom_state
@@ -213,6 +268,7 @@ impl Editor for TextureResource {
 	) -> Self::EditorState {
 		let mut new = Self::EditorState {
 			original_texture_bgra: self.recompress_with_format(TextureFormat::RawARGB32),
+			gamma_correct: self.default_resample_options().gamma_correct,
 			..Default::default()
 		};
 		new.refresh_textures_from(self, context);
@@ -220,20 +276,28 @@ impl Editor for TextureResource {
 	}
 
 	fn show_editor(&mut self, state: &mut Self::EditorState, ui: &mut Ui) -> Response {
-		if let Some(picker) = &mut state.save_file_picker {
+		if let Some((kind, picker)) = &mut state.save_file_picker {
+			let kind = *kind;
 			if let Ok(Some(handle)) = picker.try_recv() {
 				state.save_file_picker = None;
 				if let Some(handle) = handle {
 					trace!("writing to handle {handle:?}");
 					let mut cur = Cursor::new(vec![]);
-					match self.export_dds(&mut cur) {
-						Ok(()) => {
-							let res = futures::executor::block_on(handle.write(&cur.into_inner()));
-							if let Err(error) = res {
-								error!(?error);
-							}
+					let texture = state.with_lifo(self);
+					let exported = match kind {
+						ExportKind::Dds => {
+							texture.export_dds(&mut cur).map_err(|error| error!(?error))
 						}
-						Err(error) => {
+						ExportKind::Image(texture_num, mip) => texture
+							.export_image(texture_num, mip, ImageExportFormat::Png, &mut cur)
+							.map_err(|error| error!(?error)),
+						ExportKind::ContactSheet => texture
+							.export_contact_sheet(ImageExportFormat::Png, &mut cur)
+							.map_err(|error| error!(?error)),
+					};
+					if exported.is_ok() {
+						let res = futures::executor::block_on(handle.write(&cur.into_inner()));
+						if let Err(error) = res {
 							error!(?error);
 						}
 					}
@@ -263,15 +327,56 @@ impl Editor for TextureResource {
 						ui.label("Preserve transparency")
 				}).inner.on_hover_text("makes sure that thin objects with transparency also show up correctly in mipmaps\n\
 	            This option is intended for textures that use alpha testing, NOT for textures that have AlphaBlendMode set to \"blend\".\n\n\
-	            To use this option, set the texture format to DXT1 and have a look at the mipmaps; does the texture show up correctly in all mipmaps? \
+	            Set this to the alpha test reference value of the material; \
+	            the mipmaps will keep the same fraction of pixels above that value as the full size texture.\n\
+	            To check the result, set the texture format to DXT1 and have a look at the mipmaps; does the texture show up correctly in all mipmaps? \
 	            If not try adjusting this value, click \"Recalculate all mipmaps\" and look again.\n\
 	            Don't forget to set the format back to DXT5 after you're done!");
+
+				ComboBox::from_label("Filter")
+					.selected_text(format!("{:?}", state.resample_filter))
+					.show_ui(ui, |ui| {
+						for filter in all::<ResampleFilter>() {
+							ui.selectable_value(
+								&mut state.resample_filter,
+								filter,
+								format!("{filter:?}"),
+							);
+						}
+					})
+					.response
+					.on_hover_text("The filter used to calculate mipmaps and to resize the texture");
+				ui.checkbox(&mut state.gamma_correct, "Gamma correct")
+					.on_hover_text("Average colors in linear light, which keeps bright details from darkening in smaller mipmaps\n\
+					Turn this off for textures that don't store colors, like bump and normal maps");
 			}
 		});
-		let preserve_transparency = if state.preserve_transparency > 0 {
-			Some(state.preserve_transparency)
-		} else {
-			None
+		if self.any_lifo()
+			&& ui
+				.add_enabled(!state.lifos.is_empty(), Button::new("Embed LIFO mipmaps"))
+				.on_hover_text(
+					"Copy the mipmaps that are stored in LIFO resources into this texture, \
+					so it can be edited like any other texture.\n\
+					The LIFO resources themselves are left in the package.",
+				)
+				.clicked()
+		{
+			if !self.embed_lifo(&state.lifos) {
+				warn!("not all LIFO mipmaps of the texture could be found in this package");
+			}
+			state.original_texture_bgra = self.recompress_with_format(TextureFormat::RawARGB32);
+			res.mark_changed();
+			update_images = true;
+		}
+
+		let resample_options = ResampleOptions {
+			filter: state.resample_filter,
+			gamma_correct: state.gamma_correct,
+			alpha_coverage: if state.preserve_transparency > 0 {
+				Some(state.preserve_transparency)
+			} else {
+				None
+			},
 		};
 
 		if let Ok(original_bgra) = &mut state.original_texture_bgra {
@@ -290,9 +395,9 @@ impl Editor for TextureResource {
 					.clicked()
 				{
 					original_bgra.remove_smaller_mip_levels();
-					original_bgra.add_max_mip_levels(preserve_transparency);
+					original_bgra.add_max_mip_levels(&resample_options);
 					self.remove_smaller_mip_levels();
-					self.add_max_mip_levels(preserve_transparency);
+					self.add_max_mip_levels(&resample_options);
 					res.mark_changed();
 					update_images = true;
 				}
@@ -303,8 +408,8 @@ impl Editor for TextureResource {
 					)
 					.clicked()
 				{
-					original_bgra.add_max_mip_levels(preserve_transparency);
-					self.add_max_mip_levels(preserve_transparency);
+					original_bgra.add_max_mip_levels(&resample_options);
+					self.add_max_mip_levels(&resample_options);
 					res.mark_changed();
 					update_images = true;
 				}
@@ -356,8 +461,8 @@ impl Editor for TextureResource {
 						.on_hover_text(tooltip)
 						.clicked()
 					{
-						let _ = original_bgra.shrink(preserve_transparency, shrink_direction);
-						let _ = self.shrink(preserve_transparency, shrink_direction);
+						let _ = original_bgra.shrink(&resample_options, shrink_direction);
+						let _ = self.shrink(&resample_options, shrink_direction);
 						for (zoom, mip_i) in &mut state.zoom_state {
 							*zoom = match shrink_direction {
 								ShrinkDirection::Both => *zoom / 2.0,
@@ -377,6 +482,45 @@ impl Editor for TextureResource {
 					}
 				}
 			});
+
+			ui.horizontal_wrapped(|ui| {
+				ui.add(DragValue::new(&mut state.resize_width).range(1..=4096));
+				ui.label("x");
+				ui.add(DragValue::new(&mut state.resize_height).range(1..=4096));
+				let (width, height) = (state.resize_width, state.resize_height);
+				if ui
+					.add_enabled(
+						!self.any_lifo() && (width, height) != (self.width, self.height),
+						Button::new("Resize"),
+					)
+					.on_hover_text(
+						"Resample the texture to the given size and recalculate all mipmaps",
+					)
+					.clicked()
+				{
+					let scale = Vec2::new(
+						width as f32 / self.width as f32,
+						height as f32 / self.height as f32,
+					);
+					let resized = original_bgra
+						.resize(width as usize, height as usize, &resample_options)
+						.and_then(|_| {
+							self.resize(width as usize, height as usize, &resample_options)
+						});
+					if let Err(error) = resized {
+						error!(?error, msg = %error);
+					}
+					for (zoom, mip_i) in &mut state.zoom_state {
+						*zoom = Rect::from_min_max(
+							(zoom.min.to_vec2() * scale).to_pos2(),
+							(zoom.max.to_vec2() * scale).to_pos2(),
+						);
+						*mip_i = min(*mip_i, original_bgra.mip_levels().saturating_sub(1));
+					}
+					res.mark_changed();
+					update_images = true;
+				}
+			});
 		}
 
 		let mut current_format = self.get_format();
@@ -452,6 +596,7 @@ impl Editor for TextureResource {
 							new.purpose = self.purpose;
 							new.unknown = self.unknown;
 							*self = new;
+							state.gamma_correct = self.default_resample_options().gamma_correct;
 						}
 						update_images = true;
 
@@ -487,31 +632,65 @@ impl Editor for TextureResource {
 			ui.label("Purpose");
 		});
 
-		if ui
-			.button("Export DDS")
-			.on_hover_text("export the currently visible texture and all mipmaps to a .dds file")
-			.clicked()
-			&& state.save_file_picker.is_none()
-		{
-			let (tx, rx) = oneshot::channel();
-			let dialog = rfd::AsyncFileDialog::new()
-				.set_file_name(format!(
-					"{}.dds",
-					String::from_utf8_lossy(&self.file_name.name.0.data)
-				))
-				.add_filter("DirectDraw Surface", &["dds"]);
-			// TODO global options open file path set directory
-			let dialog = dialog.save_file();
-			async_execute(async move {
-				let file = dialog.await;
-				let _ = if let Some(handle) = file {
-					tx.send(Some(handle))
-				} else {
-					tx.send(None)
-				};
-			});
-			state.save_file_picker = Some(rx);
-		}
+		ui.horizontal(|ui| {
+			ui.add(DragValue::new(&mut state.import_max_size).range(0..=4096));
+			ui.label("Max import size");
+		})
+		.response
+		.on_hover_text(
+			"PNG, TGA and JPEG images dropped onto this editor are scaled down to fit within this size, \
+			0 means no limit.\n\
+			The texture format is picked automatically based on the transparency of the image.",
+		);
+
+		ui.horizontal_wrapped(|ui| {
+			let name = String::from_utf8_lossy(&self.file_name.name.0.data).to_string();
+			let selected_mip = state.zoom_state.first().map(|(_, mip)| *mip).unwrap_or(0);
+			for (kind, text, tooltip) in [
+				(
+					ExportKind::Dds,
+					"Export DDS",
+					"export the currently visible texture and all mipmaps to a .dds file",
+				),
+				(
+					ExportKind::Image(0, self.mip_levels().saturating_sub(selected_mip + 1)),
+					"Export PNG",
+					"export the currently selected mipmap to a .png file",
+				),
+				(
+					ExportKind::ContactSheet,
+					"Export contact sheet",
+					"export all mipmaps tiled next to each other to a single .png file",
+				),
+			] {
+				if ui.button(text).on_hover_text(tooltip).clicked()
+					&& state.save_file_picker.is_none()
+				{
+					let (tx, rx) = oneshot::channel();
+					let dialog = match kind {
+						ExportKind::Dds => rfd::AsyncFileDialog::new()
+							.set_file_name(format!("{name}.dds"))
+							.add_filter("DirectDraw Surface", &["dds"]),
+						ExportKind::Image(..) | ExportKind::ContactSheet => {
+							rfd::AsyncFileDialog::new()
+								.set_file_name(format!("{name}.png"))
+								.add_filter("Portable Network Graphics", &["png"])
+						}
+					};
+					// TODO global options open file path set directory
+					let dialog = dialog.save_file();
+					async_execute(async move {
+						let file = dialog.await;
+						let _ = if let Some(handle) = file {
+							tx.send(Some(handle))
+						} else {
+							tx.send(None)
+						};
+					});
+					state.save_file_picker = Some((kind, rx));
+				}
+			}
+		});
 
 		let mip_level_names = (0..self.mip_levels())
 			.map(|mip| {
@@ -575,32 +754,28 @@ impl Editor for TextureResource {
 						.zoom_range(0.1..=16.0)
 						.show(ui, zoom, |ui| {
 							if let Some(mip_level) = texture.entries.get(*cur_selected_mip_level) {
-								match mip_level {
-									TextureResourceData::Embedded(_) => {
-										match state.textures[texture_num][*cur_selected_mip_level]
-											.as_ref()
-										{
-											Ok(image) => {
-												ui.add(
-													egui::Image::new(image)
-														.tint(match current_format {
-															TextureFormat::Alpha => {
-																state.alpha_texture_color
-															}
-															_ => Color32::WHITE,
-														})
-														.fit_to_exact_size(egui::Vec2::new(
-															original_size.0 as f32,
-															original_size.1 as f32,
-														)),
-												);
-											}
-											Err(e) => {
-												ui.colored_label(Color32::RED, format!("{e}"));
-											}
-										}
+								let image =
+									state.textures[texture_num][*cur_selected_mip_level].as_ref();
+								match (mip_level, image) {
+									(_, Ok(image)) => {
+										ui.add(
+											egui::Image::new(image)
+												.tint(match current_format {
+													TextureFormat::Alpha => {
+														state.alpha_texture_color
+													}
+													_ => Color32::WHITE,
+												})
+												.fit_to_exact_size(egui::Vec2::new(
+													original_size.0 as f32,
+													original_size.1 as f32,
+												)),
+										);
+									}
+									(TextureResourceData::Embedded(_), Err(e)) => {
+										ui.colored_label(Color32::RED, format!("{e}"));
 									}
-									TextureResourceData::LIFOFile { file_name } => {
+									(TextureResourceData::LIFOFile { file_name }, Err(_)) => {
 										ui.end_row();
 										ui.label(format!(
 											"file: {}",
@@ -660,44 +835,35 @@ impl Editor for TextureResource {
 					return;
 				}
 
-				let image = if let Some(path) = file.path {
-					ImageReader::open(path).ok().map(|i| i.decode())
+				let bytes = if let Some(path) = file.path {
+					std::fs::read(path).inspect_err(|error| error!(?error)).ok()
 				} else {
-					file.bytes
-						.and_then(|bytes| {
-							ImageReader::new(Cursor::new(bytes))
-								.with_guessed_format()
-								.ok()
-						})
-						.map(|i| i.decode())
+					file.bytes.map(|bytes| bytes.to_vec())
 				};
 
-				if let Some(Ok(image)) = image {
-					let orig_format = self.get_format();
-
-					let has_mip = self.mip_levels() > 1;
-
-					self.compress_replace(
-						DecodedTexture {
-							width: image.width() as usize,
-							height: image.height() as usize,
-							data: image.into_rgba8().to_vec(),
-						},
-						Some(TextureFormat::RawARGB32),
-					);
-
-					if has_mip {
-						self.add_max_mip_levels(preserve_transparency);
-					}
-
-					state.original_texture_bgra = Ok(self.clone());
+				if let Some(bytes) = bytes {
+					let options = ImageImportOptions {
+						format: None,
+						generate_mip_levels: self.mip_levels() > 1,
+						max_size: (state.import_max_size > 0)
+							.then_some(state.import_max_size as usize),
+						resample: resample_options,
+					};
+					match TextureResource::import_image(Cursor::new(bytes), &options) {
+						Err(error) => error!(?error, msg = %error),
+						Ok(mut texture) => {
+							texture.file_name = self.file_name.clone();
+							texture.unknown = self.unknown;
+							texture.purpose = self.purpose;
+							texture.file_name_repeat = self.file_name_repeat.clone();
 
-					if let Ok(new) = self.recompress_with_format(orig_format) {
-						*self = new;
+							state.original_texture_bgra =
+								texture.recompress_with_format(TextureFormat::RawARGB32);
+							*self = texture;
+							update_images = true;
+						}
 					}
 
-					update_images = true;
-
 					i.raw.dropped_files.clear();
 				}
 			}
@@ -710,3 +876,26 @@ impl Editor for TextureResource {
 		res
 	}
 }
+
+impl Editor for LevelInformation {
+	type EditorState = ();
+
+	fn show_editor(&mut self, _state: &mut Self::EditorState, ui: &mut Ui) -> Response {
+		let mut res = self.file_name.name.show_editor(&mut 500.0, ui);
+		ui.horizontal(|ui| {
+			ui.label("Size");
+			res |= ui.add(DragValue::new(&mut self.width));
+			ui.label("x");
+			res |= ui.add(DragValue::new(&mut self.height));
+		});
+		ui.horizontal(|ui| {
+			ui.label("Z level");
+			res |= ui.add(DragValue::new(&mut self.z_level));
+		});
+		ui.label(format!(
+			"Data: {}",
+			humansize::format_size(self.data.len(), humansize::DECIMAL)
+		));
+		res
+	}
+}
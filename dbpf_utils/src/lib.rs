@@ -11,6 +11,7 @@ use tracing_subscriber::fmt::format::DefaultFields;
 pub mod editor;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod tgi_conflicts;
+pub mod unique_names;
 
 #[cfg(feature = "tracy")]
 #[derive(Default)]
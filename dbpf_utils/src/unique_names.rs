@@ -0,0 +1,40 @@
+// SPDX-FileCopyrightText: 2026 Chiel Douwes
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{collections::HashSet, ffi::OsStr, path::Path};
+
+/// Hands out the names of files written to the same directory, so they don't overwrite each other
+///
+/// Names are compared in lowercase, as most file systems are case-insensitive.
+#[derive(Clone, Debug, Default)]
+pub struct UniqueNames(HashSet<String>);
+
+impl UniqueNames {
+	/// Whether `name` was handed out already
+	pub fn is_used(&self, name: &str) -> bool {
+		self.0.contains(&name.to_lowercase())
+	}
+
+	/// `name` if it is still free, otherwise `name` with the first free `_<n>` suffix
+	pub fn claim(&mut self, name: &str) -> String {
+		let mut unique = name.to_string();
+		let mut i = 1;
+		while self.is_used(&unique) {
+			unique = format!("{name}_{i}");
+			i += 1;
+		}
+		self.0.insert(unique.to_lowercase());
+		unique
+	}
+
+	/// A free name for the output of the package at `path`, from its file name without extension
+	pub fn claim_package(&mut self, path: &Path) -> String {
+		self.claim(
+			&path
+				.file_stem()
+				.unwrap_or(OsStr::new("package"))
+				.to_string_lossy(),
+		)
+	}
+}
@@ -19,6 +19,10 @@ pub struct EntryEditorTab {
 	pub state: EditorType,
 	#[serde(skip)]
 	pub data: Weak<RefCell<OpenResource>>,
+	/// the resource the labels of the editor were decoded from and its version,
+	/// `None` before they were first looked up
+	#[serde(skip)]
+	pub labels_source: Option<Option<(Weak<RefCell<OpenResource>>, usize)>>,
 
 	pub id: usize,
 
@@ -41,6 +45,7 @@ impl EntryEditorTab {
 
 			let (_, replaced) =
 				resource_import_overlay(ui, &mut data_ref, reader, |ui, data_ref, reader| {
+					let mut changed = false;
 					ui.add_enabled_ui(!data_ref.ui_deleted, |ui| match &mut self.state {
 						EditorType::Error(err) => {
 							ScrollArea::vertical().show(ui, |ui| {
@@ -55,6 +60,7 @@ impl EntryEditorTab {
 										ScrollArea::vertical().show(ui, |ui| {
 											if ui.code_editor(&mut str).changed() {
 												data.data = str.into_bytes();
+												changed = true;
 											}
 										})
 									});
@@ -65,7 +71,10 @@ impl EntryEditorTab {
 								ui,
 								data,
 								|mem, addr| Some(mem.data[addr]),
-								|mem, addr, byte| mem.data[addr] = byte,
+								|mem, addr, byte| {
+									mem.data[addr] = byte;
+									changed = true;
+								},
 							);
 						}
 						EditorType::DecodedEditor(state) => {
@@ -76,12 +85,17 @@ impl EntryEditorTab {
 								.decoded()
 								.unwrap()
 								.unwrap();
-							decoded.show_editor(state, ui);
+							changed = decoded.show_editor(state, ui).changed();
 						}
 					});
+					if changed {
+						data_ref.version += 1;
+					}
 				});
 
 			if replaced {
+				data_ref.version += 1;
+				self.labels_source = None;
 				let type_id = data_ref.data.type_id;
 				self.state = EditorType::new(
 					&mut data_ref.data,
@@ -21,8 +21,7 @@ use std::{
 	cell::RefCell,
 	fmt::{Debug, Formatter},
 	fs,
-	fs::File,
-	io::{BufWriter, Cursor, Error, Read, Seek, Write},
+	io::{Cursor, Error, Read, Seek, Write},
 	path::{Path, PathBuf},
 	rc::Rc,
 	sync::Arc,
@@ -34,13 +33,16 @@ use binrw::{BinRead, BinResult};
 use clap::Parser;
 use dbpf::{
 	filetypes::{DBPFFileType, KnownDBPFFileType},
-	internal_file::CompressionError,
+	internal_file::{CompressionError, DecodedFile},
 	CompressionType, DBPFFile, IndexEntry,
 };
 use dbpf_utils::editor::common_ui::settings::Settings;
 use dbpf_utils::{
 	async_execute,
-	editor::{editor_supported, DecodedFileEditorState, Editor},
+	editor::{
+		editor_supported, resource_collection::ResourceCollectionEditorState,
+		DecodedFileEditorState, Editor,
+	},
 	graphical_application_main, version_info,
 };
 use editor_tab::EntryEditorTab;
@@ -59,7 +61,7 @@ use futures::channel::oneshot;
 use rfd::FileHandle;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use settings::{BackupOverwritePreference, DeletedRememberPreference, YaPeSettings};
-use tracing::error;
+use tracing::{error, warn};
 
 enum EditorType {
 	HexEditor(MemoryEditor),
@@ -170,6 +172,8 @@ fn file_type_default() -> (DBPFFileType, bool) {
 struct OpenResource {
 	ui_deleted: bool,
 	data: IndexEntry,
+	/// incremented every time the contents are edited, so other tabs know to look at them again
+	version: usize,
 }
 
 #[derive(Debug)]
@@ -253,6 +257,9 @@ struct YaPeApp {
 	/// Rusty file dialog async save as file picker
 	#[serde(skip)]
 	save_file_picker: Option<oneshot::Receiver<Option<FileHandle>>>,
+	/// why the last save did not write the file
+	#[serde(skip)]
+	save_error: Option<String>,
 }
 
 impl Default for YaPeApp {
@@ -267,6 +274,7 @@ impl Default for YaPeApp {
 
 			file_picker: None,
 			save_file_picker: None,
+			save_error: None,
 		}
 	}
 }
@@ -526,6 +534,53 @@ impl YaPeAppData {
 	}
 }
 
+/// the index of the BHAV with the instance, preferring the one in the given group
+fn find_tree(
+	resources: &[Rc<RefCell<OpenResource>>],
+	group_id: u32,
+	instance_id: u16,
+) -> Option<usize> {
+	let trees: Vec<_> = resources
+		.iter()
+		.enumerate()
+		.filter_map(|(i, res)| {
+			let res = res.borrow();
+			(res.data.type_id == DBPFFileType::Known(KnownDBPFFileType::SimanticsBehaviourFunction)
+				&& res.data.instance_id.id == instance_id as u64
+				&& !res.ui_deleted)
+				.then_some((i, res.data.group_id))
+		})
+		.collect();
+	trees
+		.iter()
+		.find(|(_, group)| *group == group_id)
+		.or(trees.first())
+		.map(|(i, _)| *i)
+}
+
+/// the resource with the type and the group and instance of `resource`
+fn find_labels(
+	resources: &[Rc<RefCell<OpenResource>>],
+	type_id: KnownDBPFFileType,
+	resource: &Rc<RefCell<OpenResource>>,
+) -> Option<Rc<RefCell<OpenResource>>> {
+	let (group_id, instance_id) = {
+		let resource = resource.borrow();
+		(resource.data.group_id, resource.data.instance_id.id)
+	};
+	resources
+		.iter()
+		.filter(|res| !Rc::ptr_eq(res, resource))
+		.find(|res| {
+			let res = res.borrow();
+			res.data.type_id == DBPFFileType::Known(type_id)
+				&& res.data.group_id == group_id
+				&& res.data.instance_id.id == instance_id
+				&& !res.ui_deleted
+		})
+		.cloned()
+}
+
 impl TabViewer for YaPeAppData {
 	type Tab = YaPeTab;
 
@@ -559,7 +614,53 @@ impl TabViewer for YaPeAppData {
 			YaPeTab::File => self.show_index(ui),
 			YaPeTab::Entry(entry) => {
 				if let Some(file) = &mut self.open_file {
+					// only decode the names again when the other resource was edited or replaced
+					if let (EditorType::DecodedEditor(state), Some(data)) =
+						(&mut entry.state, entry.data.upgrade())
+					{
+						if let Some(type_id) = state.label_type() {
+							let labels = find_labels(&file.resources, type_id, &data);
+							let source = labels
+								.as_ref()
+								.map(|res| (Rc::downgrade(res), res.borrow().version));
+							let unchanged =
+								entry.labels_source.as_ref().is_some_and(|cached| {
+									match (cached, &source) {
+										(Some((cached, cached_version)), Some((res, version))) => {
+											cached.ptr_eq(res) && cached_version == version
+										}
+										(None, None) => true,
+										_ => false,
+									}
+								});
+							if !unchanged {
+								let decoded = labels.and_then(|res| {
+									res.borrow_mut()
+										.data
+										.data(&mut file.bytes)
+										.map_err(CompressionError::from)
+										.and_then(|data| data.decoded_copy())
+										.ok()
+										.flatten()
+								});
+								state.set_labels(decoded);
+								entry.labels_source = Some(source);
+							}
+						}
+					}
 					entry.show(ui, &mut file.bytes, &self.gl_context);
+
+					if let EditorType::DecodedEditor(state) = &mut entry.state {
+						if let (Some(tree), Some(data)) =
+							(state.take_open_tree(), entry.data.upgrade())
+						{
+							let group_id = data.borrow().data.group_id;
+							match find_tree(&file.resources, group_id, tree) {
+								Some(index) => self.open_new_tab_index = Some(index),
+								None => warn!("BHAV {tree:#06X} is not in this package"),
+							}
+						}
+					}
 				}
 			}
 		}
@@ -662,6 +763,7 @@ impl YaPeApp {
 					Rc::new(RefCell::new(OpenResource {
 						data: entry,
 						ui_deleted: false,
+						version: 0,
 					}))
 				})
 				.collect(),
@@ -701,20 +803,57 @@ impl YaPeApp {
 		let id = *next_tab_id;
 		*next_tab_id = next_tab_id.wrapping_add(1);
 		let index_entry = file_resources.get(index)?;
-		let entry_ref = &mut index_entry.borrow_mut().data;
-		let file_type = entry_ref.type_id;
-
-		let res = EditorType::new(entry_ref, reader, file_type, hex_editor, ui_ctx, gl_ctx);
+		let mut res = {
+			let entry_ref = &mut index_entry.borrow_mut().data;
+			let file_type = entry_ref.type_id;
+			EditorType::new(entry_ref, reader, file_type, hex_editor, ui_ctx, gl_ctx)
+		};
+		if let EditorType::DecodedEditor(DecodedFileEditorState::ResourceCollection(state)) =
+			&mut res
+		{
+			Self::resolve_links(reader, file_resources, index, state, ui_ctx);
+		}
 
 		Some(EntryEditorTab {
 			state: res,
 			data: Rc::downgrade(index_entry),
 			index: Some(index),
 			is_hex_editor: hex_editor,
+			labels_source: None,
 			id,
 		})
 	}
 
+	/// let the editor of a resource collection look up the other resources it uses in the open package,
+	/// those are only read so they are saved unchanged
+	fn resolve_links<R: Read + Seek>(
+		reader: &mut R,
+		file_resources: &[Rc<RefCell<OpenResource>>],
+		index: usize,
+		state: &mut ResourceCollectionEditorState,
+		ui_ctx: &Context,
+	) {
+		let mut resource = file_resources[index].borrow_mut();
+		let (group_id, instance_id) = (resource.data.group_id, resource.data.instance_id.id);
+		let Ok(Some(DecodedFile::ResourceCollection(rcol))) = resource
+			.data
+			.data(reader)
+			.map_err(CompressionError::from)
+			.and_then(|data| data.decoded())
+		else {
+			return;
+		};
+
+		let mut others: Vec<_> = file_resources
+			.iter()
+			.enumerate()
+			.filter(|(i, _)| *i != index)
+			.map(|(_, res)| res.borrow_mut())
+			.collect();
+		let mut entries: Vec<_> = others.iter_mut().map(|res| &mut res.data).collect();
+		state.resolve_links(rcol, group_id, instance_id, &mut entries, reader, ui_ctx);
+	}
+
 	fn open_index_tab(
 		&mut self,
 		index: usize,
@@ -794,6 +933,61 @@ impl YaPeApp {
 		}
 	}
 
+	/// check every BHAV that was edited in this session
+	///
+	/// BHAVs that were only viewed are written as they were loaded, even when they were already invalid
+	fn validate_modified(&self) -> Result<(), String> {
+		let Some(open_file) = &self.data.open_file else {
+			return Ok(());
+		};
+		let errors: Vec<String> = open_file
+			.resources
+			.iter()
+			.filter_map(|resource| {
+				let resource = resource.borrow();
+				if resource.ui_deleted || resource.version == 0 {
+					return None;
+				}
+				let Some(DecodedFile::BehaviourFunction(bhav)) = resource.data.decoded_data()
+				else {
+					return None;
+				};
+				bhav.validate().err().map(|err| {
+					format!(
+						"BHAV {:08X} {:X} ({}): {err}",
+						resource.data.group_id, resource.data.instance_id.id, bhav.name.name
+					)
+				})
+			})
+			.collect();
+		if errors.is_empty() {
+			Ok(())
+		} else {
+			Err(errors.join("\n"))
+		}
+	}
+
+	/// encode the open file, refusing to do so if an edited resource is invalid
+	fn encode_for_save(&mut self) -> Option<Vec<u8>> {
+		if let Err(err) = self.validate_modified() {
+			error!(%err);
+			self.save_error = Some(err);
+			return None;
+		}
+		let mut buf = Cursor::new(Vec::new());
+		match self.save_bytes(&mut buf) {
+			Err(err) => {
+				error!(?err);
+				self.save_error = Some(err.to_string());
+				None
+			}
+			Ok(()) => {
+				self.save_error = None;
+				Some(buf.into_inner())
+			}
+		}
+	}
+
 	fn save_bytes<W: Write + Seek>(&mut self, writer: &mut W) -> Result<(), CompressionError> {
 		if let Some(open_file) = &mut self.data.open_file {
 			if let Ok(file) = &mut open_file.header {
@@ -870,15 +1064,11 @@ impl App for YaPeApp {
 				eprintln!("handle = {:#?}", handle);
 				self.save_file_picker = None;
 				if let Some(handle) = handle {
-					let mut buf = Cursor::new(Vec::new());
-					match self.save_bytes(&mut buf) {
-						Err(e) => error!(?e),
-						Ok(_) => {
-							if let Ok(path) = futures::executor::block_on(
-								file_io::write_file_handle(handle, &buf.into_inner()),
-							) {
-								self.data.open_file_path = path;
-							}
+					if let Some(bytes) = self.encode_for_save() {
+						if let Ok(path) =
+							futures::executor::block_on(file_io::write_file_handle(handle, &bytes))
+						{
+							self.data.open_file_path = path;
 						}
 					}
 				}
@@ -1017,39 +1207,36 @@ impl App for YaPeApp {
 									Key::S,
 								))
 							}) {
-							let mut backed_up = true;
-							if self.data.settings.backup_on_save {
-								let r = fs::exists(&path)
-									.and_then(|e| {
-										if e {
-											Self::backup_filename(
-												&path,
-												self.data.settings.backup_overwrite_preference,
-											)
-										} else {
-											Ok(None)
-										}
-									})
-									.and_then(|p| {
-										if let Some(new_path) = p {
-											fs::rename(&path, new_path)
-										} else {
-											Ok(())
-										}
-									});
-								if let Err(err) = r {
-									error!(?err);
-									backed_up = false;
+							// encode everything before touching the file, so resources that can not be written leave it intact
+							if let Some(bytes) = self.encode_for_save() {
+								let mut backed_up = true;
+								if self.data.settings.backup_on_save {
+									let r = fs::exists(&path)
+										.and_then(|e| {
+											if e {
+												Self::backup_filename(
+													&path,
+													self.data.settings.backup_overwrite_preference,
+												)
+											} else {
+												Ok(None)
+											}
+										})
+										.and_then(|p| {
+											if let Some(new_path) = p {
+												fs::rename(&path, new_path)
+											} else {
+												Ok(())
+											}
+										});
+									if let Err(err) = r {
+										error!(?err);
+										backed_up = false;
+									}
 								}
-							}
-							if backed_up {
-								match File::create(path) {
-									Err(err) => error!(?err),
-									Ok(f) => {
-										let mut writer = BufWriter::new(f);
-										if let Err(err) = self.save_bytes(&mut writer) {
-											error!(?err);
-										}
+								if backed_up {
+									if let Err(err) = fs::write(&path, bytes) {
+										error!(?err);
 									}
 								}
 							}
@@ -1081,6 +1268,10 @@ impl App for YaPeApp {
 						ui.add_enabled(false, Button::new("💾"));
 						ui.add_enabled(false, Button::new("💾✏"));
 					}
+
+					if let Some(err) = &self.save_error {
+						ui.colored_label(ui.visuals().error_fg_color, format!("not saved: {err}"));
+					}
 				});
 			});
 